@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::process::{Command, Stdio};
+
+use crate::net_monitor;
+
+pub fn by_interface(ns: Option<&str>) -> HashMap<String, Vec<String>> {
+    let mut cmd = Command::new("ip");
+    if let Some(ns) = ns { cmd.args(["-n", ns]); }
+    let mut addrs: HashMap<String, Vec<String>> = HashMap::new();
+    let Some(out) = cmd.args(["-o", "addr", "show", "scope", "global"]).stderr(Stdio::null()).output().ok().filter(|o| o.status.success()) else {
+        for (name, ip) in net_monitor::ipv4_addrs() { addrs.entry(name).or_default().push(ip); }
+        return addrs;
+    };
+    for line in String::from_utf8_lossy(&out.stdout).lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.len() < 4 || words.contains(&"temporary") || words.contains(&"deprecated") { continue; }
+        let name = words[1].split('@').next().unwrap_or(words[1]).to_string();
+        if name == "lo" { continue; }
+        let Some(ip) = words[3].split('/').next() else { continue };
+        addrs.entry(name).or_default().push(ip.to_string());
+    }
+    for list in addrs.values_mut() { list.sort(); }
+    addrs
+}
+
+pub fn public(url: &str) -> Option<String> {
+    let out = Command::new("curl").args(["-s", "-f", "-m", "10", url]).stdin(Stdio::null()).output().ok().filter(|o| o.status.success())?;
+    let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    text.parse::<IpAddr>().ok().map(|ip| ip.to_string())
+}
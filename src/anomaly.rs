@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{clock, config};
+
+const WARMUP: u32 = 30;
+const ALPHA: f64 = 0.02;
+pub const DIRECTIONS: [&str; 2] = ["download", "upload"];
+
+#[derive(Clone, Copy, Default)]
+pub struct Stats {
+    pub n: u32,
+    pub mean: f64,
+    pub var: f64,
+}
+
+impl Stats {
+    fn push(&mut self, x: f64) {
+        self.n = self.n.saturating_add(1);
+        let a = (1.0 / self.n as f64).max(ALPHA);
+        let d = x - self.mean;
+        self.mean += a * d;
+        self.var = (1.0 - a) * (self.var + a * d * d);
+    }
+
+    pub fn limit(&self, sigma: f64) -> f64 {
+        self.mean + sigma * self.var.sqrt()
+    }
+}
+
+#[derive(Clone)]
+pub struct Flag {
+    pub iface: String,
+    pub direction: usize,
+    pub rate: f64,
+    pub expected: f64,
+    pub since: i64,
+}
+
+pub struct Detector {
+    pub baselines: HashMap<String, [[Stats; 2]; 24]>,
+    pub active: Vec<Flag>,
+    minute: i64,
+    sums: HashMap<String, (f64, f64, u32)>,
+    streaks: HashMap<(String, usize), u32>,
+}
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("baseline")
+}
+
+impl Detector {
+    pub fn load() -> Detector {
+        let mut baselines: HashMap<String, [[Stats; 2]; 24]> = HashMap::new();
+        for line in fs::read_to_string(store_path()).unwrap_or_default().lines() {
+            let f: Vec<&str> = line.split('\t').collect();
+            let (Some(hour), Some(dir)) = (f.get(1).and_then(|v| v.parse::<usize>().ok()).filter(|h| *h < 24), f.get(2).and_then(|v| DIRECTIONS.iter().position(|d| d == v))) else { continue };
+            let number = |i: usize| f.get(i).and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0);
+            baselines.entry(f[0].to_string()).or_default()[hour][dir] = Stats { n: number(3) as u32, mean: number(4), var: number(5) };
+        }
+        Detector { baselines, active: Vec::new(), minute: clock::epoch_now() / 60, sums: HashMap::new(), streaks: HashMap::new() }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = store_path();
+        if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+        let mut text = String::new();
+        for (iface, hours) in &self.baselines {
+            for (hour, dirs) in hours.iter().enumerate() {
+                for (dir, s) in dirs.iter().enumerate().filter(|(_, s)| s.n > 0) {
+                    text += &format!("{}\t{}\t{}\t{}\t{}\t{}\n", iface, hour, DIRECTIONS[dir], s.n, s.mean, s.var);
+                }
+            }
+        }
+        fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn sample(&mut self, rates: &[(String, (f64, f64))], now: i64, config: &config::Anomaly) -> (Vec<Flag>, Vec<Flag>) {
+        for (iface, (rx, tx)) in rates {
+            let sum = self.sums.entry(iface.clone()).or_insert((0.0, 0.0, 0));
+            *sum = (sum.0 + rx, sum.1 + tx, sum.2 + 1);
+        }
+        if now / 60 == self.minute { return (Vec::new(), Vec::new()); }
+        let hour = clock::local(self.minute * 60).hour as usize;
+        self.minute = now / 60;
+        let (mut raised, mut cleared) = (Vec::new(), Vec::new());
+        for (iface, (rx, tx, count)) in std::mem::take(&mut self.sums) {
+            let averages = [rx / count.max(1) as f64, tx / count.max(1) as f64];
+            let stats = &mut self.baselines.entry(iface.clone()).or_default()[hour];
+            for (dir, rate) in averages.into_iter().enumerate() {
+                let expected = stats[dir].limit(config.sigma).max(config.min_rate);
+                let streak = self.streaks.entry((iface.clone(), dir)).or_insert(0);
+                if stats[dir].n >= WARMUP && rate > expected {
+                    *streak += 1;
+                    if *streak == config.minutes.max(1) {
+                        let flag = Flag { iface: iface.clone(), direction: dir, rate, expected: stats[dir].mean, since: now - 60 * *streak as i64 };
+                        self.active.push(flag.clone());
+                        raised.push(flag);
+                    }
+                    if *streak > 60 { stats[dir].push(rate); }
+                    continue;
+                }
+                *streak = 0;
+                stats[dir].push(rate);
+                if let Some(i) = self.active.iter().position(|f| f.iface == iface && f.direction == dir) { cleared.push(self.active.remove(i)); }
+            }
+        }
+        if self.minute % 10 == 0 { let _ = self.save(); }
+        (raised, cleared)
+    }
+}
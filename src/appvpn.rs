@@ -0,0 +1,158 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+const PREFIX: &str = "dashnet-";
+const FAMILIES: [&str; 2] = ["-4", "-6"];
+
+pub struct Process {
+    pub pid: u32,
+    pub name: String,
+}
+
+pub struct Tunnel {
+    pub iface: String,
+    pub mark: u32,
+    pub table: u32,
+    pub up: bool,
+    pub processes: Vec<Process>,
+}
+
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let out = Command::new(program).args(args).stdin(Stdio::null()).output().map_err(|e| format!("{}: {}", program, e))?;
+    if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+fn nft(script: &str) -> Result<(), String> {
+    let mut child = Command::new("nft").args(["-f", "-"]).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::piped()).spawn().map_err(|e| format!("nft: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() { stdin.write_all(script.as_bytes()).map_err(|e| e.to_string())?; }
+    let out = child.wait_with_output().map_err(|e| e.to_string())?;
+    if out.status.success() { Ok(()) } else { Err(format!("nft: {}", String::from_utf8_lossy(&out.stderr).trim())) }
+}
+
+fn cgroup(iface: &str) -> PathBuf {
+    Path::new(CGROUP_ROOT).join(format!("{}{}", PREFIX, iface))
+}
+
+fn ifindex(iface: &str) -> Option<u32> {
+    fs::read_to_string(format!("/sys/class/net/{}/ifindex", iface)).ok()?.trim().parse().ok()
+}
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("appvpn")
+}
+
+fn stored() -> Vec<(String, u32, u32)> {
+    fs::read_to_string(store_path()).unwrap_or_default().lines().filter_map(|line| {
+        let f: Vec<&str> = line.split('\t').collect();
+        Some((f.first()?.to_string(), f.get(1)?.parse().ok()?, f.get(2)?.parse().ok()?))
+    }).collect()
+}
+
+fn store(entries: &[(String, u32, u32)]) -> Result<(), String> {
+    let path = store_path();
+    if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    let text: String = entries.iter().map(|(iface, mark, table)| format!("{}\t{}\t{}\n", iface, mark, table)).collect();
+    fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn marks() -> Vec<u32> {
+    stored().into_iter().map(|(_, mark, _)| mark).collect()
+}
+
+fn ids(iface: &str) -> Result<(u32, u32), String> {
+    if let Some((_, mark, table)) = stored().into_iter().find(|(i, _, _)| i == iface) { return Ok((mark, table)); }
+    let index = ifindex(iface).ok_or_else(|| format!("no interface named {}", iface))?;
+    Ok((0xd000 + index, 2000 + index))
+}
+
+fn processes(dir: &Path) -> Vec<Process> {
+    fs::read_to_string(dir.join("cgroup.procs")).unwrap_or_default().lines().filter_map(|l| l.trim().parse().ok()).map(|pid: u32| Process {
+        pid,
+        name: fs::read_to_string(format!("/proc/{}/comm", pid)).map(|s| s.trim().to_string()).unwrap_or_else(|_| "?".to_string()),
+    }).collect()
+}
+
+pub fn list() -> Vec<Tunnel> {
+    let mut tunnels: Vec<Tunnel> = fs::read_dir(CGROUP_ROOT).map(|rd| rd.filter_map(Result::ok).filter_map(|e| {
+        let iface = e.file_name().to_string_lossy().strip_prefix(PREFIX)?.to_string();
+        let (mark, table) = ids(&iface).unwrap_or((0, 0));
+        Some(Tunnel { up: ifindex(&iface).is_some(), mark, table, processes: processes(&e.path()), iface })
+    }).collect()).unwrap_or_default();
+    tunnels.sort_by(|a, b| a.iface.cmp(&b.iface));
+    tunnels
+}
+
+fn setup(iface: &str) -> Result<String, String> {
+    let (mark, table) = ids(iface)?;
+    let dir = cgroup(iface);
+    if !dir.exists() { fs::create_dir(&dir).map_err(|e| format!("{}: {} (per-app routing needs root and cgroup v2)", dir.display(), e))?; }
+    let name = format!("{}{}", PREFIX, iface);
+    if run("nft", &["list", "table", "inet", &name]).is_err() {
+        nft(&format!(concat!(
+            "table inet {name} {{\n",
+            "  chain output {{ type route hook output priority mangle; socket cgroupv2 level 1 \"{name}\" meta mark set {mark:#x}; }}\n",
+            "  chain postrouting {{ type nat hook postrouting priority srcnat; meta mark {mark:#x} oifname \"{iface}\" masquerade; }}\n",
+            "}}\n"), name = name, mark = mark, iface = iface))?;
+    }
+    let mut entries = stored();
+    if !entries.iter().any(|(i, _, _)| i == iface) {
+        entries.push((iface.to_string(), mark, table));
+        store(&entries)?;
+    }
+    let (mark, table) = (format!("{:#x}", mark), table.to_string());
+    for family in FAMILIES {
+        if !rule_exists(family, &mark) { run("ip", &[family, "rule", "add", "fwmark", &mark, "table", &table, "priority", "1000"])?; }
+        run("ip", &[family, "route", "replace", "unreachable", "default", "metric", "4096", "table", &table])?;
+    }
+    run("ip", &["-4", "route", "replace", "default", "dev", iface, "table", &table])?;
+    let ipv6 = run("ip", &["-6", "route", "replace", "default", "dev", iface, "table", &table]);
+    let _ = run("sysctl", &["-qw", &format!("net.ipv4.conf.{}.rp_filter=2", iface)]);
+    Ok(if ipv6.is_ok() { String::new() } else { format!(" (IPv4 only: {} has no IPv6, so their IPv6 traffic is blocked)", iface) })
+}
+
+fn rule_exists(family: &str, mark: &str) -> bool {
+    Command::new("ip").args([family, "rule", "show", "fwmark", mark]).output().is_ok_and(|out| !out.stdout.is_empty())
+}
+
+pub fn tag(iface: &str, pid: u32) -> Result<String, String> {
+    let note = setup(iface)?;
+    fs::write(cgroup(iface).join("cgroup.procs"), pid.to_string()).map_err(|e| format!("cannot move {}: {}", pid, e))?;
+    Ok(format!("process {} now routed through {}{}", pid, iface, note))
+}
+
+pub fn launch(iface: &str, command: &str) -> Result<String, String> {
+    let note = setup(iface)?;
+    let procs = cgroup(iface).join("cgroup.procs");
+    let script = match std::env::var("SUDO_USER") {
+        Ok(user) if !user.is_empty() => format!("echo $$ > '{}' && exec runuser -u '{}' -- sh -c \"$0\"", procs.display(), user),
+        _ => format!("echo $$ > '{}' && exec sh -c \"$0\"", procs.display()),
+    };
+    let child = Command::new("sh").args(["-c", &script, command]).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn().map_err(|e| format!("sh: {}", e))?;
+    Ok(format!("'{}' started through {} (pid {}){}", command, iface, child.id(), note))
+}
+
+pub fn untag(iface: &str, pid: u32) -> Result<String, String> {
+    fs::write(Path::new(CGROUP_ROOT).join("cgroup.procs"), pid.to_string()).map_err(|e| format!("cannot move {}: {}", pid, e))?;
+    if processes(&cgroup(iface)).is_empty() { teardown(iface)?; return Ok(format!("process {} back on the default route, {} rules removed", pid, iface)); }
+    Ok(format!("process {} back on the default route", pid))
+}
+
+pub fn teardown(iface: &str) -> Result<(), String> {
+    let _ = run("nft", &["delete", "table", "inet", &format!("{}{}", PREFIX, iface)]);
+    if let Ok((mark, table)) = ids(iface) {
+        let mark = format!("{:#x}", mark);
+        for family in FAMILIES {
+            while rule_exists(family, &mark) { if run("ip", &[family, "rule", "del", "fwmark", &mark]).is_err() { break; } }
+            let _ = run("ip", &[family, "route", "flush", "table", &table.to_string()]);
+        }
+    }
+    let mut entries = stored();
+    entries.retain(|(i, _, _)| i != iface);
+    store(&entries)?;
+    fs::remove_dir(cgroup(iface)).map_err(|e| format!("{}: {}", cgroup(iface).display(), e))
+}
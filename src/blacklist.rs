@@ -0,0 +1,19 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config;
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("blacklist")
+}
+
+pub fn load() -> Vec<String> {
+    fs::read_to_string(store_path()).unwrap_or_default().lines().filter(|l| !l.is_empty()).map(str::to_string).collect()
+}
+
+pub fn save(ssids: &[String]) -> Result<(), String> {
+    let path = store_path();
+    if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    let text: String = ssids.iter().map(|ssid| format!("{}\n", ssid)).collect();
+    fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
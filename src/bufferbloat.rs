@@ -0,0 +1,113 @@
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use crate::{clock, config, format, speedtest, watchdog};
+
+const GRADES: [(f64, &str); 5] = [(5.0, "A+"), (30.0, "A"), (60.0, "B"), (200.0, "C"), (400.0, "D")];
+
+pub struct Measurement {
+    pub time: i64,
+    pub iface: String,
+    pub qdisc: String,
+    pub idle: f64,
+    pub loaded: f64,
+    pub loaded_p95: f64,
+}
+
+impl Measurement {
+    pub fn increase(&self) -> f64 {
+        (self.loaded - self.idle).max(0.0)
+    }
+
+    pub fn grade(&self) -> &'static str {
+        GRADES.iter().find(|(limit, _)| self.increase() < *limit).map_or("F", |(_, grade)| grade)
+    }
+
+    fn summary(&self) -> String {
+        format!("{} on {} ({}): +{} ms, grade {}", format::date(self.time), self.iface, self.qdisc, format::number(self.increase(), 1), self.grade())
+    }
+}
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("bufferbloat")
+}
+
+fn load() -> Vec<Measurement> {
+    fs::read_to_string(store_path()).unwrap_or_default().lines().filter_map(|line| {
+        let f: Vec<&str> = line.split('\t').collect();
+        if f.len() < 6 { return None; }
+        Some(Measurement { time: f[0].parse().ok()?, iface: f[1].to_string(), qdisc: f[2].to_string(), idle: f[3].parse().ok()?, loaded: f[4].parse().ok()?, loaded_p95: f[5].parse().ok()? })
+    }).collect()
+}
+
+fn append(m: &Measurement) -> Result<(), String> {
+    let path = store_path();
+    if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    let mut file = OpenOptions::new().create(true).append(true).open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    writeln!(file, "{}\t{}\t{}\t{:.2}\t{:.2}\t{:.2}", m.time, m.iface, m.qdisc, m.idle, m.loaded, m.loaded_p95).map_err(|e| e.to_string())
+}
+
+fn root_qdisc(iface: &str) -> String {
+    Command::new("tc").args(["qdisc", "show", "dev", iface]).output().ok()
+        .and_then(|out| String::from_utf8_lossy(&out.stdout).lines().find(|l| l.contains(" root ")).and_then(|l| l.split_whitespace().nth(1)).map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn rtt(line: &str) -> Option<f64> {
+    line.split("time=").nth(1)?.split_whitespace().next()?.parse().ok()
+}
+
+fn percentile(values: &mut [f64], p: f64) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    values.get(((values.len() - 1) as f64 * p).round() as usize).copied().unwrap_or(0.0)
+}
+
+fn ping_while(target: &str, load: impl FnOnce() -> Result<String, String>) -> Result<(Vec<f64>, String), String> {
+    let mut child = Command::new("ping").args(["-n", "-i", "0.2", target]).stdout(Stdio::piped()).stderr(Stdio::null()).spawn().map_err(|e| format!("ping: {}", e))?;
+    let stdout = child.stdout.take().ok_or("ping: no output")?;
+    let reader = thread::spawn(move || BufReader::new(stdout).lines().map_while(Result::ok).filter_map(|l| rtt(&l)).collect::<Vec<f64>>());
+    let outcome = load();
+    let _ = child.kill();
+    let _ = child.wait();
+    let samples = reader.join().unwrap_or_default();
+    outcome.map(|note| (samples, note))
+}
+
+pub fn run(target: &str) -> Vec<String> {
+    let iface = watchdog::default_iface().unwrap_or_else(|| "-".to_string());
+    let idle = ping_while(target, || { thread::sleep(std::time::Duration::from_secs(5)); Ok(String::new()) });
+    let loaded = ping_while(target, || speedtest::measure().map(|s| format!("↓ {} Mb/s  ↑ {} Mb/s", format::number(s.download, 1), format::number(s.upload, 1))));
+    let ((mut idle, _), (mut loaded, rates)) = match (idle, loaded) {
+        (Ok(idle), Ok(loaded)) if !idle.0.is_empty() && !loaded.0.is_empty() => (idle, loaded),
+        (Err(e), _) | (_, Err(e)) => return vec![format!("Bufferbloat test failed: {}", e)],
+        _ => return vec![format!("No ping reply from {}.", target)],
+    };
+    let m = Measurement {
+        time: clock::epoch_now(),
+        qdisc: root_qdisc(&iface),
+        iface,
+        idle: percentile(&mut idle, 0.5),
+        loaded: percentile(&mut loaded, 0.5),
+        loaded_p95: percentile(&mut loaded, 0.95),
+    };
+    let history = load();
+    let mut lines = vec![
+        format!("Idle latency:   {} ms (median of {} pings to {})", format::number(m.idle, 1), idle.len(), target),
+        format!("Under load:     {} ms median, {} ms p95 ({})", format::number(m.loaded, 1), format::number(m.loaded_p95, 1), rates),
+        format!("Increase:       +{} ms → grade {}", format::number(m.increase(), 1), m.grade()),
+        format!("Queueing discipline on {}: {}", m.iface, m.qdisc),
+    ];
+    if let Some(before) = history.iter().rev().find(|h| h.iface == m.iface && h.qdisc != m.qdisc) {
+        lines.push(String::new());
+        lines.push(format!("Before ({}): {}", before.qdisc, before.summary()));
+        lines.push(format!("Now    ({}): {}", m.qdisc, m.summary()));
+    } else if let Some(previous) = history.last() {
+        lines.push(String::new());
+        lines.push(format!("Previous run: {}", previous.summary()));
+    }
+    if let Err(e) = append(&m) { lines.push(format!("Cannot save the result: {}", e)); }
+    lines
+}
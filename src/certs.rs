@@ -0,0 +1,106 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+pub struct Certificate {
+    pub subject: String,
+    pub issuer: String,
+    pub sans: Vec<String>,
+    pub not_after: i64,
+    pub chain: usize,
+    pub verified: String,
+}
+
+pub struct Check {
+    pub endpoint: String,
+    pub result: Result<Certificate, String>,
+}
+
+fn split_endpoint(endpoint: &str) -> (String, u16) {
+    let rest = endpoint.trim().trim_start_matches("https://");
+    let authority = rest.split('/').next().unwrap_or(rest);
+    match authority.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => (host.trim_matches(['[', ']']).to_string(), port.parse().unwrap_or(443)),
+        _ => (authority.trim_matches(['[', ']']).to_string(), 443),
+    }
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    era * 146_097 + yoe * 365 + yoe / 4 - yoe / 100 + doy - 719_468
+}
+
+fn parse_date(text: &str) -> Option<i64> {
+    let f: Vec<&str> = text.split_whitespace().collect();
+    if f.len() < 4 { return None; }
+    let month = MONTHS.iter().position(|m| *m == f[0])? as i64 + 1;
+    let time: Vec<i64> = f[2].split(':').filter_map(|p| p.parse().ok()).collect();
+    if time.len() != 3 { return None; }
+    Some(days_from_civil(f[3].parse().ok()?, month, f[1].parse().ok()?) * 86_400 + time[0] * 3600 + time[1] * 60 + time[2])
+}
+
+fn field(text: &str, key: &str) -> String {
+    text.split(", ").find_map(|part| {
+        let (k, v) = part.split_once('=')?;
+        (k.trim() == key).then(|| v.trim().to_string())
+    }).unwrap_or_else(|| text.to_string())
+}
+
+pub fn fetch(endpoint: &str) -> Result<Certificate, String> {
+    let (host, port) = split_endpoint(endpoint);
+    let out = Command::new("openssl").args(["s_client", "-connect", &format!("{}:{}", host, port), "-servername", &host, "-showcerts"])
+        .stdin(Stdio::null()).stderr(Stdio::null()).output().map_err(|e| format!("openssl: {}", e))?;
+    let text = String::from_utf8_lossy(&out.stdout);
+    let chain = text.matches("-----BEGIN CERTIFICATE-----").count();
+    let start = text.find("-----BEGIN CERTIFICATE-----").ok_or_else(|| format!("no certificate received from {}:{}", host, port))?;
+    let end = text[start..].find("-----END CERTIFICATE-----").map(|i| start + i + 25).ok_or("truncated certificate")?;
+    let verified = text.lines().find_map(|l| l.trim().strip_prefix("Verify return code: ")).unwrap_or("unknown").to_string();
+    let mut x509 = Command::new("openssl").args(["x509", "-noout", "-subject", "-issuer", "-enddate", "-ext", "subjectAltName"])
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null()).spawn().map_err(|e| format!("openssl: {}", e))?;
+    if let Some(mut stdin) = x509.stdin.take() { let _ = stdin.write_all(text[start..end].as_bytes()); }
+    let out = x509.wait_with_output().map_err(|e| e.to_string())?;
+    let details = String::from_utf8_lossy(&out.stdout);
+    let value = |prefix: &str| details.lines().find_map(|l| l.strip_prefix(prefix)).unwrap_or("").trim().to_string();
+    let sans = details.lines().skip_while(|l| !l.contains("Subject Alternative Name")).nth(1).unwrap_or("")
+        .split(',').map(|s| s.trim().trim_start_matches("DNS:").to_string()).filter(|s| !s.is_empty()).collect();
+    Ok(Certificate {
+        subject: field(&value("subject="), "CN"),
+        issuer: field(&value("issuer="), "O"),
+        sans,
+        not_after: parse_date(&value("notAfter=")).ok_or("cannot read the expiry date")?,
+        chain,
+        verified,
+    })
+}
+
+pub fn describe(endpoint: &str, now: i64) -> Vec<String> {
+    match fetch(endpoint) {
+        Ok(cert) => vec![
+            format!("Endpoint:  {}", endpoint),
+            format!("Subject:   {}", cert.subject),
+            format!("Issuer:    {}", cert.issuer),
+            format!("SANs:      {}", cert.sans.join(", ")),
+            format!("Expires:   {} ({} days left)", crate::format::date(cert.not_after), (cert.not_after - now).div_euclid(86_400)),
+            format!("Chain:     {} certificate{}, verification: {}", cert.chain, if cert.chain == 1 { "" } else { "s" }, cert.verified),
+        ],
+        Err(e) => vec![format!("{}: {}", endpoint, e)],
+    }
+}
+
+pub fn spawn_checker(endpoints: Vec<String>, interval: u64) -> Receiver<Check> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || loop {
+        for endpoint in &endpoints {
+            if tx.send(Check { endpoint: endpoint.clone(), result: fetch(endpoint) }).is_err() { return; }
+        }
+        thread::sleep(Duration::from_secs(interval.max(1) * 3600));
+    });
+    rx
+}
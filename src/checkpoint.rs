@@ -0,0 +1,86 @@
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::routes;
+
+const NM: [&str; 3] = ["org.freedesktop.NetworkManager", "/org/freedesktop/NetworkManager", "org.freedesktop.NetworkManager"];
+
+#[derive(Clone)]
+pub enum Undo {
+    Nothing,
+    DeleteRoute(String),
+    RestoreRoute(String),
+    AddRoutes(Vec<String>),
+    Uplink(String, String),
+}
+
+impl Undo {
+    fn run(&self) -> Vec<String> {
+        match self {
+            Undo::Nothing | Undo::Uplink(..) => Vec::new(),
+            Undo::DeleteRoute(spec) => vec![match routes::delete(spec) { Ok(()) => format!("route {} removed", spec), Err(e) => format!("removing route {} failed: {}", spec, e) }],
+            Undo::RestoreRoute(line) => vec![match routes::restore(line) { Ok(()) => format!("route {} restored", line), Err(e) => format!("restoring route {} failed: {}", line, e) }],
+            Undo::AddRoutes(specs) => specs.iter().map(|spec| match routes::add(spec) { Ok(()) => format!("route {} restored", spec), Err(e) => format!("restoring route {} failed: {}", spec, e) }).collect(),
+        }
+    }
+}
+
+pub struct Pending {
+    pub path: Option<String>,
+    pub changes: Vec<(String, Undo)>,
+    pub started: Instant,
+    pub timeout: Duration,
+    pub confirm: bool,
+    pub online_before: bool,
+    pub failures: u32,
+    pub probed: Instant,
+}
+
+impl Pending {
+    pub fn remaining(&self) -> u64 {
+        self.timeout.saturating_sub(self.started.elapsed()).as_secs()
+    }
+}
+
+fn busctl(args: &[&str]) -> Result<String, String> {
+    let out = Command::new("busctl").args(["--system", "call"]).args(NM).args(args).stdin(Stdio::null()).output().map_err(|e| format!("busctl: {}", e))?;
+    if out.status.success() { Ok(String::from_utf8_lossy(&out.stdout).trim().to_string()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().trim_start_matches("Call failed: ").to_string()) }
+}
+
+fn device_name(path: &str) -> String {
+    Command::new("busctl").args(["--system", "get-property", NM[0], path, "org.freedesktop.NetworkManager.Device", "Interface"]).output().ok()
+        .and_then(|out| String::from_utf8_lossy(&out.stdout).split('"').nth(1).map(str::to_string)).unwrap_or_else(|| path.rsplit('/').next().unwrap_or(path).to_string())
+}
+
+pub fn create(timeout: u64) -> Result<String, String> {
+    let out = busctl(&["CheckpointCreate", "aouu", "0", &timeout.to_string(), "0"])?;
+    out.split('"').nth(1).map(str::to_string).ok_or_else(|| format!("unexpected answer: {}", out))
+}
+
+pub fn extend(path: &str, timeout: u64) -> Result<(), String> {
+    busctl(&["CheckpointAdjustRollbackTimeout", "ou", path, &timeout.to_string()]).map(|_| ())
+}
+
+pub fn commit(path: &str) -> Result<(), String> {
+    busctl(&["CheckpointDestroy", "o", path]).map(|_| ())
+}
+
+pub fn rollback(path: Option<&str>, undo: &[Undo]) -> Vec<String> {
+    let mut lines: Vec<String> = undo.iter().rev().flat_map(Undo::run).collect();
+    let Some(path) = path else { return lines };
+    match busctl(&["CheckpointRollback", "o", path]) {
+        Ok(out) => {
+            let words: Vec<&str> = out.split_whitespace().skip(2).collect();
+            lines.extend(words.chunks(2).filter(|w| w.len() == 2).map(|w| format!("{}: {}", device_name(w[0].trim_matches('"')), match w[1] {
+                "0" => "restored",
+                "1" => "device gone",
+                "2" => "not managed by NetworkManager",
+                _ => "rollback failed",
+            })));
+            if words.is_empty() { lines.push("NetworkManager restored the checkpoint".to_string()); }
+        }
+        Err(e) if e.contains("not exist") || e.contains("UnknownObject") => lines.push("NetworkManager had already rolled back the checkpoint (timeout)".to_string()),
+        Err(e) => lines.push(format!("NetworkManager rollback failed: {}", e)),
+    }
+    lines
+}
@@ -0,0 +1,18 @@
+use std::io::{self, Write};
+use std::process::Command;
+
+use crate::base64;
+
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut sequence = format!("\x1b]52;c;{}\x07", base64::encode(text.as_bytes()));
+    if std::env::var_os("TMUX").is_some() { sequence = format!("\x1bPtmux;\x1b{}\x1b\\", sequence); }
+    let mut stdout = io::stdout();
+    stdout.write_all(sequence.as_bytes())?;
+    stdout.flush()
+}
+
+pub fn wireguard_public_key(iface: &str) -> Option<String> {
+    let out = Command::new("wg").args(["show", iface, "public-key"]).output().ok()?;
+    let key = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    if out.status.success() && !key.is_empty() { Some(key) } else { None }
+}
@@ -0,0 +1,484 @@
+use std::fs::{self, DirBuilder};
+use std::io;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::format::{Locale, RateUnit};
+use crate::presets::Preset;
+use crate::schedule::{self, Action, Schedule};
+use crate::sla::{Check, Target};
+use crate::theme::Scheme;
+use crate::notify::{self, Prefs};
+use crate::webhook::{self, Webhook};
+
+#[derive(Clone)]
+pub struct SnmpTarget {
+    pub name: String,
+    pub host: String,
+    pub version: String,
+    pub community: String,
+    pub user: String,
+    pub auth_protocol: String,
+    pub auth_password: String,
+    pub priv_protocol: String,
+    pub priv_password: String,
+    pub interfaces: Vec<u32>,
+    pub interval: u64,
+}
+
+pub struct Roaming {
+    pub threshold: u8,
+    pub margin: u8,
+    pub hold: u64,
+    pub cooldown: u64,
+}
+
+pub struct Failover {
+    pub uplinks: Vec<String>,
+    pub target: String,
+    pub interval: u64,
+    pub failures: u32,
+}
+
+pub struct Watchdog {
+    pub target: String,
+    pub timeout: u64,
+    pub step_wait: u64,
+}
+
+pub struct PublicIp {
+    pub url: String,
+    pub interval: u64,
+}
+
+pub struct Mqtt {
+    pub host: String,
+    pub port: u16,
+    pub prefix: String,
+    pub client_id: String,
+    pub user: String,
+    pub password: String,
+    pub tls: bool,
+    pub cafile: Option<String>,
+    pub interval: u64,
+}
+
+#[derive(Clone)]
+pub enum InfluxTarget {
+    Http(String),
+    File(PathBuf),
+    Udp(String),
+}
+
+pub struct Influx {
+    pub target: InfluxTarget,
+    pub token: String,
+    pub interval: u64,
+}
+
+pub struct Lan {
+    pub interval: u64,
+    pub networks: Vec<String>,
+}
+
+pub struct Outages {
+    pub target: String,
+    pub interval: u64,
+    pub failures: u32,
+}
+
+pub struct Power {
+    pub adaptive: bool,
+    pub sample: Duration,
+    pub redraw: Duration,
+    pub probes: bool,
+}
+
+pub struct Checkpoint {
+    pub enabled: bool,
+    pub timeout: u64,
+    pub failures: u32,
+    pub confirm: bool,
+    pub target: Option<String>,
+}
+
+pub struct Anomaly {
+    pub sigma: f64,
+    pub minutes: u32,
+    pub min_rate: f64,
+}
+
+pub struct Protocols {
+    pub interval: f64,
+    pub width: u16,
+}
+
+pub struct Certificates {
+    pub endpoints: Vec<String>,
+    pub warn_days: i64,
+    pub interval: u64,
+}
+
+pub struct Config {
+    pub snmp: Vec<SnmpTarget>,
+    pub reachability_url: String,
+    pub ipv6_target: String,
+    pub expected_routers: Vec<String>,
+    pub presets: Vec<Preset>,
+    pub schedules: Vec<Schedule>,
+    pub trusted_ssids: Vec<String>,
+    pub autovpn: Option<String>,
+    pub roaming: Option<Roaming>,
+    pub failover: Option<Failover>,
+    pub watchdog: Option<Watchdog>,
+    pub public_ip: Option<PublicIp>,
+    pub capacities: Vec<(String, f64)>,
+    pub warn_percent: f64,
+    pub critical_percent: f64,
+    pub rate_unit: RateUnit,
+    pub sample_interval: Duration,
+    pub redraw_interval: Duration,
+    pub power: Power,
+    pub checkpoint: Checkpoint,
+    pub locale: Locale,
+    pub scheme: Scheme,
+    pub linear: bool,
+    pub graphics: String,
+    pub websocket: Option<String>,
+    pub websocket_origins: Vec<String>,
+    pub mqtt: Option<Mqtt>,
+    pub influx: Option<Influx>,
+    pub webhooks: Vec<Webhook>,
+    pub notifications: Prefs,
+    pub lan: Option<Lan>,
+    pub rogue_detection: bool,
+    pub latency_interval: u64,
+    pub latency_target: Option<String>,
+    pub openvpn_management: Option<String>,
+    pub wireguard_servers: Vec<String>,
+    pub speedtest_interval: Option<u64>,
+    pub targets: Vec<Target>,
+    pub outages: Option<Outages>,
+    pub anomaly: Option<Anomaly>,
+    pub protocols: Option<Protocols>,
+    pub certificates: Option<Certificates>,
+    pub slots: Vec<(usize, String)>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            snmp: Vec::new(),
+            reachability_url: "https://ifconfig.co/port/{port}".to_string(),
+            ipv6_target: "ipv6.google.com".to_string(),
+            expected_routers: Vec::new(),
+            presets: Vec::new(),
+            schedules: Vec::new(),
+            trusted_ssids: Vec::new(),
+            autovpn: None,
+            roaming: None,
+            failover: None,
+            watchdog: None,
+            public_ip: None,
+            capacities: Vec::new(),
+            warn_percent: 60.0,
+            critical_percent: 85.0,
+            rate_unit: RateUnit::Bits,
+            sample_interval: Duration::from_millis(500),
+            redraw_interval: Duration::from_millis(500),
+            power: Power { adaptive: true, sample: Duration::from_secs(2), redraw: Duration::from_secs(1), probes: false },
+            checkpoint: Checkpoint { enabled: true, timeout: 60, failures: 3, confirm: true, target: None },
+            locale: Locale::detect(None),
+            scheme: Scheme::Default,
+            linear: false,
+            graphics: "off".to_string(),
+            websocket: None,
+            websocket_origins: Vec::new(),
+            mqtt: None,
+            influx: None,
+            webhooks: Vec::new(),
+            notifications: Prefs::default(),
+            lan: None,
+            rogue_detection: true,
+            latency_interval: 2,
+            latency_target: None,
+            openvpn_management: None,
+            wireguard_servers: Vec::new(),
+            speedtest_interval: None,
+            targets: Vec::new(),
+            outages: None,
+            anomaly: None,
+            protocols: None,
+            certificates: None,
+            slots: Vec::new(),
+        }
+    }
+}
+
+type Section = (String, Vec<(String, String)>);
+
+pub fn config_dir() -> PathBuf {
+    std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        .or_else(|| home::home_dir().map(|h| h.join(".config")))
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("dashnet")
+}
+
+pub fn data_dir() -> PathBuf {
+    std::env::var_os("XDG_DATA_HOME").map(PathBuf::from)
+        .or_else(|| home::home_dir().map(|h| h.join(".local/share")))
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("dashnet")
+}
+
+pub fn private_dir(purpose: &str) -> io::Result<PathBuf> {
+    let base = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from).unwrap_or_else(std::env::temp_dir);
+    for attempt in 0..100 {
+        let dir = base.join(format!("dashnet-{}-{}-{}", purpose, std::process::id(), attempt));
+        match DirBuilder::new().mode(0o700).create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("no free directory name in {}", base.display())))
+}
+
+fn parse_ini(text: &str) -> Vec<Section> {
+    let mut sections: Vec<Section> = vec![(String::new(), Vec::new())];
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') { continue; }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push((name.trim().to_string(), Vec::new()));
+        } else if let Some((key, value)) = line.split_once('=') {
+            if let Some(section) = sections.last_mut() {
+                section.1.push((key.trim().to_string(), value.trim().trim_matches('"').to_string()));
+            }
+        }
+    }
+    sections
+}
+
+fn get<'a>(entries: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    entries.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+}
+
+fn get_or(entries: &[(String, String)], key: &str, default: &str) -> String {
+    get(entries, key).unwrap_or(default).to_string()
+}
+
+fn get_list(entries: &[(String, String)], key: &str) -> Vec<String> {
+    get(entries, key).map(|v| v.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect()).unwrap_or_default()
+}
+
+fn seconds(value: &str) -> Option<Duration> {
+    value.parse::<f64>().ok().filter(|s| s.is_finite()).map(|s| Duration::from_secs_f64(s.clamp(0.1, 60.0)))
+}
+
+impl Config {
+    pub fn capacity(&self, iface: &str) -> Option<f64> {
+        self.capacities.iter().find(|(name, _)| name == iface).map(|(_, c)| *c)
+    }
+
+    pub fn load() -> Config {
+        let text = fs::read_to_string(config_dir().join("config")).unwrap_or_default();
+        let mut config = Config::default();
+        for (section, entries) in parse_ini(&text) {
+            if section == "reachability" {
+                if let Some(url) = get(&entries, "url") { config.reachability_url = url.to_string(); }
+            } else if section == "ipv6" {
+                if let Some(target) = get(&entries, "target") { config.ipv6_target = target.to_string(); }
+            } else if section == "autovpn" {
+                config.trusted_ssids = get_list(&entries, "trusted");
+                config.autovpn = get(&entries, "vpn").map(str::to_string);
+            } else if section == "roaming" {
+                if get(&entries, "enabled").is_some_and(|v| matches!(v, "yes" | "true" | "on" | "1")) {
+                    let num = |key: &str, default: u64| get(&entries, key).and_then(|v| v.parse().ok()).unwrap_or(default);
+                    config.roaming = Some(Roaming { threshold: num("threshold", 40) as u8, margin: num("margin", 15) as u8, hold: num("hold", 10), cooldown: num("cooldown", 60) });
+                }
+            } else if section == "failover" {
+                let uplinks = get_list(&entries, "uplinks");
+                if uplinks.len() < 2 { continue; }
+                config.failover = Some(Failover {
+                    uplinks,
+                    target: get_or(&entries, "target", "1.1.1.1"),
+                    interval: get(&entries, "interval").and_then(|v| v.parse().ok()).unwrap_or(5),
+                    failures: get(&entries, "failures").and_then(|v| v.parse().ok()).unwrap_or(3),
+                });
+            } else if section == "public_ip" {
+                if let Some(url) = get(&entries, "url").filter(|u| !u.is_empty()) {
+                    config.public_ip = Some(PublicIp { url: url.to_string(), interval: get(&entries, "interval").and_then(|v| v.parse().ok()).unwrap_or(300) });
+                }
+            } else if section == "watchdog" {
+                if get(&entries, "enabled").is_some_and(|v| matches!(v, "yes" | "true" | "on" | "1")) {
+                    config.watchdog = Some(Watchdog {
+                        target: get_or(&entries, "target", "1.1.1.1"),
+                        timeout: get(&entries, "timeout").and_then(|v| v.parse().ok()).unwrap_or(30),
+                        step_wait: get(&entries, "step_wait").and_then(|v| v.parse().ok()).unwrap_or(20),
+                    });
+                }
+            } else if section == "outages" {
+                if get(&entries, "enabled").is_some_and(|v| matches!(v, "yes" | "true" | "on" | "1")) {
+                    config.outages = Some(Outages {
+                        target: get_or(&entries, "target", "1.1.1.1"),
+                        interval: get(&entries, "interval").and_then(|v| v.parse().ok()).unwrap_or(10),
+                        failures: get(&entries, "failures").and_then(|v| v.parse().ok()).unwrap_or(3),
+                    });
+                }
+            } else if section == "anomaly" {
+                if get(&entries, "enabled").is_some_and(|v| matches!(v, "yes" | "true" | "on" | "1")) {
+                    config.anomaly = Some(Anomaly {
+                        sigma: get(&entries, "sigma").and_then(|v| v.parse().ok()).unwrap_or(4.0),
+                        minutes: get(&entries, "minutes").and_then(|v| v.parse().ok()).unwrap_or(5),
+                        min_rate: get(&entries, "min_rate").and_then(|v| v.parse().ok()).unwrap_or(1.0),
+                    });
+                }
+            } else if section == "protocols" {
+                if get(&entries, "enabled").is_some_and(|v| matches!(v, "yes" | "true" | "on" | "1")) {
+                    config.protocols = Some(Protocols {
+                        interval: get(&entries, "interval").and_then(|v| v.parse().ok()).unwrap_or(2.0),
+                        width: get(&entries, "width").and_then(|v| v.parse().ok()).unwrap_or(40),
+                    });
+                }
+            } else if section == "certificates" {
+                let endpoints = get_list(&entries, "endpoints");
+                if endpoints.is_empty() { continue; }
+                config.certificates = Some(Certificates {
+                    endpoints,
+                    warn_days: get(&entries, "warn_days").and_then(|v| v.parse().ok()).unwrap_or(14),
+                    interval: get(&entries, "interval").and_then(|v| v.parse().ok()).unwrap_or(12),
+                });
+            } else if section == "slots" {
+                config.slots = entries.iter().filter_map(|(k, v)| k.parse().ok().map(|n| (n, v.clone()))).collect();
+            } else if section == "capacity" {
+                config.capacities = entries.iter().filter_map(|(k, v)| v.parse().ok().map(|c| (k.clone(), c))).collect();
+            } else if section == "graph" {
+                if let Some(v) = get(&entries, "warn").and_then(|v| v.parse().ok()) { config.warn_percent = v; }
+                if let Some(v) = get(&entries, "critical").and_then(|v| v.parse().ok()) { config.critical_percent = v; }
+                if let Some(unit) = get(&entries, "units").and_then(RateUnit::parse) { config.rate_unit = unit; }
+                if let Some(v) = get(&entries, "sample").and_then(seconds) { config.sample_interval = v; }
+            } else if section == "display" {
+                if let Some(scheme) = get(&entries, "scheme").and_then(Scheme::parse) { config.scheme = scheme; }
+                config.linear = get(&entries, "layout") == Some("linear");
+                config.graphics = get_or(&entries, "graphics", "off");
+                if let Some(v) = get(&entries, "refresh").and_then(seconds) { config.redraw_interval = v; }
+            } else if section == "power" {
+                if let Some(v) = get(&entries, "adaptive") { config.power.adaptive = matches!(v, "yes" | "true" | "on" | "1"); }
+                if let Some(v) = get(&entries, "battery_sample").and_then(seconds) { config.power.sample = v; }
+                if let Some(v) = get(&entries, "battery_refresh").and_then(seconds) { config.power.redraw = v; }
+                if let Some(v) = get(&entries, "battery_probes") { config.power.probes = matches!(v, "yes" | "true" | "on" | "1"); }
+            } else if section == "checkpoint" {
+                if let Some(v) = get(&entries, "enabled") { config.checkpoint.enabled = matches!(v, "yes" | "true" | "on" | "1"); }
+                if let Some(v) = get(&entries, "timeout").and_then(|v| v.parse().ok()) { config.checkpoint.timeout = v; }
+                if let Some(v) = get(&entries, "failures").and_then(|v| v.parse().ok()) { config.checkpoint.failures = v; }
+                if let Some(v) = get(&entries, "confirm") { config.checkpoint.confirm = matches!(v, "yes" | "true" | "on" | "1"); }
+                config.checkpoint.target = get(&entries, "target").map(str::to_string);
+            } else if section == "websocket" {
+                config.websocket = get(&entries, "listen").map(str::to_string);
+                config.websocket_origins = get_list(&entries, "origins");
+            } else if section == "mqtt" {
+                let Some(host) = get(&entries, "host") else { continue };
+                let tls = get(&entries, "tls").is_some_and(|v| matches!(v, "yes" | "true" | "on" | "1"));
+                config.mqtt = Some(Mqtt {
+                    host: host.to_string(),
+                    port: get(&entries, "port").and_then(|v| v.parse().ok()).unwrap_or(if tls { 8883 } else { 1883 }),
+                    prefix: get_or(&entries, "prefix", "dashnet"),
+                    client_id: get_or(&entries, "client_id", "dashnet"),
+                    user: get_or(&entries, "user", ""),
+                    password: get_or(&entries, "password", ""),
+                    tls,
+                    cafile: get(&entries, "cafile").map(str::to_string),
+                    interval: get(&entries, "interval").and_then(|v| v.parse().ok()).unwrap_or(10),
+                });
+            } else if section == "influx" {
+                let target = if let Some(url) = get(&entries, "url") { InfluxTarget::Http(url.to_string()) }
+                    else if let Some(file) = get(&entries, "file") { InfluxTarget::File(crate::profile::expand_home(file)) }
+                    else if let Some(addr) = get(&entries, "udp") { InfluxTarget::Udp(addr.to_string()) }
+                    else { continue };
+                config.influx = Some(Influx {
+                    target,
+                    token: get_or(&entries, "token", ""),
+                    interval: get(&entries, "interval").and_then(|v| v.parse().ok()).unwrap_or(10),
+                });
+            } else if section == "lan" {
+                if get(&entries, "enabled").is_some_and(|v| matches!(v, "yes" | "true" | "on" | "1")) {
+                    config.lan = Some(Lan { interval: get(&entries, "interval").and_then(|v| v.parse().ok()).unwrap_or(300), networks: get_list(&entries, "networks") });
+                }
+            } else if section == "wifi" {
+                if let Some(v) = get(&entries, "rogue_detection") { config.rogue_detection = matches!(v, "yes" | "true" | "on" | "1"); }
+            } else if section == "latency" {
+                if let Some(v) = get(&entries, "interval").and_then(|v| v.parse().ok()) { config.latency_interval = v; }
+                config.latency_target = get(&entries, "target").map(str::to_string);
+            } else if section == "vpn_server" {
+                config.openvpn_management = get(&entries, "openvpn_management").map(str::to_string);
+                config.wireguard_servers = get_list(&entries, "wireguard");
+            } else if section == "speedtest" {
+                config.speedtest_interval = get(&entries, "interval").and_then(|v| v.parse().ok()).filter(|m| *m > 0);
+            } else if section == "locale" {
+                config.locale = Locale::detect(get(&entries, "name"));
+                if let Some(c) = get(&entries, "decimal").and_then(|v| v.chars().next()) { config.locale.decimal = c; }
+                if let Some(clock) = get(&entries, "clock") { config.locale.clock24 = clock != "12h"; }
+            } else if section == "ra" {
+                config.expected_routers = get_list(&entries, "expected_routers");
+            } else if let Some(name) = section.strip_prefix("preset.") {
+                config.presets.push(Preset {
+                    name: name.to_string(),
+                    wifi: get(&entries, "wifi").map(str::to_string),
+                    vpns: get_list(&entries, "vpn"),
+                    proxy: get(&entries, "proxy").map(str::to_string),
+                });
+            } else if let Some(name) = section.strip_prefix("schedule.") {
+                let time = get(&entries, "at").and_then(Schedule::parse_time);
+                let days = schedule::parse_days(get(&entries, "days").unwrap_or("daily"));
+                let action = get(&entries, "action").and_then(Action::parse);
+                if let (Some((hour, minute)), Some(days), Some(action)) = (time, days, action) {
+                    config.schedules.push(Schedule { name: name.to_string(), hour, minute, days, action });
+                }
+            } else if let Some(name) = section.strip_prefix("target.") {
+                let check = if let Some(host) = get(&entries, "ping") { Check::Ping(host.to_string()) }
+                    else if let Some(url) = get(&entries, "url") { Check::Http(url.to_string()) }
+                    else { continue };
+                config.targets.push(Target { name: name.to_string(), check, interval: get(&entries, "interval").and_then(|v| v.parse().ok()).unwrap_or(30) });
+            } else if section == "notifications" {
+                let level = |key: &str| get(&entries, key).and_then(notify::Level::parse).unwrap_or(notify::Level::All);
+                config.notifications = Prefs {
+                    vpn: level("vpn"),
+                    wifi: level("wifi"),
+                    alerts: level("alerts"),
+                    urgency: entries.iter().filter_map(|(k, v)| k.strip_prefix("urgency.").map(|e| (e.to_string(), v.clone()))).collect(),
+                    dedup: get(&entries, "dedup").and_then(|v| v.parse().ok()).unwrap_or(60),
+                    per_minute: get(&entries, "per_minute").and_then(|v| v.parse().ok()).unwrap_or(0),
+                    quiet: get(&entries, "quiet_hours").and_then(Prefs::parse_quiet),
+                };
+            } else if let Some(name) = section.strip_prefix("webhook.") {
+                let Some(url) = get(&entries, "url") else { continue };
+                config.webhooks.push(Webhook {
+                    name: name.to_string(),
+                    url: url.to_string(),
+                    kind: webhook::Kind::parse(get(&entries, "type").unwrap_or(""), url),
+                    events: get_list(&entries, "events"),
+                    critical_only: get(&entries, "critical_only").is_some_and(|v| matches!(v, "yes" | "true" | "on" | "1")),
+                    template: get_or(&entries, "template", "[{host}] {summary}: {body}"),
+                    templates: entries.iter().filter_map(|(k, v)| k.strip_prefix("template.").map(|e| (e.to_string(), v.clone()))).collect(),
+                });
+            } else if let Some(name) = section.strip_prefix("snmp.") {
+                let Some(host) = get(&entries, "host") else { continue };
+                config.snmp.push(SnmpTarget {
+                    name: name.to_string(),
+                    host: host.to_string(),
+                    version: get_or(&entries, "version", "2c"),
+                    community: get_or(&entries, "community", "public"),
+                    user: get_or(&entries, "user", ""),
+                    auth_protocol: get_or(&entries, "auth_protocol", "SHA"),
+                    auth_password: get_or(&entries, "auth_password", ""),
+                    priv_protocol: get_or(&entries, "priv_protocol", "AES"),
+                    priv_password: get_or(&entries, "priv_password", ""),
+                    interfaces: get_list(&entries, "interfaces").iter().filter_map(|i| i.parse().ok()).collect(),
+                    interval: get(&entries, "interval").and_then(|v| v.parse().ok()).unwrap_or(5),
+                });
+            }
+        }
+        config
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+use crate::input::TextInput;
+use crate::rogue::split_escaped;
+
+const HINTS: [(&[&str], &str); 6] = [
+    (&["secret", "password", "authentication"], "Check the password, key or certificate of this connection."),
+    (&["plugin", "service failed", "vpn service"], "The NetworkManager VPN plugin for this connection type is probably not installed."),
+    (&["no network with ssid", "not found"], "The network is out of range or no longer broadcast; rescan with R."),
+    (&["unknown connection"], "The profile no longer exists; refresh the lists with R."),
+    (&["timeout", "timed out"], "NetworkManager gave up waiting; the server or access point did not answer."),
+    (&["ip configuration", "dhcp"], "No IP address was obtained (DHCP did not answer)."),
+];
+
+#[derive(Clone)]
+pub enum Target {
+    Profile(String),
+    Network(String),
+}
+
+pub enum Outcome {
+    Connected,
+    SecretRequired(Vec<Field>),
+    Failed(String),
+}
+
+#[derive(Clone, Copy)]
+pub enum Kind {
+    Secret(&'static str),
+    Property(&'static str),
+    VpnData(&'static str),
+}
+
+pub struct Field {
+    pub label: &'static str,
+    pub kind: Kind,
+    pub masked: bool,
+    pub input: TextInput,
+    original: String,
+}
+
+impl Field {
+    fn secret(label: &'static str, key: &'static str) -> Field {
+        Field { label, kind: Kind::Secret(key), masked: true, input: TextInput::default(), original: String::new() }
+    }
+
+    fn plain(label: &'static str, kind: Kind, value: &str) -> Field {
+        let mut input = TextInput::default();
+        input.set(value);
+        Field { label, kind, masked: false, input, original: value.to_string() }
+    }
+
+    pub fn entry(label: &'static str, key: &'static str, masked: bool, value: &str) -> Field {
+        Field { masked, ..Field::plain(label, Kind::Property(key), value) }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self.kind { Kind::Secret(key) | Kind::Property(key) | Kind::VpnData(key) => key }
+    }
+
+    pub fn saved(&self) -> bool {
+        !matches!(self.kind, Kind::Secret(_))
+    }
+
+    pub fn answer(&self) -> Option<(Kind, String)> {
+        let value = self.input.value();
+        (!value.is_empty() && value != self.original).then(|| (self.kind, value.to_string()))
+    }
+}
+
+impl Target {
+    pub fn name(&self) -> &str {
+        match self { Target::Profile(name) | Target::Network(name) => name }
+    }
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::new("nmcli");
+        match self {
+            Target::Profile(name) => cmd.args(["--wait", "90", "con", "up", "id", name]),
+            Target::Network(name) => cmd.args(["--wait", "90", "dev", "wifi", "connect", name]),
+        };
+        cmd
+    }
+}
+
+fn secret_required(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("secrets were required") || error.contains("no-secrets") || (error.contains("password") && error.contains("required"))
+}
+
+fn error_text(out: &Output) -> String {
+    let text = String::from_utf8_lossy(&out.stderr);
+    let text = text.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("").trim().trim_start_matches("Error: ");
+    if text.is_empty() { format!("nmcli exited with {}", out.status) } else { text.to_string() }
+}
+
+fn outcome(target: &Target, output: io::Result<Output>) -> Outcome {
+    match output {
+        Ok(out) if out.status.success() => Outcome::Connected,
+        Ok(out) => {
+            let error = error_text(&out);
+            if secret_required(&error) { Outcome::SecretRequired(fields(target)) } else { Outcome::Failed(error) }
+        }
+        Err(e) => Outcome::Failed(format!("nmcli: {}", e)),
+    }
+}
+
+fn properties(name: &str) -> HashMap<String, String> {
+    let Ok(out) = Command::new("nmcli").args(["-t", "con", "show", "id", name]).output() else { return HashMap::new() };
+    String::from_utf8_lossy(&out.stdout).lines().filter_map(|l| l.split_once(':')).map(|(k, v)| (k.to_string(), v.replace("\\:", ":"))).collect()
+}
+
+fn data_value(data: &str, key: &str) -> String {
+    data.split(", ").find_map(|kv| kv.split_once(" = ").filter(|(k, _)| k.trim() == key).map(|(_, v)| v.trim().to_string())).unwrap_or_default()
+}
+
+pub fn fields(target: &Target) -> Vec<Field> {
+    let Target::Profile(name) = target else { return vec![Field::secret("Password", "802-11-wireless-security.psk")] };
+    let props = properties(name);
+    let get = |key: &str| props.get(key).map(String::as_str).unwrap_or("");
+    let data = get("vpn.data");
+    let user = || Field::plain("Username", Kind::Property("vpn.user-name"), get("vpn.user-name"));
+    match (get("connection.type"), get("vpn.service-type").rsplit('.').next().unwrap_or("")) {
+        ("802-11-wireless", _) if get("802-11-wireless-security.key-mgmt") == "wpa-eap" => vec![Field::plain("Identity", Kind::Property("802-1x.identity"), get("802-1x.identity")), Field::secret("Password", "802-1x.password")],
+        ("802-11-wireless", _) => vec![Field::secret("Password", "802-11-wireless-security.psk")],
+        ("wireguard", _) => vec![Field::secret("Private key", "wireguard.private-key")],
+        (_, "openvpn") => {
+            let kind = data_value(data, "connection-type");
+            let mut fields = Vec::new();
+            if kind.contains("password") {
+                fields.push(Field::plain("Username", Kind::VpnData("username"), &data_value(data, "username")));
+                fields.push(Field::secret("Password", "vpn.secrets.password"));
+            }
+            if kind.is_empty() || kind.contains("tls") { fields.push(Field::secret("Key passphrase", "vpn.secrets.cert-pass")); }
+            if !data_value(data, "static-challenge").is_empty() { fields.push(Field::secret("One-time token", "vpn.secrets.challenge-response")); }
+            fields
+        }
+        (_, "vpnc") => vec![
+            Field::plain("Username", Kind::VpnData("Xauth username"), &data_value(data, "Xauth username")),
+            Field::secret("Password", "vpn.secrets.Xauth password"),
+            Field::secret("Group password", "vpn.secrets.IPSec secret"),
+        ],
+        (_, "openconnect") => vec![
+            Field { masked: false, ..Field::secret("Username", "vpn.secrets.form:main:username") },
+            Field::secret("Password", "vpn.secrets.form:main:password"),
+            Field::secret("One-time token", "vpn.secrets.form:main:secondary_password"),
+        ],
+        (_, "strongswan") if matches!(data_value(data, "method").as_str(), "key" | "agent" | "smartcard") => vec![Field::secret("Key passphrase", "vpn.secrets.password")],
+        (_, "fortisslvpn") => vec![user(), Field::secret("Password", "vpn.secrets.password"), Field::secret("One-time token", "vpn.secrets.otp")],
+        _ => vec![user(), Field::secret("Password", "vpn.secrets.password")],
+    }
+}
+
+pub fn activate(target: &Target) -> Outcome {
+    outcome(target, target.command().stdin(Stdio::null()).output())
+}
+
+fn passwd_file(secrets: &[(&str, &str)]) -> io::Result<PathBuf> {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from).unwrap_or_else(std::env::temp_dir);
+    for attempt in 0..100 {
+        let path = dir.join(format!("dashnet-secrets-{}-{}", std::process::id(), attempt));
+        let mut file = match OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        let written = secrets.iter().try_for_each(|(key, value)| writeln!(file, "{}:{}", key, value));
+        if let Err(e) = written { let _ = fs::remove_file(&path); return Err(e); }
+        return Ok(path);
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("no free secrets file name in {}", dir.display())))
+}
+
+fn modify(name: &str, property: &str, value: &str) -> Result<(), String> {
+    let out = Command::new("nmcli").args(["con", "modify", "id", name, property, value]).output().map_err(|e| format!("nmcli: {}", e))?;
+    if out.status.success() { Ok(()) } else { Err(format!("cannot set {}: {}", property, error_text(&out))) }
+}
+
+pub fn activate_with(target: &Target, answers: &[(Kind, String)]) -> Outcome {
+    let secrets: Vec<(&str, &str)> = answers.iter().filter_map(|(kind, value)| match kind { Kind::Secret(key) => Some((*key, value.as_str())), _ => None }).collect();
+    let output = match target {
+        Target::Network(_) => {
+            let child = target.command().arg("--ask").stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn();
+            child.and_then(|mut child| {
+                if let Some(mut stdin) = child.stdin.take() { for (_, value) in &secrets { writeln!(stdin, "{}", value)?; } }
+                child.wait_with_output()
+            })
+        }
+        Target::Profile(name) => {
+            for (kind, value) in answers {
+                let result = match kind {
+                    Kind::Property(property) => modify(name, property, value),
+                    Kind::VpnData(key) => modify(name, "+vpn.data", &format!("{} = {}", key, value)),
+                    Kind::Secret(_) => Ok(()),
+                };
+                if let Err(e) = result { return Outcome::Failed(e); }
+            }
+            let path = match passwd_file(&secrets) { Ok(path) => path, Err(e) => return Outcome::Failed(format!("cannot write the secrets file: {}", e)) };
+            let output = target.command().arg("passwd-file").arg(&path).stdin(Stdio::null()).output();
+            let _ = fs::remove_file(&path);
+            output
+        }
+    };
+    match outcome(target, output) {
+        Outcome::SecretRequired(_) => Outcome::Failed("the credentials were rejected".to_string()),
+        other => other,
+    }
+}
+
+pub fn deactivate(name: &str) -> Result<(), String> {
+    let out = Command::new("nmcli").args(["con", "down", "id", name]).stdin(Stdio::null()).output().map_err(|e| format!("nmcli: {}", e))?;
+    if out.status.success() { Ok(()) } else { Err(error_text(&out)) }
+}
+
+fn nmcli_rows(args: &[&str]) -> Vec<Vec<String>> {
+    Command::new("nmcli").args(args).output().map(|out| String::from_utf8_lossy(&out.stdout).lines().map(split_escaped).collect()).unwrap_or_default()
+}
+
+fn device(target: &Target) -> Option<(String, String)> {
+    nmcli_rows(&["-t", "-f", "DEVICE,TYPE,STATE,CONNECTION", "dev"]).into_iter().filter(|f| f.len() >= 4).find(|f| match target {
+        Target::Profile(name) => f[3] == *name,
+        Target::Network(name) => f[1] == "wifi" && (f[3] == *name || f[2].starts_with("connecting")),
+    }).map(|f| (f[0].clone(), f[2].clone()))
+}
+
+pub fn stage(target: &Target) -> String {
+    if let Some((dev, state)) = device(target) { return format!("{} on {}", state, dev); }
+    nmcli_rows(&["-t", "-f", "NAME,STATE", "con", "show", "--active"]).into_iter()
+        .find(|f| f.len() >= 2 && f[0] == target.name()).map(|f| f[1].clone())
+        .unwrap_or_else(|| "waiting for NetworkManager".to_string())
+}
+
+pub fn cancel(target: &Target) -> Result<(), String> {
+    let args: Vec<String> = match (target, device(target)) {
+        (Target::Network(_), Some((dev, _))) => vec!["dev".into(), "disconnect".into(), dev],
+        _ => vec!["con".into(), "down".into(), "id".into(), target.name().to_string()],
+    };
+    let out = Command::new("nmcli").args(&args).stdin(Stdio::null()).output().map_err(|e| format!("nmcli: {}", e))?;
+    if out.status.success() { Ok(()) } else { Err(error_text(&out)) }
+}
+
+pub fn hint(error: &str) -> Option<&'static str> {
+    let error = error.to_lowercase();
+    HINTS.iter().find(|(words, _)| words.iter().any(|w| error.contains(w))).map(|(_, hint)| *hint)
+}
@@ -0,0 +1,268 @@
+use std::io::{self, Read, Write};
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixStream};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub const NAME: &str = "org.dashnet";
+pub const PATH: &str = "/org/dashnet";
+pub const INTERFACE: &str = "org.dashnet.Events";
+pub const STATUS: &str = "org.dashnet.Status";
+
+const BUS: [&str; 3] = ["org.freedesktop.DBus", "/org/freedesktop/DBus", "org.freedesktop.DBus"];
+
+const INTROSPECTION: &str = r#"<node>
+ <interface name="org.dashnet.Status">
+  <method name="GetSsid"><arg type="s" direction="out"/></method>
+  <method name="GetVpns"><arg type="as" direction="out"/></method>
+  <method name="GetRates"><arg type="s" direction="out"/></method>
+ </interface>
+ <interface name="org.dashnet.Events">
+  <signal name="VpnUp"><arg name="name" type="s"/></signal>
+  <signal name="VpnDown"><arg name="name" type="s"/></signal>
+  <signal name="SsidChanged"><arg name="old" type="s"/><arg name="new" type="s"/></signal>
+  <signal name="BandwidthAlert"><arg name="iface" type="s"/><arg name="mbits" type="d"/><arg name="percent" type="d"/></signal>
+ </interface>
+ <interface name="org.freedesktop.DBus.Introspectable">
+  <method name="Introspect"><arg type="s" direction="out"/></method>
+ </interface>
+ <interface name="org.freedesktop.DBus.Peer">
+  <method name="Ping"/>
+ </interface>
+</node>
+"#;
+
+pub enum Arg<'a> {
+    Str(&'a str),
+    Double(f64),
+    U32(u32),
+    List(&'a [String]),
+}
+
+enum Field<'a> {
+    Path(&'a str),
+    Str(u8, &'a str),
+    Serial(u32),
+}
+
+#[derive(Default)]
+pub struct Status {
+    pub ssid: String,
+    pub vpns: Vec<String>,
+    pub rates: String,
+}
+
+#[derive(Default)]
+struct Call {
+    kind: u8,
+    flags: u8,
+    serial: u32,
+    path: String,
+    interface: String,
+    member: String,
+    sender: String,
+    reply_serial: u32,
+    body: Vec<u8>,
+}
+
+pub struct Service {
+    stream: Arc<Mutex<UnixStream>>,
+    serial: Arc<AtomicU32>,
+    status: Arc<Mutex<Status>>,
+    pub owner: bool,
+}
+
+#[derive(Default)]
+struct Buf(Vec<u8>);
+
+impl Buf {
+    fn pad(&mut self, n: usize) {
+        while !self.0.len().is_multiple_of(n) { self.0.push(0); }
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.pad(4);
+        self.0.extend(v.to_le_bytes());
+    }
+
+    fn str(&mut self, s: &str) {
+        self.u32(s.len() as u32);
+        self.0.extend(s.as_bytes());
+        self.0.push(0);
+    }
+
+    fn sig(&mut self, s: &str) {
+        self.0.push(s.len() as u8);
+        self.0.extend(s.as_bytes());
+        self.0.push(0);
+    }
+
+    fn arg(&mut self, arg: &Arg) {
+        match arg {
+            Arg::Str(s) => self.str(s),
+            Arg::Double(d) => { self.pad(8); self.0.extend(d.to_le_bytes()); }
+            Arg::U32(v) => self.u32(*v),
+            Arg::List(items) => {
+                self.u32(0);
+                let start = self.0.len();
+                for item in items.iter() { self.str(item); }
+                let len = (self.0.len() - start) as u32;
+                self.0[start - 4..start].copy_from_slice(&len.to_le_bytes());
+            }
+        }
+    }
+}
+
+fn signature(args: &[Arg]) -> String {
+    args.iter().map(|a| match a { Arg::Str(_) => "s", Arg::Double(_) => "d", Arg::U32(_) => "u", Arg::List(_) => "as" }).collect()
+}
+
+fn message(kind: u8, serial: u32, fields: &[Field], args: &[Arg]) -> Vec<u8> {
+    let mut body = Buf::default();
+    for arg in args { body.arg(arg); }
+    let signature = signature(args);
+    let mut m = Buf(vec![b'l', kind, if kind == 4 { 1 } else { 0 }, 1]);
+    m.u32(body.0.len() as u32);
+    m.u32(serial);
+    m.u32(0);
+    let start = m.0.len();
+    let typed = (!signature.is_empty()).then_some(Field::Str(8, &signature));
+    for field in fields.iter().chain(typed.as_ref()) {
+        m.pad(8);
+        match field {
+            Field::Path(path) => { m.0.push(1); m.sig("o"); m.str(path); }
+            Field::Str(8, sig) => { m.0.push(8); m.sig("g"); m.sig(sig); }
+            Field::Str(code, s) => { m.0.push(*code); m.sig("s"); m.str(s); }
+            Field::Serial(serial) => { m.0.push(5); m.sig("u"); m.u32(*serial); }
+        }
+    }
+    let len = (m.0.len() - start) as u32;
+    m.0[start - 4..start].copy_from_slice(&len.to_le_bytes());
+    m.pad(8);
+    m.0.extend(body.0);
+    m.0
+}
+
+fn parse(header: &[u8], body: Vec<u8>) -> Call {
+    let big = header[0] == b'B';
+    let u32_at = |at: usize| header.get(at..at + 4).map_or(0, |b| { let b = [b[0], b[1], b[2], b[3]]; if big { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) } });
+    let mut call = Call { kind: header[1], flags: header[2], serial: u32_at(8), body, ..Call::default() };
+    let end = 16 + u32_at(12) as usize;
+    let mut at = 16;
+    while at < end.min(header.len()) {
+        let code = header[at];
+        let sig_len = header.get(at + 1).copied().unwrap_or(0) as usize;
+        let sig = header.get(at + 2..at + 2 + sig_len).unwrap_or_default().to_vec();
+        at += 3 + sig_len;
+        match sig.as_slice() {
+            b"s" | b"o" => {
+                at = at.next_multiple_of(4);
+                let len = u32_at(at) as usize;
+                let value = String::from_utf8_lossy(header.get(at + 4..at + 4 + len).unwrap_or_default()).to_string();
+                match code { 1 => call.path = value, 2 => call.interface = value, 3 => call.member = value, 7 => call.sender = value, _ => {} }
+                at += 5 + len;
+            }
+            b"g" => at += 2 + header.get(at).copied().unwrap_or(0) as usize,
+            b"u" => {
+                at = at.next_multiple_of(4);
+                if code == 5 { call.reply_serial = u32_at(at); }
+                at += 4;
+            }
+            _ => break,
+        }
+        at = at.next_multiple_of(8);
+    }
+    call
+}
+
+fn read_message(stream: &mut UnixStream) -> io::Result<Call> {
+    let mut fixed = [0u8; 16];
+    stream.read_exact(&mut fixed)?;
+    let number = |b: &[u8]| { let b = [b[0], b[1], b[2], b[3]]; (if fixed[0] == b'B' { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) }) as usize };
+    let (body_len, fields_len) = (number(&fixed[4..8]), number(&fixed[12..16]));
+    let mut header = fixed.to_vec();
+    header.resize((16 + fields_len).next_multiple_of(8), 0);
+    stream.read_exact(&mut header[16..])?;
+    let mut body = vec![0u8; body_len];
+    stream.read_exact(&mut body)?;
+    Ok(parse(&header, body))
+}
+
+fn connect() -> io::Result<UnixStream> {
+    let address = std::env::var("DBUS_SESSION_BUS_ADDRESS").map_err(|_| io::Error::new(io::ErrorKind::NotFound, "no session bus"))?;
+    for entry in address.split(';').filter_map(|a| a.strip_prefix("unix:")) {
+        let value = |key: &str| entry.split(',').find_map(|kv| kv.strip_prefix(key)).map(str::to_string);
+        if let Some(path) = value("path=") { return UnixStream::connect(path); }
+        if let Some(name) = value("abstract=") { return UnixStream::connect_addr(&SocketAddr::from_abstract_name(name.as_bytes())?); }
+    }
+    Err(io::Error::new(io::ErrorKind::Unsupported, format!("unsupported bus address {}", address)))
+}
+
+fn authenticate(stream: &mut UnixStream) -> io::Result<()> {
+    let uid: String = unsafe { libc::geteuid() }.to_string().bytes().map(|b| format!("{:02x}", b)).collect();
+    stream.write_all(format!("\0AUTH EXTERNAL {}\r\n", uid).as_bytes())?;
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    while !line.ends_with(b"\r\n") {
+        stream.read_exact(&mut byte)?;
+        line.push(byte[0]);
+    }
+    if !line.starts_with(b"OK ") { return Err(io::Error::new(io::ErrorKind::PermissionDenied, String::from_utf8_lossy(&line).trim().to_string())); }
+    stream.write_all(b"BEGIN\r\n")
+}
+
+fn answer(call: &Call, serial: u32, status: &Mutex<Status>) -> Result<Vec<u8>, String> {
+    let status = status.lock().map_err(|e| e.to_string())?;
+    let fields = [Field::Serial(call.serial), Field::Str(6, &call.sender)];
+    if call.path != PATH { return Err(format!("no object at {}", call.path)); }
+    Ok(match (call.interface.as_str(), call.member.as_str()) {
+        ("org.freedesktop.DBus.Introspectable", "Introspect") => message(2, serial, &fields, &[Arg::Str(INTROSPECTION)]),
+        ("org.freedesktop.DBus.Peer", "Ping") => message(2, serial, &fields, &[]),
+        (STATUS | "", "GetSsid") => message(2, serial, &fields, &[Arg::Str(&status.ssid)]),
+        (STATUS | "", "GetVpns") => message(2, serial, &fields, &[Arg::List(&status.vpns)]),
+        (STATUS | "", "GetRates") => message(2, serial, &fields, &[Arg::Str(&status.rates)]),
+        (interface, member) => return Err(format!("no method {} on interface '{}'", member, interface)),
+    })
+}
+
+fn serve(mut reader: UnixStream, stream: Arc<Mutex<UnixStream>>, serial: Arc<AtomicU32>, status: Arc<Mutex<Status>>) {
+    while let Ok(call) = read_message(&mut reader) {
+        if call.kind != 1 || call.flags & 1 != 0 { continue; }
+        let id = serial.fetch_add(1, Ordering::Relaxed);
+        let reply = match answer(&call, id, &status) {
+            Ok(reply) => reply,
+            Err(e) => message(3, id, &[Field::Str(4, "org.freedesktop.DBus.Error.UnknownMethod"), Field::Serial(call.serial), Field::Str(6, &call.sender)], &[Arg::Str(&e)]),
+        };
+        let Ok(mut stream) = stream.lock() else { return };
+        if stream.write_all(&reply).is_err() { return; }
+    }
+}
+
+impl Service {
+    pub fn start() -> io::Result<Service> {
+        let mut stream = connect()?;
+        authenticate(&mut stream)?;
+        let bus = |member| [Field::Path(BUS[1]), Field::Str(2, BUS[2]), Field::Str(3, member), Field::Str(6, BUS[0])];
+        stream.write_all(&message(1, 1, &bus("Hello"), &[]))?;
+        stream.write_all(&message(1, 2, &bus("RequestName"), &[Arg::Str(NAME), Arg::U32(4)]))?;
+        let owner = loop {
+            let reply = read_message(&mut stream)?;
+            if reply.reply_serial == 2 { break reply.kind == 2 && matches!(reply.body.get(..4), Some([1 | 4, 0, 0, 0])); }
+        };
+        let reader = stream.try_clone()?;
+        let service = Service { stream: Arc::new(Mutex::new(stream)), serial: Arc::new(AtomicU32::new(3)), status: Arc::default(), owner };
+        let (stream, serial, status) = (service.stream.clone(), service.serial.clone(), service.status.clone());
+        thread::spawn(move || serve(reader, stream, serial, status));
+        Ok(service)
+    }
+
+    pub fn update(&self, status: Status) {
+        if let Ok(mut current) = self.status.lock() { *current = status; }
+    }
+
+    pub fn emit(&self, signal: &str, args: &[Arg]) {
+        let message = message(4, self.serial.fetch_add(1, Ordering::Relaxed), &[Field::Path(PATH), Field::Str(2, INTERFACE), Field::Str(3, signal)], args);
+        if let Ok(mut stream) = self.stream.lock() { let _ = stream.write_all(&message); }
+    }
+}
@@ -0,0 +1,18 @@
+use std::os::unix::fs::PermissionsExt;
+
+const TOOLS: [&str; 16] = ["nmcli", "ip", "notify-send", "wg", "tc", "ping", "curl", "upnpc", "dhclient", "snmpget", "nm-connection-editor", "wpa_cli", "journalctl", "openssl", "iperf3", "nft"];
+
+pub fn in_path(program: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|paths| std::env::split_paths(&paths).any(|dir| {
+        dir.join(program).metadata().is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    }))
+}
+
+pub fn missing() -> Vec<&'static str> {
+    TOOLS.iter().copied().filter(|t| !in_path(t)).collect()
+}
+
+pub fn networkmanager_running() -> bool {
+    std::process::Command::new("nmcli").args(["-t", "-f", "RUNNING", "general"]).output()
+        .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).trim() == "running")
+}
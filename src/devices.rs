@@ -0,0 +1,42 @@
+use std::fs;
+use std::path::Path;
+
+pub struct Device {
+    pub name: String,
+    pub kind: &'static str,
+    pub operstate: String,
+    pub carrier: bool,
+    pub speed: Option<u32>,
+    pub mtu: String,
+    pub mac: String,
+}
+
+fn read(path: &Path) -> String {
+    fs::read_to_string(path).map(|s| s.trim().to_string()).unwrap_or_default()
+}
+
+fn kind(dir: &Path) -> &'static str {
+    if dir.join("wireless").exists() || dir.join("phy80211").exists() { "wifi" }
+    else if dir.join("bridge").exists() { "bridge" }
+    else if dir.join("bonding").exists() { "bond" }
+    else if !dir.join("device").exists() { "virtual" }
+    else { "ethernet" }
+}
+
+pub fn list() -> Vec<Device> {
+    let Ok(entries) = fs::read_dir("/sys/class/net") else { return Vec::new() };
+    let mut devices: Vec<Device> = entries.flatten().map(|e| {
+        let dir = e.path();
+        Device {
+            name: e.file_name().to_string_lossy().to_string(),
+            kind: kind(&dir),
+            operstate: read(&dir.join("operstate")),
+            carrier: read(&dir.join("carrier")) == "1",
+            speed: read(&dir.join("speed")).parse::<i64>().ok().filter(|s| *s > 0).map(|s| s as u32),
+            mtu: read(&dir.join("mtu")),
+            mac: read(&dir.join("address")),
+        }
+    }).filter(|d| d.name != "lo").collect();
+    devices.sort_by(|a, b| a.name.cmp(&b.name));
+    devices
+}
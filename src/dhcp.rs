@@ -0,0 +1,53 @@
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn nm_manages(iface: &str) -> bool {
+    Command::new("nmcli").args(["-t", "-f", "DEVICE,STATE", "dev"]).output().map(|out| {
+        String::from_utf8_lossy(&out.stdout).lines().any(|l| l.split(':').next() == Some(iface) && !l.ends_with(":unmanaged"))
+    }).unwrap_or(false)
+}
+
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let out = Command::new(program).args(args).stdin(Stdio::null()).output().map_err(|e| format!("{}: {}", program, e))?;
+    if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+fn format_duration(secs: u64) -> String {
+    if secs >= 3600 { format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60) } else { format!("{}m{:02}s", secs / 60, secs % 60) }
+}
+
+pub fn renew(iface: &str, release: bool) -> Result<String, String> {
+    if nm_manages(iface) {
+        if release {
+            run("nmcli", &["device", "disconnect", iface])?;
+            run("nmcli", &["device", "connect", iface])?;
+            Ok(format!("released and renewed {} via NetworkManager", iface))
+        } else {
+            run("nmcli", &["device", "reapply", iface])?;
+            Ok(format!("renewed {} via NetworkManager", iface))
+        }
+    } else {
+        if release { run("dhclient", &["-r", iface])?; }
+        run("dhclient", &["-1", iface])?;
+        Ok(format!("{} {} via dhclient", if release { "released and renewed" } else { "renewed" }, iface))
+    }
+}
+
+pub fn lease(iface: &str) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let Ok(out) = Command::new("nmcli").args(["-t", "-f", "DHCP4", "dev", "show", iface]).output() else { return fields };
+    let s = String::from_utf8_lossy(&out.stdout);
+    let options: Vec<(&str, &str)> = s.lines().filter_map(|l| l.split_once(':')).filter_map(|(_, v)| v.split_once(" = ")).collect();
+    let get = |key: &str| options.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string());
+    for (key, label) in [("ip_address", "Address"), ("subnet_mask", "Netmask"), ("routers", "Gateway"), ("domain_name_servers", "DNS"), ("dhcp_server_identifier", "DHCP server")] {
+        if let Some(v) = get(key) { fields.push((label.to_string(), v)); }
+    }
+    if let Some(secs) = get("dhcp_lease_time").and_then(|v| v.parse::<u64>().ok()) {
+        fields.push(("Lease time".to_string(), format_duration(secs)));
+    }
+    if let Some(expiry) = get("expiry").and_then(|v| v.parse::<u64>().ok()) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        fields.push(("Expires in".to_string(), format_duration(expiry.saturating_sub(now))));
+    }
+    fields
+}
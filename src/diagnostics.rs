@@ -0,0 +1,161 @@
+use std::fmt::Write;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write as _};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::clock;
+
+const SECTIONS: [(&str, &str, &[&str]); 9] = [
+    ("Interfaces", "ip", &["addr", "show"]),
+    ("IPv4 routes", "ip", &["-4", "route", "show", "table", "all"]),
+    ("IPv6 routes", "ip", &["-6", "route", "show"]),
+    ("Policy rules", "ip", &["rule", "show"]),
+    ("DNS (resolvectl)", "resolvectl", &["status"]),
+    ("NetworkManager devices", "nmcli", &["-f", "DEVICE,TYPE,STATE,CONNECTION", "device"]),
+    ("NetworkManager connections", "nmcli", &["-f", "NAME,TYPE,DEVICE,AUTOCONNECT", "connection", "show"]),
+    ("NetworkManager general", "nmcli", &["general", "status"]),
+    ("Link statistics", "ip", &["-s", "link", "show"]),
+];
+
+const TOOLS: [(&str, &str); 8] = [
+    ("nmcli", "--version"), ("ip", "-V"), ("curl", "--version"), ("wg", "--version"),
+    ("upnpc", "--help"), ("snmpget", "--version"), ("dhclient", "--version"), ("notify-send", "--version"),
+];
+
+fn is_mac(token: &str) -> bool {
+    let parts: Vec<&str> = token.split(':').collect();
+    parts.len() == 6 && parts.iter().all(|p| p.len() == 2 && p.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn is_uuid(token: &str) -> bool {
+    let parts: Vec<&str> = token.split('-').collect();
+    parts.iter().map(|p| p.len()).eq([8, 4, 4, 4, 12]) && parts.iter().all(|p| p.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn redact_token(token: &str) -> String {
+    if is_mac(token) { return format!("{}:xx:xx:xx", &token[..8]); }
+    if is_uuid(token) { return format!("{}-xxxx-xxxx-xxxx-xxxxxxxxxxxx", &token[..8]); }
+    if let Ok(ip) = token.parse::<Ipv4Addr>() {
+        if ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified() || ip.is_broadcast() || ip.octets()[0] >= 224 { return token.to_string(); }
+        let o = ip.octets();
+        return format!("{}.{}.x.x", o[0], o[1]);
+    }
+    if let Ok(ip) = token.parse::<Ipv6Addr>() {
+        let s = ip.segments();
+        if (s[0] & 0xffc0) == 0xfe80 { return format!("{:x}::xxxx", s[0]); }
+        if (s[0] & 0xfe00) == 0xfc00 { return format!("{:x}xx:xxxx:xxxx::", s[0] >> 8); }
+        if (s[0] & 0xe000) != 0x2000 { return token.to_string(); }
+        return format!("{:x}:{:x}:xxxx::", s[0], s[1]);
+    }
+    token.to_string()
+}
+
+fn mask_ssids(text: &str, ssids: &[String]) -> String {
+    let mut ssids: Vec<&String> = ssids.iter().filter(|s| !s.trim().is_empty()).collect();
+    ssids.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    let mut text = text.to_string();
+    for ssid in ssids {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text.as_str();
+        while let Some(at) = rest.find(ssid.as_str()) {
+            let (before, after) = (rest[..at].chars().next_back(), rest[at + ssid.len()..].chars().next());
+            let bounded = !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric);
+            out.push_str(&rest[..at]);
+            out.push_str(if bounded { "<ssid>" } else { ssid });
+            rest = &rest[at + ssid.len()..];
+        }
+        out.push_str(rest);
+        text = out;
+    }
+    text
+}
+
+pub fn redact(text: &str, ssids: &[String]) -> String {
+    let text = mask_ssids(text, ssids);
+    let mut out = String::with_capacity(text.len());
+    let mut token = String::new();
+    let flush = |token: &mut String, out: &mut String| {
+        let trimmed = token.trim_end_matches(['.', ':', '-']);
+        out.push_str(&redact_token(trimmed));
+        out.push_str(&token[trimmed.len()..]);
+        token.clear();
+    };
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, ':' | '.' | '-') { token.push(c); } else { flush(&mut token, &mut out); out.push(c); }
+    }
+    flush(&mut token, &mut out);
+    out
+}
+
+fn run(program: &str, args: &[&str]) -> String {
+    match Command::new(program).args(args).output() {
+        Ok(out) => format!("{}{}", String::from_utf8_lossy(&out.stdout), String::from_utf8_lossy(&out.stderr)),
+        Err(e) => format!("unavailable: {}\n", e),
+    }
+}
+
+pub fn write_bundle(events: Vec<String>, ssids: Vec<String>) -> Result<PathBuf, String> {
+    let now = clock::epoch_now();
+    let mut report = format!("DashNet {} diagnostics bundle\nGenerated at epoch {}\n", env!("CARGO_PKG_VERSION"), now);
+    for (title, program, args) in SECTIONS {
+        let _ = write!(report, "\n=== {} ({} {}) ===\n{}", title, program, args.join(" "), run(program, args));
+    }
+    let _ = write!(report, "\n=== /etc/resolv.conf ===\n{}", fs::read_to_string("/etc/resolv.conf").unwrap_or_else(|e| format!("unavailable: {}\n", e)));
+    report.push_str("\n=== External tools ===\n");
+    for (program, flag) in TOOLS {
+        let version = run(program, &[flag]).lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim().to_string();
+        let _ = writeln!(report, "{:<12} {}", program, version);
+    }
+    report.push_str("\n=== Recent DashNet events ===\n");
+    if events.is_empty() { report.push_str("(none)\n"); }
+    for line in events { let _ = writeln!(report, "{}", line); }
+    let text = redact(&report, &ssids);
+    for attempt in 0..100 {
+        let name = if attempt == 0 { format!("dashnet-diagnostics-{}.txt", now) } else { format!("dashnet-diagnostics-{}-{}.txt", now, attempt) };
+        let path = std::env::temp_dir().join(name);
+        let mut file = match OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("cannot write {}: {}", path.display(), e)),
+        };
+        file.write_all(text.as_bytes()).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+        return Ok(path);
+    }
+    Err(format!("no free file name in {}", std::env::temp_dir().display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_identifiers() {
+        assert_eq!(redact("link/ether 3c:52:82:1a:2b:3c brd ff:ff:ff:ff:ff:ff", &[]), "link/ether 3c:52:82:xx:xx:xx brd ff:ff:ff:xx:xx:xx");
+        assert_eq!(redact("uuid 6f1c2a3b-1234-4cde-8f00-0123456789ab.", &[]), "uuid 6f1c2a3b-xxxx-xxxx-xxxx-xxxxxxxxxxxx.");
+    }
+
+    #[test]
+    fn masks_public_ipv4_only() {
+        assert_eq!(redact("inet 203.0.113.7/24 via 192.168.1.1", &[]), "inet 203.0.x.x/24 via 192.168.1.1");
+        assert_eq!(redact("127.0.0.1 169.254.3.4 224.0.0.251", &[]), "127.0.0.1 169.254.3.4 224.0.0.251");
+    }
+
+    #[test]
+    fn masks_ipv6_that_identify_a_host_or_site() {
+        assert_eq!(redact("inet6 2a01:e0a:1f2:3450::12/64", &[]), "inet6 2a01:e0a:xxxx::/64");
+        assert_eq!(redact("inet6 fe80::3e52:82ff:fe1a:2b3c/64", &[]), "inet6 fe80::xxxx/64");
+        assert_eq!(redact("inet6 febf::1", &[]), "inet6 febf::xxxx");
+        assert_eq!(redact("inet6 fd12:3456:789a:1::5/64", &[]), "inet6 fdxx:xxxx:xxxx::/64");
+        assert_eq!(redact("inet6 fc00::1", &[]), "inet6 fcxx:xxxx:xxxx::");
+        assert_eq!(redact("::1 ff02::1 fec0::1", &[]), "::1 ff02::1 fec0::1");
+    }
+
+    #[test]
+    fn masks_ssids_as_whole_words() {
+        let ssids = vec!["Home".to_string(), "Home 5G".to_string(), " ".to_string()];
+        assert_eq!(redact("wlan0  wifi  connected  Home 5G\nHomeOffice Home.", &ssids), "wlan0  wifi  connected  <ssid>\nHomeOffice <ssid>.");
+    }
+}
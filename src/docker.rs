@@ -0,0 +1,91 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::json::Json;
+
+pub struct Container {
+    pub name: String,
+    pub image: String,
+    pub state: String,
+    pub networks: Vec<String>,
+    pub veths: Vec<String>,
+}
+
+fn socket_path() -> String {
+    std::env::var("DOCKER_HOST").ok()
+        .and_then(|h| h.strip_prefix("unix://").map(|p| p.to_string()))
+        .unwrap_or_else(|| "/var/run/docker.sock".to_string())
+}
+
+fn api_get(path: &str) -> Result<Json, String> {
+    let mut stream = UnixStream::connect(socket_path()).map_err(|e| format!("docker socket: {}", e))?;
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+    write!(stream, "GET {} HTTP/1.0\r\nHost: docker\r\n\r\n", path).map_err(|e| e.to_string())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).map_err(|e| e.to_string())?;
+    let (head, body) = response.split_once("\r\n\r\n").ok_or("malformed docker response")?;
+    if !head.starts_with("HTTP/1.0 200") && !head.starts_with("HTTP/1.1 200") {
+        return Err(head.lines().next().unwrap_or("docker API error").to_string());
+    }
+    Json::parse(body).ok_or_else(|| "invalid JSON from docker API".to_string())
+}
+
+fn host_ifindex_map() -> HashMap<String, String> {
+    fs::read_dir("/sys/class/net").map(|entries| entries.flatten().filter_map(|e| {
+        let idx = fs::read_to_string(e.path().join("ifindex")).ok()?;
+        Some((idx.trim().to_string(), e.file_name().to_string_lossy().to_string()))
+    }).collect()).unwrap_or_default()
+}
+
+fn container_veths(pid: u64, host_ifaces: &HashMap<String, String>) -> Vec<String> {
+    let sys = format!("/proc/{}/root/sys/class/net", pid);
+    let mut veths: Vec<String> = fs::read_dir(Path::new(&sys)).map(|entries| entries.flatten()
+        .filter(|e| e.file_name() != "lo")
+        .filter_map(|e| fs::read_to_string(e.path().join("iflink")).ok())
+        .filter_map(|idx| host_ifaces.get(idx.trim()).cloned())
+        .collect()).unwrap_or_default();
+    veths.sort();
+    veths
+}
+
+pub fn list_containers() -> Result<Vec<Container>, String> {
+    let list = api_get("/containers/json")?;
+    let host_ifaces = host_ifindex_map();
+    let mut containers = Vec::new();
+    for c in list.as_array() {
+        let id = c.get("Id").and_then(Json::as_str).unwrap_or_default();
+        let name = c.get("Names").and_then(|n| n.as_array().first()).and_then(Json::as_str).unwrap_or(id).trim_start_matches('/').to_string();
+        let networks = c.get("NetworkSettings").and_then(|n| n.get("Networks")).map(|n| n.entries().iter().map(|(k, _)| k.clone()).collect()).unwrap_or_default();
+        let pid = api_get(&format!("/containers/{}/json", id)).ok().and_then(|d| {
+            let state = d.get("State")?;
+            if state.get("Running").and_then(Json::as_bool) != Some(true) { return None; }
+            state.get("Pid").and_then(Json::as_f64)
+        }).unwrap_or(0.0) as u64;
+        containers.push(Container {
+            name,
+            image: c.get("Image").and_then(Json::as_str).unwrap_or_default().to_string(),
+            state: c.get("Status").and_then(Json::as_str).unwrap_or_default().to_string(),
+            networks,
+            veths: if pid > 0 { container_veths(pid, &host_ifaces) } else { Vec::new() },
+        });
+    }
+    containers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(containers)
+}
+
+pub fn interfaces() -> HashSet<String> {
+    let Ok(networks) = api_get("/networks") else {
+        let names = fs::read_dir("/sys/class/net").into_iter().flatten().flatten().map(|e| e.file_name().to_string_lossy().to_string());
+        return names.filter(|n| n.starts_with("docker") || n.starts_with("br-") || n.starts_with("veth")).collect();
+    };
+    let bridges: Vec<String> = networks.as_array().iter().filter(|n| n.get("Driver").and_then(Json::as_str) == Some("bridge")).filter_map(|n| {
+        let named = n.get("Options").and_then(|o| o.get("com.docker.network.bridge.name")).and_then(Json::as_str).map(str::to_string);
+        named.or_else(|| n.get("Id").and_then(Json::as_str).and_then(|id| id.get(..12)).map(|id| format!("br-{}", id)))
+    }).collect();
+    let ports = bridges.iter().flat_map(|b| fs::read_dir(format!("/sys/class/net/{}/brif", b)).into_iter().flatten().flatten().map(|e| e.file_name().to_string_lossy().to_string()));
+    ports.chain(bridges.iter().cloned()).collect()
+}
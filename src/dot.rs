@@ -0,0 +1,101 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::clock;
+
+pub const MODES: [&str; 3] = ["no", "opportunistic", "yes"];
+const DROP_IN: &str = "/etc/systemd/resolved.conf.d/dashnet-dns-over-tls.conf";
+
+#[derive(Clone)]
+pub struct Link {
+    pub iface: Option<String>,
+    pub mode: String,
+    pub servers: String,
+    pub connection: Option<(String, String)>,
+}
+
+impl Link {
+    pub fn label(&self) -> String {
+        match (&self.iface, &self.connection) {
+            (None, _) => "System default (resolved.conf)".to_string(),
+            (Some(iface), Some((name, _))) => format!("{} ({})", iface, name),
+            (Some(iface), None) => iface.clone(),
+        }
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let out = Command::new(program).args(args).stdin(Stdio::null()).output().map_err(|e| format!("{}: {}", program, e))?;
+    if out.status.success() { Ok(String::from_utf8_lossy(&out.stdout).to_string()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().trim_start_matches("Error: ").to_string()) }
+}
+
+fn per_link(text: &str) -> Vec<(Option<String>, String)> {
+    text.lines().filter_map(|line| {
+        let (head, value) = line.split_once(':')?;
+        let iface = if head.trim() == "Global" { None } else { Some(head.split_once('(')?.1.trim_end_matches(')').to_string()) };
+        Some((iface, value.trim().to_string()))
+    }).collect()
+}
+
+pub fn next(mode: &str) -> &'static str {
+    MODES[(MODES.iter().position(|m| *m == mode).map_or(0, |i| i + 1)) % MODES.len()]
+}
+
+pub fn status() -> Result<Vec<Link>, String> {
+    let modes = per_link(&run("resolvectl", &["dns-over-tls"])?);
+    let servers = per_link(&run("resolvectl", &["dns"]).unwrap_or_default());
+    let active = run("nmcli", &["-t", "-f", "DEVICE,NAME", "con", "show", "--active"]).unwrap_or_default();
+    Ok(modes.into_iter().filter(|(iface, _)| iface.as_deref() != Some("lo")).map(|(iface, mode)| {
+        let connection = iface.as_ref().and_then(|i| active.lines().find_map(|l| l.split_once(':').filter(|(d, _)| d == i).map(|(_, n)| n.replace("\\:", ":"))))
+            .map(|name| { let setting = run("nmcli", &["-g", "connection.dns-over-tls", "con", "show", &name]).map(|s| s.trim().to_string()).unwrap_or_default(); (name, setting) });
+        let servers = servers.iter().find(|(i, _)| *i == iface).map(|(_, s)| s.clone()).unwrap_or_default();
+        Link { iface, mode, servers, connection }
+    }).collect())
+}
+
+pub fn set(link: &Link, mode: &str) -> Result<String, String> {
+    match (&link.iface, &link.connection) {
+        (None, _) => {
+            if unsafe { libc::geteuid() } != 0 { return Err(format!("changing the system default needs root: DashNet writes {} and restarts systemd-resolved", DROP_IN)); }
+            let dir = Path::new(DROP_IN).parent().unwrap_or(Path::new("/"));
+            fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+            fs::write(DROP_IN, format!("[Resolve]\nDNSOverTLS={}\n", mode)).map_err(|e| format!("{}: {}", DROP_IN, e))?;
+            run("systemctl", &["restart", "systemd-resolved"])?;
+            Ok(format!("DNSOverTLS={} written to {} and systemd-resolved restarted", mode, DROP_IN))
+        }
+        (Some(iface), Some((name, _))) => {
+            run("nmcli", &["con", "modify", name, "connection.dns-over-tls", mode])?;
+            run("nmcli", &["dev", "reapply", iface]).or_else(|_| run("nmcli", &["con", "up", name]))?;
+            Ok(format!("connection.dns-over-tls set to {} on '{}' and applied to {}", mode, name, iface))
+        }
+        (Some(iface), None) => {
+            run("resolvectl", &["dns-over-tls", iface, mode])?;
+            Ok(format!("{} switched to {} until the link is reconfigured (not managed by NetworkManager)", iface, mode))
+        }
+    }
+}
+
+pub fn verify() -> Vec<String> {
+    let mut lines = Vec::new();
+    let status = run("resolvectl", &["status"]).unwrap_or_default();
+    let current: Vec<String> = status.lines().filter_map(|l| l.trim().strip_prefix("Current DNS Server:")).map(|s| s.trim().split('#').next().unwrap_or("").to_string()).filter(|s| !s.is_empty()).collect();
+    let configured = status.contains("+DNSOverTLS");
+    lines.push(format!("DNS-over-TLS configured on a link: {}", if configured { "yes" } else { "no" }));
+    lines.push(format!("Current DNS server(s): {}", if current.is_empty() { "none".to_string() } else { current.join(", ") }));
+    let _ = run("resolvectl", &["flush-caches"]);
+    let name = format!("dashnet-{}.example.com", clock::epoch_now());
+    let answered = run("resolvectl", &["query", "--cache=no", &name]).map(|_| "answered").unwrap_or_else(|e| if e.contains("NXDOMAIN") || e.contains("not found") || e.contains("does not exist") { "answered (NXDOMAIN)" } else { "no answer" });
+    lines.push(format!("Uncached query for {}: {}", name, answered));
+    let sockets = run("ss", &["-tnH", "state", "established", "( dport = :853 )"]).unwrap_or_default();
+    let tls: Vec<String> = sockets.lines().filter_map(|l| l.split_whitespace().last().map(str::to_string)).collect();
+    let used = tls.iter().any(|peer| current.iter().any(|server| peer.contains(server.as_str())));
+    lines.push(String::new());
+    lines.push(match (tls.is_empty(), used) {
+        (false, true) => format!("✔ Queries are encrypted: TLS connection to {}", tls.join(", ")),
+        (false, false) => format!("⚠ TLS connection to {} open, but not to the current DNS server", tls.join(", ")),
+        (true, _) if configured => "✖ No TLS connection on port 853 after the query: the server does not offer DNS-over-TLS (opportunistic mode falls back to plain DNS, strict mode fails the query)".to_string(),
+        (true, _) => "✖ Queries go out in plain text (port 53): DNS-over-TLS is off".to_string(),
+    });
+    lines
+}
@@ -0,0 +1,43 @@
+use std::collections::VecDeque;
+
+use crate::{clock, format};
+
+pub struct Event {
+    pub time: i64,
+    pub category: &'static str,
+    pub message: String,
+}
+
+impl Event {
+    pub fn timestamp(&self) -> String {
+        format::time(self.time)
+    }
+}
+
+pub struct EventLog {
+    entries: VecDeque<Event>,
+    capacity: usize,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        EventLog { entries: VecDeque::new(), capacity }
+    }
+
+    pub fn push(&mut self, category: &'static str, message: String) {
+        if self.entries.len() >= self.capacity { self.entries.pop_front(); }
+        self.entries.push_back(Event { time: clock::epoch_now(), category, message });
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Event> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn last(&self) -> Option<&Event> {
+        self.entries.back()
+    }
+}
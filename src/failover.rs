@@ -0,0 +1,21 @@
+use std::process::Command;
+
+fn nmcli(args: &[&str]) -> Result<(), String> {
+    let out = Command::new("nmcli").args(args).output().map_err(|e| format!("nmcli: {}", e))?;
+    if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+fn is_connected(iface: &str) -> bool {
+    Command::new("nmcli").args(["-t", "-f", "DEVICE,STATE", "device"]).output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).lines().any(|l| l == format!("{}:connected", iface)))
+        .unwrap_or(false)
+}
+
+pub fn switch_to(iface: &str, previous: Option<&str>) -> Result<String, String> {
+    if !is_connected(iface) { nmcli(&["device", "connect", iface])?; }
+    nmcli(&["device", "modify", iface, "ipv4.route-metric", "50", "ipv6.route-metric", "50"])?;
+    if let Some(prev) = previous.filter(|p| *p != iface) {
+        let _ = nmcli(&["device", "modify", prev, "ipv4.route-metric", "900", "ipv6.route-metric", "900"]);
+    }
+    Ok(format!("Default route now via {}", iface))
+}
@@ -0,0 +1,23 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config;
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("favorites")
+}
+
+pub fn load() -> Vec<String> {
+    fs::read_to_string(store_path()).unwrap_or_default().lines().filter(|l| !l.is_empty()).map(str::to_string).collect()
+}
+
+pub fn save(favorites: &[String]) -> Result<(), String> {
+    let path = store_path();
+    if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    let text: String = favorites.iter().map(|name| format!("{}\n", name)).collect();
+    fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn sort_first(names: &mut [String], favorites: &[String]) {
+    names.sort_by_key(|name| !favorites.contains(name));
+}
@@ -0,0 +1,98 @@
+use std::sync::OnceLock;
+
+use crate::clock;
+
+const COMMA_LANGUAGES: [&str; 18] = ["fr", "de", "es", "it", "pt", "nl", "ru", "pl", "sv", "da", "fi", "nb", "nn", "cs", "tr", "uk", "el", "ro"];
+const TWELVE_HOUR_LOCALES: [&str; 7] = ["en_US", "en_CA", "en_AU", "en_NZ", "en_PH", "en_IN", "es_MX"];
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+#[derive(Clone, Copy)]
+pub struct Locale {
+    pub decimal: char,
+    pub clock24: bool,
+}
+
+impl Locale {
+    pub fn detect(name: Option<&str>) -> Locale {
+        let env = ["LC_ALL", "LC_NUMERIC", "LANG"].iter().filter_map(|v| std::env::var(v).ok()).find(|v| !v.is_empty());
+        let name = name.map(str::to_string).or(env).unwrap_or_default();
+        let name = name.split(['.', '@']).next().unwrap_or("");
+        let language = name.split('_').next().unwrap_or("");
+        Locale { decimal: if COMMA_LANGUAGES.contains(&language) { ',' } else { '.' }, clock24: !TWELVE_HOUR_LOCALES.contains(&name) }
+    }
+}
+
+pub fn init(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or(Locale { decimal: '.', clock24: true })
+}
+
+pub fn number(value: f64, decimals: usize) -> String {
+    let s = format!("{:.*}", decimals, value);
+    match locale().decimal { '.' => s, c => s.replace('.', &c.to_string()) }
+}
+
+pub fn hour_minute(hour: u32, minute: u32) -> String {
+    if locale().clock24 { format!("{:02}:{:02}", hour, minute) } else { format!("{}:{:02} {}", (hour + 11) % 12 + 1, minute, if hour < 12 { "AM" } else { "PM" }) }
+}
+
+pub fn time(epoch: i64) -> String {
+    let t = clock::local(epoch);
+    if locale().clock24 { format!("{:02}:{:02}:{:02}", t.hour, t.minute, t.second) } else { format!("{}:{:02}:{:02} {}", (t.hour + 11) % 12 + 1, t.minute, t.second, if t.hour < 12 { "AM" } else { "PM" }) }
+}
+
+pub fn date(epoch: i64) -> String {
+    let t = clock::local(epoch);
+    format!("{}-{:02}-{:02}", t.year, t.month, t.day)
+}
+
+pub fn duration(seconds: i64) -> String {
+    let s = seconds.max(0);
+    if s >= 86_400 { format!("{}d {:02}h{:02}m", s / 86_400, (s % 86_400) / 3600, (s % 3600) / 60) }
+    else if s >= 3600 { format!("{}h{:02}m{:02}s", s / 3600, (s % 3600) / 60, s % 60) }
+    else { format!("{}m{:02}s", s / 60, s % 60) }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum RateUnit {
+    Bits,
+    Bytes,
+}
+
+impl RateUnit {
+    pub fn parse(value: &str) -> Option<RateUnit> {
+        match value {
+            "bits" | "Mb/s" => Some(RateUnit::Bits),
+            "bytes" | "MB/s" => Some(RateUnit::Bytes),
+            _ => None,
+        }
+    }
+
+    pub fn toggle(self) -> RateUnit {
+        match self { RateUnit::Bits => RateUnit::Bytes, RateUnit::Bytes => RateUnit::Bits }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self { RateUnit::Bits => "Mb/s", RateUnit::Bytes => "MB/s" }
+    }
+
+    pub fn scale(self, mbits: f64) -> f64 {
+        match self { RateUnit::Bits => mbits, RateUnit::Bytes => mbits / 8.0 }
+    }
+}
+
+pub fn rate(mbits: f64, unit: RateUnit) -> String {
+    format!("{} {}", number(unit.scale(mbits), 2), unit.label())
+}
+
+pub fn bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 { value /= 1024.0; unit += 1; }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{} {}", number(value, 1), UNITS[unit]) }
+}
@@ -0,0 +1,76 @@
+use std::collections::VecDeque;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{format, health, power};
+
+pub const INTERNET: &str = "internet";
+const HISTORY: usize = 60;
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub struct Sample {
+    pub label: String,
+    pub host: String,
+    pub rtt: Option<f64>,
+}
+
+pub struct Series {
+    pub host: String,
+    pub samples: VecDeque<Option<f64>>,
+    pub seen: Instant,
+}
+
+impl Series {
+    pub fn new(host: String) -> Series {
+        Series { host, samples: VecDeque::new(), seen: Instant::now() }
+    }
+
+    pub fn push(&mut self, rtt: Option<f64>) {
+        self.samples.push_back(rtt);
+        if self.samples.len() > HISTORY { self.samples.pop_front(); }
+        self.seen = Instant::now();
+    }
+
+    pub fn describe(&self) -> String {
+        match self.samples.back() {
+            Some(Some(ms)) => format!("{} ms", format::number(*ms, 1)),
+            Some(None) => "no reply".to_string(),
+            None => "…".to_string(),
+        }
+    }
+
+    pub fn sparkline(&self, width: usize) -> String {
+        let max = self.samples.iter().flatten().fold(1.0_f64, |m, v| m.max(*v));
+        self.samples.iter().skip(self.samples.len().saturating_sub(width)).map(|s| match s {
+            Some(ms) => BARS[((ms / max) * (BARS.len() - 1) as f64).round() as usize],
+            None => '×',
+        }).collect()
+    }
+}
+
+pub fn defaults() -> Vec<(String, String)> {
+    let Ok(out) = Command::new("ip").args(["-4", "route", "show", "default"]).output() else { return Vec::new() };
+    let mut gateways: Vec<(String, String)> = String::from_utf8_lossy(&out.stdout).lines().filter_map(|line| {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let after = |key: &str| words.iter().position(|w| *w == key).and_then(|i| words.get(i + 1)).map(|w| w.to_string());
+        Some((after("dev")?, after("via")?))
+    }).collect();
+    gateways.dedup_by(|a, b| a.0 == b.0);
+    gateways
+}
+
+pub fn spawn_monitor(internet: String, interval: u64) -> Receiver<Sample> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || loop {
+        power::wait_while_paused();
+        for (iface, gateway) in defaults() {
+            let rtt = health::probe(Some(&iface), &gateway);
+            if tx.send(Sample { label: iface, host: gateway, rtt }).is_err() { return; }
+        }
+        if tx.send(Sample { label: INTERNET.to_string(), host: internet.clone(), rtt: health::probe(None, &internet) }).is_err() { return; }
+        thread::sleep(Duration::from_secs(interval.max(1)));
+    });
+    rx
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use ratatui::style::Color;
+
+use crate::base64;
+
+const BACKGROUND: [u8; 3] = [16, 16, 16];
+const GRID: [u8; 3] = [58, 58, 58];
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Protocol {
+    Kitty,
+    Sixel,
+}
+
+impl Protocol {
+    pub fn detect(setting: &str) -> Option<Protocol> {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        match setting {
+            "kitty" => Some(Protocol::Kitty),
+            "sixel" => Some(Protocol::Sixel),
+            "auto" if var("TERM") == "xterm-kitty" || !var("KITTY_WINDOW_ID").is_empty() || var("TERM_PROGRAM") == "WezTerm" => Some(Protocol::Kitty),
+            "auto" if var("TERM").contains("sixel") || ["foot", "mlterm", "contour"].iter().any(|t| var("TERM").starts_with(t)) => Some(Protocol::Sixel),
+            _ => None,
+        }
+    }
+
+    pub fn clear(self) -> &'static str {
+        match self { Protocol::Kitty => "\x1b_Ga=d,q=2\x1b\\", Protocol::Sixel => "" }
+    }
+}
+
+pub fn cell_size() -> Option<(u32, u32)> {
+    let mut ws: libc::winsize = unsafe { std::mem::zeroed() };
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut ws) } != 0 || ws.ws_xpixel == 0 || ws.ws_col == 0 { return None; }
+    Some((ws.ws_xpixel as u32 / ws.ws_col as u32, ws.ws_ypixel as u32 / ws.ws_row as u32))
+}
+
+pub fn rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Red => [205, 49, 49],
+        Color::Green => [13, 188, 121],
+        Color::Yellow => [229, 229, 16],
+        Color::Blue => [36, 114, 200],
+        Color::Magenta => [188, 63, 188],
+        Color::Cyan => [17, 168, 205],
+        Color::LightRed => [241, 76, 76],
+        Color::LightGreen => [35, 209, 139],
+        Color::LightYellow => [245, 245, 67],
+        Color::LightBlue => [59, 142, 234],
+        Color::LightMagenta => [214, 112, 214],
+        Color::LightCyan => [41, 184, 219],
+        Color::Rgb(r, g, b) => [r, g, b],
+        _ => [229, 229, 229],
+    }
+}
+
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    fn set(&mut self, x: u32, y: u32, color: [u8; 3]) {
+        if x < self.width && y < self.height { self.pixels[(y * self.width + x) as usize] = color; }
+    }
+}
+
+pub fn plot(width: u32, height: u32, data: &[(f64, f64)], x_range: (f64, f64), max: f64, color: impl Fn(f64) -> [u8; 3]) -> Image {
+    let mut image = Image { width, height, pixels: vec![BACKGROUND; (width * height) as usize] };
+    if width == 0 || height == 0 { return image; }
+    for fraction in [0.25, 0.5, 0.75] {
+        let y = height - 1 - ((height - 1) as f64 * fraction) as u32;
+        for x in (0..width).step_by(2) { image.set(x, y, GRID); }
+    }
+    let to_px = |(x, y): (f64, f64)| ((x - x_range.0) / (x_range.1 - x_range.0) * (width - 1) as f64, (height - 1) as f64 * (1.0 - y / max));
+    for pair in data.windows(2) {
+        let (x0, y0) = to_px(pair[0]);
+        let (x1, y1) = to_px(pair[1]);
+        let line = color(pair[0].1.max(pair[1].1));
+        let fill = line.map(|c| c / 3);
+        for px in x0.max(0.0) as u32..=x1.max(0.0) as u32 {
+            let t = if x1 > x0 { (px as f64 - x0) / (x1 - x0) } else { 0.0 };
+            let y = (y0 + (y1 - y0) * t).clamp(0.0, (height - 1) as f64) as u32;
+            for py in y..height { image.set(px, py, fill); }
+        }
+        let steps = (x1 - x0).abs().max((y1 - y0).abs()).max(1.0) as u32;
+        for i in 0..=steps {
+            let t = i as f64 / steps as f64;
+            let (x, y) = (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t);
+            if x < 0.0 { continue; }
+            for dy in 0..2 { image.set(x as u32, (y.max(0.0) as u32 + dy).min(height - 1), line); }
+        }
+    }
+    image
+}
+
+fn kitty(image: &Image, cols: u16, rows: u16) -> String {
+    let raw: Vec<u8> = image.pixels.iter().flatten().copied().collect();
+    let payload = base64::encode(&raw);
+    let chunks: Vec<&str> = payload.as_bytes().chunks(4096).map(|c| std::str::from_utf8(c).unwrap_or("")).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = (i + 1 < chunks.len()) as u8;
+        if i == 0 {
+            let _ = write!(out, "\x1b_Ga=T,f=24,q=2,C=1,s={},v={},c={},r={},m={};{}\x1b\\", image.width, image.height, cols, rows, more, chunk);
+        } else {
+            let _ = write!(out, "\x1b_Gm={};{}\x1b\\", more, chunk);
+        }
+    }
+    out
+}
+
+fn sixel(image: &Image) -> String {
+    let mut palette: HashMap<[u8; 3], usize> = HashMap::new();
+    let indices: Vec<usize> = image.pixels.iter().map(|p| { let n = palette.len(); *palette.entry(*p).or_insert(n) }).collect();
+    let mut out = format!("\x1bPq\"1;1;{};{}", image.width, image.height);
+    let mut colors: Vec<(&[u8; 3], &usize)> = palette.iter().collect();
+    colors.sort_by_key(|(_, i)| **i);
+    for (c, i) in &colors {
+        let _ = write!(out, "#{};2;{};{};{}", i, c[0] as u32 * 100 / 255, c[1] as u32 * 100 / 255, c[2] as u32 * 100 / 255);
+    }
+    for band in (0..image.height).step_by(6) {
+        for (_, index) in &colors {
+            let mut row = String::new();
+            let mut used = false;
+            let mut run: Option<(char, usize)> = None;
+            for x in 0..image.width {
+                let bits = (0..6).filter(|dy| band + dy < image.height && indices[((band + dy) * image.width + x) as usize] == **index).fold(0u8, |acc, dy| acc | 1 << dy);
+                used |= bits != 0;
+                let ch = (63 + bits) as char;
+                run = match run {
+                    Some((c, n)) if c == ch => Some((c, n + 1)),
+                    Some((c, n)) => { push_run(&mut row, c, n); Some((ch, 1)) }
+                    None => Some((ch, 1)),
+                };
+            }
+            if let Some((c, n)) = run { push_run(&mut row, c, n); }
+            if used { let _ = write!(out, "#{}{}$", index, row); }
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+fn push_run(out: &mut String, ch: char, n: usize) {
+    if n > 3 { let _ = write!(out, "!{}{}", n, ch); } else { out.extend(std::iter::repeat_n(ch, n)); }
+}
+
+pub fn encode(image: &Image, protocol: Protocol, cols: u16, rows: u16) -> String {
+    match protocol { Protocol::Kitty => kitty(image, cols, rows), Protocol::Sixel => sixel(image) }
+}
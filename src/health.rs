@@ -0,0 +1,30 @@
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+pub struct Probe {
+    pub iface: String,
+    pub rtt: Option<f64>,
+}
+
+pub fn probe(iface: Option<&str>, target: &str) -> Option<f64> {
+    let mut cmd = Command::new("ping");
+    cmd.args(["-n", "-c", "1", "-W", "2"]);
+    if let Some(iface) = iface { cmd.args(["-I", iface]); }
+    let out = cmd.arg(target).output().ok()?;
+    if !out.status.success() { return None; }
+    let s = String::from_utf8_lossy(&out.stdout);
+    s.split("time=").nth(1).and_then(|t| t.split_whitespace().next()).and_then(|t| t.parse().ok()).or(Some(0.0))
+}
+
+pub fn spawn_checker(ifaces: Vec<String>, target: String, interval: u64) -> Receiver<Probe> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || loop {
+        for iface in &ifaces {
+            if tx.send(Probe { iface: iface.clone(), rtt: probe(Some(iface), &target) }).is_err() { return; }
+        }
+        thread::sleep(Duration::from_secs(interval.max(1)));
+    });
+    rx
+}
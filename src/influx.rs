@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::net::UdpSocket;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::{self, Influx, InfluxTarget};
+use crate::net_monitor;
+
+const MAX_BUFFERED: usize = 10_000;
+
+pub struct Sink {
+    host: String,
+    tx: Sender<Vec<String>>,
+}
+
+fn escape_tag(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(',', "\\,").replace('=', "\\=").replace(' ', "\\ ")
+}
+
+fn header_file(token: &str) -> io::Result<PathBuf> {
+    let dir = config::private_dir("influx")?;
+    let path = dir.join("headers");
+    let written = OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path).and_then(|mut file| writeln!(file, "Authorization: Token {}", token.replace(['\n', '\r'], "")));
+    if let Err(e) = written { let _ = fs::remove_dir_all(&dir); return Err(e); }
+    Ok(path)
+}
+
+fn write(target: &InfluxTarget, headers: Option<&Path>, body: &str) -> Result<(), String> {
+    match target {
+        InfluxTarget::Http(url) => {
+            let mut cmd = Command::new("curl");
+            cmd.args(["-sS", "-f", "-m", "10", "-X", "POST", "--data-binary", "@-", "-H", "Content-Type: text/plain; charset=utf-8"]);
+            if let Some(headers) = headers { cmd.arg("-H").arg(format!("@{}", headers.display())); }
+            let mut child = cmd.arg(url).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::piped()).spawn().map_err(|e| format!("curl: {}", e))?;
+            if let Some(mut stdin) = child.stdin.take() { stdin.write_all(body.as_bytes()).map_err(|e| e.to_string())?; }
+            let mut err = String::new();
+            if let Some(mut stderr) = child.stderr.take() { let _ = stderr.read_to_string(&mut err); }
+            let status = child.wait().map_err(|e| e.to_string())?;
+            if status.success() { Ok(()) } else { Err(err.trim().to_string()) }
+        }
+        InfluxTarget::File(path) => {
+            let mut file = OpenOptions::new().create(true).append(true).open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            file.write_all(body.as_bytes()).map_err(|e| e.to_string())
+        }
+        InfluxTarget::Udp(addr) => {
+            let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
+            for chunk in body.lines().collect::<Vec<_>>().chunks(20) {
+                socket.send_to((chunk.join("\n") + "\n").as_bytes(), addr.as_str()).map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl Sink {
+    pub fn start(config: &Influx) -> Sink {
+        let (tx, rx) = mpsc::channel::<Vec<String>>();
+        let target = config.target.clone();
+        let token = config.token.clone();
+        thread::spawn(move || {
+            let headers = match (&target, token.is_empty()) {
+                (InfluxTarget::Http(_), false) => match header_file(&token) { Ok(path) => Some(path), Err(_) => return },
+                _ => None,
+            };
+            let mut pending: VecDeque<String> = VecDeque::new();
+            loop {
+                let Ok(lines) = rx.recv() else {
+                    if let Some(dir) = headers.as_ref().and_then(|h| h.parent()) { let _ = fs::remove_dir_all(dir); }
+                    return;
+                };
+                pending.extend(lines);
+                pending.extend(rx.try_iter().flatten());
+                if pending.len() > MAX_BUFFERED { pending.drain(..pending.len() - MAX_BUFFERED); }
+                let body = pending.iter().fold(String::new(), |body, line| body + line + "\n");
+                match write(&target, headers.as_deref(), &body) {
+                    Ok(()) => pending.clear(),
+                    Err(_) => thread::sleep(Duration::from_secs(5)),
+                }
+            }
+        });
+        Sink { host: escape_tag(&net_monitor::hostname()), tx }
+    }
+
+    pub fn record(&self, rates: &[(&String, (f64, f64))], session_bytes: (u64, u64), epoch: i64) {
+        let time = epoch * 1_000_000_000;
+        let mut lines: Vec<String> = rates.iter()
+            .map(|(iface, (rx, tx))| format!("dashnet_rate,host={},iface={} rx_mbits={},tx_mbits={} {}", self.host, escape_tag(iface), rx, tx, time))
+            .collect();
+        lines.push(format!("dashnet_session,host={} rx_bytes={}i,tx_bytes={}i {}", self.host, session_bytes.0, session_bytes.1, time));
+        let _ = self.tx.send(lines);
+    }
+}
@@ -0,0 +1,96 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+
+#[derive(Default)]
+pub struct TextInput {
+    text: String,
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn value(&self) -> &str {
+        &self.text
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn set(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.len();
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn byte(&self, cursor: usize) -> usize {
+        self.text.char_indices().nth(cursor).map_or(self.text.len(), |(i, _)| i)
+    }
+
+    pub fn insert(&mut self, text: &str) {
+        let text: String = text.chars().map(|c| if c == '\n' || c == '\t' { ' ' } else { c }).filter(|c| !c.is_control()).collect();
+        let at = self.byte(self.cursor);
+        self.text.insert_str(at, &text);
+        self.cursor += text.chars().count();
+    }
+
+    fn remove(&mut self, from: usize, to: usize) {
+        let (start, end) = (self.byte(from), self.byte(to));
+        self.text.replace_range(start..end, "");
+        self.cursor = from;
+    }
+
+    fn word_left(&self) -> usize {
+        let chars: Vec<char> = self.text.chars().take(self.cursor).collect();
+        let spaces = chars.iter().rev().take_while(|c| c.is_whitespace()).count();
+        self.cursor - spaces - chars.iter().rev().skip(spaces).take_while(|c| !c.is_whitespace()).count()
+    }
+
+    fn word_right(&self) -> usize {
+        let chars: Vec<char> = self.text.chars().skip(self.cursor).collect();
+        let spaces = chars.iter().take_while(|c| c.is_whitespace()).count();
+        self.cursor + spaces + chars.iter().skip(spaces).take_while(|c| !c.is_whitespace()).count()
+    }
+
+    pub fn handle(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let word = ctrl || key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Left if word => self.cursor = self.word_left(),
+            KeyCode::Right if word => self.cursor = self.word_right(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.len(),
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.len(),
+            KeyCode::Backspace if word => self.remove(self.word_left(), self.cursor),
+            KeyCode::Char('w') if ctrl => self.remove(self.word_left(), self.cursor),
+            KeyCode::Char('u') if ctrl => self.remove(0, self.cursor),
+            KeyCode::Char('k') if ctrl => self.remove(self.cursor, self.len()),
+            KeyCode::Backspace if self.cursor > 0 => self.remove(self.cursor - 1, self.cursor),
+            KeyCode::Delete if self.cursor < self.len() => self.remove(self.cursor, self.cursor + 1),
+            KeyCode::Backspace | KeyCode::Delete => {}
+            KeyCode::Char(c) if !ctrl => self.insert(&c.to_string()),
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn shown(&self, masked: bool) -> String {
+        if masked { "*".repeat(self.len()) } else { self.text.clone() }
+    }
+
+    pub fn line(&self, masked: bool) -> Line<'static> {
+        let shown = self.shown(masked);
+        let chars: Vec<char> = shown.chars().collect();
+        let before: String = chars[..self.cursor].iter().collect();
+        let at = chars.get(self.cursor).map_or(" ".to_string(), |c| c.to_string());
+        let after: String = chars.get(self.cursor + 1..).unwrap_or_default().iter().collect();
+        Line::from(vec![Span::raw(before), Span::styled(at, Style::default().add_modifier(Modifier::REVERSED)), Span::raw(after)])
+    }
+}
@@ -0,0 +1,79 @@
+use std::io::{self, BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use crate::format;
+
+pub enum Update {
+    Rate(f64),
+    Done(Vec<String>),
+    Failed(String),
+}
+
+pub struct Request {
+    pub host: String,
+    pub reverse: bool,
+    pub seconds: u64,
+}
+
+impl Request {
+    pub fn parse(text: &str) -> Result<Request, String> {
+        let mut request = Request { host: String::new(), reverse: false, seconds: 10 };
+        for word in text.split_whitespace() {
+            match word {
+                "-R" | "reverse" | "download" => request.reverse = true,
+                _ if word.parse::<u64>().is_ok() => request.seconds = word.parse().unwrap_or(10).clamp(1, 300),
+                _ if request.host.is_empty() => request.host = word.to_string(),
+                _ => return Err(format!("unexpected '{}'", word)),
+            }
+        }
+        if request.host.is_empty() { Err("no server given".to_string()) } else { Ok(request) }
+    }
+}
+
+fn retransmits(line: &str) -> Option<u64> {
+    line.split_whitespace().skip_while(|w| !w.ends_with("bits/sec")).nth(1)?.parse().ok()
+}
+
+fn mbits(line: &str) -> Option<f64> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let pos = words.iter().position(|w| w.ends_with("bits/sec"))?;
+    let value: f64 = words.get(pos.checked_sub(1)?)?.parse().ok()?;
+    Some(match words[pos].chars().next() { Some('K') => value / 1000.0, Some('G') => value * 1000.0, Some('b') => value / 1_000_000.0, _ => value })
+}
+
+pub fn start_client(request: &Request) -> Receiver<Update> {
+    let (tx, rx) = mpsc::channel();
+    let mut cmd = Command::new("iperf3");
+    cmd.args(["-c", &request.host, "-t", &request.seconds.to_string(), "-i", "1", "--forceflush"]);
+    if request.reverse { cmd.arg("-R"); }
+    let direction = if request.reverse { "download" } else { "upload" };
+    let child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn();
+    thread::spawn(move || {
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => { let _ = tx.send(Update::Failed(format!("iperf3: {}", e))); return; }
+        };
+        let mut summary = Vec::new();
+        let Some(stdout) = child.stdout.take() else { return };
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let Some(rate) = mbits(&line) else { continue };
+            match ["sender", "receiver"].into_iter().find(|role| line.trim_end().ends_with(role)) {
+                Some(role) => summary.push(format!("  {:<9} {} Mb/s{}", role, format::number(rate, 1), retransmits(&line).map(|r| format!(", {} retransmits", r)).unwrap_or_default())),
+                None => if tx.send(Update::Rate(rate)).is_err() { let _ = child.kill(); return; },
+            }
+        }
+        let status = child.wait_with_output();
+        let _ = tx.send(match status {
+            Ok(out) if summary.is_empty() => Update::Failed(String::from_utf8_lossy(&out.stderr).trim().trim_start_matches("iperf3: error - ").to_string()),
+            Err(e) => Update::Failed(e.to_string()),
+            Ok(_) => Update::Done(std::iter::once(format!("{} test ({}):", direction, if direction == "download" { "server → this host" } else { "this host → server" })).chain(summary).collect()),
+        });
+    });
+    rx
+}
+
+pub fn start_server() -> io::Result<Child> {
+    Command::new("iperf3").args(["-s"]).stdout(Stdio::null()).stderr(Stdio::null()).spawn()
+}
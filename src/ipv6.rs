@@ -0,0 +1,59 @@
+use std::process::Command;
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    Command::new(program).args(args).output().ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+}
+
+fn check(lines: &mut Vec<String>, passed: &mut usize, label: &str, result: Result<String, String>) {
+    match result {
+        Ok(detail) => { *passed += 1; lines.push(format!("[PASS] {}: {}", label, detail)); }
+        Err(detail) => lines.push(format!("[FAIL] {}: {}", label, detail)),
+    }
+}
+
+pub fn diagnose(target: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut passed = 0;
+
+    let addrs = run("ip", &["-6", "-o", "addr", "show", "scope", "global"]).unwrap_or_default();
+    let globals: Vec<String> = addrs.lines().filter_map(|l| {
+        let parts: Vec<&str> = l.split_whitespace().collect();
+        Some(format!("{} {}", parts.get(1)?, parts.get(3)?))
+    }).collect();
+    let has_address = !globals.is_empty();
+    check(&mut lines, &mut passed, "Global address / prefix", if has_address { Ok(globals.join(", ")) } else { Err("no global IPv6 address (no prefix delegated or advertised)".to_string()) });
+
+    let routes = run("ip", &["-6", "route", "show", "default"]).unwrap_or_default();
+    let default_route = routes.lines().next().map(|l| l.trim().to_string());
+    let has_route = default_route.is_some();
+    check(&mut lines, &mut passed, "Default route", default_route.ok_or_else(|| "no IPv6 default route (no Router Advertisement received?)".to_string()));
+
+    let dns: Vec<String> = run("nmcli", &["-t", "-f", "IP6.DNS", "dev", "show"]).unwrap_or_default().lines()
+        .filter_map(|l| l.split_once(':').map(|(_, v)| v.replace("\\:", ":")))
+        .chain(std::fs::read_to_string("/etc/resolv.conf").unwrap_or_default().lines()
+            .filter_map(|l| l.strip_prefix("nameserver")).map(|v| v.trim().to_string()).filter(|v| v.contains(':')))
+        .filter(|v| !v.is_empty()).collect();
+    check(&mut lines, &mut passed, "IPv6 DNS servers", if dns.is_empty() { Err("no RA/DHCPv6-provided DNS server".to_string()) } else { Ok(dns.join(", ")) });
+
+    let resolved = run("getent", &["ahostsv6", target]).and_then(|s| s.split_whitespace().next().map(str::to_string));
+    check(&mut lines, &mut passed, &format!("AAAA lookup of {}", target), resolved.ok_or_else(|| "no AAAA record resolved".to_string()));
+
+    let ping = run("ping", &["-6", "-c", "3", "-W", "2", target]);
+    check(&mut lines, &mut passed, &format!("Reach {} over IPv6", target), match ping {
+        Some(out) => Ok(out.lines().find(|l| l.contains("min/avg/max")).map(|l| l.trim().to_string()).unwrap_or_else(|| "replies received".to_string())),
+        None => Err("no ICMPv6 echo reply".to_string()),
+    });
+
+    let total = 5;
+    lines.push(String::new());
+    lines.push(if passed == total {
+        "Summary: IPv6 is FULLY FUNCTIONAL".to_string()
+    } else if !has_address && !has_route {
+        "Summary: IPv6 is ABSENT on this network".to_string()
+    } else {
+        format!("Summary: IPv6 is PARTIALLY BROKEN ({}/{} checks passed)", passed, total)
+    });
+    lines
+}
@@ -0,0 +1,20 @@
+use std::process::Command;
+
+const UNITS: [&str; 2] = ["NetworkManager", "wpa_supplicant"];
+
+pub fn tail(filters: &[String], count: usize) -> Vec<String> {
+    let mut cmd = Command::new("journalctl");
+    cmd.args(["--no-pager", "-q", "-o", "short-iso", "-n", "2000"]);
+    for unit in UNITS { cmd.args(["-u", unit]); }
+    let out = match cmd.output() {
+        Ok(out) => out,
+        Err(e) => return vec![format!("journalctl: {}", e)],
+    };
+    if !out.status.success() { return vec![String::from_utf8_lossy(&out.stderr).trim().to_string()]; }
+    let s = String::from_utf8_lossy(&out.stdout);
+    let mut lines: Vec<String> = s.lines()
+        .filter(|l| filters.is_empty() || filters.iter().any(|f| l.contains(f.as_str())))
+        .map(|l| l.to_string()).collect();
+    if lines.len() > count { lines.drain(..lines.len() - count); }
+    lines
+}
@@ -0,0 +1,224 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+pub enum Json {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(input: &str) -> Option<Json> {
+        let mut chars = input.chars().peekable();
+        let value = parse_value(&mut chars, 0)?;
+        skip_ws(&mut chars);
+        if chars.peek().is_none() { Some(value) } else { None }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Obj(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self { Json::Str(s) => Some(s), _ => None }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self { Json::Bool(b) => Some(*b), _ => None }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self { Json::Num(n) => Some(*n), _ => None }
+    }
+
+    pub fn as_array(&self) -> &[Json] {
+        match self { Json::Arr(items) => items, _ => &[] }
+    }
+
+    pub fn entries(&self) -> &[(String, Json)] {
+        match self { Json::Obj(fields) => fields, _ => &[] }
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Num(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Num(_) => write!(f, "null"),
+            Json::Str(s) => write_string(f, s),
+            Json::Arr(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() { if i > 0 { write!(f, ",")?; } write!(f, "{}", item)?; }
+                write!(f, "]")
+            }
+            Json::Obj(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() { if i > 0 { write!(f, ",")?; } write_string(f, key)?; write!(f, ":{}", value)?; }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+fn skip_ws(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) { chars.next(); }
+}
+
+const MAX_DEPTH: usize = 64;
+
+fn parse_value(chars: &mut Peekable<Chars>, depth: usize) -> Option<Json> {
+    skip_ws(chars);
+    if depth >= MAX_DEPTH && matches!(chars.peek(), Some('{' | '[')) { return None; }
+    match *chars.peek()? {
+        '{' => {
+            chars.next();
+            let mut fields = Vec::new();
+            loop {
+                skip_ws(chars);
+                match chars.next()? {
+                    '}' => break,
+                    ',' => continue,
+                    '"' => {
+                        let key = parse_string(chars)?;
+                        skip_ws(chars);
+                        if chars.next()? != ':' { return None; }
+                        fields.push((key, parse_value(chars, depth + 1)?));
+                    }
+                    _ => return None,
+                }
+            }
+            Some(Json::Obj(fields))
+        }
+        '[' => {
+            chars.next();
+            let mut items = Vec::new();
+            loop {
+                skip_ws(chars);
+                match chars.peek()? {
+                    ']' => { chars.next(); break; }
+                    ',' => { chars.next(); }
+                    _ => items.push(parse_value(chars, depth + 1)?),
+                }
+            }
+            Some(Json::Arr(items))
+        }
+        '"' => { chars.next(); parse_string(chars).map(Json::Str) }
+        't' | 'f' | 'n' => {
+            let word: String = std::iter::from_fn(|| chars.next_if(|c| c.is_ascii_alphabetic())).collect();
+            match word.as_str() { "true" => Some(Json::Bool(true)), "false" => Some(Json::Bool(false)), "null" => Some(Json::Null), _ => None }
+        }
+        _ => {
+            let num: String = std::iter::from_fn(|| chars.next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))).collect();
+            num.parse().ok().map(Json::Num)
+        }
+    }
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Option<String> {
+    let mut out = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(out),
+            '\\' => match chars.next()? {
+                'n' => out.push('\n'),
+                't' => out.push('\t'),
+                'r' => out.push('\r'),
+                'b' => out.push('\u{8}'),
+                'f' => out.push('\u{c}'),
+                'u' => {
+                    let mut code = parse_hex4(chars);
+                    if let Some(high @ 0xd800..=0xdbff) = code {
+                        let mut ahead = chars.clone();
+                        if ahead.next() == Some('\\') && ahead.next() == Some('u') {
+                            if let Some(low @ 0xdc00..=0xdfff) = parse_hex4(&mut ahead) { *chars = ahead; code = Some(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)); }
+                        }
+                    }
+                    out.push(code.and_then(char::from_u32).unwrap_or('\u{fffd}'));
+                }
+                c => out.push(c),
+            },
+            c => out.push(c),
+        }
+    }
+}
+
+fn parse_hex4(chars: &mut Peekable<Chars>) -> Option<u32> {
+    let hex: String = (0..4).filter_map(|_| chars.next_if(|c| c.is_ascii_hexdigit())).collect();
+    if hex.len() == 4 { u32::from_str_radix(&hex, 16).ok() } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(input: &str) -> Option<String> {
+        Json::parse(input).map(|json| json.to_string())
+    }
+
+    #[test]
+    fn decodes_escapes_and_surrogate_pairs() {
+        let json = Json::parse(r#""a\"b\\c\n\t\u00e9\/""#).unwrap();
+        assert_eq!(json.as_str(), Some("a\"b\\c\n\té/"));
+        assert_eq!(Json::parse(r#""\uD83D\uDE00""#).unwrap().as_str(), Some("\u{1f600}"));
+        assert_eq!(Json::parse(r#""\ud83d\n""#).unwrap().as_str(), Some("\u{fffd}\n"));
+        assert_eq!(Json::parse(r#""\uDE00\uD83D""#).unwrap().as_str(), Some("\u{fffd}\u{fffd}"));
+        assert_eq!(Json::parse(r#""\u12""#).unwrap().as_str(), Some("\u{fffd}"));
+    }
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(Json::parse("-12.5e2").and_then(|json| json.as_f64()), Some(-1250.0));
+        assert_eq!(Json::parse(" 0 ").and_then(|json| json.as_f64()), Some(0.0));
+        assert!(Json::parse("1.2.3").is_none());
+        assert!(Json::parse("-").is_none());
+    }
+
+    #[test]
+    fn parses_nested_values() {
+        let json = Json::parse(r#"{"a": [1, {"b": true}, null], "c": {"d": "e"}}"#).unwrap();
+        assert_eq!(json.get("a").unwrap().as_array().len(), 3);
+        assert_eq!(json.get("a").unwrap().as_array()[1].get("b").and_then(Json::as_bool), Some(true));
+        assert_eq!(json.get("c").and_then(|c| c.get("d")).and_then(Json::as_str), Some("e"));
+        assert_eq!(text(r#"{ "k" : [ 1 , "x\u0001" ] }"#).as_deref(), Some(r#"{"k":[1,"x\u0001"]}"#));
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let deep = |n: usize| format!("{}{}", "[".repeat(n), "]".repeat(n));
+        assert!(Json::parse(&deep(MAX_DEPTH)).is_some());
+        assert!(Json::parse(&deep(MAX_DEPTH + 1)).is_none());
+        assert!(Json::parse(&"[".repeat(100_000)).is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for input in ["", "{", "[1, 2", r#"{"a" 1}"#, r#"{"a": }"#, r#""open"#, "tru", "nul", "[1] 2", "{1: 2}"] {
+            assert!(Json::parse(input).is_none(), "{:?}", input);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use std::net::{Ipv4Addr, UdpSocket};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use crate::{config, docker, net_monitor, power};
+
+const MAX_PREFIX_HOSTS: u32 = 1024;
+
+pub struct Neighbor {
+    pub ip: String,
+    pub mac: String,
+    pub iface: String,
+}
+
+pub struct Device {
+    pub mac: String,
+    pub label: String,
+    pub ip: String,
+    pub iface: String,
+    pub network: String,
+    pub first_seen: i64,
+    pub last_seen: i64,
+}
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("devices")
+}
+
+pub fn load() -> Option<Vec<Device>> {
+    let text = fs::read_to_string(store_path()).ok()?;
+    Some(text.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 6 { return None; }
+        Some(Device { mac: fields[0].to_string(), label: fields[1].to_string(), ip: fields[2].to_string(), iface: fields[3].to_string(), network: fields.get(6).unwrap_or(&fields[3]).to_string(), first_seen: fields[4].parse().ok()?, last_seen: fields[5].parse().ok()? })
+    }).collect())
+}
+
+pub fn save(devices: &[Device]) -> Result<(), String> {
+    let path = store_path();
+    if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    let text: String = devices.iter().map(|d| format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\n", d.mac, d.label.replace('\t', " "), d.ip, d.iface, d.first_seen, d.last_seen, d.network)).collect();
+    fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn neighbors() -> Vec<Neighbor> {
+    fs::read_to_string("/proc/net/arp").unwrap_or_default().lines().skip(1).filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 || fields[2] == "0x0" || fields[3] == "00:00:00:00:00:00" { return None; }
+        Some(Neighbor { ip: fields[0].to_string(), mac: fields[3].to_lowercase(), iface: fields[5].to_string() })
+    }).collect()
+}
+
+fn probe_subnets() {
+    let Ok(socket) = UdpSocket::bind("0.0.0.0:0") else { return };
+    let docker = docker::interfaces();
+    for (iface, ip, prefix) in net_monitor::ipv4_networks() {
+        if prefix >= 31 || docker.contains(&iface) || iface.starts_with("veth") { continue; }
+        let Some(hosts) = 1u32.checked_shl(32 - prefix).filter(|h| *h <= MAX_PREFIX_HOSTS) else { continue };
+        let network = u32::from(ip) & !(hosts - 1);
+        for host in 1..hosts - 1 {
+            let _ = socket.send_to(&[0], (Ipv4Addr::from(network + host), 9));
+        }
+    }
+}
+
+pub fn spawn_scanner(interval: u64) -> Receiver<Vec<Neighbor>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || loop {
+        power::wait_while_paused();
+        probe_subnets();
+        thread::sleep(Duration::from_secs(3));
+        if tx.send(neighbors()).is_err() { return; }
+        thread::sleep(Duration::from_secs(interval.max(30)));
+    });
+    rx
+}
@@ -0,0 +1,83 @@
+use std::ffi::CStr;
+use std::net::IpAddr;
+use std::process::Command;
+
+use crate::deps;
+use crate::json::Json;
+
+fn ptr(ip: IpAddr) -> Option<String> {
+    let mut host = [0 as libc::c_char; 1025];
+    let rc = match ip {
+        IpAddr::V4(addr) => {
+            let mut sin: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+            sin.sin_family = libc::AF_INET as libc::sa_family_t;
+            sin.sin_addr.s_addr = u32::from(addr).to_be();
+            unsafe { libc::getnameinfo(&sin as *const _ as *const libc::sockaddr, std::mem::size_of::<libc::sockaddr_in>() as libc::socklen_t, host.as_mut_ptr(), host.len() as libc::socklen_t, std::ptr::null_mut(), 0, libc::NI_NAMEREQD) }
+        }
+        IpAddr::V6(addr) => {
+            let mut sin6: libc::sockaddr_in6 = unsafe { std::mem::zeroed() };
+            sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sin6.sin6_addr.s6_addr = addr.octets();
+            unsafe { libc::getnameinfo(&sin6 as *const _ as *const libc::sockaddr, std::mem::size_of::<libc::sockaddr_in6>() as libc::socklen_t, host.as_mut_ptr(), host.len() as libc::socklen_t, std::ptr::null_mut(), 0, libc::NI_NAMEREQD) }
+        }
+    };
+    (rc == 0).then(|| unsafe { CStr::from_ptr(host.as_ptr()) }.to_string_lossy().to_string())
+}
+
+fn private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(a) => a.is_private() || a.is_loopback() || a.is_link_local() || a.octets()[0] == 100 && a.octets()[1] & 0xc0 == 64,
+        IpAddr::V6(a) => a.is_loopback() || a.segments()[0] & 0xfe00 == 0xfc00 || a.segments()[0] & 0xffc0 == 0xfe80,
+    }
+}
+
+fn vcard_name(entity: &Json) -> Option<String> {
+    entity.get("vcardArray")?.as_array().get(1)?.as_array().iter()
+        .find(|prop| prop.as_array().first().and_then(Json::as_str) == Some("fn"))
+        .and_then(|prop| prop.as_array().get(3)?.as_str().map(str::to_string))
+}
+
+fn rdap(ip: IpAddr) -> Result<Vec<String>, String> {
+    let out = Command::new("curl").args(["-sfL", "-m", "10", "-H", "Accept: application/rdap+json", &format!("https://rdap.org/ip/{}", ip)]).output().map_err(|e| format!("curl: {}", e))?;
+    if !out.status.success() { return Err("RDAP query failed".to_string()); }
+    let json = Json::parse(&String::from_utf8_lossy(&out.stdout)).ok_or("RDAP: unexpected answer")?;
+    let text = |key: &str| json.get(key).and_then(Json::as_str).unwrap_or("").to_string();
+    let mut lines = Vec::new();
+    let cidrs: Vec<String> = json.get("cidr0_cidrs").map(Json::as_array).unwrap_or_default().iter().filter_map(|c| {
+        let prefix = c.get("v4prefix").or_else(|| c.get("v6prefix"))?.as_str()?;
+        Some(format!("{}/{}", prefix, c.get("length")?.as_f64()? as u32))
+    }).collect();
+    lines.push(format!("Netblock:  {}", if cidrs.is_empty() { format!("{} - {}", text("startAddress"), text("endAddress")) } else { cidrs.join(", ") }));
+    lines.push(format!("Network:   {} ({})", text("name"), text("handle")));
+    let orgs: Vec<String> = json.get("entities").map(Json::as_array).unwrap_or_default().iter()
+        .filter(|e| e.get("roles").map(Json::as_array).unwrap_or_default().iter().any(|r| matches!(r.as_str(), Some("registrant" | "administrative"))))
+        .filter_map(vcard_name).collect();
+    if let Some(org) = orgs.first() { lines.push(format!("Org:       {}", org)); }
+    if !text("country").is_empty() { lines.push(format!("Country:   {}", text("country"))); }
+    Ok(lines)
+}
+
+fn whois(ip: IpAddr) -> Result<Vec<String>, String> {
+    let out = Command::new("whois").arg(ip.to_string()).output().map_err(|e| format!("whois: {}", e))?;
+    const KEYS: [&str; 9] = ["netrange", "cidr", "inetnum", "inet6num", "netname", "orgname", "org-name", "descr", "country"];
+    let lines: Vec<String> = String::from_utf8_lossy(&out.stdout).lines().filter_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        KEYS.contains(&key.trim().to_lowercase().as_str()).then(|| format!("{:<10} {}", format!("{}:", key.trim()), value.trim()))
+    }).take(8).collect();
+    if lines.is_empty() { Err("whois: no record".to_string()) } else { Ok(lines) }
+}
+
+pub fn describe(address: &str) -> Vec<String> {
+    let Ok(ip) = address.trim().parse::<IpAddr>() else { return vec![format!("'{}' is not an IP address", address)] };
+    let mut lines = vec![format!("Address:   {}", ip), format!("PTR:       {}", ptr(ip).unwrap_or_else(|| "(none)".to_string()))];
+    if private(ip) {
+        lines.push("Private or local address: no whois record.".to_string());
+        return lines;
+    }
+    let result = if deps::in_path("curl") { rdap(ip).or_else(|e| if deps::in_path("whois") { whois(ip) } else { Err(e) }) } else { whois(ip) };
+    match result {
+        Ok(more) => lines.extend(more),
+        Err(e) => lines.push(e),
+    }
+    lines
+}
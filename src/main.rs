@@ -1,77 +1,798 @@
+mod addrs;
+mod anomaly;
+mod appvpn;
+mod base64;
+mod blacklist;
+mod bufferbloat;
+mod certs;
+mod checkpoint;
+mod clipboard;
+mod clock;
+mod config;
+mod connect;
+mod dbus;
+mod deps;
+mod devices;
+mod dhcp;
+mod diagnostics;
+mod docker;
+mod dot;
+mod events;
+mod failover;
+mod favorites;
+mod format;
+mod gateway;
+mod graphics;
+mod health;
+mod influx;
+mod input;
+mod iperf;
+mod ipv6;
+mod journal;
+mod json;
+mod lan;
+mod lookup;
 mod net_monitor;
+mod ntp;
+mod outages;
+mod palette;
+mod power;
+mod peers;
+mod picker;
+mod presets;
+mod profile;
+mod protocols;
+mod ra;
+mod reachability;
+mod recent;
+mod report;
+mod rogue;
+mod routes;
+mod rules;
+mod schedule;
+mod sla;
+mod slots;
+mod snapshot;
+mod split;
+mod sha1;
+mod mqtt;
+mod natpmp;
+mod netns;
+mod notify;
+mod snmp;
+mod speedtest;
+mod sweep;
+mod systemd;
+mod talkers;
+mod task;
+mod theme;
+mod topology;
+mod troubleshoot;
+mod upnp;
+mod usage;
+mod watchdog;
+mod webhook;
+mod websocket;
+mod wizard;
+mod wps;
 
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Style, Modifier},
     symbols,
-    widgets::{Block, Borders, List, ListItem, Paragraph, BorderType, canvas::{Canvas, Line}, ListState, Clear},
+    widgets::{Block, Borders, List, ListItem, Paragraph, BorderType, canvas::{Canvas, Line}, ListState, Clear, Tabs, Scrollbar, ScrollbarOrientation, ScrollbarState},
     Terminal, Frame,
 };
 use crossterm::{
-    event::{self, Event, KeyCode, EnableMouseCapture, DisableMouseCapture},
-    execute,
+    event::{self, Event, KeyCode, KeyModifiers, EnableMouseCapture, DisableMouseCapture, EnableBracketedPaste, DisableBracketedPaste},
+    cursor::MoveTo,
+    execute, queue,
+    style::Print,
     terminal::{enable_raw_mode, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use std::{io::{self, Write}, time::{Duration, Instant}, process::{Command, Stdio}, collections::HashMap};
+use std::{io::{self, Write}, path::{Path, PathBuf}, time::{Duration, Instant}, process::{Command, Stdio}, collections::{HashMap, HashSet, VecDeque}, sync::mpsc::{Receiver, TryRecvError}};
 
 #[derive(PartialEq, Clone, Copy)]
 enum SelectionMode {
-    VPN,
+    Overview,
+    Vpn,
     WiFi,
-    PasswordInput,
+    AccessPoints,
+    DataUsage,
+    FilePicker,
+    Topology,
+    Namespaces,
+    Docker,
+    PortMappings,
+    Tools,
+    RouterAdverts,
+    PropertyEditor,
+    SplitTunnel,
+    Schedule,
+    Events,
+    Journal,
+    LanDevices,
+    SpeedHistory,
+    Outages,
+    PingSweep,
+    Certificates,
+    AppVpn,
+    Peers,
+    Routes,
+    EncryptedDns,
+    TopTalkers,
+    Rules,
+    NewConnection,
+    Credentials,
+    TextInput,
+    Palette,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum InputAction {
+    UpnpAdd,
+    ReachabilityPort,
+    EditProperty,
+    ExportProfile,
+    ImportDirectory,
+    CloneProfile,
+    LabelDevice,
+    PingSweep,
+    Lookup,
+    CertEndpoint,
+    Iperf,
+    AppVpn,
+    SplitEntry,
+    AddRoute,
+    PinSlot,
+}
+
+#[derive(Clone)]
+enum ConfirmAction {
+    ApplyProfileEdits,
+    ApplySplitTunnel,
+    ExportProfile(String, String),
+    ApplyFix(troubleshoot::Fix),
+    SetCertificates(String, Vec<Option<PathBuf>>),
+    AddRoute(String),
+    DeleteRoute(String),
+    CleanUpRoutes,
+    RevealPassword(String),
+    CreateConnection(wizard::Kind, Vec<(&'static str, String)>),
+    DisconnectPeer(peers::Peer),
+    RevokePeer(peers::Peer),
+    SetDnsOverTls(dot::Link, &'static str),
+    KeepChanges,
+}
+
+#[derive(Clone)]
+enum Change {
+    Failover(String, String),
+    AddRoute(String),
+    DeleteRoute(String),
+    CleanUpRoutes,
+    ProfileEdits(bool),
+    CreateConnection(wizard::Kind, Vec<(&'static str, String)>),
+}
+
+#[derive(Clone)]
+enum Jump {
+    View(SelectionMode),
+    Vpn(String),
+    WiFi(String),
+    Interface(String),
+    Host(String),
+    Tool(Tool),
+    Report,
+    ExportGraph,
+}
+
+const VIEWS: [(&str, SelectionMode, &str); 24] = [
+    ("Overview", SelectionMode::Overview, ""),
+    ("WiFi networks", SelectionMode::WiFi, ""),
+    ("Data usage per WiFi network", SelectionMode::DataUsage, ""),
+    ("VPN profiles", SelectionMode::Vpn, ""),
+    ("New connection", SelectionMode::NewConnection, "nmcli"),
+    ("Per-app VPN", SelectionMode::AppVpn, "nft"),
+    ("VPN server peers", SelectionMode::Peers, ""),
+    ("Routing table", SelectionMode::Routes, "ip"),
+    ("Policy routing rules", SelectionMode::Rules, "ip"),
+    ("Top talkers", SelectionMode::TopTalkers, "ss"),
+    ("Encrypted DNS (DNS-over-TLS)", SelectionMode::EncryptedDns, "resolvectl"),
+    ("Port mappings (UPnP, NAT-PMP, PCP)", SelectionMode::PortMappings, ""),
+    ("LAN devices", SelectionMode::LanDevices, ""),
+    ("Certificates", SelectionMode::Certificates, ""),
+    ("Tools", SelectionMode::Tools, ""),
+    ("Speed test history", SelectionMode::SpeedHistory, ""),
+    ("Schedule", SelectionMode::Schedule, ""),
+    ("Events", SelectionMode::Events, ""),
+    ("Journal", SelectionMode::Journal, "journalctl"),
+    ("Outages", SelectionMode::Outages, ""),
+    ("Bridge/bond topology", SelectionMode::Topology, ""),
+    ("Network namespaces", SelectionMode::Namespaces, ""),
+    ("Docker containers", SelectionMode::Docker, ""),
+    ("Router advertisements", SelectionMode::RouterAdverts, ""),
+];
+
+const HOST_ONLY: [SelectionMode; 8] = [SelectionMode::Topology, SelectionMode::Docker, SelectionMode::PortMappings, SelectionMode::RouterAdverts, SelectionMode::LanDevices, SelectionMode::AppVpn, SelectionMode::Peers, SelectionMode::EncryptedDns];
+
+#[derive(PartialEq, Clone, Copy)]
+enum Tool {
+    PortReachability,
+    Ipv6Diagnostics,
+    ImportProfiles,
+    DiagnosticsBundle,
+    Troubleshoot,
+    TestWebhooks,
+    SpeedTest,
+    PingSweep,
+    Certificate,
+    Bufferbloat,
+    Iperf,
+    IperfServer,
+    EncryptedDns,
+}
+
+impl Tool {
+    const ALL: [Tool; 13] = [Tool::Troubleshoot, Tool::SpeedTest, Tool::Bufferbloat, Tool::Iperf, Tool::IperfServer, Tool::PingSweep, Tool::Certificate, Tool::EncryptedDns, Tool::PortReachability, Tool::Ipv6Diagnostics, Tool::ImportProfiles, Tool::DiagnosticsBundle, Tool::TestWebhooks];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Tool::PortReachability => "External port reachability test",
+            Tool::Ipv6Diagnostics => "IPv6 connectivity diagnostics",
+            Tool::ImportProfiles => "Import connection profiles from a directory",
+            Tool::DiagnosticsBundle => "Write a redacted diagnostics bundle to /tmp",
+            Tool::Troubleshoot => "Why is my internet broken? (step-by-step troubleshooting)",
+            Tool::TestWebhooks => "Send a test message to the configured webhooks",
+            Tool::SpeedTest => "Run a speed test (kept in the history, H)",
+            Tool::PingSweep => "Ping sweep of the connected subnet or any CIDR",
+            Tool::Certificate => "Check the TLS certificate of a host:port",
+            Tool::Bufferbloat => "Bufferbloat test (latency under load, graded A+ to F)",
+            Tool::Iperf => "iperf3 throughput test against a server (plotted live in the graph)",
+            Tool::IperfServer => "Start or stop a local iperf3 server (port 5201)",
+            Tool::EncryptedDns => "Encrypted DNS: view, switch and verify DNS-over-TLS",
+        }
+    }
+
+    fn requires(&self) -> Option<&'static str> {
+        match self {
+            Tool::PortReachability | Tool::TestWebhooks => Some("curl"),
+            Tool::Troubleshoot | Tool::ImportProfiles => Some("nmcli"),
+            Tool::Ipv6Diagnostics => Some("ip"),
+            Tool::PingSweep | Tool::Bufferbloat => Some("ping"),
+            Tool::Iperf | Tool::IperfServer => Some("iperf3"),
+            Tool::Certificate => Some("openssl"),
+            Tool::EncryptedDns => Some("resolvectl"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum GraphMode {
+    Single,
+    Overlay,
+    Stacked,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Page {
+    Overview,
+    WiFi,
+    Vpn,
+    Connections,
+    Tools,
+    Logs,
+}
+
+impl Page {
+    const ALL: [Page; 6] = [Page::Overview, Page::WiFi, Page::Vpn, Page::Connections, Page::Tools, Page::Logs];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Page::Overview => "Overview",
+            Page::WiFi => "WiFi",
+            Page::Vpn => "VPN",
+            Page::Connections => "Connections",
+            Page::Tools => "Tools",
+            Page::Logs => "Logs",
+        }
+    }
+
+    fn home(&self) -> SelectionMode {
+        match self {
+            Page::Overview => SelectionMode::Overview,
+            Page::WiFi => SelectionMode::WiFi,
+            Page::Vpn => SelectionMode::Vpn,
+            Page::Connections => SelectionMode::Routes,
+            Page::Tools => SelectionMode::Tools,
+            Page::Logs => SelectionMode::Events,
+        }
+    }
+
+    fn of(mode: SelectionMode) -> Page {
+        match mode {
+            SelectionMode::WiFi | SelectionMode::AccessPoints | SelectionMode::DataUsage => Page::WiFi,
+            SelectionMode::Vpn | SelectionMode::SplitTunnel | SelectionMode::PropertyEditor | SelectionMode::AppVpn | SelectionMode::Peers | SelectionMode::FilePicker | SelectionMode::NewConnection => Page::Vpn,
+            SelectionMode::Routes | SelectionMode::Rules | SelectionMode::TopTalkers | SelectionMode::EncryptedDns | SelectionMode::PortMappings | SelectionMode::LanDevices | SelectionMode::Certificates => Page::Connections,
+            SelectionMode::Tools | SelectionMode::PingSweep | SelectionMode::SpeedHistory | SelectionMode::Schedule => Page::Tools,
+            SelectionMode::Events | SelectionMode::Journal | SelectionMode::Outages => Page::Logs,
+            _ => Page::Overview,
+        }
+    }
+
+    fn back_from(mode: SelectionMode) -> SelectionMode {
+        let home = Page::of(mode).home();
+        if home == mode { SelectionMode::Overview } else { home }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Density {
+    Full,
+    Compact,
+    StatusLine,
+}
+
+impl Density {
+    fn of(area: Rect) -> Density {
+        if area.width < 40 || area.height < 12 { Density::StatusLine } else if area.width < 80 || area.height < 24 { Density::Compact } else { Density::Full }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Protection {
+    Trusted,
+    Securing(Instant),
+    Protected,
+    Failed,
+}
+
+const OVERLAY_COLORS: [Color; 6] = [Color::Yellow, Color::Cyan, Color::LightGreen, Color::Magenta, Color::LightRed, Color::LightBlue];
+
+struct GraphAxes<'a> {
+    last_x: f64,
+    times: &'a VecDeque<(f64, i64)>,
+    cursor: Option<f64>,
+    unit: format::RateUnit,
+}
+
+impl GraphAxes<'_> {
+    fn time_at(&self, x: f64) -> Option<i64> {
+        self.times.iter().find(|(c, _)| *c == x.round()).map(|(_, t)| *t)
+    }
 }
 
 struct InterfaceData {
     history: Vec<(f64, f64)>,
     current_speed: f64,
     color: Color,
+    remote: bool,
+}
+
+struct SeenRouter {
+    advert: ra::Advert,
+    last_seen: Instant,
+    expected: bool,
+}
+
+struct DetailsUpdate {
+    iface: String,
+    action: Option<String>,
+    fields: Vec<(String, String)>,
 }
 
 struct App {
+    config: config::Config,
     vpn_names: Vec<String>,
     wifi_ssids: Vec<String>,
+    scanned_ssids: Vec<String>,
+    access_points: Vec<rogue::Ap>,
+    expanded_ssid: String,
+    blacklist: Vec<String>,
+    show_blacklisted: bool,
     active_vpns: Vec<String>,
     previous_active_vpns: Vec<String>,
+    last_vpn: Option<String>,
+    slots: Vec<Option<String>>,
+    favorites: Vec<String>,
     current_ssid: String,
     previous_ssid: String,
+    protection: Protection,
+    current_signal: u8,
+    wifi_iface: String,
+    visible_signals: Vec<(String, u8)>,
+    known_wifi: Vec<String>,
+    weak_since: Option<Instant>,
+    last_roam: Option<Instant>,
+    events: events::EventLog,
+    missing: Vec<&'static str>,
+    websocket: Option<websocket::Server>,
+    mqtt: Option<mqtt::Publisher>,
+    influx: Option<influx::Sink>,
+    dbus: Option<dbus::Service>,
+    saturated: Vec<String>,
+    monitor_only: bool,
+    devices: Vec<devices::Device>,
+    routes: Vec<String>,
+    created_routes: Vec<String>,
+    rules: Vec<rules::Rule>,
+    peers: Vec<peers::Peer>,
+    dns_links: Result<Vec<dot::Link>, String>,
+    dns_task: Option<task::Task<Result<Vec<dot::Link>, String>>>,
+    anomalies: Option<anomaly::Detector>,
+    talkers: Vec<talkers::Talker>,
+    talker_sockets: HashMap<String, (u64, u64)>,
+    talkers_sampled: Instant,
+    talker_error: Option<String>,
+    protocols: Option<protocols::Collector>,
+    protocol_classes: Vec<protocols::Class>,
+    peer_errors: Vec<String>,
+    peers_task: Option<task::Task<(Vec<peers::Peer>, Vec<String>)>>,
+    uplink_probes: Option<Receiver<health::Probe>>,
+    latency_probes: Option<Receiver<gateway::Sample>>,
+    latency: HashMap<String, gateway::Series>,
+    uplink_failures: HashMap<String, u32>,
+    active_uplink: Option<String>,
+    wan_down: bool,
+    failover_task: Option<task::Task<Result<String, String>>>,
+    uplink_hold: Option<(String, Instant)>,
+    checkpoint: Option<checkpoint::Pending>,
+    checkpoint_probe: Option<task::Task<bool>>,
+    checkpoint_task: Option<task::Task<(Result<String, String>, bool)>>,
+    guarded: Option<(String, checkpoint::Undo, bool, Change)>,
+    rollback_task: Option<task::Task<Vec<String>>>,
+    watchdog_probe: Option<task::Task<bool>>,
+    last_probe: Instant,
+    last_online: Instant,
+    repair_step: usize,
+    repair_task: Option<task::Task<Result<String, String>>>,
     selection_mode: SelectionMode,
     previous_mode: SelectionMode,
-    password_input: String,
+    page_views: [(SelectionMode, ListState); 6],
+    palette_index: usize,
+    list_rows: usize,
+    form: Vec<connect::Field>,
+    form_focus: usize,
+    picker_dir: PathBuf,
+    picker_entries: Vec<picker::Entry>,
+    cert_flow: Option<(String, Vec<Option<PathBuf>>)>,
+    text_input: input::TextInput,
+    input_action: InputAction,
     list_state: ListState,
     interfaces: HashMap<String, InterfaceData>,
     last_stats: HashMap<String, net_monitor::NetStats>,
+    rates: HashMap<String, (f64, f64)>,
+    session_bytes: (u64, u64),
+    session_by_iface: HashMap<String, (u64, u64)>,
+    usage: usage::Ledger,
+    topology: Vec<topology::Master>,
+    namespaces: Vec<String>,
+    netns: Option<String>,
+    containers: Result<Vec<docker::Container>, String>,
+    containers_task: Option<task::Task<Result<Vec<docker::Container>, String>>>,
+    docker_ifaces: HashSet<String>,
+    netdev_task: Option<task::Task<Result<HashMap<String, net_monitor::NetStats>, String>>>,
+    netns_stats: Option<HashMap<String, net_monitor::NetStats>>,
+    netns_error: Option<String>,
+    docker_ifaces_task: Option<task::Task<HashSet<String>>>,
+    remote_samples: Receiver<snmp::Sample>,
+    upnp: Option<Result<upnp::Gateway, String>>,
+    upnp_task: Option<task::Task<(String, Result<upnp::Gateway, String>)>>,
+    upnp_message: String,
+    popup: Option<(String, Vec<String>)>,
+    tool_task: Option<task::Task<Vec<String>>>,
+    report_task: Option<task::Task<String>>,
+    wizard_task: Option<task::Task<troubleshoot::Report>>,
+    journal: Vec<String>,
+    journal_filters: Vec<String>,
+    journal_all: bool,
+    journal_task: Option<task::Task<Vec<String>>>,
+    lan_devices: Vec<lan::Device>,
+    lan_scan: Option<Receiver<Vec<lan::Neighbor>>>,
+    rogue_history: Vec<rogue::Seen>,
+    rogue_task: Option<task::Task<Vec<rogue::Ap>>>,
+    rogue_alerted: Vec<String>,
+    speedtests: Vec<speedtest::Sample>,
+    speedtest_task: Option<task::Task<Result<speedtest::Sample, String>>>,
+    speed_range: usize,
+    speedtest_attempt: i64,
+    sla: sla::History,
+    sla_checks: Option<Receiver<sla::Outcome>>,
+    sla_status: HashMap<String, Option<f64>>,
+    outages: Vec<outages::Outage>,
+    outage_checks: Option<Receiver<health::Probe>>,
+    outage_failures: u32,
+    outage_since: Option<i64>,
+    online_iface: String,
+    sweep_target: String,
+    sweep_total: usize,
+    sweep_replies: Vec<sweep::Reply>,
+    sweep_rx: Option<Receiver<sweep::Reply>>,
+    certs: Vec<certs::Check>,
+    app_tunnels: Vec<appvpn::Tunnel>,
+    cert_checks: Option<Receiver<certs::Check>>,
+    cert_alerted: HashMap<String, i64>,
+    ntp: ntp::Status,
+    iperf_target: String,
+    iperf_rx: Option<Receiver<iperf::Update>>,
+    wps_rx: Option<Receiver<wps::Update>>,
+    wps_log: Vec<String>,
+    wizard: Option<wizard::Kind>,
+    iperf_server: Option<std::process::Child>,
+    ntp_task: Option<task::Task<ntp::Status>>,
+    addresses: HashMap<String, Vec<String>>,
+    public_ip: Option<String>,
+    public_ip_task: Option<task::Task<Option<String>>>,
+    ra_events: Receiver<Result<ra::Advert, String>>,
+    routers: Vec<SeenRouter>,
+    ra_error: Option<String>,
+    details_iface: String,
+    details: Vec<(String, String)>,
+    details_action: Option<String>,
+    details_task: Option<task::Task<DetailsUpdate>>,
+    connect_task: Option<task::Task<connect::Outcome>>,
+    connect_target: Option<connect::Target>,
+    connect_since: Instant,
+    connect_stage: String,
+    connect_cancelled: bool,
+    stage_task: Option<task::Task<String>>,
+    disconnect_task: Option<task::Task<(String, Result<(), String>)>>,
+    editor: Option<profile::ProfileEditor>,
+    split: Option<split::SplitTunnel>,
+    confirm: Option<(String, Vec<String>, ConfirmAction)>,
+    preset_progress: Option<Receiver<String>>,
+    schedule_checked: i64,
     counter: f64,
+    schedule: std::cell::RefCell<HashMap<&'static std::panic::Location<'static>, Instant>>,
+    last_sample: Instant,
+    on_battery: bool,
     graph_index: usize, 
+    graph_mode: GraphMode,
+    tick_times: VecDeque<(f64, i64)>,
+    graph_cursor: usize,
+    rate_unit: format::RateUnit,
+    scheme: theme::Scheme,
+    linear: bool,
+    graphics: Option<graphics::Protocol>,
+    raster: Option<(Rect, String)>,
+    raster_key: Option<(u64, Rect, String)>,
+    raster_reset: bool,
+    compared: Vec<String>,
 }
 
 impl App {
     fn new() -> Self {
+        let config = config::Config::load();
+        format::init(config.locale);
+        webhook::init(config.webhooks.clone());
+        notify::init(config.notifications.clone());
         let mut app = App {
+            remote_samples: snmp::spawn_collectors(&config.snmp),
+            latency_probes: None,
+            latency: HashMap::new(),
+            uplink_probes: config.failover.as_ref().map(|f| health::spawn_checker(f.uplinks.clone(), f.target.clone(), f.interval)),
+            config,
             vpn_names: Self::get_nm_vpn_connections(),
-            wifi_ssids: Self::scan_wifi_ssids(),
+            wifi_ssids: Vec::new(),
+            scanned_ssids: Vec::new(),
+            access_points: Vec::new(),
+            expanded_ssid: String::new(),
+            blacklist: blacklist::load(),
+            show_blacklisted: false,
             active_vpns: Vec::new(),
             previous_active_vpns: Vec::new(),
+            last_vpn: recent::last_vpn(),
+            slots: Vec::new(),
+            favorites: favorites::load(),
             current_ssid: String::new(),
             previous_ssid: String::new(),
-            selection_mode: SelectionMode::VPN,
-            previous_mode: SelectionMode::VPN,
-            password_input: String::new(),
+            protection: Protection::Trusted,
+            current_signal: 0,
+            wifi_iface: String::new(),
+            visible_signals: Vec::new(),
+            known_wifi: Self::get_saved_wifi(),
+            weak_since: None,
+            last_roam: None,
+            events: events::EventLog::new(500),
+            missing: deps::missing(),
+            websocket: None,
+            mqtt: None,
+            influx: None,
+            dbus: None,
+            saturated: Vec::new(),
+            monitor_only: false,
+            devices: Vec::new(),
+            routes: Vec::new(),
+            created_routes: routes::created(),
+            rules: Vec::new(),
+            peers: Vec::new(),
+            dns_links: Ok(Vec::new()),
+            dns_task: None,
+            anomalies: None,
+            talkers: Vec::new(),
+            talker_sockets: HashMap::new(),
+            talkers_sampled: Instant::now(),
+            talker_error: None,
+            protocols: None,
+            protocol_classes: Vec::new(),
+            peer_errors: Vec::new(),
+            peers_task: None,
+            uplink_failures: HashMap::new(),
+            active_uplink: None,
+            wan_down: false,
+            failover_task: None,
+            uplink_hold: None,
+            checkpoint: None,
+            checkpoint_probe: None,
+            checkpoint_task: None,
+            guarded: None,
+            rollback_task: None,
+            watchdog_probe: None,
+            last_probe: Instant::now(),
+            last_online: Instant::now(),
+            repair_step: 0,
+            repair_task: None,
+            selection_mode: SelectionMode::Overview,
+            previous_mode: SelectionMode::Overview,
+            page_views: Page::ALL.map(|p| (p.home(), ListState::default())),
+            palette_index: 0,
+            list_rows: 10,
+            form: Vec::new(),
+            form_focus: 0,
+            picker_dir: PathBuf::new(),
+            picker_entries: Vec::new(),
+            cert_flow: None,
+            text_input: input::TextInput::default(),
+            input_action: InputAction::UpnpAdd,
             list_state: ListState::default(),
             interfaces: HashMap::new(),
             last_stats: net_monitor::get_net_data(),
+            rates: HashMap::new(),
+            session_bytes: (0, 0),
+            session_by_iface: HashMap::new(),
+            usage: usage::Ledger::load(),
+            topology: topology::get_topology(),
+            namespaces: Vec::new(),
+            netns: None,
+            containers: Ok(Vec::new()),
+            containers_task: None,
+            docker_ifaces: HashSet::new(),
+            netdev_task: None,
+            netns_stats: None,
+            netns_error: None,
+            docker_ifaces_task: None,
+            upnp: None,
+            upnp_task: None,
+            upnp_message: String::new(),
+            popup: None,
+            tool_task: None,
+            report_task: None,
+            wizard_task: None,
+            journal: Vec::new(),
+            journal_filters: Vec::new(),
+            journal_all: false,
+            journal_task: None,
+            lan_devices: Vec::new(),
+            lan_scan: None,
+            rogue_history: rogue::load(),
+            rogue_task: None,
+            rogue_alerted: Vec::new(),
+            speedtests: speedtest::load(),
+            speedtest_task: None,
+            speed_range: 1,
+            speedtest_attempt: 0,
+            sla: sla::History::load(),
+            sla_checks: None,
+            outages: outages::load(),
+            outage_checks: None,
+            outage_failures: 0,
+            outage_since: None,
+            online_iface: String::new(),
+            sweep_target: String::new(),
+            sweep_total: 0,
+            sweep_replies: Vec::new(),
+            sweep_rx: None,
+            certs: Vec::new(),
+            app_tunnels: Vec::new(),
+            cert_checks: None,
+            cert_alerted: HashMap::new(),
+            ntp: ntp::Status::default(),
+            iperf_target: String::new(),
+            iperf_rx: None,
+            wps_rx: None,
+            wps_log: Vec::new(),
+            wizard: None,
+            iperf_server: None,
+            ntp_task: Some(task::Task::spawn(ntp::query)),
+            addresses: HashMap::new(),
+            public_ip: None,
+            public_ip_task: None,
+            sla_status: HashMap::new(),
+            ra_events: ra::spawn_listener(),
+            routers: Vec::new(),
+            ra_error: None,
+            details_iface: String::new(),
+            details: Vec::new(),
+            details_action: None,
+            details_task: None,
+            connect_task: None,
+            connect_target: None,
+            connect_since: Instant::now(),
+            connect_stage: String::new(),
+            connect_cancelled: false,
+            stage_task: None,
+            disconnect_task: None,
+            editor: None,
+            split: None,
+            confirm: None,
+            preset_progress: None,
+            schedule_checked: clock::epoch_now(),
             counter: 0.0,
+            schedule: Default::default(),
+            last_sample: Instant::now(),
+            on_battery: false,
             graph_index: 0,
+            graph_mode: GraphMode::Single,
+            tick_times: VecDeque::new(),
+            graph_cursor: 0,
+            rate_unit: format::RateUnit::Bits,
+            scheme: theme::Scheme::Default,
+            linear: false,
+            graphics: None,
+            raster: None,
+            raster_key: None,
+            raster_reset: false,
+            compared: Vec::new(),
         };
+        app.rate_unit = app.config.rate_unit;
+        app.slots = slots::load(&app.config.slots);
+        app.refresh_wifi();
+        if !deps::networkmanager_running() { app.missing.push("NetworkManager"); }
+        app.monitor_only = !app.has("NetworkManager");
+        app.update_power();
+        if let Some(addr) = app.config.websocket.clone() {
+            match websocket::Server::start(&addr, app.config.websocket_origins.clone()) {
+                Ok(server) => app.websocket = Some(server),
+                Err(e) => app.events.push("websocket", format!("cannot listen on {}: {}", addr, e)),
+            }
+        }
+        if std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some() {
+            match dbus::Service::start() {
+                Ok(service) => {
+                    if !service.owner { app.events.push("dbus", format!("{} is owned by another process, only signals are sent", dbus::NAME)); }
+                    app.dbus = Some(service);
+                }
+                Err(e) => app.events.push("dbus", format!("cannot connect to the session bus: {}", e)),
+            }
+        }
+        app.influx = app.config.influx.as_ref().map(influx::Sink::start);
+        if !app.config.targets.is_empty() { app.sla_checks = Some(sla::spawn_checks(&app.config.targets)); }
+        app.cert_checks = app.config.certificates.as_ref().map(|c| certs::spawn_checker(c.endpoints.clone(), c.interval));
+        app.outage_checks = app.config.outages.as_ref().map(|o| outages::spawn_checker(o.target.clone(), o.interval));
+        if let Some(lan) = &app.config.lan {
+            app.lan_devices = lan::load().unwrap_or_default();
+            app.lan_scan = Some(lan::spawn_scanner(lan.interval));
+        }
+        if let Some(config) = &app.config.mqtt {
+            if deps::in_path("mosquitto_pub") { app.mqtt = Some(mqtt::Publisher::start(config)); } else { app.events.push("mqtt", "mosquitto_pub not found, MQTT publishing disabled".to_string()); }
+        }
+        if app.config.anomaly.is_some() { app.anomalies = Some(anomaly::Detector::load()); }
+        if app.config.latency_interval > 0 { app.latency_probes = Some(gateway::spawn_monitor(app.report_target(), app.config.latency_interval)); }
+        app.public_ip_task = app.config.public_ip.as_ref().map(|p| { let url = p.url.clone(); task::Task::spawn(move || addrs::public(&url)) });
+        let no_color = std::env::args().any(|a| a == "--no-color") || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        app.scheme = if no_color { theme::Scheme::NoColor } else { app.config.scheme };
+        app.linear = app.config.linear || std::env::args().any(|a| a == "--linear");
+        app.graphics = graphics::Protocol::detect(&app.config.graphics).filter(|_| !app.linear && app.scheme != theme::Scheme::NoColor);
         app.list_state.select(Some(0));
         app.update_active_states();
         app
     }
 
-    fn send_notification(summary: &str, body: &str, critical: bool) {
-        let urgency = if critical { "critical" } else { "normal" };
-        let icon = if critical { "network-error" } else { "network-transmit-receive" };
-        let _ = Command::new("notify-send").args(["-u", urgency, "-i", icon, summary, body]).spawn();
+    fn send_notification(event: &'static str, summary: &str, body: &str, critical: bool) {
+        notify::send(event, summary, body, critical);
+        webhook::dispatch(event, summary, body, notify::urgency(event, critical) == "critical");
     }
 
     fn get_nm_vpn_connections() -> Vec<String> {
@@ -81,227 +802,3493 @@ impl App {
             let mut names: Vec<String> = s.lines()
                 .filter(|line| line.contains(":vpn") || line.contains(":wireguard"))
                 .map(|line| line.split(':').next().unwrap_or("").to_string()).collect();
-            names.sort(); names
+            names.sort(); favorites::sort_first(&mut names, &favorites::load()); names
         } else { Vec::new() }
     }
 
-    fn scan_wifi_ssids() -> Vec<String> {
-        let output = Command::new("nmcli").args(["-t", "-f", "SSID", "dev", "wifi", "list"]).output();
+    fn get_saved_wifi() -> Vec<String> {
+        let output = Command::new("nmcli").args(["-t", "-f", "NAME,TYPE", "connection", "show"]).output();
         if let Ok(out) = output {
             let s = String::from_utf8_lossy(&out.stdout);
-            let mut ssids: Vec<String> = s.lines().filter(|l| !l.is_empty() && *l != "--").map(|s| s.to_string()).collect();
-            ssids.sort(); ssids.dedup(); ssids
+            s.lines().filter(|line| line.ends_with(":802-11-wireless")).filter_map(|line| line.rsplit_once(':').map(|(n, _)| n.replace("\\:", ":"))).collect()
         } else { Vec::new() }
     }
 
+    fn refresh_wifi(&mut self) {
+        self.access_points = rogue::scan();
+        self.access_points.sort_by_key(|ap| std::cmp::Reverse(ap.signal));
+        self.scanned_ssids = self.access_points.iter().map(|ap| ap.ssid.clone()).collect();
+        self.scanned_ssids.sort();
+        self.scanned_ssids.dedup();
+        favorites::sort_first(&mut self.scanned_ssids, &self.favorites);
+        self.filter_wifi();
+    }
+
+    fn bssids(&self) -> Vec<&rogue::Ap> {
+        self.access_points.iter().filter(|ap| ap.ssid == self.expanded_ssid).collect()
+    }
+
+    fn ap_badge(&self, ssid: &str) -> String {
+        let count = self.access_points.iter().filter(|ap| ap.ssid == ssid).count();
+        if count > 1 { format!(" ×{}", count) } else { String::new() }
+    }
+
+    fn usage_badge(&self, ssid: &str) -> String {
+        self.usage.this_month(ssid, clock::epoch_now()).map(|bytes| format!(" · {} this month", format::bytes(bytes))).unwrap_or_default()
+    }
+
+    fn expand_ssid(&mut self) {
+        let Some(ssid) = self.selected_name() else { return };
+        self.expanded_ssid = ssid;
+        self.selection_mode = SelectionMode::AccessPoints;
+        self.list_state.select(Some(0));
+    }
+
+    fn collapse_ssid(&mut self) {
+        self.selection_mode = SelectionMode::WiFi;
+        let ssid = std::mem::take(&mut self.expanded_ssid);
+        self.list_state.select(Some(self.wifi_ssids.iter().position(|s| *s == ssid).unwrap_or(0)));
+    }
+
+    fn filter_wifi(&mut self) {
+        self.wifi_ssids = self.scanned_ssids.iter().filter(|s| self.show_blacklisted || !self.blacklist.contains(s)).cloned().collect();
+    }
+
     fn update_active_states(&mut self) {
+        if self.every(5.0) { self.update_addresses(); }
+        if self.monitor_only {
+            self.devices = devices::list();
+            self.routes = routes::list(self.netns.as_deref());
+            return;
+        }
         self.previous_active_vpns = self.active_vpns.clone();
         if let Ok(out) = Command::new("nmcli").args(["-t", "-f", "NAME,STATE", "con", "show", "--active"]).output() {
             let s = String::from_utf8_lossy(&out.stdout);
             self.active_vpns = s.lines().map(|l| l.split(':').next().unwrap_or("").to_string()).filter(|n| !n.is_empty()).collect();
         }
-        if let Ok(out) = Command::new("nmcli").args(["-t", "-f", "ACTIVE,SSID", "dev", "wifi"]).output() {
+        if let Ok(out) = Command::new("nmcli").args(["-t", "-f", "ACTIVE,SIGNAL,DEVICE,SSID", "dev", "wifi"]).output() {
             let s = String::from_utf8_lossy(&out.stdout);
-            self.current_ssid = s.lines().find(|l| l.starts_with("yes")).map(|l| l.split(':').nth(1).unwrap_or("").to_string()).unwrap_or_default();
+            let rows: Vec<(bool, u8, String, String)> = s.lines().filter_map(|l| {
+                let mut parts = l.splitn(4, ':');
+                Some((parts.next()? == "yes", parts.next()?.parse().unwrap_or(0), parts.next()?.to_string(), parts.next()?.replace("\\:", ":")))
+            }).collect();
+            let current = rows.iter().find(|(active, _, _, _)| *active);
+            self.current_ssid = current.map(|(_, _, _, ssid)| ssid.clone()).unwrap_or_default();
+            self.current_signal = current.map(|(_, signal, _, _)| *signal).unwrap_or(0);
+            self.wifi_iface = current.map(|(_, _, iface, _)| iface.clone()).unwrap_or_default();
+            self.visible_signals = rows.into_iter().filter(|(_, _, _, ssid)| !ssid.is_empty()).map(|(_, signal, _, ssid)| (ssid, signal)).collect();
         }
         for vpn in &self.previous_active_vpns {
-            if !self.active_vpns.contains(vpn) { Self::send_notification("VPN Déconnecté", &format!("Tunnel '{}' fermé.", vpn), true); }
+            if !self.active_vpns.contains(vpn) {
+                Self::send_notification("vpn-down", "VPN Déconnecté", &format!("Tunnel '{}' fermé.", vpn), true);
+                self.events.push("vpn", format!("{} down", vpn));
+                if let Some(mqtt) = &self.mqtt { mqtt.publish(&format!("vpn/{}", vpn), "down".to_string(), true); }
+                if let Some(bus) = &self.dbus { bus.emit("VpnDown", &[dbus::Arg::Str(vpn)]); }
+            }
         }
         for vpn in &self.active_vpns {
-            if !self.previous_active_vpns.contains(vpn) { Self::send_notification("VPN Connecté", &format!("Tunnel '{}' actif.", vpn), false); }
+            if !self.previous_active_vpns.contains(vpn) {
+                Self::send_notification("vpn-up", "VPN Connecté", &format!("Tunnel '{}' actif.", vpn), false);
+                self.events.push("vpn", format!("{} up", vpn));
+                if self.vpn_names.contains(vpn) && self.last_vpn.as_ref() != Some(vpn) {
+                    self.last_vpn = Some(vpn.clone());
+                    let _ = recent::remember_vpn(vpn);
+                }
+                if let Some(mqtt) = &self.mqtt { mqtt.publish(&format!("vpn/{}", vpn), "up".to_string(), true); }
+                if let Some(bus) = &self.dbus { bus.emit("VpnUp", &[dbus::Arg::Str(vpn)]); }
+            }
+        }
+        if self.current_ssid != self.previous_ssid {
+            self.events.push("wifi", if self.current_ssid.is_empty() { format!("left {}", self.previous_ssid) } else { format!("joined {} ({}%)", self.current_ssid, self.current_signal) });
         }
+        if let Some(bus) = self.dbus.as_ref().filter(|_| self.current_ssid != self.previous_ssid) { bus.emit("SsidChanged", &[dbus::Arg::Str(&self.previous_ssid), dbus::Arg::Str(&self.current_ssid)]); }
+        if let Some(mqtt) = self.mqtt.as_ref().filter(|_| self.current_ssid != self.previous_ssid) {
+            let trusted = if self.config.trusted_ssids.is_empty() { json::Json::Null } else { json::Json::Bool(self.config.trusted_ssids.contains(&self.current_ssid)) };
+            let ssid = if self.current_ssid.is_empty() { json::Json::Null } else { json::Json::Str(self.current_ssid.clone()) };
+            mqtt.publish("wifi", json::Json::Obj(vec![("ssid".to_string(), ssid), ("signal".to_string(), json::Json::Num(self.current_signal as f64)), ("trusted".to_string(), trusted)]).to_string(), true);
+        }
+        self.update_protection();
+        self.update_roaming();
+        self.previous_ssid = self.current_ssid.clone();
     }
 
-    fn update_metrics(&mut self) {
-        self.update_active_states();
-        let current_stats = net_monitor::get_net_data();
-        self.counter += 1.0;
-        for (name, stats) in current_stats.iter() {
-            if name == "lo" || name.contains("docker") || name.contains("br-") { continue; }
-            if let Some(old_stats) = self.last_stats.get(name) {
-                let speed = ((stats.rx.saturating_sub(old_stats.rx) as f64) * 8.0) / (1024.0 * 1024.0);
-                let entry = self.interfaces.entry(name.clone()).or_insert(InterfaceData {
-                    history: Vec::new(),
-                    current_speed: 0.0,
-                    color: if name.starts_with('w') { Color::Yellow } else if name.starts_with('e') { Color::Green } else { Color::Cyan },
-                });
-                entry.current_speed = speed;
-                entry.history.push((self.counter, speed));
-                if entry.history.len() > 300 { entry.history.remove(0); }
+    fn update_addresses(&mut self) {
+        let current = addrs::by_interface(self.netns.as_deref());
+        for (iface, new) in &current {
+            match self.addresses.get(iface) {
+                Some(old) if old != new => {
+                    self.events.push("ip", format!("{}: {} → {}", iface, old.join(", "), new.join(", ")));
+                    Self::send_notification("ip-change", "Adresse IP modifiée", &format!("{} : {} → {}.", iface, old.join(", "), new.join(", ")), false);
+                    if let Some(mqtt) = &self.mqtt { mqtt.publish(&format!("address/{}", iface), new.join(","), true); }
+                }
+                None if !self.addresses.is_empty() => self.events.push("ip", format!("{}: {}", iface, new.join(", "))),
+                _ => {}
             }
         }
-        self.last_stats = current_stats;
-        self.interfaces.retain(|name, _| self.last_stats.contains_key(name));
+        for iface in self.addresses.keys().filter(|i| !current.contains_key(*i)) { self.events.push("ip", format!("{}: no address", iface)); }
+        self.addresses = current;
     }
 
-    fn get_active_ips(&self) -> Vec<(String, String)> {
-        let mut ips = Vec::new();
-        if let Ok(out) = Command::new("ip").args(["-4", "-o", "addr", "show"]).output() {
-            let s = String::from_utf8_lossy(&out.stdout);
-            for line in s.lines() {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 4 {
-                    let name = parts[1].to_string();
-                    let ip = parts[3].split('/').next().unwrap_or("").to_string();
-                    if name != "lo" { ips.push((name, ip)); }
-                }
+    fn update_protection(&mut self) {
+        let Some(vpn) = self.config.autovpn.clone() else { return };
+        let untrusted = !self.current_ssid.is_empty() && !self.config.trusted_ssids.contains(&self.current_ssid);
+        let tunnel_up = self.active_vpns.contains(&vpn);
+        if self.current_ssid != self.previous_ssid {
+            self.protection = Protection::Trusted;
+            if untrusted {
+                Self::send_notification("untrusted-wifi", "Réseau non fiable", &format!("'{}' n'est pas de confiance, activation de '{}'...", self.current_ssid, vpn), true);
+                self.events.push("autovpn", format!("{} is untrusted, bringing up {}", self.current_ssid, vpn));
+                self.secure_with(&vpn);
+            }
+        }
+        if untrusted && !tunnel_up && self.protection == Protection::Protected { self.secure_with(&vpn); }
+        if let Protection::Securing(since) = self.protection {
+            if tunnel_up {
+                self.protection = Protection::Protected;
+                Self::send_notification("protected", "Connexion protégée", &format!("Trafic sur '{}' protégé par '{}'.", self.current_ssid, vpn), false);
+                self.events.push("autovpn", format!("{} protected by {}", self.current_ssid, vpn));
+            } else if since.elapsed() > Duration::from_secs(60) {
+                self.protection = Protection::Failed;
+                Self::send_notification("protection-failed", "Protection échouée", &format!("'{}' n'a pas pu être activé sur '{}'.", vpn, self.current_ssid), true);
+                self.events.push("autovpn", format!("{} could not be brought up on {}", vpn, self.current_ssid));
             }
         }
-        ips
     }
-}
 
-fn main() -> Result<(), io::Error> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    fn update_bandwidth_alerts(&mut self) {
+        for (name, (rx, tx)) in &self.rates {
+            let Some(capacity) = self.config.capacity(name) else { continue };
+            let percent = rx.max(*tx) * 100.0 / capacity;
+            let alerted = self.saturated.contains(name);
+            if !alerted && percent >= self.config.critical_percent {
+                self.saturated.push(name.clone());
+                self.events.push("bandwidth", format!("{} at {}% of {} Mb/s", name, format::number(percent, 0), capacity));
+                if let Some(bus) = &self.dbus { bus.emit("BandwidthAlert", &[dbus::Arg::Str(name), dbus::Arg::Double(rx.max(*tx)), dbus::Arg::Double(percent)]); }
+            } else if alerted && percent < self.config.warn_percent {
+                self.saturated.retain(|n| n != name);
+            }
+        }
+    }
 
-    let mut app = App::new();
-    let tick_rate = Duration::from_millis(500);
-    let mut last_tick = Instant::now();
+    fn update_anomalies(&mut self) {
+        let (Some(detector), Some(config)) = (self.anomalies.as_mut(), &self.config.anomaly) else { return };
+        let rates: Vec<(String, (f64, f64))> = self.rates.iter().filter(|(name, _)| !(name.as_str() == "lo" || self.docker_ifaces.contains(name.as_str()) || name.starts_with("veth"))).map(|(name, rate)| (name.clone(), *rate)).collect();
+        let (raised, cleared) = detector.sample(&rates, clock::epoch_now(), config);
+        for flag in raised {
+            let direction = anomaly::DIRECTIONS[flag.direction];
+            self.events.push("anomaly", format!("{} {} at {} for {} min, usually {} at this hour (⇧M: top talkers)", flag.iface, direction, format::rate(flag.rate, self.rate_unit), config.minutes, format::rate(flag.expected, self.rate_unit)));
+            let body = format!("{} soutenu sur {} : {} depuis {} (habituellement {} à cette heure). Touche ⇧M pour voir les processus.", if flag.direction == 1 { "Envoi" } else { "Réception" }, flag.iface, format::rate(flag.rate, self.rate_unit), format::time(flag.since), format::rate(flag.expected, self.rate_unit));
+            Self::send_notification("traffic-anomaly", "Trafic inhabituel", &body, flag.direction == 1);
+            if let Some(mqtt) = &self.mqtt { mqtt.publish(&format!("anomaly/{}/{}", flag.iface, direction), "yes".to_string(), true); }
+        }
+        for flag in cleared {
+            self.events.push("anomaly", format!("{} {} back to its usual level", flag.iface, anomaly::DIRECTIONS[flag.direction]));
+            if let Some(mqtt) = &self.mqtt { mqtt.publish(&format!("anomaly/{}/{}", flag.iface, anomaly::DIRECTIONS[flag.direction]), "no".to_string(), true); }
+        }
+    }
 
-    loop {
-        terminal.draw(|f| ui(f, &mut app))?;
-        let timeout = tick_rate.checked_sub(last_tick.elapsed()).unwrap_or(Duration::ZERO);
-        if event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                if app.selection_mode == SelectionMode::PasswordInput {
-                    match key.code {
-                        KeyCode::Enter => {
-                            let secret = app.password_input.clone();
-                            let idx = app.list_state.selected().unwrap_or(0);
-                            let target = if app.previous_mode == SelectionMode::VPN { app.vpn_names.get(idx).cloned() } else { app.wifi_ssids.get(idx).cloned() };
-                            if let Some(name) = target {
-                                let mut child = if app.previous_mode == SelectionMode::VPN { 
-                                    Command::new("nmcli").args(["con", "up", "id", &name, "--ask"])
-                                        .stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()? 
-                                } else {
-                                    Command::new("nmcli").args(["dev", "wifi", "connect", &name, "--ask"])
-                                        .stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()? 
-                                };
-                                if let Some(mut stdin) = child.stdin.take() { let _ = writeln!(stdin, "{}", secret); }
-                            }
-                            app.selection_mode = app.previous_mode;
-                        }
-                        KeyCode::Esc => app.selection_mode = app.previous_mode,
-                        KeyCode::Backspace => { app.password_input.pop(); }
-                        KeyCode::Char(c) => { app.password_input.push(c); }
-                        _ => {}
-                    }
-                } else {
-                    let list_len = if app.selection_mode == SelectionMode::VPN { app.vpn_names.len() } else { app.wifi_ssids.len() };
-                    match key.code {
-                        KeyCode::Char('q') => break,
-                        KeyCode::Tab => { app.selection_mode = if app.selection_mode == SelectionMode::VPN { SelectionMode::WiFi } else { SelectionMode::VPN }; app.list_state.select(Some(0)); }
-                        KeyCode::Down | KeyCode::Char('j') => if list_len > 0 {
-                            let i = match app.list_state.selected() { Some(i) => if i >= list_len - 1 { 0 } else { i + 1 }, None => 0 };
-                            app.list_state.select(Some(i));
-                        }
-                        KeyCode::Up | KeyCode::Char('k') => if list_len > 0 {
-                            let i = match app.list_state.selected() { Some(i) => if i == 0 { list_len - 1 } else { i - 1 }, None => 0 };
-                            app.list_state.select(Some(i));
-                        }
-                        KeyCode::Enter => if list_len > 0 { app.previous_mode = app.selection_mode; app.selection_mode = SelectionMode::PasswordInput; app.password_input.clear(); }
-                        KeyCode::Char('x') => if app.selection_mode == SelectionMode::VPN { 
-                            if let Some(idx) = app.list_state.selected() {
-                                if let Some(name) = app.vpn_names.get(idx) { 
-                                    let _ = Command::new("nmcli").args(["con", "down", "id", name])
-                                        .stdout(Stdio::null()).stderr(Stdio::null()).spawn(); 
-                                }
-                            }
-                        }
-                        KeyCode::Char('r') => { app.vpn_names = App::get_nm_vpn_connections(); app.wifi_ssids = App::scan_wifi_ssids(); }
-                        KeyCode::Char('g') => { app.graph_index += 1; }
-                        KeyCode::Char('a') => { let _ = Command::new("nm-connection-editor").stdout(Stdio::null()).stderr(Stdio::null()).spawn(); }
-                        _ => {}
+    fn refresh_talkers(&mut self) {
+        let secs = self.talkers_sampled.elapsed().as_secs_f64();
+        self.talkers_sampled = Instant::now();
+        match talkers::sample(self.netns.as_deref(), &mut self.talker_sockets, secs) {
+            Ok(talkers) => { self.talkers = talkers; self.talker_error = None; }
+            Err(e) => self.talker_error = Some(e),
+        }
+        self.list_state.select(Some(self.list_state.selected().unwrap_or(0).min(self.talkers.len().saturating_sub(1))));
+    }
+
+    fn follow_protocols(&mut self) {
+        if self.config.protocols.is_none() || self.netns.is_some() || self.probes_paused() || self.details_iface.is_empty() {
+            self.protocols = None;
+            self.protocol_classes.clear();
+            return;
+        }
+        if self.protocols.as_ref().is_some_and(|c| c.iface == self.details_iface) { return; }
+        self.protocols = Some(protocols::Collector::start(&self.details_iface));
+        self.protocol_classes.clear();
+    }
+
+    #[track_caller]
+    fn every(&self, seconds: f64) -> bool {
+        let (site, now) = (std::panic::Location::caller(), Instant::now());
+        let mut schedule = self.schedule.borrow_mut();
+        if schedule.get(site).is_some_and(|last| now.duration_since(*last) + self.intervals().0 / 2 < Duration::from_secs_f64(seconds)) { return false; }
+        schedule.insert(site, now);
+        true
+    }
+
+    fn intervals(&self) -> (Duration, Duration) {
+        if !self.on_battery { return (self.config.sample_interval, self.config.redraw_interval); }
+        (self.config.sample_interval.max(self.config.power.sample), self.config.redraw_interval.max(self.config.power.redraw))
+    }
+
+    fn probes_paused(&self) -> bool {
+        self.on_battery && !self.config.power.probes
+    }
+
+    fn update_power(&mut self) {
+        let battery = self.config.power.adaptive && power::on_battery() == Some(true);
+        if battery == self.on_battery { return; }
+        self.on_battery = battery;
+        power::set_paused(self.probes_paused());
+        let (sample, _) = self.intervals();
+        self.events.push("power", if battery { format!("on battery: sampling every {} s{}", format::number(sample.as_secs_f64(), 1), if self.probes_paused() { ", background probes paused" } else { "" }) } else { "on AC power: normal sampling".to_string() });
+    }
+
+    fn update_latency(&mut self) {
+        let Some(probes) = &self.latency_probes else { return };
+        for sample in probes.try_iter() {
+            let series = self.latency.entry(sample.label).or_insert_with(|| gateway::Series::new(sample.host.clone()));
+            series.host = sample.host;
+            series.push(sample.rtt);
+        }
+        let stale = Duration::from_secs(self.config.latency_interval.max(1) * 3 + 10);
+        self.latency.retain(|_, series| series.seen.elapsed() < stale);
+    }
+
+    fn latency_rows(&self) -> Vec<(String, &gateway::Series)> {
+        let mut rows: Vec<(String, &gateway::Series)> = self.latency.iter().filter(|(label, _)| *label != gateway::INTERNET).map(|(iface, series)| (format!("gw {}", iface), series)).collect();
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        rows.extend(self.latency.get(gateway::INTERNET).map(|series| (gateway::INTERNET.to_string(), series)));
+        rows
+    }
+
+    fn update_sla(&mut self) {
+        let Some(checks) = &self.sla_checks else { return };
+        let now = clock::epoch_now();
+        for outcome in checks.try_iter() {
+            self.sla.record(&outcome.name, outcome.up, now);
+            self.sla_status.insert(outcome.name, outcome.latency);
+        }
+        if self.every(60.0) {
+            if let Err(e) = self.sla.save() { self.events.push("sla", format!("cannot save availability history: {}", e)); }
+        }
+    }
+
+    fn update_outages(&mut self) {
+        let (Some(checks), Some(config)) = (&self.outage_checks, &self.config.outages) else { return };
+        let now = clock::epoch_now();
+        let mut changed = false;
+        for probe in checks.try_iter() {
+            if probe.rtt.is_some() {
+                if probe.iface != "-" { self.online_iface = probe.iface; }
+                self.outage_failures = 0;
+                self.outage_since = None;
+                if let Some(outage) = self.outages.last_mut().filter(|o| o.is_open()) {
+                    outage.end = Some(now);
+                    self.events.push("outage", format!("connectivity back on {} after {}", outage.iface, format::duration(outage.duration(now))));
+                    changed = true;
+                }
+                continue;
+            }
+            self.outage_failures += 1;
+            let since = *self.outage_since.get_or_insert(now);
+            if self.outage_failures != config.failures.max(1) || self.outages.last().is_some_and(|o| o.is_open()) { continue; }
+            let iface = if probe.iface == "-" && !self.online_iface.is_empty() { self.online_iface.clone() } else { probe.iface };
+            self.events.push("outage", format!("connectivity lost on {} ({} unreachable)", iface, config.target));
+            self.outages.push(outages::Outage { start: since, end: None, iface, target: config.target.clone(), stopped: None });
+            changed = true;
+        }
+        if changed { if let Err(e) = outages::save(&self.outages) { self.events.push("outage", format!("cannot save the outage log: {}", e)); } }
+    }
+
+    fn update_certificates(&mut self) {
+        let (Some(checks), Some(config)) = (&self.cert_checks, &self.config.certificates) else { return };
+        let now = clock::epoch_now();
+        for check in checks.try_iter() {
+            match &check.result {
+                Ok(cert) => {
+                    let days = (cert.not_after - now).div_euclid(86_400);
+                    if self.cert_alerted.get(&check.endpoint).is_some_and(|alerted| *alerted != cert.not_after) { self.cert_alerted.remove(&check.endpoint); }
+                    if days < config.warn_days && !self.cert_alerted.contains_key(&check.endpoint) {
+                        let message = format!("{} expire dans {} jour(s) ({})", check.endpoint, days, format::date(cert.not_after));
+                        Self::send_notification("cert-expiry", "Certificat TLS bientôt expiré", &message, days < 3);
+                        self.events.push("tls", format!("certificate of {} expires in {} days", check.endpoint, days));
+                        self.cert_alerted.insert(check.endpoint.clone(), cert.not_after);
                     }
                 }
+                Err(e) => self.events.push("tls", format!("{}: {}", check.endpoint, e)),
+            }
+            match self.certs.iter_mut().find(|c| c.endpoint == check.endpoint) {
+                Some(existing) => *existing = check,
+                None => self.certs.push(check),
             }
         }
-        if last_tick.elapsed() >= tick_rate { app.update_metrics(); last_tick = Instant::now(); }
     }
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
-    Ok(())
-}
 
-fn ui(f: &mut Frame, app: &mut App) {
-    let main_chunks = Layout::default().direction(Direction::Vertical).constraints([
-        Constraint::Percentage(60), 
-        Constraint::Percentage(30), 
-        Constraint::Length(3)
-    ]).split(f.size());
-
-    let top_chunks = Layout::default().direction(Direction::Horizontal).constraints([
-        Constraint::Percentage(40), 
-        Constraint::Percentage(60)
-    ]).split(main_chunks[0]);
-
-    let (title, items) = match app.selection_mode {
-        SelectionMode::WiFi => (" [ WIFI SCAN ] ", app.wifi_ssids.iter().map(|s| {
-            let active = s == &app.current_ssid;
-            ListItem::new(format!(" {} {}", if active { "📶" } else { "  " }, s)).style(if active { Style::default().fg(Color::Yellow) } else { Style::default() })
-        }).collect::<Vec<ListItem>>()),
-        _ => (" [ VPN LIST ] ", app.vpn_names.iter().map(|s| {
-            let active = app.active_vpns.contains(s);
-            ListItem::new(format!(" {} {}", if active { "●" } else { "○" }, s)).style(if active { Style::default().fg(Color::Cyan) } else { Style::default() })
-        }).collect::<Vec<ListItem>>()),
-    };
+    fn update_iperf(&mut self) {
+        let Some(rx) = &self.iperf_rx else { return };
+        let key = format!("iperf3:{}", self.iperf_target);
+        for update in rx.try_iter().collect::<Vec<_>>() {
+            match update {
+                iperf::Update::Rate(mbits) => {
+                    self.rates.insert(key.clone(), (mbits, 0.0));
+                    let entry = self.interfaces.entry(key.clone()).or_insert(InterfaceData { history: Vec::new(), current_speed: 0.0, color: Color::LightYellow, remote: true });
+                    entry.current_speed = mbits;
+                    entry.history.push((self.counter, mbits));
+                    if entry.history.len() > 300 { entry.history.remove(0); }
+                    if let Some(pos) = self.graph_targets(&self.get_active_ips()).iter().position(|(name, _)| **name == key).filter(|_| self.interfaces[&key].history.len() == 1) { self.graph_index = pos; }
+                }
+                iperf::Update::Done(lines) => {
+                    self.events.push("iperf", format!("{}: {}", self.iperf_target, lines[1..].iter().map(|l| l.trim()).collect::<Vec<_>>().join(", ")));
+                    self.popup = Some((format!(" iperf3 {} ", self.iperf_target), lines));
+                    self.iperf_rx = None;
+                }
+                iperf::Update::Failed(e) => {
+                    self.events.push("iperf", format!("{}: {}", self.iperf_target, e));
+                    self.popup = Some((" iperf3 ".to_string(), vec![format!("Test against {} failed: {}", self.iperf_target, e)]));
+                    self.iperf_rx = None;
+                }
+            }
+        }
+        if self.iperf_rx.is_none() { self.rates.remove(&key); }
+    }
 
-    let list_widget = List::new(items)
-        .block(Block::default().title(title).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(if app.selection_mode == SelectionMode::WiFi { Color::Yellow } else { Color::Cyan })))
-        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
-        .highlight_symbol(">> ");
-    f.render_stateful_widget(list_widget, top_chunks[0], &mut app.list_state);
+    fn start_wps(&mut self) {
+        let target = match self.selection_mode {
+            SelectionMode::AccessPoints => self.list_state.selected().and_then(|i| self.bssids().get(i).map(|ap| (ap.ssid.clone(), Some(ap.bssid.clone())))),
+            _ => self.selected_name().map(|ssid| {
+                let bssid = self.access_points.iter().filter(|ap| ap.ssid == ssid).max_by_key(|ap| ap.signal).map(|ap| ap.bssid.clone());
+                (ssid, bssid)
+            }),
+        };
+        let Some((ssid, bssid)) = target else { return };
+        if self.wps_rx.is_some() { self.popup = Some((" WPS push-button ".to_string(), self.wps_log.clone())); return; }
+        let Some(iface) = wps::wifi_iface() else { self.popup = Some((" WPS push-button ".to_string(), vec!["No Wi-Fi interface found.".to_string()])); return };
+        self.wps_log = vec![format!("Router: {} ({})", ssid, bssid.as_deref().unwrap_or("any access point in push-button mode"))];
+        self.popup = Some((" WPS push-button ".to_string(), self.wps_log.clone()));
+        self.events.push("user", format!("WPS push-button pairing with {} on {}", ssid, iface));
+        self.wps_rx = Some(wps::start(iface, bssid));
+    }
 
-    let active_ips = app.get_active_ips();
-    let ifs: Vec<ListItem> = active_ips.iter().map(|(n, ip)| {
-        let color = if n.starts_with("tun") || n.starts_with("wg") { Color::Cyan } else { Color::Green };
-        ListItem::new(format!(" • {:<15}: {}", n, ip)).style(Style::default().fg(color))
-    }).collect();
-    f.render_widget(List::new(ifs).block(Block::default().title(" [ ACTIVE INTERFACES ] ").borders(Borders::ALL)), top_chunks[1]);
-
-    let mut ifaces_with_ip: Vec<_> = app.interfaces.iter()
-        .filter(|(name, _)| active_ips.iter().any(|(ip_name, _)| ip_name == *name))
-        .collect();
-    let mut physical_active: Vec<_> = ifaces_with_ip.iter().filter(|(n, _)| n.starts_with('e') || n.starts_with('w')).collect();
-    let mut tunnel_active: Vec<_> = ifaces_with_ip.iter().filter(|(n, _)| n.starts_with("tun") || n.starts_with("wg") || n.starts_with("ppp")).collect();
-
-    physical_active.sort_by_key(|(n, _)| (*n).clone());
-    tunnel_active.sort_by_key(|(n, _)| (*n).clone());
-
-    if app.graph_index % 2 != 0 && !tunnel_active.is_empty() {
-        let (name, data) = tunnel_active[(app.graph_index / 2) % tunnel_active.len()];
-        render_braille_graph(f, main_chunks[1], name, data.current_speed, &data.history, Color::Cyan, app.counter);
-    } else if let Some((name, data)) = physical_active.first() {
-        render_braille_graph(f, main_chunks[1], name, data.current_speed, &data.history, data.color, app.counter);
-    } else {
-        f.render_widget(Paragraph::new("Attente d'une IP active...").alignment(ratatui::layout::Alignment::Center).block(Block::default().borders(Borders::ALL)), main_chunks[1]);
+    fn update_wps(&mut self) {
+        let Some(rx) = &self.wps_rx else { return };
+        for update in rx.try_iter().collect::<Vec<_>>() {
+            let showing = self.popup.as_ref().is_some_and(|(title, _)| title == " WPS push-button ");
+            match update {
+                wps::Update::Stage(line) => {
+                    self.wps_log.push(line);
+                    if showing { self.popup = Some((" WPS push-button ".to_string(), self.wps_log.clone())); }
+                }
+                wps::Update::Done(ssid) => {
+                    self.events.push("wifi", format!("joined {} with WPS", ssid));
+                    self.wps_log.push(format!("Connected to '{}'.", ssid));
+                    if !self.known_wifi.contains(&ssid) { self.wps_log.push("The credentials are held by wpa_supplicant; NetworkManager has no saved profile for this network yet.".to_string()); }
+                    self.popup = Some((" WPS push-button ".to_string(), self.wps_log.clone()));
+                    self.wps_rx = None;
+                }
+                wps::Update::Failed(e) => {
+                    self.events.push("wifi", format!("WPS pairing failed: {}", e));
+                    self.wps_log.push(format!("Failed: {}", e));
+                    self.popup = Some((" WPS push-button ".to_string(), self.wps_log.clone()));
+                    self.wps_rx = None;
+                }
+            }
+        }
     }
 
-    f.render_widget(Paragraph::new(" [TAB] Mode | [G] Graph | [A] Add VPN | [ENTER] Connect | [X] Disc | [Q] Quit ").block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded)).style(Style::default().fg(Color::Gray)), main_chunks[2]);
+    fn start_iperf(&mut self, text: &str) {
+        let request = match iperf::Request::parse(text) {
+            Ok(request) => request,
+            Err(e) => { self.popup = Some((" iperf3 ".to_string(), vec![e, "Expected: <server> [-R] [seconds]".to_string()])); return; }
+        };
+        self.interfaces.retain(|name, _| !name.starts_with("iperf3:"));
+        self.rates.retain(|name, _| !name.starts_with("iperf3:"));
+        self.iperf_target = request.host.clone();
+        self.events.push("user", format!("iperf3 {} test against {} for {}s", if request.reverse { "download" } else { "upload" }, request.host, request.seconds));
+        self.iperf_rx = Some(iperf::start_client(&request));
+    }
 
-    if app.selection_mode == SelectionMode::PasswordInput {
-        let area = centered_rect(50, 20, f.size());
-        f.render_widget(Clear, area);
-        f.render_widget(Paragraph::new("*".repeat(app.password_input.len())).block(Block::default().title(" Password Required ").borders(Borders::ALL).border_style(Style::default().fg(Color::Magenta)).border_type(BorderType::Double)).alignment(ratatui::layout::Alignment::Center), area);
+    fn toggle_iperf_server(&mut self) {
+        let message = match self.iperf_server.take() {
+            Some(mut child) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                "iperf3 server stopped.".to_string()
+            }
+            None => match iperf::start_server() {
+                Ok(child) => { self.iperf_server = Some(child); "iperf3 server listening on port 5201 until stopped or DashNet exits.".to_string() }
+                Err(e) => format!("Cannot start iperf3 -s: {}", e),
+            },
+        };
+        self.events.push("iperf", message.clone());
+        self.popup = Some((" iperf3 server ".to_string(), vec![message]));
     }
-}
 
-fn centered_rect(px: u16, py: u16, r: Rect) -> Rect {
-    let layout = Layout::default().direction(Direction::Vertical).constraints([Constraint::Percentage((100-py)/2), Constraint::Percentage(py), Constraint::Percentage((100-py)/2)]).split(r);
-    Layout::default().direction(Direction::Horizontal).constraints([Constraint::Percentage((100-px)/2), Constraint::Percentage(px), Constraint::Percentage((100-px)/2)]).split(layout[1])[1]
+    fn close_outages(&mut self) {
+        let Some(outage) = self.outages.last_mut().filter(|o| o.is_open()) else { return };
+        outage.stopped = Some(clock::epoch_now());
+        let _ = outages::save(&self.outages);
+    }
+
+    fn update_lan(&mut self) {
+        let Some(scan) = &self.lan_scan else { return };
+        let batches: Vec<Vec<lan::Neighbor>> = scan.try_iter().collect();
+        if batches.is_empty() { return; }
+        let now = clock::epoch_now();
+        let networks = self.config.lan.as_ref().map(|l| l.networks.clone()).unwrap_or_default();
+        let scanned: HashSet<String> = self.lan_devices.iter().map(|d| d.network.clone()).collect();
+        for neighbor in batches.into_iter().flatten() {
+            let network = if !self.current_ssid.is_empty() && usage::is_wireless(&neighbor.iface) { self.current_ssid.clone() } else { neighbor.iface.clone() };
+            if let Some(device) = self.lan_devices.iter_mut().find(|d| d.mac == neighbor.mac && d.network == network) {
+                device.ip = neighbor.ip;
+                device.iface = neighbor.iface;
+                device.last_seen = now;
+                continue;
+            }
+            let watched = networks.is_empty() || networks.iter().any(|n| *n == network || *n == neighbor.iface);
+            if scanned.contains(&network) && watched {
+                Self::send_notification("new-device", "Nouvel appareil", &format!("{} ({}) sur {}.", neighbor.mac, neighbor.ip, network), true);
+                self.events.push("lan", format!("new device {} ({}) on {}", neighbor.mac, neighbor.ip, network));
+            }
+            let label = self.lan_devices.iter().find(|d| d.mac == neighbor.mac).map(|d| d.label.clone()).unwrap_or_default();
+            self.lan_devices.push(lan::Device { mac: neighbor.mac, label, ip: neighbor.ip, iface: neighbor.iface, network, first_seen: now, last_seen: now });
+        }
+        if let Err(e) = lan::save(&self.lan_devices) { self.events.push("lan", format!("cannot save known devices: {}", e)); }
+    }
+
+    fn update_roaming(&mut self) {
+        let Some(roaming) = &self.config.roaming else { return };
+        if self.current_ssid.is_empty() || self.current_signal >= roaming.threshold { self.weak_since = None; return; }
+        let weak_since = *self.weak_since.get_or_insert_with(Instant::now);
+        if weak_since.elapsed() < Duration::from_secs(roaming.hold) { return; }
+        if self.last_roam.is_some_and(|t| t.elapsed() < Duration::from_secs(roaming.cooldown)) { return; }
+        let candidate = self.visible_signals.iter()
+            .filter(|(ssid, signal)| *ssid != self.current_ssid && self.known_wifi.contains(ssid) && *signal >= self.current_signal.saturating_add(roaming.margin))
+            .max_by_key(|(_, signal)| *signal).cloned();
+        if let Some((ssid, signal)) = candidate {
+            let message = format!("{} ({}%) → {} ({}%)", self.current_ssid, self.current_signal, ssid, signal);
+            Self::send_notification("roam", "Roaming WiFi", &message, false);
+            self.events.push("roam", message);
+            let _ = Command::new("nmcli").args(["con", "up", "id", &ssid]).stdout(Stdio::null()).stderr(Stdio::null()).spawn();
+            self.last_roam = Some(Instant::now());
+            self.weak_since = None;
+        }
+    }
+
+    fn update_failover(&mut self) {
+        let (Some(failover), Some(probes)) = (&self.config.failover, &self.uplink_probes) else { return };
+        for probe in probes.try_iter() {
+            let count = self.uplink_failures.entry(probe.iface).or_insert(0);
+            *count = if probe.rtt.is_some() { 0 } else { *count + 1 };
+        }
+        if self.failover_task.is_some() || self.checkpoint_task.is_some() { return; }
+        let failures = &self.uplink_failures;
+        let held = self.uplink_hold.as_ref().filter(|(_, since)| since.elapsed() < Duration::from_secs(600)).map(|(u, _)| u);
+        let Some(desired) = failover.uplinks.iter().find(|u| failures.get(*u).copied().unwrap_or(0) < failover.failures && Some(*u) != held).cloned() else {
+            if !self.wan_down {
+                self.wan_down = true;
+                Self::send_notification("wan-down", "Aucune liaison WAN", "Toutes les liaisons configurées sont injoignables.", true);
+                self.events.push("failover", "all uplinks down".to_string());
+            }
+            return;
+        };
+        self.wan_down = false;
+        let Some(active) = self.active_uplink.clone() else { self.active_uplink = Some(desired); return };
+        if desired == active { return; }
+        let message = format!("{} → {}", active, desired);
+        Self::send_notification("failover", "Bascule WAN", &message, true);
+        self.events.push("failover", message.clone());
+        self.protect(format!("failover {}", message), checkpoint::Undo::Uplink(active.clone(), desired.clone()), false, Change::Failover(active, desired));
+    }
+
+    fn checkpoint_target(&self) -> String {
+        self.config.checkpoint.target.clone().unwrap_or_else(|| self.report_target())
+    }
+
+    fn protect(&mut self, change: String, undo: checkpoint::Undo, confirm: bool, then: Change) {
+        if self.checkpoint_task.is_some() {
+            self.popup = Some((" Checkpoint ".to_string(), vec![format!("A checkpoint is still being created, try the {} again in a moment.", change)]));
+            return;
+        }
+        if !self.config.checkpoint.enabled || self.rollback_task.is_some() { return self.apply_change(then); }
+        let (timeout, confirm) = (self.config.checkpoint.timeout.max(10), confirm && self.config.checkpoint.confirm);
+        if let Some(pending) = self.checkpoint.as_mut() {
+            if let Some(path) = pending.path.clone() { std::thread::spawn(move || checkpoint::extend(&path, timeout + 15)); }
+            pending.changes.push((change, undo));
+            pending.started = Instant::now();
+            pending.confirm |= confirm;
+            return self.apply_change(then);
+        }
+        let target = self.checkpoint_target();
+        self.checkpoint_task = Some(task::Task::spawn(move || (checkpoint::create(timeout + 15), health::probe(None, &target).is_some())));
+        self.guarded = Some((change, undo, confirm, then));
+    }
+
+    fn checkpoint_created(&mut self, created: Result<String, String>, online_before: bool) {
+        let Some((change, undo, confirm, then)) = self.guarded.take() else { return };
+        let timeout = self.config.checkpoint.timeout.max(10);
+        let path = match created {
+            Ok(path) => Some(path),
+            Err(e) => { self.events.push("checkpoint", format!("no NetworkManager checkpoint before {}: {}", change, e)); None }
+        };
+        if path.is_none() && matches!(undo, checkpoint::Undo::Nothing | checkpoint::Undo::Uplink(..)) { return self.apply_change(then); }
+        self.events.push("checkpoint", format!("{} before {}, rollback in {} s{}", if path.is_some() { "checkpoint created" } else { "undo recorded" }, change, timeout, if online_before { String::new() } else { format!(" ({} not answering, no rollback on connectivity loss)", self.checkpoint_target()) }));
+        self.checkpoint = Some(checkpoint::Pending { path, changes: vec![(change, undo)], started: Instant::now(), timeout: Duration::from_secs(timeout), confirm, online_before, failures: 0, probed: Instant::now() });
+        self.apply_change(then);
+    }
+
+    fn apply_change(&mut self, change: Change) {
+        match change {
+            Change::Failover(active, desired) => {
+                self.active_uplink = Some(desired.clone());
+                self.failover_task = Some(task::Task::spawn(move || failover::switch_to(&desired, Some(&active))));
+            }
+            Change::AddRoute(spec) => {
+                let result = routes::add(&spec);
+                if result.is_err() { self.abandon_change(); }
+                self.events.push("user", match &result { Ok(()) => format!("route added: {}", spec), Err(e) => format!("adding route {} failed: {}", spec, e) });
+                self.popup = Some((" Routes ".to_string(), vec![match result { Ok(()) => format!("Added {}", spec), Err(e) => format!("ip refused the route: {}", e) }]));
+                self.refresh_routes();
+                self.ask_keep_changes();
+            }
+            Change::DeleteRoute(line) => {
+                let result = routes::delete(&line);
+                if result.is_err() { self.abandon_change(); }
+                self.events.push("user", match &result { Ok(()) => format!("route deleted: {}", line), Err(e) => format!("deleting route {} failed: {}", line, e) });
+                if let Err(e) = result { self.popup = Some((" Routes ".to_string(), vec![format!("ip refused to delete the route: {}", e)])); }
+                self.refresh_routes();
+                self.ask_keep_changes();
+            }
+            Change::CleanUpRoutes => {
+                let lines = routes::clean_up();
+                self.events.push("user", format!("cleaned up {} route(s) added by DashNet", lines.len()));
+                self.popup = Some((" Routes ".to_string(), lines));
+                self.refresh_routes();
+                self.ask_keep_changes();
+            }
+            Change::ProfileEdits(live) => {
+                let Some(editor) = &self.editor else { return };
+                let connection = editor.connection.clone();
+                let result = editor.apply();
+                self.events.push("user", match &result { Ok(n) => format!("{} propert{} changed on {}", n, if *n == 1 { "y" } else { "ies" }, connection), Err(e) => format!("editing {} failed: {}", connection, e) });
+                if live && self.checkpoint.is_some() {
+                    if let Some(n) = result.as_ref().ok().copied().filter(|n| *n > 0) {
+                        let summary = format!("{} propert{} updated on '{}', reactivating it under a checkpoint...", n, if n == 1 { "y" } else { "ies" }, connection);
+                        let name = connection.clone();
+                        self.run_tool(" Property editor ", move || vec![summary, match profile::reactivate(&name) { Ok(()) => format!("'{}' is active again with the new settings.", name), Err(e) => format!("Reactivating '{}' failed: {}", name, e) }]);
+                        if let Ok(editor) = profile::ProfileEditor::load(&connection) { self.editor = Some(editor); }
+                        self.ask_keep_changes();
+                        return;
+                    }
+                    self.abandon_change();
+                }
+                self.popup = Some((" Property editor ".to_string(), vec![match result {
+                    Ok(n) => format!("{} propert{} updated on '{}'.", n, if n == 1 { "y" } else { "ies" }, connection),
+                    Err(e) => format!("nmcli refused the change: {}", e),
+                }]));
+                if let Ok(editor) = profile::ProfileEditor::load(&connection) { self.editor = Some(editor); }
+            }
+            Change::CreateConnection(kind, values) => match wizard::create(kind, &values) {
+                Ok(created) => {
+                    self.events.push("user", format!("{} profile {} created", kind.title(), created.name));
+                    self.vpn_names = Self::get_nm_vpn_connections();
+                    self.known_wifi = Self::get_saved_wifi();
+                    self.enter_view(match kind { wizard::Kind::WiFi => SelectionMode::WiFi, wizard::Kind::WireGuard => SelectionMode::Vpn, wizard::Kind::Ethernet => SelectionMode::Overview });
+                    self.popup = Some((format!(" New {} connection ", kind.title()), vec![created.message]));
+                    if created.activate { self.start_activation(connect::Target::Profile(created.name), Vec::new()); }
+                    self.ask_keep_changes();
+                }
+                Err(e) => {
+                    self.abandon_change();
+                    self.events.push("user", format!("creating a {} profile failed: {}", kind.title(), e));
+                    self.popup = Some((format!(" New {} connection ", kind.title()), vec![format!("NetworkManager refused the profile: {}", e)]));
+                }
+            },
+        }
+    }
+
+    fn abandon_change(&mut self) {
+        let Some(pending) = self.checkpoint.as_mut() else { return };
+        pending.changes.pop();
+        if !pending.changes.is_empty() { return; }
+        if let Some(path) = pending.path.as_deref() { let _ = checkpoint::commit(path); }
+        self.checkpoint = None;
+    }
+
+    fn checkpoint_lines(&self) -> Vec<String> {
+        let Some(pending) = &self.checkpoint else { return Vec::new() };
+        let mut lines: Vec<String> = pending.changes.iter().map(|(change, _)| format!("- {}", change)).collect();
+        lines.push(String::new());
+        lines.push(format!("Everything is rolled back in {} s unless you keep the changes{}.", pending.remaining(), if pending.online_before { format!(", or as soon as {} stops answering", self.checkpoint_target()) } else { String::new() }));
+        lines.push(match &pending.path {
+            Some(_) => "NetworkManager restores the checkpoint by itself if DashNet is gone (lost SSH session).".to_string(),
+            None => "No NetworkManager checkpoint: only the routes can be put back.".to_string(),
+        });
+        lines
+    }
+
+    fn ask_keep_changes(&mut self) {
+        if self.checkpoint.as_ref().is_some_and(|p| p.confirm) { self.confirm = Some((" Keep these changes? [Y/n] ".to_string(), self.checkpoint_lines(), ConfirmAction::KeepChanges)); }
+    }
+
+    fn keep_changes(&mut self) {
+        let Some(pending) = self.checkpoint.take() else { return };
+        self.checkpoint_probe = None;
+        let changes: Vec<String> = pending.changes.into_iter().map(|(change, _)| change).collect();
+        self.events.push("checkpoint", match pending.path.as_deref().map_or(Ok(()), checkpoint::commit) {
+            Ok(()) => format!("kept: {}", changes.join(", ")),
+            Err(e) => format!("keeping {} failed: {}", changes.join(", "), e),
+        });
+    }
+
+    fn start_rollback(&mut self, reason: String) {
+        let Some(pending) = self.checkpoint.take() else { return };
+        self.checkpoint_probe = None;
+        if matches!(self.confirm, Some((_, _, ConfirmAction::KeepChanges))) { self.confirm = None; }
+        let changes: Vec<String> = pending.changes.iter().map(|(change, _)| change.clone()).collect();
+        for (_, undo) in &pending.changes {
+            if let checkpoint::Undo::Uplink(previous, failed) = undo {
+                self.active_uplink = Some(previous.clone());
+                self.uplink_hold = Some((failed.clone(), Instant::now()));
+            }
+        }
+        Self::send_notification("checkpoint-rollback", "Modifications réseau annulées", &format!("{} : {}.", changes.join(", "), reason), true);
+        self.events.push("checkpoint", format!("rolling back {}: {}", changes.join(", "), reason));
+        let undo: Vec<checkpoint::Undo> = pending.changes.into_iter().map(|(_, undo)| undo).collect();
+        let path = pending.path;
+        self.rollback_task = Some(task::Task::spawn(move || checkpoint::rollback(path.as_deref(), &undo)));
+    }
+
+    fn update_checkpoint(&mut self) {
+        let Some(pending) = &self.checkpoint else { return };
+        if pending.online_before && self.checkpoint_probe.is_none() && pending.probed.elapsed() >= Duration::from_secs(5) {
+            let target = self.checkpoint_target();
+            self.checkpoint_probe = Some(task::Task::spawn(move || health::probe(None, &target).is_some()));
+            if let Some(pending) = self.checkpoint.as_mut() { pending.probed = Instant::now(); }
+        }
+        if let Some(online) = self.checkpoint_probe.as_ref().and_then(|t| t.poll()) {
+            self.checkpoint_probe = None;
+            if let Some(pending) = self.checkpoint.as_mut() { pending.failures = if online { 0 } else { pending.failures + 1 }; }
+        }
+        let Some(pending) = &self.checkpoint else { return };
+        if pending.failures >= self.config.checkpoint.failures.max(1) {
+            self.start_rollback(format!("{} stopped answering", self.checkpoint_target()));
+        } else if pending.remaining() == 0 {
+            if pending.confirm { self.start_rollback("changes not confirmed in time".to_string()); } else { self.keep_changes(); }
+        } else if matches!(self.confirm, Some((_, _, ConfirmAction::KeepChanges))) {
+            let lines = self.checkpoint_lines();
+            if let Some(confirm) = self.confirm.as_mut() { confirm.1 = lines; }
+        }
+    }
+
+    fn update_watchdog(&mut self) {
+        let Some(watchdog) = &self.config.watchdog else { return };
+        if self.watchdog_probe.is_none() && self.last_probe.elapsed() >= Duration::from_secs(5) {
+            let target = watchdog.target.clone();
+            self.watchdog_probe = Some(task::Task::spawn(move || health::probe(None, &target).is_some()));
+            self.last_probe = Instant::now();
+        }
+        if let Some(online) = self.watchdog_probe.as_ref().and_then(|t| t.poll()) {
+            self.watchdog_probe = None;
+            if online {
+                if self.repair_step > 0 {
+                    let step = watchdog::Step::SEQUENCE[self.repair_step - 1].describe();
+                    Self::send_notification("connectivity-restored", "Connexion rétablie", &format!("Connectivité retrouvée après : {}.", step), false);
+                    self.events.push("watchdog", format!("connectivity restored after '{}'", step));
+                }
+                self.repair_step = 0;
+                self.last_online = Instant::now();
+            }
+        }
+        if self.repair_task.is_some() || self.last_online.elapsed() < Duration::from_secs(watchdog.timeout) { return; }
+        let wait = Duration::from_secs(watchdog.timeout + watchdog.step_wait * self.repair_step as u64);
+        let Some(step) = watchdog::Step::SEQUENCE.get(self.repair_step).copied() else { return };
+        if self.last_online.elapsed() < wait { return; }
+        self.repair_step += 1;
+        self.events.push("watchdog", format!("offline for {}s, step {}: {}", self.last_online.elapsed().as_secs(), self.repair_step, step.describe()));
+        if let watchdog::Step::Notify = step {
+            Self::send_notification("connectivity-lost", "Connexion perdue", "Le watchdog n'a pas pu rétablir la connectivité.", true);
+            return;
+        }
+        let Some(iface) = watchdog::default_iface().or_else(|| self.selected_interface()) else { return };
+        self.repair_task = Some(task::Task::spawn(move || step.run(&iface)));
+    }
+
+    fn secure_with(&mut self, vpn: &str) {
+        self.protection = Protection::Securing(Instant::now());
+        let _ = Command::new("nmcli").args(["con", "up", "id", vpn]).stdout(Stdio::null()).stderr(Stdio::null()).spawn();
+    }
+
+    fn sample_local(&mut self, current_stats: HashMap<String, net_monitor::NetStats>) {
+        let now = Instant::now();
+        let secs = now.duration_since(self.last_sample).as_secs_f64().max(0.001);
+        self.last_sample = now;
+        let interfaces = &self.interfaces;
+        self.rates.retain(|name, _| interfaces.get(name).is_some_and(|data| data.remote));
+        self.rates.extend(current_stats.iter().filter_map(|(name, stats)| self.last_stats.get(name).map(|old| {
+            let to_mbits = |bytes: u64| (bytes as f64 * 8.0) / (1024.0 * 1024.0) / secs;
+            (name.clone(), (to_mbits(stats.rx.saturating_sub(old.rx)), to_mbits(stats.tx.saturating_sub(old.tx))))
+        })));
+        for (name, stats) in current_stats.iter() {
+            if name == "lo" || self.docker_ifaces.contains(name) { continue; }
+            if let Some(old_stats) = self.last_stats.get(name) {
+                self.session_bytes.0 += stats.rx.saturating_sub(old_stats.rx);
+                self.session_bytes.1 += stats.tx.saturating_sub(old_stats.tx);
+                let session = self.session_by_iface.entry(name.clone()).or_default();
+                session.0 += stats.rx.saturating_sub(old_stats.rx);
+                session.1 += stats.tx.saturating_sub(old_stats.tx);
+                if !self.current_ssid.is_empty() && self.netns.is_none() && *name == self.wifi_iface {
+                    self.usage.add(&self.current_ssid, clock::epoch_now(), stats.rx.saturating_sub(old_stats.rx), stats.tx.saturating_sub(old_stats.tx));
+                }
+                let speed = ((stats.rx.saturating_sub(old_stats.rx) as f64) * 8.0) / (1024.0 * 1024.0) / secs;
+                let entry = self.interfaces.entry(name.clone()).or_insert(InterfaceData {
+                    history: Vec::new(),
+                    current_speed: 0.0,
+                    color: if name.starts_with('w') { Color::Yellow } else if name.starts_with('e') { Color::Green } else { Color::Cyan },
+                    remote: false,
+                });
+                entry.current_speed = speed;
+                entry.history.push((self.counter, speed));
+                if entry.history.len() > 300 { entry.history.remove(0); }
+            }
+        }
+        self.last_stats = current_stats;
+    }
+
+    fn update_metrics(&mut self) {
+        self.update_active_states();
+        let current_stats = match self.netns.clone() {
+            None => Some(net_monitor::get_net_data()),
+            Some(ns) => {
+                if self.netdev_task.is_none() { self.netdev_task = Some(task::Task::spawn(move || net_monitor::get_net_data_in(&ns))); }
+                self.netns_stats.take()
+            }
+        };
+        self.counter += 1.0;
+        self.tick_times.push_back((self.counter, clock::epoch_now()));
+        if self.tick_times.len() > 301 { self.tick_times.pop_front(); }
+        if let Some(current_stats) = current_stats { self.sample_local(current_stats); }
+        self.topology = topology::get_topology();
+        for sample in self.remote_samples.try_iter() {
+            let key = format!("{}:{}", sample.source, sample.iface);
+            self.rates.insert(key.clone(), (sample.rx, sample.tx));
+            let entry = self.interfaces.entry(key).or_insert(InterfaceData {
+                history: Vec::new(),
+                current_speed: 0.0,
+                color: Color::Magenta,
+                remote: true,
+            });
+            entry.current_speed = sample.rx;
+            entry.history.push((self.counter, sample.rx));
+            if entry.history.len() > 300 { entry.history.remove(0); }
+        }
+        self.update_router_adverts();
+        self.run_schedules();
+        self.update_failover();
+        self.update_checkpoint();
+        self.update_watchdog();
+        self.update_bandwidth_alerts();
+        self.update_anomalies();
+        self.update_lan();
+        self.update_sla();
+        self.update_latency();
+        self.update_outages();
+        self.update_certificates();
+        self.update_iperf();
+        self.update_wps();
+        if let Some(server) = &self.websocket { server.broadcast(&self.sample_json().to_string()); }
+        if let Some(bus) = &self.dbus {
+            let vpns = self.active_vpns.iter().filter(|v| self.vpn_names.contains(v)).cloned().collect();
+            bus.update(dbus::Status { ssid: self.current_ssid.clone(), vpns, rates: self.sample_json().to_string() });
+        }
+        if let Some(mqtt) = self.mqtt.as_ref().filter(|_| self.every(self.config.mqtt.as_ref().map_or(10, |m| m.interval.max(1)) as f64)) {
+            mqtt.publish("rates", self.sample_json().to_string(), false);
+        }
+        if let Some(sink) = self.influx.as_ref().filter(|_| self.every(self.config.influx.as_ref().map_or(10, |c| c.interval.max(1)) as f64)) {
+            let mut rates: Vec<(&String, (f64, f64))> = self.rates.iter().map(|(name, rate)| (name, *rate)).collect();
+            rates.sort_by(|a, b| a.0.cmp(b.0));
+            sink.record(&rates, self.session_bytes, clock::epoch_now());
+        }
+        if self.every(10.0) { self.update_power(); }
+        if self.config.protocols.as_ref().is_some_and(|p| self.every(p.interval)) {
+            if let Some(collector) = self.protocols.as_mut() { self.protocol_classes = collector.sample(); }
+        }
+        if self.every(60.0) { if let Err(e) = self.usage.save() { self.events.push("usage", format!("cannot save data usage: {}", e)); } }
+        if self.every(5.0) { self.refresh_details(None); }
+        if self.docker_ifaces_task.is_none() && self.netns.is_none() && self.every(30.0) { self.docker_ifaces_task = Some(task::Task::spawn(docker::interfaces)); }
+        if self.ntp_task.is_none() && self.every(30.0) { self.ntp_task = Some(task::Task::spawn(ntp::query)); }
+        if let Some(public) = self.config.public_ip.as_ref().filter(|p| self.public_ip_task.is_none() && !self.probes_paused() && self.every(p.interval.max(10) as f64)) {
+            let url = public.url.clone();
+            self.public_ip_task = Some(task::Task::spawn(move || addrs::public(&url)));
+        }
+        if self.selection_mode == SelectionMode::Journal && self.every(2.0) { self.refresh_journal(); }
+        if self.selection_mode == SelectionMode::AppVpn && self.every(5.0) { self.app_tunnels = appvpn::list(); }
+        if self.selection_mode == SelectionMode::Rules && self.every(2.0) { self.rules = rules::list(self.netns.as_deref()); }
+        if self.selection_mode == SelectionMode::Peers && self.every(2.0) { self.refresh_peers(); }
+        if self.selection_mode == SelectionMode::EncryptedDns && self.tool_task.is_none() && self.every(5.0) { self.refresh_dns_links(); }
+        if self.selection_mode == SelectionMode::TopTalkers && self.every(2.0) { self.refresh_talkers(); }
+        if matches!(self.selection_mode, SelectionMode::Routes | SelectionMode::Overview) && !self.monitor_only && self.every(2.0) { self.routes = routes::list(self.netns.as_deref()); }
+        if let Some(minutes) = self.config.speedtest_interval.filter(|_| !self.probes_paused()) {
+            let last = self.speedtests.last().map_or(0, |s| s.time).max(self.speedtest_attempt);
+            if clock::epoch_now() - last >= minutes as i64 * 60 { self.start_speedtest(); }
+        }
+        if self.config.rogue_detection && !self.monitor_only && self.rogue_task.is_none() && self.every(30.0) { self.rogue_task = Some(task::Task::spawn(rogue::scan)); }
+        if let (Some(target), true, None) = (self.connect_target.clone().filter(|_| self.every(1.0)), self.connect_task.is_some(), &self.stage_task) {
+            self.stage_task = Some(task::Task::spawn(move || connect::stage(&target)));
+        }
+        self.interfaces.retain(|name, data| data.remote || self.last_stats.contains_key(name));
+    }
+
+    fn poll_tasks(&mut self) {
+        if let Some(result) = self.netdev_task.as_ref().and_then(|t| t.poll()) {
+            self.netdev_task = None;
+            match result {
+                Ok(stats) => { self.netns_stats = Some(stats); self.netns_error = None; }
+                Err(e) => {
+                    if self.netns_error.as_ref() != Some(&e) { self.events.push("netns", format!("cannot read the counters of {}: {}", self.netns.as_deref().unwrap_or_default(), e)); }
+                    self.netns_error = Some(e);
+                }
+            }
+        }
+        if let Some((created, online_before)) = self.checkpoint_task.as_ref().and_then(|t| t.poll()) {
+            self.checkpoint_task = None;
+            self.checkpoint_created(created, online_before);
+        }
+        if let Some((peers, errors)) = self.peers_task.as_ref().and_then(|t| t.poll()) {
+            (self.peers, self.peer_errors) = (peers, errors);
+            self.peers_task = None;
+            if self.selection_mode == SelectionMode::Peers { self.list_state.select(Some(self.list_state.selected().unwrap_or(0).min(self.peers.len().saturating_sub(1)))); }
+        }
+        if let Some(containers) = self.containers_task.as_ref().and_then(|t| t.poll()) {
+            self.containers = containers;
+            self.containers_task = None;
+            if self.selection_mode == SelectionMode::Docker { self.list_state.select(Some(self.list_state.selected().unwrap_or(0).min(self.containers.as_ref().map_or(0, |c| c.len()).saturating_sub(1)))); }
+        }
+        if let Some(ifaces) = self.docker_ifaces_task.as_ref().and_then(|t| t.poll()) {
+            self.docker_ifaces = ifaces;
+            self.docker_ifaces_task = None;
+        }
+        if let Some(links) = self.dns_task.as_ref().and_then(|t| t.poll()) {
+            self.dns_links = links;
+            self.dns_task = None;
+            if self.selection_mode == SelectionMode::EncryptedDns { self.list_state.select(Some(self.list_state.selected().unwrap_or(0).min(self.dns_links.as_ref().map_or(0, |l| l.len()).saturating_sub(1)))); }
+        }
+        if let Some(stage) = self.stage_task.as_ref().and_then(|t| t.poll()) {
+            self.stage_task = None;
+            if self.connect_task.is_some() && stage != self.connect_stage {
+                self.events.push("user", format!("{}: {}", self.connect_target.as_ref().map_or("", |t| t.name()), stage));
+                self.connect_stage = stage;
+            }
+        }
+        if let Some(outcome) = self.connect_task.as_ref().and_then(|t| t.poll()) {
+            self.connect_task = None;
+            let name = self.connect_target.as_ref().map(|t| t.name().to_string()).unwrap_or_default();
+            match outcome {
+                _ if self.connect_cancelled => { self.events.push("user", format!("activation of {} cancelled", name)); self.connect_target = None; }
+                connect::Outcome::Connected => { self.events.push("user", format!("{} connected", name)); self.connect_target = None; }
+                connect::Outcome::SecretRequired(fields) if !matches!(self.selection_mode, SelectionMode::Credentials | SelectionMode::TextInput | SelectionMode::Palette) => {
+                    self.events.push("user", format!("{} needs credentials", name));
+                    self.open_form(fields);
+                }
+                connect::Outcome::SecretRequired(_) => self.connect_target = None,
+                connect::Outcome::Failed(e) => { self.connection_failed(" Connection failed ", &name, &e); self.connect_target = None; }
+            }
+        }
+        if let Some((name, result)) = self.disconnect_task.as_ref().and_then(|t| t.poll()) {
+            self.disconnect_task = None;
+            match result {
+                Ok(()) => self.events.push("user", format!("{} disconnected", name)),
+                Err(e) => self.connection_failed(" Disconnect failed ", &name, &e),
+            }
+        }
+        if let Some((message, gateway)) = self.upnp_task.as_ref().and_then(|t| t.poll()) {
+            self.upnp = Some(gateway);
+            if !message.is_empty() { self.events.push("upnp", message.clone()); }
+            self.upnp_message = message;
+            self.upnp_task = None;
+        }
+        if let Some(update) = self.details_task.as_ref().and_then(|t| t.poll()) {
+            if update.action.is_some() || update.iface != self.details_iface { self.details_action = update.action; }
+            self.details = update.fields;
+            self.details_iface = update.iface;
+            self.details_task = None;
+            self.follow_protocols();
+        }
+        if let Some(progress) = &self.preset_progress {
+            loop {
+                match progress.try_recv() {
+                    Ok(line) => {
+                        self.events.push("preset", line.clone());
+                        if let Some(popup) = self.popup.as_mut() { popup.1.push(line); }
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => { self.preset_progress = None; break; }
+                }
+            }
+        }
+        if let Some(result) = self.failover_task.as_ref().and_then(|t| t.poll()) {
+            match result {
+                Ok(message) => self.events.push("failover", message),
+                Err(e) => {
+                    Self::send_notification("failover-failed", "Bascule WAN échouée", &e, true);
+                    self.events.push("failover", format!("switch failed: {}", e));
+                }
+            }
+            self.failover_task = None;
+        }
+        if let Some(lines) = self.rollback_task.as_ref().and_then(|t| t.poll()) {
+            for line in &lines { self.events.push("checkpoint", line.clone()); }
+            self.popup = Some((" Changes rolled back ".to_string(), lines));
+            self.rollback_task = None;
+            self.routes = routes::list(self.netns.as_deref());
+            self.created_routes = routes::created();
+        }
+        if let Some(result) = self.repair_task.as_ref().and_then(|t| t.poll()) {
+            self.events.push("watchdog", match result { Ok(message) => message, Err(e) => format!("repair failed: {}", e) });
+            self.repair_task = None;
+        }
+        if let Some(report) = self.wizard_task.as_ref().and_then(|t| t.poll()) {
+            self.wizard_task = None;
+            match report.fix {
+                Some(fix) => {
+                    self.popup = None;
+                    let mut lines = report.lines;
+                    lines.push(format!("Apply it now ({})?", fix.describe()));
+                    self.confirm = Some((" Troubleshooting ".to_string(), lines, ConfirmAction::ApplyFix(fix)));
+                }
+                None => if let Some(popup) = self.popup.as_mut() { popup.1 = report.lines; },
+            }
+        }
+        if let Some(result) = self.speedtest_task.as_ref().and_then(|t| t.poll()) {
+            self.speedtest_task = None;
+            let lines = match result {
+                Ok(sample) => {
+                    let line = format!("↓ {} Mb/s  ↑ {} Mb/s  ping {} ms  {}", format::number(sample.download, 1), format::number(sample.upload, 1), format::number(sample.ping, 0), sample.server);
+                    self.events.push("speedtest", line.clone());
+                    self.speedtests.push(sample);
+                    vec![line]
+                }
+                Err(e) => {
+                    self.events.push("speedtest", format!("failed: {}", e));
+                    vec![format!("Speed test failed: {}", e)]
+                }
+            };
+            if let Some(popup) = self.popup.as_mut().filter(|p| p.0 == " Speed test ") { popup.1 = lines; }
+        }
+        if let Some(aps) = self.rogue_task.as_ref().and_then(|t| t.poll()) {
+            self.rogue_task = None;
+            let (warnings, changed) = rogue::check(&mut self.rogue_history, &aps, &self.known_wifi);
+            if changed { if let Err(e) = rogue::save(&self.rogue_history) { self.events.push("rogue", format!("cannot save BSSID history: {}", e)); } }
+            for (bssid, message) in warnings {
+                if self.rogue_alerted.contains(&bssid) { continue; }
+                Self::send_notification("rogue-ap", "Point d'accès suspect", &message, true);
+                self.events.push("rogue", message);
+                self.rogue_alerted.push(bssid);
+            }
+        }
+        if let Some(rx) = &self.sweep_rx {
+            loop {
+                match rx.try_recv() {
+                    Ok(reply) => {
+                        let at = self.sweep_replies.partition_point(|r| r.ip < reply.ip);
+                        self.sweep_replies.insert(at, reply);
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        let alive = self.sweep_replies.iter().filter(|r| r.rtt.is_some()).count();
+                        self.events.push("sweep", format!("{}: {} of {} addresses answered", self.sweep_target, alive, self.sweep_total));
+                        self.sweep_rx = None;
+                        break;
+                    }
+                }
+            }
+        }
+        if let Some(ip) = self.public_ip_task.as_ref().and_then(|t| t.poll()) {
+            self.public_ip_task = None;
+            if let Some(ip) = ip.filter(|ip| self.public_ip.as_ref() != Some(ip)) {
+                match &self.public_ip {
+                    Some(old) => {
+                        self.events.push("ip", format!("public address {} → {}", old, ip));
+                        Self::send_notification("public-ip-change", "Adresse publique modifiée", &format!("{} → {}.", old, ip), false);
+                    }
+                    None => self.events.push("ip", format!("public address {}", ip)),
+                }
+                if let Some(mqtt) = &self.mqtt { mqtt.publish("address/public", ip.clone(), true); }
+                self.public_ip = Some(ip);
+            }
+        }
+        if let Some(status) = self.ntp_task.as_ref().and_then(|t| t.poll()) {
+            self.ntp_task = None;
+            if status.synced != self.ntp.synced && self.ntp.synced.is_some() {
+                self.events.push("clock", if status.synced == Some(true) { "system clock synchronized".to_string() } else { format!("system clock no longer synchronized: {}", status.describe()) });
+            }
+            self.ntp = status;
+        }
+        if let Some(lines) = self.journal_task.as_ref().and_then(|t| t.poll()) {
+            let following = self.list_state.selected().is_none_or(|i| i + 1 >= self.journal.len());
+            self.journal = lines;
+            if following && self.selection_mode == SelectionMode::Journal { self.list_state.select(self.journal.len().checked_sub(1)); }
+            self.journal_task = None;
+        }
+        if let Some(text) = self.report_task.as_ref().and_then(|t| t.poll()) {
+            self.report_task = None;
+            let saved = match report::save(&text) { Ok(path) => format!("Written to {}", path.display()), Err(e) => format!("Cannot save the report: {}", e) };
+            let copied = if clipboard::copy(&text).is_ok() { ", copied to the clipboard" } else { "" };
+            self.popup = Some((" Status report ".to_string(), std::iter::once(format!("{}{}", saved, copied)).chain(std::iter::once(String::new())).chain(text.lines().map(str::to_string)).collect()));
+        }
+        if let Some(lines) = self.tool_task.as_ref().and_then(|t| t.poll()) {
+            if let Some(popup) = self.popup.as_mut() { popup.1 = lines; }
+            self.tool_task = None;
+        }
+    }
+
+    fn status_report(&self) -> Vec<String> {
+        let now = clock::epoch_now();
+        let mut lines = vec![format!("# DashNet status report: {}", net_monitor::hostname()), format!("{} {}", format::date(now), format::time(now)), String::new(), "## Interfaces".to_string()];
+        let mut ips = self.get_active_ips();
+        ips.sort();
+        lines.extend(ips.iter().map(|(name, ip)| {
+            let rate = self.rates.get(name).map(|(rx, tx)| format!(", ↓ {} ↑ {}", format::rate(*rx, self.rate_unit), format::rate(*tx, self.rate_unit))).unwrap_or_default();
+            let totals = self.transfer_rows(name).iter().map(|(k, v)| format!(", {} {}", k.to_lowercase(), v)).collect::<String>();
+            format!("- {}{}: {}{}{}", name, if self.active_uplink.as_ref() == Some(name) { " (uplink)" } else { "" }, ip, rate, totals)
+        }));
+        let (rx, tx) = self.total_rates();
+        let boot = self.boot_bytes();
+        lines.push(format!("- total: ↓ {} ↑ {}, session ↓ {} ↑ {}, since boot ↓ {} ↑ {}", format::rate(rx, self.rate_unit), format::rate(tx, self.rate_unit), format::bytes(self.session_bytes.0), format::bytes(self.session_bytes.1), format::bytes(boot.0), format::bytes(boot.1)));
+        lines.push(String::new());
+        lines.push("## Connections".to_string());
+        lines.push(format!("- Wi-Fi: {}", if self.current_ssid.is_empty() { "not connected" } else { &self.current_ssid }));
+        let vpns: Vec<&String> = self.active_vpns.iter().filter(|v| self.vpn_names.contains(v)).collect();
+        lines.push(format!("- VPN: {}", if vpns.is_empty() { "none".to_string() } else { vpns.iter().map(|v| v.as_str()).collect::<Vec<_>>().join(", ") }));
+        lines.push(format!("- Clock: {}", self.ntp.describe()));
+        if !self.config.targets.is_empty() {
+            lines.push(String::new());
+            lines.push("## Services".to_string());
+            lines.extend(self.config.targets.iter().map(|t| {
+                let state = match self.sla_status.get(&t.name) { Some(Some(ms)) => format!("up, {} ms", format::number(*ms, 0)), Some(None) => "down".to_string(), None => "not checked yet".to_string() };
+                let windows: Vec<String> = sla::WINDOWS.iter().filter_map(|(label, span)| self.sla.availability(&t.name, *span, now).map(|pct| format!("{} {}%", label, format::number(pct, 2)))).collect();
+                format!("- {}: {}{}", t.name, state, if windows.is_empty() { String::new() } else { format!(" ({})", windows.join(", ")) })
+            }));
+        }
+        if self.events.len() > 0 {
+            lines.push(String::new());
+            lines.push("## Recent events".to_string());
+            let mut recent: Vec<String> = self.events.iter().rev().take(10).map(|e| format!("- {} [{}] {}", e.timestamp(), e.category, e.message)).collect();
+            recent.reverse();
+            lines.extend(recent);
+        }
+        lines
+    }
+
+    fn report_target(&self) -> String {
+        self.config.latency_target.clone().or_else(|| self.config.watchdog.as_ref().map(|w| w.target.clone())).or_else(|| self.config.failover.as_ref().map(|f| f.target.clone())).unwrap_or_else(|| "1.1.1.1".to_string())
+    }
+
+    fn start_report(&mut self) {
+        let (lines, target) = (self.status_report(), self.report_target());
+        self.popup = Some((" Status report ".to_string(), vec!["Measuring latency...".to_string()]));
+        self.report_task = Some(task::Task::spawn(move || report::complete(lines, &target)));
+    }
+
+    fn run_tool(&mut self, title: &str, job: impl FnOnce() -> Vec<String> + Send + 'static) {
+        self.popup = Some((title.to_string(), vec!["Running...".to_string()]));
+        self.tool_task = Some(task::Task::spawn(job));
+    }
+
+    fn launch_tool(&mut self, tool: Tool) {
+        match tool {
+            Tool::PortReachability => self.open_input(InputAction::ReachabilityPort),
+            Tool::Ipv6Diagnostics => {
+                let target = self.config.ipv6_target.clone();
+                self.run_tool(" IPv6 diagnostics ", move || ipv6::diagnose(&target));
+            }
+            Tool::Troubleshoot => {
+                self.popup = Some((" Troubleshooting ".to_string(), vec!["Running: link → IP → gateway → DNS → Internet → captive portal...".to_string()]));
+                self.wizard_task = Some(task::Task::spawn(troubleshoot::run_wizard));
+            }
+            Tool::DiagnosticsBundle => {
+                let events: Vec<String> = self.events.iter().map(|e| format!("{} [{}] {}", e.timestamp(), e.category, e.message)).collect();
+                let ssids: Vec<String> = self.known_wifi.iter().chain(&self.scanned_ssids).chain([&self.current_ssid]).cloned().collect();
+                self.run_tool(" Diagnostics bundle ", move || match diagnostics::write_bundle(events, ssids) {
+                    Ok(path) => vec![format!("Written to {}", path.display()), String::new(), "MAC addresses, UUIDs, SSIDs, public IP addresses and IPv6 link-local and ULA addresses are masked.".to_string(), "Review the file before sharing it.".to_string()],
+                    Err(e) => vec![e],
+                });
+            }
+            Tool::TestWebhooks => self.run_tool(" Webhooks ", webhook::test),
+            Tool::Certificate => self.open_input(InputAction::CertEndpoint),
+            Tool::Iperf => {
+                self.open_input(InputAction::Iperf);
+                self.text_input.set(self.iperf_target.clone());
+            }
+            Tool::IperfServer => self.toggle_iperf_server(),
+            Tool::EncryptedDns => self.enter_view(SelectionMode::EncryptedDns),
+            Tool::Bufferbloat => {
+                let target = self.config.outages.as_ref().map_or("1.1.1.1".to_string(), |o| o.target.clone());
+                self.popup = Some((" Bufferbloat ".to_string(), vec![format!("Measuring idle latency to {}, then again during a speed test (about 40 seconds)...", target)]));
+                self.tool_task = Some(task::Task::spawn(move || bufferbloat::run(&target)));
+            }
+            Tool::PingSweep => {
+                self.open_input(InputAction::PingSweep);
+                self.text_input.set(if self.sweep_target.is_empty() { sweep::local_subnet(&self.docker_ifaces).unwrap_or_default() } else { self.sweep_target.clone() });
+            }
+            Tool::SpeedTest => {
+                self.popup = Some((" Speed test ".to_string(), vec!["Running (this takes about 30 seconds)...".to_string()]));
+                self.start_speedtest();
+            }
+            Tool::ImportProfiles => {
+                self.open_input(InputAction::ImportDirectory);
+                self.text_input.set("~/dashnet-export".to_string());
+            }
+        }
+    }
+
+    fn start_upnp(&mut self, action: impl FnOnce() -> Result<String, String> + Send + 'static) {
+        if self.upnp_task.is_some() { return; }
+        self.upnp_task = Some(task::Task::spawn(move || {
+            let message = action().unwrap_or_else(|e| format!("Error: {}", e));
+            (message, upnp::discover())
+        }));
+    }
+
+    fn run_schedules(&mut self) {
+        let now = clock::epoch_now();
+        let last = std::mem::replace(&mut self.schedule_checked, now);
+        if now <= last { return; }
+        let due: Vec<(String, schedule::Action)> = self.config.schedules.iter()
+            .filter(|s| s.due(last, now))
+            .map(|s| (s.name.clone(), s.action.clone())).collect();
+        for (name, action) in due {
+            Self::send_notification("schedule", "Action planifiée", &format!("{} : {}", name, action.describe()), false);
+            self.events.push("schedule", format!("{}: {}", name, action.describe()));
+            match &action {
+                schedule::Action::Preset(preset) => {
+                    if let Some(index) = self.config.presets.iter().position(|p| &p.name == preset) { self.activate_preset(index); }
+                }
+                _ => action.spawn(),
+            }
+        }
+    }
+
+    fn activate_preset(&mut self, index: usize) {
+        let Some(preset) = self.config.presets.get(index).cloned() else { return };
+        if self.preset_progress.is_some() { return; }
+        self.popup = Some((format!(" Preset: {} ", preset.name), Vec::new()));
+        self.events.push("user", format!("activating preset {}", preset.name));
+        self.preset_progress = Some(presets::activate(preset));
+    }
+
+    fn start_speedtest(&mut self) {
+        if self.speedtest_task.is_some() { return; }
+        self.speedtest_attempt = clock::epoch_now();
+        self.speedtest_task = Some(task::Task::spawn(speedtest::run));
+    }
+
+    fn start_sweep(&mut self, target: String) {
+        match sweep::start(&target) {
+            Ok((total, rx)) => {
+                self.sweep_total = total;
+                self.sweep_replies.clear();
+                self.sweep_rx = Some(rx);
+                self.sweep_target = target;
+                self.selection_mode = SelectionMode::PingSweep;
+                self.list_state.select(Some(0));
+            }
+            Err(e) => self.popup = Some((" Ping sweep ".to_string(), vec![e])),
+        }
+    }
+
+    fn reconnect_last_vpn(&mut self) {
+        let Some(name) = self.last_vpn.clone().filter(|n| self.vpn_names.contains(n)) else {
+            self.popup = Some((" Reconnect VPN ".to_string(), vec!["No VPN has been used yet.".to_string()]));
+            return;
+        };
+        if self.active_vpns.contains(&name) { self.events.push("user", format!("{} is already up", name)); return; }
+        self.start_activation(connect::Target::Profile(name), Vec::new());
+    }
+
+    fn slot_badge(&self, name: &str) -> String {
+        self.slots.iter().position(|s| s.as_deref() == Some(name)).map(|i| format!(" [{}]", i + 1)).unwrap_or_default()
+    }
+
+    fn star(&self, name: &str) -> &'static str {
+        if self.favorites.iter().any(|f| f == name) { "★ " } else { "" }
+    }
+
+    fn toggle_favorite(&mut self) {
+        let Some(name) = self.selected_name() else { return };
+        match self.favorites.iter().position(|f| *f == name) {
+            Some(i) => { self.favorites.remove(i); self.events.push("user", format!("{} removed from favorites", name)); }
+            None => { self.favorites.push(name.clone()); self.events.push("user", format!("{} added to favorites", name)); }
+        }
+        if let Err(e) = favorites::save(&self.favorites) { self.events.push("user", format!("cannot save favorites: {}", e)); }
+        self.vpn_names.sort();
+        favorites::sort_first(&mut self.vpn_names, &self.favorites);
+        self.scanned_ssids.sort();
+        favorites::sort_first(&mut self.scanned_ssids, &self.favorites);
+        self.filter_wifi();
+        let list = if self.selection_mode == SelectionMode::WiFi { &self.wifi_ssids } else { &self.vpn_names };
+        self.list_state.select(list.iter().position(|n| *n == name));
+    }
+
+    fn toggle_blacklist(&mut self) {
+        let Some(name) = self.selected_name().filter(|_| self.selection_mode == SelectionMode::WiFi) else { return };
+        match self.blacklist.iter().position(|b| *b == name) {
+            Some(i) => { self.blacklist.remove(i); self.events.push("user", format!("{} removed from the SSID blacklist", name)); }
+            None => { self.blacklist.push(name.clone()); self.events.push("user", format!("{} hidden from the Wi-Fi scan", name)); }
+        }
+        if let Err(e) = blacklist::save(&self.blacklist) { self.events.push("user", format!("cannot save the blacklist: {}", e)); }
+        let idx = self.list_state.selected().unwrap_or(0);
+        self.filter_wifi();
+        self.list_state.select(Some(idx.min(self.wifi_ssids.len().saturating_sub(1))));
+    }
+
+    fn toggle_show_blacklisted(&mut self) {
+        self.show_blacklisted = !self.show_blacklisted;
+        let name = self.selected_name();
+        self.filter_wifi();
+        self.list_state.select(Some(name.and_then(|n| self.wifi_ssids.iter().position(|s| *s == n)).unwrap_or(0)));
+    }
+
+    fn pin_to_slot(&mut self, slot: usize) {
+        let Some(name) = self.selected_name() else { return };
+        let unpin = self.slots[slot].as_ref() == Some(&name);
+        for s in self.slots.iter_mut().filter(|s| s.as_ref() == Some(&name)) { *s = None; }
+        if !unpin { self.slots[slot] = Some(name.clone()); }
+        self.events.push("user", if unpin { format!("{} unpinned from slot {}", name, slot + 1) } else { format!("{} pinned to slot {}", name, slot + 1) });
+        if let Err(e) = slots::save(&self.slots, &self.config.slots) { self.events.push("user", format!("cannot save slots: {}", e)); }
+    }
+
+    fn activate_slot(&mut self, slot: usize) {
+        let Some(name) = self.slots[slot].clone() else {
+            self.popup = Some((" Quick connect ".to_string(), vec![format!("Slot {} is empty. Select a connection and press # to pin it.", slot + 1)]));
+            return;
+        };
+        if self.vpn_names.contains(&name) {
+            let _ = recent::remember_vpn(&name);
+            self.last_vpn = Some(name.clone());
+        }
+        let target = if self.vpn_names.contains(&name) || self.known_wifi.contains(&name) { connect::Target::Profile(name) } else { connect::Target::Network(name) };
+        self.start_activation(target, Vec::new());
+    }
+
+    fn open_journal(&mut self) {
+        self.journal_filters = self.selected_name().into_iter().chain(self.selected_interface()).collect();
+        self.journal_all = self.journal_filters.is_empty();
+        self.journal.clear();
+        self.list_state.select(None);
+        self.selection_mode = SelectionMode::Journal;
+        self.refresh_journal();
+    }
+
+    fn refresh_journal(&mut self) {
+        if self.journal_task.is_some() { return; }
+        let filters = if self.journal_all { Vec::new() } else { self.journal_filters.clone() };
+        self.journal_task = Some(task::Task::spawn(move || journal::tail(&filters, 500)));
+    }
+
+    fn connection_failed(&mut self, title: &str, name: &str, error: &str) {
+        self.events.push("user", format!("{}: {}", name, error));
+        Self::send_notification("connect-failed", &format!("Échec : {}", name), error, false);
+        let mut lines = vec![format!("{}: {}", name, error)];
+        if let Some(hint) = connect::hint(error) { lines.extend([String::new(), hint.to_string()]); }
+        self.popup = Some((title.to_string(), lines));
+    }
+
+    fn start_activation(&mut self, target: connect::Target, answers: Vec<(connect::Kind, String)>) {
+        if self.connect_task.is_some() { self.events.push("user", format!("{} is still being activated", self.connect_target.as_ref().map_or("a connection", |t| t.name()))); return; }
+        self.events.push("user", format!("connecting to {}", target.name()));
+        self.connect_target = Some(target.clone());
+        self.connect_since = Instant::now();
+        self.connect_stage = "waiting for NetworkManager".to_string();
+        self.connect_cancelled = false;
+        self.connect_task = Some(task::Task::spawn(move || if answers.is_empty() { connect::activate(&target) } else { connect::activate_with(&target, &answers) }));
+    }
+
+    fn cancel_activation(&mut self) {
+        let Some(target) = self.connect_target.clone() else { return };
+        if self.connect_task.is_none() || self.connect_cancelled { return; }
+        self.connect_cancelled = true;
+        self.events.push("user", format!("cancelling activation of {}", target.name()));
+        self.disconnect_task = Some(task::Task::spawn(move || { let result = connect::cancel(&target); (target.name().to_string(), result) }));
+    }
+
+    fn activation_badge(&self, name: &str) -> String {
+        const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+        match &self.connect_target {
+            Some(target) if self.connect_task.is_some() && target.name() == name => {
+                let state = if self.connect_cancelled { "cancelling" } else { &self.connect_stage };
+                format!("  {} {}s · {}", SPINNER[self.counter as usize % SPINNER.len()], self.connect_since.elapsed().as_secs(), state)
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn disconnect(&mut self, name: String) {
+        self.events.push("user", format!("disconnecting {}", name));
+        self.disconnect_task = Some(task::Task::spawn(move || { let result = connect::deactivate(&name); (name, result) }));
+    }
+
+    fn connect_selected(&mut self) {
+        if self.monitor_only || self.connect_task.is_some() { return; }
+        let idx = self.list_state.selected().unwrap_or(0);
+        let open = |ssid: &str| self.access_points.iter().any(|ap| ap.ssid == ssid && ap.security.is_empty());
+        let (target, saved) = match self.selection_mode {
+            SelectionMode::Vpn => {
+                let Some(name) = self.vpn_names.get(idx).cloned() else { return };
+                let _ = recent::remember_vpn(&name);
+                self.last_vpn = Some(name.clone());
+                (connect::Target::Profile(name), true)
+            }
+            SelectionMode::WiFi => {
+                let Some(name) = self.wifi_ssids.get(idx).cloned() else { return };
+                if self.known_wifi.contains(&name) { (connect::Target::Profile(name), true) } else { let saved = open(&name); (connect::Target::Network(name), saved) }
+            }
+            SelectionMode::AccessPoints => {
+                let Some(ap) = self.bssids().get(idx).map(|ap| (ap.bssid.clone(), self.known_wifi.contains(&ap.ssid) || ap.security.is_empty())) else { return };
+                (connect::Target::Network(ap.0), ap.1)
+            }
+            _ => return,
+        };
+        if saved {
+            self.start_activation(target, Vec::new());
+        } else {
+            self.open_form(connect::fields(&target));
+            self.connect_target = Some(target);
+        }
+    }
+
+    fn start_cert_flow(&mut self) {
+        let Some(name) = self.selected_name().filter(|_| self.selection_mode == SelectionMode::Vpn) else { return };
+        if let Err(e) = profile::cert_keys(&name) {
+            self.popup = Some((" Certificate files ".to_string(), vec![e]));
+            return;
+        }
+        self.cert_flow = Some((name, Vec::new()));
+        self.open_picker(home::home_dir().unwrap_or_else(|| PathBuf::from("/")));
+    }
+
+    fn open_picker(&mut self, dir: PathBuf) {
+        self.picker_entries = picker::list(&dir);
+        self.picker_dir = dir;
+        self.selection_mode = SelectionMode::FilePicker;
+        self.list_state.select(Some(0));
+    }
+
+    fn pick_file(&mut self, file: Option<PathBuf>) {
+        let Some((_, files)) = self.cert_flow.as_mut() else { return };
+        files.push(file);
+        if files.len() < profile::CERT_STEPS.len() { self.list_state.select(Some(0)); return; }
+        let Some((name, files)) = self.cert_flow.take() else { return };
+        self.selection_mode = SelectionMode::Vpn;
+        let mut lines: Vec<String> = profile::CERT_STEPS.iter().zip(&files).map(|(step, file)| format!("{:<19} {}", format!("{}:", step), file.as_ref().map_or("(unchanged)".to_string(), |f| f.display().to_string()))).collect();
+        lines.extend([String::new(), "Set these files on the profile?".to_string()]);
+        self.confirm = Some((format!(" Certificates for '{}' ", name), lines, ConfirmAction::SetCertificates(name, files)));
+    }
+
+    fn open_form(&mut self, fields: Vec<connect::Field>) {
+        self.form_focus = fields.iter().position(|f| f.input.value().is_empty()).unwrap_or(0);
+        self.form = fields;
+        self.previous_mode = self.selection_mode;
+        self.selection_mode = SelectionMode::Credentials;
+    }
+
+    fn open_wizard(&mut self, kind: wizard::Kind) {
+        self.wizard = Some(kind);
+        self.open_form(kind.fields());
+        self.form_focus = 0;
+    }
+
+    fn submit_wizard(&mut self, kind: wizard::Kind) {
+        let values = wizard::values(&self.form);
+        if let Err(e) = wizard::validate(kind, &values) {
+            self.popup = Some((format!(" New {} connection ", kind.title()), vec![e]));
+            return;
+        }
+        self.close_form();
+        self.confirm = Some((" Create this connection? [Y/n] ".to_string(), wizard::summary(kind, &values), ConfirmAction::CreateConnection(kind, values)));
+    }
+
+    fn close_form(&mut self) {
+        self.connect_target = None;
+        self.wizard = None;
+        self.form.clear();
+        self.selection_mode = self.previous_mode;
+    }
+
+    fn submit_form(&mut self) {
+        if let Some(kind) = self.wizard { self.submit_wizard(kind); return; }
+        let saved: Vec<&str> = self.form.iter().filter(|f| f.saved() && f.answer().is_some()).map(|f| f.label).collect();
+        if let (false, Some(connect::Target::Profile(name))) = (saved.is_empty(), &self.connect_target) { self.events.push("user", format!("{} saved in the profile '{}'", saved.join(", "), name)); }
+        let answers: Vec<(connect::Kind, String)> = self.form.drain(..).filter_map(|f| f.answer()).collect();
+        self.selection_mode = self.previous_mode;
+        if let Some(target) = self.connect_target.take() { self.start_activation(target, answers); }
+    }
+
+    fn open_input(&mut self, action: InputAction) {
+        self.previous_mode = self.selection_mode;
+        self.selection_mode = SelectionMode::TextInput;
+        self.input_action = action;
+        self.text_input.clear();
+    }
+
+    fn submit_input(&mut self) {
+        let value = self.text_input.value().trim().to_string();
+        self.selection_mode = self.previous_mode;
+        if value.is_empty() && !matches!(self.input_action, InputAction::EditProperty | InputAction::LabelDevice) { return; }
+        match self.input_action {
+            InputAction::UpnpAdd => {
+                let (lan_ip, via) = match &self.upnp { Some(Ok(gw)) => (gw.lan_ip.clone(), gw.protocol), _ => (String::new(), upnp::Protocol::Upnp) };
+                self.start_upnp(move || upnp::add_mapping(&value, &lan_ip, via).map(|_| format!("Mapping '{}' added", value)));
+            }
+            InputAction::EditProperty => {
+                let idx = self.list_state.selected().unwrap_or(0);
+                if let Some(property) = self.editor.as_mut().and_then(|e| e.properties.get_mut(idx)) {
+                    match profile::validate(property, &value) {
+                        Ok(()) => property.value = value,
+                        Err(e) => self.popup = Some((" Invalid value ".to_string(), vec![format!("{}: {}", property.key, e)])),
+                    }
+                }
+            }
+            InputAction::ExportProfile => {
+                if let Some(name) = self.selected_name() {
+                    self.confirm = Some((format!(" Export '{}' ", name), vec![format!("Target: {}", value), String::new(), "Include secrets (passwords, keys) in the keyfile?".to_string()], ConfirmAction::ExportProfile(name, value)));
+                }
+            }
+            InputAction::CloneProfile => {
+                let Some(name) = self.selected_name() else { return };
+                match profile::clone_profile(&name, &value) {
+                    Ok(()) => {
+                        self.vpn_names = Self::get_nm_vpn_connections();
+                        self.open_editor(&value);
+                    }
+                    Err(e) => self.popup = Some((" Clone profile ".to_string(), vec![format!("Cannot clone '{}': {}", name, e)])),
+                }
+            }
+            InputAction::ImportDirectory => {
+                let dir = profile::expand_home(&value);
+                self.run_tool(" Import profiles ", move || profile::import_directory(&dir));
+            }
+            InputAction::LabelDevice => {
+                let idx = self.list_state.selected().unwrap_or(0);
+                if let Some(device) = self.lan_devices.get_mut(idx) {
+                    device.label = value;
+                    if let Err(e) = lan::save(&self.lan_devices) { self.popup = Some((" LAN devices ".to_string(), vec![e])); }
+                }
+            }
+            InputAction::PingSweep => self.start_sweep(value),
+            InputAction::AddRoute => match routes::parse(&value) {
+                Ok(spec) => self.confirm = Some((" Add route? [Y/n] ".to_string(), vec![format!("ip route add {}", spec), String::new(), "DashNet remembers this route so it can be removed later (C in this view).".to_string()], ConfirmAction::AddRoute(spec))),
+                Err(e) => self.popup = Some((" Invalid route ".to_string(), vec![e])),
+            },
+            InputAction::SplitEntry => {
+                if let Some(Err(e)) = self.split.as_mut().map(|s| s.add(&value)) { self.popup = Some((" Invalid entry ".to_string(), vec![e])); }
+            }
+            InputAction::Iperf => self.start_iperf(&value),
+            InputAction::CertEndpoint => self.run_tool(" TLS certificate ", move || certs::describe(&value, clock::epoch_now())),
+            InputAction::PinSlot => match value.parse::<usize>() {
+                Ok(slot @ 1..=9) => self.pin_to_slot(slot - 1),
+                _ => self.popup = Some((" Quick connect ".to_string(), vec![format!("'{}' is not a slot number between 1 and 9.", value)])),
+            },
+            InputAction::Lookup => self.run_tool(" Lookup ", move || lookup::describe(&value)),
+            InputAction::AppVpn => match value.split_once(' ').map(|(iface, what)| (iface.to_string(), what.trim().to_string())) {
+                Some((iface, what)) if !what.is_empty() => self.run_tool(" Per-app VPN ", move || vec![match what.parse::<u32>() {
+                    Ok(pid) => appvpn::tag(&iface, pid),
+                    Err(_) => appvpn::launch(&iface, &what),
+                }.unwrap_or_else(|e| format!("Failed: {}", e))]),
+                _ => self.popup = Some((" Per-app VPN ".to_string(), vec![format!("Expected '<tunnel> <pid or command>', got '{}'", value)])),
+            },
+            InputAction::ReachabilityPort => match value.parse::<u16>() {
+                Ok(port) if port > 0 => {
+                    let service = self.config.reachability_url.clone();
+                    self.run_tool(" Port reachability ", move || reachability::check(port, &service));
+                }
+                _ => self.popup = Some((" Port reachability ".to_string(), vec![format!("Invalid port '{}'", value)])),
+            },
+        }
+    }
+
+    fn update_router_adverts(&mut self) {
+        for event in self.ra_events.try_iter() {
+            let advert = match event { Ok(advert) => advert, Err(e) => { self.ra_error = Some(e); continue; } };
+            if let Some(seen) = self.routers.iter_mut().find(|r| r.advert.router == advert.router && r.advert.iface == advert.iface) {
+                seen.advert = advert;
+                seen.last_seen = Instant::now();
+                continue;
+            }
+            let expected = if self.config.expected_routers.is_empty() { self.routers.is_empty() } else {
+                self.config.expected_routers.iter().any(|r| r.eq_ignore_ascii_case(&advert.router.to_string()) || r.eq_ignore_ascii_case(&advert.mac))
+            };
+            if !expected {
+                Self::send_notification("rogue-router", "Routeur IPv6 inattendu", &format!("{} ({}) annonce des préfixes sur {}.", advert.router, advert.mac, advert.iface), true);
+                self.events.push("ra", format!("unexpected router {} ({}) on {}", advert.router, advert.mac, advert.iface));
+            }
+            self.routers.push(SeenRouter { advert, last_seen: Instant::now(), expected });
+        }
+    }
+
+    fn open_editor(&mut self, connection: &str) {
+        match profile::ProfileEditor::load(connection) {
+            Ok(editor) => {
+                self.editor = Some(editor);
+                self.selection_mode = SelectionMode::PropertyEditor;
+                self.list_state.select(Some(0));
+            }
+            Err(e) => self.popup = Some((" Property editor ".to_string(), vec![format!("Cannot load '{}': {}", connection, e)])),
+        }
+    }
+
+    fn view(&self) -> SelectionMode {
+        if matches!(self.selection_mode, SelectionMode::Credentials | SelectionMode::TextInput | SelectionMode::Palette) { self.previous_mode } else { self.selection_mode }
+    }
+
+    fn page(&self) -> Page {
+        Page::of(self.view())
+    }
+
+    fn enter_view(&mut self, mode: SelectionMode) {
+        if let Some(ns) = self.netns.as_ref().filter(|_| HOST_ONLY.contains(&mode)) {
+            let name = VIEWS.iter().find(|(_, m, _)| *m == mode).map_or("This view", |(name, _, _)| name);
+            self.popup = Some((" Host namespace only ".to_string(), vec![format!("{} reads the host network namespace, not {}.", name, ns), "Press n and pick (host) to use it.".to_string()]));
+            return;
+        }
+        if Page::of(mode) != self.page() {
+            let current = Page::ALL.iter().position(|p| *p == self.page()).unwrap_or(0);
+            self.page_views[current] = (self.view(), self.list_state.clone());
+        }
+        match mode {
+            SelectionMode::Routes => self.refresh_routes(),
+            SelectionMode::Rules => self.rules = rules::list(self.netns.as_deref()),
+            SelectionMode::Docker => self.refresh_containers(),
+            SelectionMode::AppVpn => self.app_tunnels = appvpn::list(),
+            SelectionMode::Peers => self.refresh_peers(),
+            SelectionMode::EncryptedDns => self.refresh_dns_links(),
+            SelectionMode::TopTalkers => self.refresh_talkers(),
+            SelectionMode::Namespaces => self.namespaces = netns::list_namespaces(),
+            SelectionMode::PortMappings => self.start_upnp(|| Ok(String::new())),
+            SelectionMode::Journal => self.refresh_journal(),
+            _ => {}
+        }
+        self.selection_mode = mode;
+        self.list_state.select(Some(0));
+    }
+
+    fn toggle_view(&mut self, mode: SelectionMode) {
+        self.enter_view(if self.selection_mode == mode { Page::back_from(mode) } else { mode });
+    }
+
+    fn switch_page(&mut self, forward: bool) {
+        let current = Page::ALL.iter().position(|p| *p == self.page()).unwrap_or(0);
+        self.show_page((current + if forward { 1 } else { Page::ALL.len() - 1 }) % Page::ALL.len());
+    }
+
+    fn show_page(&mut self, index: usize) {
+        if Page::ALL[index] == self.page() { return self.enter_view(Page::ALL[index].home()); }
+        let (mode, state) = self.page_views[index].clone();
+        self.enter_view(mode);
+        self.list_state = state;
+        self.list_state.select(Some(self.list_state.selected().unwrap_or(0).min(self.list_len().saturating_sub(1))));
+    }
+
+    fn open_palette(&mut self) {
+        self.previous_mode = self.selection_mode;
+        self.selection_mode = SelectionMode::Palette;
+        self.palette_index = 0;
+        self.text_input.clear();
+    }
+
+    fn palette_matches(&self) -> Vec<(String, Jump)> {
+        let mut entries: Vec<(String, Jump)> = Vec::new();
+        if !self.monitor_only {
+            entries.extend(self.vpn_names.iter().map(|n| (format!("VPN    {}{}", n, if self.active_vpns.contains(n) { " (active)" } else { "" }), Jump::Vpn(n.clone()))));
+            entries.extend(self.wifi_ssids.iter().map(|s| (format!("WiFi   {}{}", s, if *s == self.current_ssid { " (connected)" } else { "" }), Jump::WiFi(s.clone()))));
+        }
+        entries.extend(self.get_active_ips().into_iter().map(|(n, ip)| (format!("iface  {} {}", n, ip), Jump::Interface(n))));
+        entries.extend(self.lan_devices.iter().map(|d| (format!("host   {} {} {}", d.ip, d.label, d.mac), Jump::Host(d.mac.clone()))));
+        entries.extend(VIEWS.iter().filter(|(_, _, tool)| tool.is_empty() || self.has(tool)).map(|(label, mode, _)| (format!("view   {}", label), Jump::View(*mode))));
+        entries.extend(Tool::ALL.iter().filter(|t| t.requires().is_none_or(|r| self.has(r))).map(|t| (format!("tool   {}", t.label()), Jump::Tool(*t))));
+        entries.push(("action Status report (Markdown, copied to the clipboard)".to_string(), Jump::Report));
+        entries.push(("action Export the graph as PNG and SVG".to_string(), Jump::ExportGraph));
+        palette::rank(self.text_input.value(), entries)
+    }
+
+    fn run_palette(&mut self) {
+        let Some((_, jump)) = self.palette_matches().into_iter().nth(self.palette_index) else { return };
+        self.selection_mode = self.previous_mode;
+        match jump {
+            Jump::View(SelectionMode::Journal) => self.open_journal(),
+            Jump::View(mode) => self.enter_view(mode),
+            Jump::Vpn(name) => {
+                self.enter_view(SelectionMode::Vpn);
+                self.list_state.select(self.vpn_names.iter().position(|n| *n == name));
+            }
+            Jump::WiFi(ssid) => {
+                self.enter_view(SelectionMode::WiFi);
+                self.list_state.select(self.wifi_ssids.iter().position(|s| *s == ssid));
+            }
+            Jump::Interface(name) => {
+                self.enter_view(SelectionMode::Overview);
+                self.list_state.select(self.get_active_ips().iter().position(|(n, _)| *n == name));
+                self.graph_interface(&name);
+            }
+            Jump::Host(mac) => {
+                self.enter_view(SelectionMode::LanDevices);
+                self.list_state.select(self.lan_devices.iter().position(|d| d.mac == mac));
+            }
+            Jump::Tool(tool) => self.launch_tool(tool),
+            Jump::Report => self.start_report(),
+            Jump::ExportGraph => self.export_graph(),
+        }
+    }
+
+    fn graph_interface(&mut self, name: &str) {
+        if let Some(pos) = self.graph_targets(&self.get_active_ips()).iter().position(|(n, _)| *n == name) {
+            self.graph_index = pos;
+            self.refresh_details(None);
+        }
+    }
+
+    fn act_on_peer(&mut self, peer: peers::Peer, revoke: bool) {
+        let result = if revoke { peers::revoke(&peer) } else { peers::disconnect(&peer) };
+        let verb = if revoke { "revoking" } else { "disconnecting" };
+        self.events.push("user", match &result { Ok(message) => message.clone(), Err(e) => format!("{} peer {} on {} failed: {}", verb, peer.name, peer.server.label(), e) });
+        self.popup = Some((" Peers ".to_string(), vec![result.unwrap_or_else(|e| format!("Failed: {}", e))]));
+        self.refresh_peers();
+    }
+
+    fn refresh_peers(&mut self) {
+        if self.peers_task.is_some() { return; }
+        let (servers, management) = (self.config.wireguard_servers.clone(), self.config.openvpn_management.clone());
+        self.peers_task = Some(task::Task::spawn(move || peers::list(&servers, management.as_deref())));
+    }
+
+    fn refresh_containers(&mut self) {
+        if self.containers_task.is_none() { self.containers_task = Some(task::Task::spawn(docker::list_containers)); }
+    }
+
+    fn refresh_dns_links(&mut self) {
+        if self.dns_task.is_none() { self.dns_task = Some(task::Task::spawn(dot::status)); }
+    }
+
+    fn refresh_routes(&mut self) {
+        self.routes = routes::list(self.netns.as_deref());
+        self.created_routes = routes::created();
+        self.list_state.select(Some(self.list_state.selected().unwrap_or(0).min(self.routes.len().saturating_sub(1))));
+    }
+
+    fn open_split(&mut self, connection: &str) {
+        match split::SplitTunnel::load(connection) {
+            Ok(split) => {
+                self.split = Some(split);
+                self.routes = routes::list(self.netns.as_deref());
+                self.selection_mode = SelectionMode::SplitTunnel;
+                self.list_state.select(Some(0));
+            }
+            Err(e) => self.popup = Some((" Split tunnel ".to_string(), vec![format!("Cannot load '{}': {}", connection, e)])),
+        }
+    }
+
+    fn selected_name(&self) -> Option<String> {
+        let idx = self.list_state.selected()?;
+        match self.selection_mode {
+            SelectionMode::Vpn => self.vpn_names.get(idx).cloned(),
+            SelectionMode::WiFi => self.wifi_ssids.get(idx).cloned(),
+            _ => None,
+        }
+    }
+
+    fn selected_address(&self) -> Option<String> {
+        let idx = self.list_state.selected()?;
+        match self.selection_mode {
+            SelectionMode::PingSweep => self.sweep_replies.iter().filter(|r| r.rtt.is_some()).nth(idx).map(|r| r.ip.to_string()),
+            SelectionMode::LanDevices => self.lan_devices.get(idx).map(|d| d.ip.clone()),
+            SelectionMode::PortMappings => match &self.upnp { Some(Ok(gw)) => gw.mappings.get(idx).map(|m| m.internal_addr.clone()), _ => None },
+            SelectionMode::TopTalkers => self.talkers.get(idx).map(|t| talkers::host(&t.remote).to_string()).filter(|h| !h.is_empty()),
+            SelectionMode::Peers => self.peers.get(idx).map(|p| talkers::host(&p.endpoint).to_string()).filter(|h| !h.is_empty() && h != "-"),
+            _ => None,
+        }
+    }
+
+    fn run_declined(&mut self, action: ConfirmAction) {
+        match action {
+            ConfirmAction::ExportProfile(name, path) => self.export_profile(&name, &path, false),
+            ConfirmAction::KeepChanges => self.start_rollback("changes declined".to_string()),
+            _ => {}
+        }
+    }
+
+    fn export_profile(&mut self, name: &str, path: &str, with_secrets: bool) {
+        let result = profile::export_keyfile(name, &profile::expand_home(path), with_secrets);
+        self.popup = Some((" Export profile ".to_string(), vec![match result {
+            Ok(target) => format!("'{}' exported to {} ({}).", name, target.display(), if with_secrets { "with secrets" } else { "secrets removed" }),
+            Err(e) => format!("Export failed: {}", e),
+        }]));
+    }
+
+    fn run_confirmed(&mut self, action: ConfirmAction) {
+        match action {
+            ConfirmAction::ExportProfile(name, path) => self.export_profile(&name, &path, true),
+            ConfirmAction::AddRoute(spec) => self.protect(format!("route add {}", spec), checkpoint::Undo::DeleteRoute(spec.clone()), true, Change::AddRoute(spec)),
+            ConfirmAction::DeleteRoute(line) => self.protect(format!("route del {}", line), checkpoint::Undo::RestoreRoute(line.clone()), true, Change::DeleteRoute(line)),
+            ConfirmAction::RevealPassword(name) => {
+                let result = profile::wifi_password(&name);
+                self.events.push("user", match &result { Ok(_) => format!("password of {} revealed", name), Err(e) => format!("revealing the password of {} failed: {}", name, e) });
+                self.popup = Some((format!(" {} ", name), match result {
+                    Ok(password) => vec![format!("Network  : {}", name), format!("Password : {}", password)],
+                    Err(e) => vec![e],
+                }));
+            }
+            ConfirmAction::CreateConnection(wizard::Kind::Ethernet, values) => {
+                let change = format!("activation of '{}'", wizard::name(wizard::Kind::Ethernet, &values));
+                self.protect(change, checkpoint::Undo::Nothing, true, Change::CreateConnection(wizard::Kind::Ethernet, values));
+            }
+            ConfirmAction::CreateConnection(kind, values) => self.apply_change(Change::CreateConnection(kind, values)),
+            ConfirmAction::DisconnectPeer(peer) => self.act_on_peer(peer, false),
+            ConfirmAction::RevokePeer(peer) => self.act_on_peer(peer, true),
+            ConfirmAction::SetDnsOverTls(link, mode) => {
+                self.events.push("user", format!("DNS-over-TLS on {} switched to {}", link.label(), mode));
+                self.run_tool(" Encrypted DNS ", move || {
+                    let mut lines = vec![dot::set(&link, mode).unwrap_or_else(|e| format!("Failed: {}", e))];
+                    std::thread::sleep(Duration::from_secs(2));
+                    lines.push(String::new());
+                    lines.extend(dot::verify());
+                    lines
+                });
+            }
+            ConfirmAction::CleanUpRoutes => self.protect("removal of the routes added by DashNet".to_string(), checkpoint::Undo::AddRoutes(routes::created()), true, Change::CleanUpRoutes),
+            ConfirmAction::KeepChanges => self.keep_changes(),
+            ConfirmAction::SetCertificates(name, files) => {
+                let result = profile::set_cert_files(&name, &files);
+                self.events.push("user", match &result { Ok(message) => format!("certificates set on {}", message), Err(e) => format!("setting certificates on {} failed: {}", name, e) });
+                self.popup = Some((" Certificate files ".to_string(), vec![match result {
+                    Ok(_) => format!("'{}' now uses the selected files. The key passphrase is asked when the VPN is activated.", name),
+                    Err(e) => format!("Cannot update '{}': {}", name, e),
+                }]));
+            }
+            ConfirmAction::ApplyFix(fix) => {
+                self.events.push("user", format!("applying fix: {}", fix.describe()));
+                self.run_tool(" Troubleshooting fix ", move || vec![match fix.apply() {
+                Ok(message) => format!("Done: {}. Run the troubleshooter again to verify.", message),
+                Err(e) => format!("Fix failed: {}", e),
+            }]);
+            }
+            ConfirmAction::ApplySplitTunnel => {
+                let Some(split) = &self.split else { return };
+                let connection = split.connection.clone();
+                let result = split.apply();
+                self.events.push("user", match &result { Ok(()) => format!("split tunnel settings changed on {}", connection), Err(e) => format!("editing {} failed: {}", connection, e) });
+                self.popup = Some((" Split tunnel ".to_string(), vec![match result {
+                    Ok(()) => format!("'{}' updated. The new routes apply the next time it is activated.", connection),
+                    Err(e) => format!("nmcli refused the change: {}", e),
+                }]));
+                if let Ok(split) = split::SplitTunnel::load(&connection) { self.split = Some(split); }
+            }
+            ConfirmAction::ApplyProfileEdits => {
+                let Some(editor) = &self.editor else { return };
+                if self.config.checkpoint.enabled && editor.is_active() {
+                    let change = format!("edits to '{}'", editor.connection);
+                    self.protect(change, checkpoint::Undo::Nothing, true, Change::ProfileEdits(true));
+                } else {
+                    self.apply_change(Change::ProfileEdits(false));
+                }
+            }
+        }
+    }
+
+    fn switch_netns(&mut self, ns: Option<String>) {
+        self.netns = ns;
+        self.interfaces.retain(|_, data| data.remote);
+        self.rates.clear();
+        self.session_by_iface.clear();
+        self.follow_protocols();
+        self.netdev_task = None;
+        self.netns_stats = None;
+        self.netns_error = None;
+        self.last_stats = if self.netns.is_none() { net_monitor::get_net_data() } else { HashMap::new() };
+        self.graph_index = 0;
+        self.talker_sockets.clear();
+        self.routes = routes::list(self.netns.as_deref());
+        self.docker_ifaces.clear();
+        self.details_iface.clear();
+        self.refresh_details(None);
+    }
+
+    fn boot_bytes(&self) -> (u64, u64) {
+        self.last_stats.iter().filter(|(name, _)| !(name.as_str() == "lo" || self.docker_ifaces.contains(name.as_str()))).fold((0, 0), |(rx, tx), (_, s)| (rx + s.rx, tx + s.tx))
+    }
+
+    fn transfer_rows(&self, iface: &str) -> Vec<(String, String)> {
+        let Some(boot) = self.last_stats.get(iface) else { return Vec::new() };
+        let session = self.session_by_iface.get(iface).copied().unwrap_or_default();
+        vec![
+            ("Session".to_string(), format!("↓ {}  ↑ {}", format::bytes(session.0), format::bytes(session.1))),
+            ("Since boot".to_string(), format!("↓ {}  ↑ {}", format::bytes(boot.rx), format::bytes(boot.tx))),
+        ]
+    }
+
+    fn sample_json(&self) -> json::Json {
+        use json::Json;
+        let mut names: Vec<&String> = self.rates.keys().collect();
+        names.sort();
+        let rate = |(rx, tx): (f64, f64)| Json::Obj(vec![("rx".to_string(), Json::Num(rx)), ("tx".to_string(), Json::Num(tx))]);
+        let (rx, tx) = self.total_rates();
+        Json::Obj(vec![
+            ("time".to_string(), Json::Num(clock::epoch_now() as f64)),
+            ("unit".to_string(), Json::Str("Mb/s".to_string())),
+            ("interfaces".to_string(), Json::Obj(names.into_iter().map(|n| (n.clone(), rate(self.rates[n]))).collect())),
+            ("total".to_string(), rate((rx, tx))),
+            ("session_bytes".to_string(), Json::Obj(vec![("rx".to_string(), Json::Num(self.session_bytes.0 as f64)), ("tx".to_string(), Json::Num(self.session_bytes.1 as f64))])),
+            ("boot_bytes".to_string(), { let (rx, tx) = self.boot_bytes(); Json::Obj(vec![("rx".to_string(), Json::Num(rx as f64)), ("tx".to_string(), Json::Num(tx as f64))]) }),
+            ("ssid".to_string(), if self.current_ssid.is_empty() { Json::Null } else { Json::Str(self.current_ssid.clone()) }),
+            ("vpns".to_string(), Json::Arr(self.active_vpns.iter().filter(|v| self.vpn_names.contains(v)).map(|v| Json::Str(v.clone())).collect())),
+        ])
+    }
+
+    fn total_rates(&self) -> (f64, f64) {
+        self.rates.iter().filter(|(name, _)| self.interfaces.get(*name).is_some_and(|d| !d.remote))
+            .fold((0.0, 0.0), |(rx, tx), (_, (r, t))| (rx + r, tx + t))
+    }
+
+    fn graph_targets(&self, active_ips: &[(String, String)]) -> Vec<(&String, &InterfaceData)> {
+        let ifaces_with_ip: Vec<_> = self.interfaces.iter()
+            .filter(|(name, data)| !data.remote && active_ips.iter().any(|(ip_name, _)| ip_name == *name))
+            .collect();
+        let mut physical_active: Vec<_> = ifaces_with_ip.iter().filter(|(n, _)| n.starts_with('e') || n.starts_with('w')).copied().collect();
+        let mut tunnel_active: Vec<_> = ifaces_with_ip.iter().filter(|(n, _)| n.starts_with("tun") || n.starts_with("wg") || n.starts_with("ppp")).copied().collect();
+        let mut remote_sources: Vec<_> = self.interfaces.iter().filter(|(_, data)| data.remote).collect();
+
+        physical_active.sort_by_key(|(n, _)| (*n).clone());
+        tunnel_active.sort_by_key(|(n, _)| (*n).clone());
+        remote_sources.sort_by_key(|(n, _)| (*n).clone());
+
+        physical_active.into_iter().take(1).chain(tunnel_active).chain(remote_sources).collect()
+    }
+
+    fn export_graph(&mut self) {
+        let active_ips = self.get_active_ips();
+        let graphs = self.graph_targets(&active_ips);
+        if graphs.is_empty() { self.popup = Some((" Graph export ".to_string(), vec!["No graph to export yet.".to_string()])); return; }
+        let times: HashMap<i64, i64> = self.tick_times.iter().map(|(c, t)| (*c as i64, *t)).collect();
+        let unit = self.rate_unit;
+        let points = |history: &[(f64, f64)]| history.iter().filter_map(|(x, y)| times.get(&(x.round() as i64)).map(|t| (*t, unit.scale(*y)))).collect::<Vec<_>>();
+        let overlay = |(i, (name, data)): (usize, &(&String, &InterfaceData))| snapshot::Series { name: name.to_string(), color: graphics::rgb(OVERLAY_COLORS[i % OVERLAY_COLORS.len()]), points: points(&data.history) };
+        let chart = match self.graph_mode {
+            GraphMode::Single => {
+                let (name, data) = graphs[self.graph_index % graphs.len()];
+                snapshot::Chart { title: name.to_string(), unit: unit.label(), series: vec![snapshot::Series { name: name.to_string(), color: graphics::rgb(data.color), points: points(&data.history) }] }
+            }
+            GraphMode::Overlay => {
+                let marked: Vec<_> = graphs.iter().filter(|(n, _)| self.compared.contains(n)).copied().collect();
+                let shown = if marked.len() >= 2 { &marked } else { &graphs };
+                snapshot::Chart { title: "Comparison".to_string(), unit: unit.label(), series: shown.iter().enumerate().map(overlay).collect() }
+            }
+            GraphMode::Stacked => {
+                let mut base: HashMap<i64, f64> = HashMap::new();
+                let series = graphs.iter().filter(|(_, d)| !d.remote).enumerate().map(|(i, (name, data))| {
+                    let stacked: Vec<(f64, f64)> = data.history.iter().map(|&(x, y)| { let lower = base.entry(x as i64).or_insert(0.0); *lower += y; (x, *lower) }).collect();
+                    snapshot::Series { name: name.to_string(), color: graphics::rgb(OVERLAY_COLORS[i % OVERLAY_COLORS.len()]), points: points(&stacked) }
+                }).collect();
+                snapshot::Chart { title: "Total bandwidth (stacked)".to_string(), unit: unit.label(), series }
+            }
+        };
+        self.popup = Some((" Graph export ".to_string(), vec![match snapshot::export(&chart) {
+            Ok((png, svg)) => format!("Written to {} and {}", png.display(), svg.display()),
+            Err(e) => format!("Export failed: {}", e),
+        }]));
+    }
+
+    fn selected_interface(&self) -> Option<String> {
+        let active_ips = self.get_active_ips();
+        let graphs = self.graph_targets(&active_ips);
+        if graphs.is_empty() { return None; }
+        let (name, data) = graphs[self.graph_index % graphs.len()];
+        if data.remote { None } else { Some(name.clone()) }
+    }
+
+    fn has(&self, tool: &str) -> bool {
+        !self.missing.contains(&tool)
+    }
+
+    fn host_only_key(&self, code: KeyCode) -> bool {
+        self.netns.is_some() && match code {
+            KeyCode::Char('u' | 'U') => true,
+            KeyCode::Char('+' | '-' | 'C') | KeyCode::Delete => self.selection_mode == SelectionMode::Routes,
+            _ => false,
+        }
+    }
+
+    fn missing_tool_for(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<&'static str> {
+        let lists = matches!(self.selection_mode, SelectionMode::Vpn | SelectionMode::WiFi | SelectionMode::AccessPoints);
+        let required = match code {
+            KeyCode::Enter | KeyCode::Char('x' | 'e' | 'E' | 'C' | 'S' | 'V') if lists => "nmcli",
+            KeyCode::F(_) | KeyCode::Char('c') => "nmcli",
+            KeyCode::Char('1'..='9') if modifiers.contains(KeyModifiers::ALT) => "nmcli",
+            KeyCode::Char('a') => "nmcli",
+            KeyCode::Char('J') => "journalctl",
+            KeyCode::Char('w') if matches!(self.selection_mode, SelectionMode::WiFi | SelectionMode::AccessPoints) => "wpa_cli",
+            KeyCode::Char('R' | 'P') => "ip",
+            KeyCode::Char('M') => "ss",
+            KeyCode::Enter | KeyCode::Char('t') if self.selection_mode == SelectionMode::EncryptedDns => "resolvectl",
+            KeyCode::Char('+' | '-' | 'C') | KeyCode::Delete if self.selection_mode == SelectionMode::Routes => "ip",
+            KeyCode::Char('+') | KeyCode::Enter if self.selection_mode == SelectionMode::AppVpn => "nft",
+            KeyCode::Char('-') | KeyCode::Delete if self.selection_mode == SelectionMode::Peers && self.peers.get(self.list_state.selected().unwrap_or(0)).is_some_and(|p| matches!(p.server, peers::Server::WireGuard(_))) => "wg",
+            KeyCode::Char('u' | 'U') if !self.has("nmcli") => "dhclient",
+            KeyCode::Enter if self.selection_mode == SelectionMode::Tools => Tool::ALL.get(self.list_state.selected().unwrap_or(0)).and_then(Tool::requires)?,
+            _ => return None,
+        };
+        if !self.has(required) { Some(required) } else if required == "nmcli" && self.monitor_only { Some("NetworkManager") } else { None }
+    }
+
+    fn yank(&mut self, interface_ip: bool) {
+        let selected = self.list_state.selected().unwrap_or(0);
+        let value = if interface_ip {
+            self.selected_interface().and_then(|iface| self.get_active_ips().into_iter().find(|(n, _)| *n == iface)).map(|(n, ip)| (format!("IP of {}", n), ip))
+        } else {
+            match self.selection_mode {
+                SelectionMode::WiFi => self.wifi_ssids.get(selected).map(|s| ("SSID".to_string(), s.clone())),
+                SelectionMode::Vpn => self.vpn_names.get(selected).map(|name| match clipboard::wireguard_public_key(name) {
+                    Some(key) => (format!("WireGuard public key of {}", name), key),
+                    None => ("VPN name".to_string(), name.clone()),
+                }),
+                SelectionMode::PortMappings => match &self.upnp { Some(Ok(gw)) => Some(("public IP".to_string(), gw.external_ip.clone())), _ => None },
+                SelectionMode::Namespaces => self.namespaces.get(selected.wrapping_sub(1)).map(|ns| ("namespace".to_string(), ns.clone())),
+                _ => None,
+            }
+        };
+        let Some((label, value)) = value.filter(|(_, v)| !v.is_empty()) else { return };
+        match clipboard::copy(&value) {
+            Ok(()) => self.events.push("clipboard", format!("copied {}: {}", label, value)),
+            Err(e) => self.events.push("clipboard", format!("copy failed: {}", e)),
+        }
+    }
+
+    fn refresh_details(&mut self, action: Option<bool>) {
+        if self.details_task.is_some() { return; }
+        let Some(iface) = self.selected_interface() else { self.details.clear(); return };
+        if let Some(ns) = &self.netns {
+            self.details = vec![("Namespace".to_string(), ns.clone()), ("DHCP lease".to_string(), "only read in the host namespace".to_string())];
+            self.details_action = None;
+            self.details_iface = iface;
+            return self.follow_protocols();
+        }
+        self.details_task = Some(task::Task::spawn(move || {
+            let action = action.map(|release| dhcp::renew(&iface, release).unwrap_or_else(|e| format!("failed: {}", e)));
+            DetailsUpdate { fields: dhcp::lease(&iface), iface, action }
+        }));
+    }
+
+    fn app_vpn_rows(&self) -> Vec<(&appvpn::Tunnel, Option<&appvpn::Process>)> {
+        self.app_tunnels.iter().flat_map(|t| if t.processes.is_empty() { vec![(t, None)] } else { t.processes.iter().map(|p| (t, Some(p))).collect() }).collect()
+    }
+
+    fn list_len(&self) -> usize {
+        match self.selection_mode {
+            SelectionMode::Overview => self.get_active_ips().len(),
+            SelectionMode::Topology => self.topology.iter().map(|m| 1 + m.ports.len()).sum(),
+            SelectionMode::Docker => self.containers.as_ref().map_or(0, |c| c.len()),
+            SelectionMode::RouterAdverts => self.ra_error.is_some() as usize + self.routers.iter().map(|r| 1 + r.advert.prefixes.len() + !r.advert.dns.is_empty() as usize).sum::<usize>(),
+            SelectionMode::Schedule => self.config.schedules.iter().filter(|s| s.next_run(clock::epoch_now()).is_some()).count(),
+            SelectionMode::Vpn if self.monitor_only => self.devices.len(),
+            SelectionMode::WiFi if self.monitor_only => self.routes.len(),
+            SelectionMode::Vpn => self.vpn_names.len(),
+            SelectionMode::WiFi => self.wifi_ssids.len(),
+            SelectionMode::AccessPoints => self.bssids().len(),
+            SelectionMode::DataUsage => self.usage.rows(clock::epoch_now()).len(),
+            SelectionMode::FilePicker => self.picker_entries.len(),
+            SelectionMode::Namespaces => self.namespaces.len() + 1,
+            SelectionMode::Tools => Tool::ALL.len(),
+            SelectionMode::NewConnection => wizard::Kind::ALL.len() + 1,
+            SelectionMode::Events => self.events.len(),
+            SelectionMode::Journal => self.journal.len(),
+            SelectionMode::LanDevices => self.lan_devices.len(),
+            SelectionMode::Outages => self.outages.len(),
+            SelectionMode::Certificates => self.certs.len(),
+            SelectionMode::AppVpn => self.app_vpn_rows().len(),
+            SelectionMode::Peers => self.peers.len(),
+            SelectionMode::EncryptedDns => self.dns_links.as_ref().map_or(0, |l| l.len()),
+            SelectionMode::TopTalkers => self.talkers.len(),
+            SelectionMode::Routes => self.routes.len(),
+            SelectionMode::Rules => self.rules.len(),
+            SelectionMode::PingSweep => self.sweep_replies.iter().filter(|r| r.rtt.is_some()).count(),
+            SelectionMode::PropertyEditor => self.editor.as_ref().map(|e| e.properties.len()).unwrap_or(0),
+            SelectionMode::SplitTunnel => self.split.as_ref().map(|s| s.rows().len()).unwrap_or(0),
+            SelectionMode::PortMappings => match &self.upnp { Some(Ok(gw)) => gw.mappings.len(), _ => 0 },
+            _ => 0,
+        }
+    }
+
+    fn get_active_ips(&self) -> Vec<(String, String)> {
+        if self.netns.is_none() && !self.has("ip") { return net_monitor::ipv4_addrs(); }
+        let mut ips = Vec::new();
+        let output = match &self.netns {
+            Some(ns) => Command::new("ip").args(["-n", ns, "-4", "-o", "addr", "show"]).output(),
+            None => Command::new("ip").args(["-4", "-o", "addr", "show"]).output(),
+        };
+        if let Ok(out) = output {
+            let s = String::from_utf8_lossy(&out.stdout);
+            for line in s.lines() {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() >= 4 {
+                    let name = parts[1].to_string();
+                    let ip = parts[3].split('/').next().unwrap_or("").to_string();
+                    if name != "lo" { ips.push((name, ip)); }
+                }
+            }
+        }
+        ips
+    }
+}
+
+fn run_daemon() -> Result<(), io::Error> {
+    systemd::handle_termination();
+    let mut app = App::new();
+    let watchdog = systemd::watchdog_interval();
+    let mut last_ping = Instant::now();
+    systemd::notify("READY=1\nSTATUS=Collecting metrics");
+    while !systemd::terminating() {
+        app.poll_tasks();
+        app.update_metrics();
+        if watchdog.is_some_and(|interval| last_ping.elapsed() >= interval) {
+            systemd::notify("WATCHDOG=1");
+            last_ping = Instant::now();
+        }
+        std::thread::sleep(app.intervals().0);
+    }
+    systemd::notify("STOPPING=1");
+    if app.checkpoint.as_ref().is_some_and(|p| !p.confirm) { app.keep_changes(); }
+    let _ = app.sla.save();
+    let _ = app.usage.save();
+    app.close_outages();
+    Ok(())
+}
+
+fn run_report() -> Result<(), io::Error> {
+    let mut app = App::new();
+    app.update_metrics();
+    std::thread::sleep(Duration::from_secs(1));
+    app.update_metrics();
+    let target = app.report_target();
+    print!("{}", report::complete(app.status_report(), &target));
+    Ok(())
+}
+
+fn main() -> Result<(), io::Error> {
+    if std::env::args().any(|a| a == "--daemon") { return run_daemon(); }
+    if std::env::args().nth(1).as_deref() == Some("report") { return run_report(); }
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let mut app = App::new();
+    let mut last_tick = Instant::now();
+
+    loop {
+        let (tick_rate, redraw_rate) = app.intervals();
+        app.poll_tasks();
+        terminal.draw(|f| ui(f, &mut app))?;
+        if let Some(protocol) = app.graphics {
+            if app.raster_reset {
+                queue!(terminal.backend_mut(), Print(protocol.clear()))?;
+                terminal.clear()?;
+                app.raster_reset = false;
+            }
+            if let Some((area, image)) = app.raster.take() {
+                queue!(terminal.backend_mut(), Print(protocol.clear()), MoveTo(area.x, area.y), Print(image))?;
+                terminal.backend_mut().flush()?;
+            }
+        }
+        let timeout = tick_rate.checked_sub(last_tick.elapsed()).unwrap_or(Duration::ZERO).min(redraw_rate);
+        if event::poll(timeout)? {
+            let event = event::read()?;
+            if let Event::Paste(text) = &event {
+                match app.selection_mode {
+                    SelectionMode::Credentials => if let Some(field) = app.form.get_mut(app.form_focus) { field.input.insert(text) },
+                    SelectionMode::TextInput | SelectionMode::Palette => { app.text_input.insert(text); app.palette_index = 0; }
+                    _ => {}
+                }
+            }
+            if let Event::Key(key) = event {
+                if let Some((_, _, action)) = &app.confirm {
+                    let action = action.clone();
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => { app.confirm = None; app.run_confirmed(action); }
+                        KeyCode::Char('n') | KeyCode::Char('N') => { app.confirm = None; app.run_declined(action); }
+                        KeyCode::Esc | KeyCode::Char('q') if !matches!(action, ConfirmAction::KeepChanges) => app.confirm = None,
+                        _ => {}
+                    }
+                } else if app.popup.is_some() {
+                    if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) { app.popup = None; }
+                } else if app.selection_mode == SelectionMode::Credentials {
+                    match key.code {
+                        KeyCode::Enter => app.submit_form(),
+                        KeyCode::Esc => app.close_form(),
+                        KeyCode::Tab | KeyCode::Down => app.form_focus = (app.form_focus + 1) % app.form.len().max(1),
+                        KeyCode::BackTab | KeyCode::Up => app.form_focus = (app.form_focus + app.form.len().max(1) - 1) % app.form.len().max(1),
+                        _ => if let Some(field) = app.form.get_mut(app.form_focus) { field.input.handle(key); },
+                    }
+                } else if app.selection_mode == SelectionMode::TextInput {
+                    match key.code {
+                        KeyCode::Enter => app.submit_input(),
+                        KeyCode::Esc => app.selection_mode = app.previous_mode,
+                        _ => { app.text_input.handle(key); }
+                    }
+                } else if app.selection_mode == SelectionMode::Palette {
+                    match key.code {
+                        KeyCode::Enter => app.run_palette(),
+                        KeyCode::Esc => app.selection_mode = app.previous_mode,
+                        KeyCode::Down | KeyCode::Tab => app.palette_index = (app.palette_index + 1).min(app.palette_matches().len().saturating_sub(1)),
+                        KeyCode::Up | KeyCode::BackTab => app.palette_index = app.palette_index.saturating_sub(1),
+                        _ => if app.text_input.handle(key) { app.palette_index = 0; },
+                    }
+                } else if key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    app.open_palette();
+                } else if let Some(tool) = app.missing_tool_for(key.code, key.modifiers) {
+                    app.popup = Some((" Unavailable ".to_string(), vec![format!("This feature needs '{}', which is not installed.", tool)]));
+                } else if app.host_only_key(key.code) {
+                    app.popup = Some((" Host namespace only ".to_string(), vec![format!("DHCP renewals and route changes apply to the host namespace, not {}.", app.netns.as_deref().unwrap_or_default()), "Press n and pick (host) to use them.".to_string()]));
+                } else {
+                    let list_len = app.list_len();
+                    match key.code {
+                        KeyCode::Char('q') => break,
+                        KeyCode::Enter if app.selection_mode == SelectionMode::FilePicker => {
+                            if let Some((dir, path)) = app.picker_entries.get(app.list_state.selected().unwrap_or(0)).map(|e| (e.dir, e.path.clone())) {
+                                if dir { app.open_picker(path); } else { app.pick_file(Some(path)); }
+                            }
+                        }
+                        KeyCode::Backspace | KeyCode::Left if app.selection_mode == SelectionMode::FilePicker => {
+                            if let Some(parent) = app.picker_dir.parent().map(Path::to_path_buf) { app.open_picker(parent); }
+                        }
+                        KeyCode::Tab if app.selection_mode == SelectionMode::FilePicker => app.pick_file(None),
+                        KeyCode::Esc if app.selection_mode == SelectionMode::FilePicker => { app.cert_flow = None; app.selection_mode = SelectionMode::Vpn; app.list_state.select(Some(0)); }
+                        KeyCode::Char('F') if app.selection_mode == SelectionMode::Vpn && !app.monitor_only => app.start_cert_flow(),
+                        KeyCode::Tab => app.switch_page(true),
+                        KeyCode::BackTab => app.switch_page(false),
+                        KeyCode::Down | KeyCode::Char('j') if list_len > 0 => {
+                            let i = match app.list_state.selected() { Some(i) => if i >= list_len - 1 { 0 } else { i + 1 }, None => 0 };
+                            app.list_state.select(Some(i));
+                        }
+                        KeyCode::Up | KeyCode::Char('k') if list_len > 0 => {
+                            let i = match app.list_state.selected() { Some(i) => if i == 0 { list_len - 1 } else { i - 1 }, None => 0 };
+                            app.list_state.select(Some(i));
+                        }
+                        KeyCode::PageDown if list_len > 0 => app.list_state.select(Some((app.list_state.selected().unwrap_or(0) + app.list_rows).min(list_len - 1))),
+                        KeyCode::PageUp if list_len > 0 => app.list_state.select(Some(app.list_state.selected().unwrap_or(0).saturating_sub(app.list_rows))),
+                        KeyCode::Home if list_len > 0 => app.list_state.select(Some(0)),
+                        KeyCode::End if list_len > 0 => app.list_state.select(Some(list_len - 1)),
+                        KeyCode::Enter if app.selection_mode == SelectionMode::Namespaces => {
+                            let idx = app.list_state.selected().unwrap_or(0);
+                            app.switch_netns(if idx == 0 { None } else { app.namespaces.get(idx - 1).cloned() });
+                        }
+                        KeyCode::Enter if app.selection_mode == SelectionMode::PropertyEditor && list_len > 0 => {
+                            let value = app.editor.as_ref().and_then(|e| e.properties.get(app.list_state.selected().unwrap_or(0))).map(|p| p.value.clone()).unwrap_or_default();
+                            app.open_input(InputAction::EditProperty);
+                            app.text_input.set(value);
+                        }
+                        KeyCode::Char('w') if app.selection_mode == SelectionMode::PropertyEditor => {
+                            if let Some(editor) = &app.editor {
+                                let diff = editor.diff();
+                                if diff.is_empty() { app.popup = Some((" Property editor ".to_string(), vec!["No pending change.".to_string()])); }
+                                else { app.confirm = Some((format!(" Apply to '{}'? [Y/n] ", editor.connection), diff, ConfirmAction::ApplyProfileEdits)); }
+                            }
+                        }
+                        KeyCode::Char('e') if matches!(app.selection_mode, SelectionMode::Vpn | SelectionMode::WiFi) => {
+                            if let Some(name) = app.selected_name() { app.open_editor(&name); }
+                        }
+                        KeyCode::Char('C') if matches!(app.selection_mode, SelectionMode::Vpn | SelectionMode::WiFi) => {
+                            if let Some(name) = app.selected_name() {
+                                app.open_input(InputAction::CloneProfile);
+                                app.text_input.set(format!("{} copy", name));
+                            }
+                        }
+                        KeyCode::Char('E') if matches!(app.selection_mode, SelectionMode::Vpn | SelectionMode::WiFi) && app.selected_name().is_some() => {
+                            app.open_input(InputAction::ExportProfile);
+                            app.text_input.set("~/dashnet-export".to_string());
+                        }
+                        KeyCode::Char('R') => app.toggle_view(SelectionMode::Routes),
+                        KeyCode::Char('I') => app.export_graph(),
+                        KeyCode::Char('T') => app.start_report(),
+                        KeyCode::Char('P') => app.toggle_view(SelectionMode::Rules),
+                        KeyCode::Char('+') if app.selection_mode == SelectionMode::Routes => app.open_input(InputAction::AddRoute),
+                        KeyCode::Char('-') | KeyCode::Delete if app.selection_mode == SelectionMode::Routes => {
+                            if let Some(line) = app.routes.get(app.list_state.selected().unwrap_or(0)).cloned() {
+                                let note = if routes::is_created(&line, &app.created_routes) { "Added by DashNet." } else { "This route was not added by DashNet: removing it may cut connectivity until the interface is reconfigured." };
+                                app.confirm = Some((" Delete route? [Y/n] ".to_string(), vec![format!("ip route del {}", line), String::new(), note.to_string()], ConfirmAction::DeleteRoute(line)));
+                            }
+                        }
+                        KeyCode::Char('C') if app.selection_mode == SelectionMode::Routes => {
+                            let lines = if app.created_routes.is_empty() { vec!["No route added by DashNet.".to_string()] } else { app.created_routes.iter().map(|r| format!("ip route del {}", r)).collect() };
+                            app.confirm = Some((" Remove the routes added by DashNet? [Y/n] ".to_string(), lines, ConfirmAction::CleanUpRoutes));
+                        }
+                        KeyCode::Char('V') if app.selection_mode == SelectionMode::WiFi => {
+                            match app.selected_name() {
+                                Some(name) if app.known_wifi.contains(&name) => app.confirm = Some((" Show the password? [Y/n] ".to_string(), vec![format!("The saved password of '{}' will be displayed in clear text.", name), "Make sure nobody is reading your screen over your shoulder.".to_string()], ConfirmAction::RevealPassword(name))),
+                                Some(name) => app.popup = Some((" Password ".to_string(), vec![format!("'{}' is not a saved network: there is no stored password to show.", name)])),
+                                None => {}
+                            }
+                        }
+                        KeyCode::Char('S') if app.selection_mode == SelectionMode::Vpn => {
+                            if let Some(name) = app.selected_name() { app.open_split(&name); }
+                        }
+                        KeyCode::Enter | KeyCode::Char(' ') if app.selection_mode == SelectionMode::SplitTunnel => {
+                            let row = app.split.as_ref().and_then(|s| s.rows().into_iter().nth(app.list_state.selected().unwrap_or(0)));
+                            if let Some(split) = app.split.as_mut() {
+                                match row {
+                                    Some(split::Row::Mode) => split::Settings::toggle(&mut split.settings.never_default),
+                                    Some(split::Row::Pushed) => split::Settings::toggle(&mut split.settings.ignore_pushed),
+                                    _ => {}
+                                }
+                            }
+                        }
+                        KeyCode::Char('+') if app.selection_mode == SelectionMode::SplitTunnel => app.open_input(InputAction::SplitEntry),
+                        KeyCode::Char('-') | KeyCode::Delete if app.selection_mode == SelectionMode::SplitTunnel => {
+                            let row = app.split.as_ref().and_then(|s| s.rows().into_iter().nth(app.list_state.selected().unwrap_or(0)));
+                            if let (Some(split), Some(row)) = (app.split.as_mut(), row) { split.remove(&row); }
+                        }
+                        KeyCode::Char('w') if app.selection_mode == SelectionMode::SplitTunnel => {
+                            if let Some(split) = &app.split {
+                                let diff = split.diff();
+                                if diff.is_empty() { app.popup = Some((" Split tunnel ".to_string(), vec!["No pending change.".to_string()])); }
+                                else { app.confirm = Some((format!(" Apply to '{}'? [Y/n] ", split.connection), diff, ConfirmAction::ApplySplitTunnel)); }
+                            }
+                        }
+                        KeyCode::Esc if app.selection_mode == SelectionMode::SplitTunnel => { app.split = None; app.selection_mode = SelectionMode::Vpn; app.list_state.select(Some(0)); }
+                        KeyCode::Esc if app.selection_mode == SelectionMode::PropertyEditor => { app.editor = None; app.selection_mode = SelectionMode::Vpn; app.list_state.select(Some(0)); }
+                        KeyCode::Enter if app.selection_mode == SelectionMode::LanDevices => {
+                            if let Some(label) = app.lan_devices.get(app.list_state.selected().unwrap_or(0)).map(|d| d.label.clone()) {
+                                app.open_input(InputAction::LabelDevice);
+                                app.text_input.set(label);
+                            }
+                        }
+                        KeyCode::Enter if app.selection_mode == SelectionMode::NewConnection => {
+                            match wizard::Kind::ALL.get(app.list_state.selected().unwrap_or(0)) {
+                                Some(kind) => app.open_wizard(*kind),
+                                None if app.has("nm-connection-editor") => { let _ = Command::new("nm-connection-editor").stdout(Stdio::null()).stderr(Stdio::null()).spawn(); }
+                                None => app.popup = Some((" Unavailable ".to_string(), vec!["Other connection types need 'nm-connection-editor', which is not installed.".to_string()])),
+                            }
+                        }
+                        KeyCode::Enter if app.selection_mode == SelectionMode::Tools => {
+                            if let Some(tool) = Tool::ALL.get(app.list_state.selected().unwrap_or(0)) { app.launch_tool(*tool); }
+                        }
+                        KeyCode::Char('+') | KeyCode::Enter if app.selection_mode == SelectionMode::AppVpn => {
+                            let iface = app.app_vpn_rows().get(app.list_state.selected().unwrap_or(0)).map(|(t, _)| t.iface.clone())
+                                .or_else(|| { let mut tunnels: Vec<&String> = app.last_stats.keys().filter(|n| ["tun", "wg", "ppp", "tap"].iter().any(|p| n.starts_with(p))).collect(); tunnels.sort(); tunnels.first().map(|n| n.to_string()) });
+                            app.open_input(InputAction::AppVpn);
+                            app.text_input.set(iface.map(|i| format!("{} ", i)).unwrap_or_default());
+                        }
+                        KeyCode::Char('-') | KeyCode::Delete if app.selection_mode == SelectionMode::AppVpn => {
+                            if let Some((tunnel, process)) = app.app_vpn_rows().get(app.list_state.selected().unwrap_or(0)) {
+                                let (iface, pid) = (tunnel.iface.clone(), process.map(|p| p.pid));
+                                app.run_tool(" Per-app VPN ", move || vec![match pid {
+                                    Some(pid) => appvpn::untag(&iface, pid),
+                                    None => appvpn::teardown(&iface).map(|_| format!("{} rules removed", iface)),
+                                }.unwrap_or_else(|e| format!("Failed: {}", e))]);
+                            }
+                        }
+                        KeyCode::Enter if app.selection_mode == SelectionMode::EncryptedDns => {
+                            if let Some(link) = app.dns_links.as_ref().ok().and_then(|l| l.get(app.list_state.selected().unwrap_or(0))).cloned() {
+                                let mode = dot::next(&link.mode);
+                                let how = match (&link.iface, &link.connection) {
+                                    (None, _) => "Written to a resolved.conf drop-in; systemd-resolved is restarted (lookups pause for a moment). Needs root.".to_string(),
+                                    (Some(_), Some((name, _))) => format!("Saved in the NetworkManager profile '{}' (connection.dns-over-tls) and reapplied.", name),
+                                    (Some(_), None) => "Applied with resolvectl until the link is reconfigured.".to_string(),
+                                };
+                                let strict = if mode == "yes" { "Strict mode: lookups fail if the server does not offer DNS-over-TLS." } else { "" };
+                                app.confirm = Some((format!(" DNS-over-TLS on {}: {} → {}? [Y/n] ", link.label(), link.mode, mode), [how, strict.to_string(), "The result is verified with an uncached query right after.".to_string()].into_iter().filter(|l| !l.is_empty()).collect(), ConfirmAction::SetDnsOverTls(link, mode)));
+                            }
+                        }
+                        KeyCode::Char('t') if app.selection_mode == SelectionMode::EncryptedDns => app.run_tool(" Encrypted DNS ", dot::verify),
+                        KeyCode::Char('x' | '-') | KeyCode::Delete if app.selection_mode == SelectionMode::Peers => {
+                            if let Some(peer) = app.peers.get(app.list_state.selected().unwrap_or(0)).cloned() {
+                                let revoke = key.code != KeyCode::Char('x');
+                                let lines = vec![
+                                    format!("{} on {} ({})", peer.name, peer.server.label(), peer.endpoint),
+                                    if revoke { "Its key is removed from the running interface: it can no longer connect until it is added back.".to_string() } else { "The client is dropped now; it may reconnect with its credentials.".to_string() },
+                                ];
+                                app.confirm = Some((format!(" {} this peer? [Y/n] ", if revoke { "Revoke" } else { "Disconnect" }), lines, if revoke { ConfirmAction::RevokePeer(peer) } else { ConfirmAction::DisconnectPeer(peer) }));
+                            }
+                        }
+                        KeyCode::Enter if app.selection_mode == SelectionMode::Overview => {
+                            if let Some((name, _)) = app.get_active_ips().get(app.list_state.selected().unwrap_or(0)).cloned() { app.graph_interface(&name); }
+                        }
+                        KeyCode::Enter if list_len > 0 => app.connect_selected(),
+                        KeyCode::Char('x') if app.selection_mode == SelectionMode::Vpn => {
+                            if let Some(name) = app.list_state.selected().and_then(|idx| app.vpn_names.get(idx)).cloned() { app.disconnect(name); }
+                        }
+                        KeyCode::Char('c') => app.reconnect_last_vpn(),
+                        KeyCode::Char('B') if app.selection_mode == SelectionMode::WiFi => app.toggle_blacklist(),
+                        KeyCode::Char('H') if app.selection_mode == SelectionMode::WiFi => app.toggle_show_blacklisted(),
+                        KeyCode::Right if app.selection_mode == SelectionMode::WiFi && !app.monitor_only => app.expand_ssid(),
+                        KeyCode::Char('w') if matches!(app.selection_mode, SelectionMode::WiFi | SelectionMode::AccessPoints) && !app.monitor_only => app.start_wps(),
+                        KeyCode::Esc if app.connect_task.is_some() && matches!(app.selection_mode, SelectionMode::Vpn | SelectionMode::WiFi | SelectionMode::AccessPoints) => app.cancel_activation(),
+                        KeyCode::Left | KeyCode::Esc if app.selection_mode == SelectionMode::AccessPoints => app.collapse_ssid(),
+                        KeyCode::Char('*') if matches!(app.selection_mode, SelectionMode::Vpn | SelectionMode::WiFi) => app.toggle_favorite(),
+                        KeyCode::Char(c @ '1'..='9') if key.modifiers.contains(KeyModifiers::ALT) => app.activate_slot(c as usize - '1' as usize),
+                        KeyCode::Char(c @ '1'..='6') => app.show_page(c as usize - '1' as usize),
+                        KeyCode::Char('#') if app.selected_name().is_some() => app.open_input(InputAction::PinSlot),
+                        KeyCode::Char('t') => app.toggle_view(SelectionMode::Topology),
+                        KeyCode::Char('n') => app.toggle_view(SelectionMode::Namespaces),
+                        KeyCode::Char('d') => app.toggle_view(SelectionMode::Docker),
+                        KeyCode::Char('p') => app.toggle_view(SelectionMode::PortMappings),
+                        KeyCode::Char('+') if app.selection_mode == SelectionMode::PortMappings => app.open_input(InputAction::UpnpAdd),
+                        KeyCode::Char('-') | KeyCode::Delete if app.selection_mode == SelectionMode::PortMappings => {
+                            if let Some(Ok(gw)) = &app.upnp {
+                                if let Some(m) = gw.mappings.get(app.list_state.selected().unwrap_or(0)) {
+                                    let (mapping, via) = (m.clone(), gw.protocol);
+                                    app.start_upnp(move || upnp::remove_mapping(&mapping, via).map(|_| format!("Mapping {}/{} removed", mapping.external_port, mapping.protocol)));
+                                }
+                            }
+                        }
+                        KeyCode::Char('o') => app.toggle_view(SelectionMode::Tools),
+                        KeyCode::Char('v') => app.toggle_view(SelectionMode::RouterAdverts),
+                        KeyCode::Char('s') => app.toggle_view(SelectionMode::Schedule),
+                        KeyCode::Char('J') => if app.selection_mode == SelectionMode::Journal { app.enter_view(Page::back_from(SelectionMode::Journal)); } else { app.open_journal(); },
+                        KeyCode::Char('f') if app.selection_mode == SelectionMode::Journal && !app.journal_filters.is_empty() => {
+                            app.journal_all = !app.journal_all;
+                            app.list_state.select(None);
+                            app.refresh_journal();
+                        }
+                        KeyCode::Char('i') => app.toggle_view(SelectionMode::LanDevices),
+                        KeyCode::Char('h') => app.toggle_view(SelectionMode::SpeedHistory),
+                        KeyCode::Char('+') if app.selection_mode == SelectionMode::SpeedHistory => app.speed_range = app.speed_range.saturating_sub(1),
+                        KeyCode::Char('-') if app.selection_mode == SelectionMode::SpeedHistory => app.speed_range = (app.speed_range + 1).min(SPEED_RANGES.len() - 1),
+                        KeyCode::Char('O') => app.toggle_view(SelectionMode::Outages),
+                        KeyCode::Char('e') if app.selection_mode == SelectionMode::Outages => {
+                            let now = clock::epoch_now();
+                            let mut lines = vec![match outages::export(&app.outages, now) { Ok(path) => format!("Written to {}", path.display()), Err(e) => format!("Export failed: {}", e) }, String::new()];
+                            lines.extend(outages::report(&app.outages, now).into_iter().skip(2).take(4));
+                            app.popup = Some((" Outage report ".to_string(), lines));
+                        }
+                        KeyCode::Char('K') => app.toggle_view(SelectionMode::Certificates),
+                        KeyCode::Char('A') => app.toggle_view(SelectionMode::AppVpn),
+                        KeyCode::Char('D') => app.toggle_view(SelectionMode::Peers),
+                        KeyCode::Char('M') => app.toggle_view(SelectionMode::TopTalkers),
+                        KeyCode::Char('L') => app.toggle_view(SelectionMode::DataUsage),
+                        KeyCode::Char('W') => {
+                            let address = app.selected_address().unwrap_or_default();
+                            app.open_input(InputAction::Lookup);
+                            app.text_input.set(address);
+                        }
+                        KeyCode::Char('l') => app.toggle_view(SelectionMode::Events),
+                        KeyCode::Esc if matches!(app.selection_mode, SelectionMode::Topology | SelectionMode::Namespaces | SelectionMode::Docker | SelectionMode::PortMappings | SelectionMode::Tools | SelectionMode::RouterAdverts | SelectionMode::Schedule | SelectionMode::Events | SelectionMode::Journal | SelectionMode::LanDevices | SelectionMode::SpeedHistory | SelectionMode::Outages | SelectionMode::PingSweep | SelectionMode::Certificates | SelectionMode::AppVpn | SelectionMode::Peers | SelectionMode::Routes | SelectionMode::Rules | SelectionMode::TopTalkers | SelectionMode::DataUsage | SelectionMode::EncryptedDns | SelectionMode::NewConnection) => app.enter_view(Page::back_from(app.selection_mode)),
+                        KeyCode::Char('r') => {
+                            app.vpn_names = App::get_nm_vpn_connections(); app.refresh_wifi(); app.known_wifi = App::get_saved_wifi();
+                            if app.selection_mode == SelectionMode::Docker { app.refresh_containers(); }
+                            if app.selection_mode == SelectionMode::PortMappings { app.start_upnp(|| Ok(String::new())); }
+                            if app.selection_mode == SelectionMode::Journal { app.refresh_journal(); }
+                            if app.selection_mode == SelectionMode::PingSweep && app.sweep_rx.is_none() { app.start_sweep(app.sweep_target.clone()); }
+                        }
+                        KeyCode::F(n) if (1..=12).contains(&n) => app.activate_preset(n as usize - 1),
+                        KeyCode::Char('g') => { app.graph_index += 1; app.refresh_details(None); }
+                        KeyCode::Char('G') => app.graph_mode = match app.graph_mode { GraphMode::Single => GraphMode::Overlay, GraphMode::Overlay => GraphMode::Stacked, GraphMode::Stacked => GraphMode::Single },
+                        KeyCode::Char('b') => app.rate_unit = app.rate_unit.toggle(),
+                        KeyCode::Char('y') => app.yank(false),
+                        KeyCode::Char('Y') => app.yank(true),
+                        KeyCode::Char('[') => app.graph_cursor = (app.graph_cursor + 1).min(299),
+                        KeyCode::Char(']') => app.graph_cursor = app.graph_cursor.saturating_sub(1),
+                        KeyCode::Char('m') => {
+                            let graphs = app.graph_targets(&app.get_active_ips());
+                            if !graphs.is_empty() {
+                                let name = graphs[app.graph_index % graphs.len()].0.clone();
+                                if let Some(pos) = app.compared.iter().position(|n| *n == name) { app.compared.remove(pos); } else { app.compared.push(name); }
+                            }
+                        }
+                        KeyCode::Char('u') => app.refresh_details(Some(false)),
+                        KeyCode::Char('U') => app.refresh_details(Some(true)),
+                        KeyCode::Char('a') => app.toggle_view(SelectionMode::NewConnection),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        if last_tick.elapsed() >= tick_rate { app.update_metrics(); last_tick = Instant::now(); }
+    }
+    if app.checkpoint.as_ref().is_some_and(|p| !p.confirm) { app.keep_changes(); }
+    let _ = app.sla.save();
+    let _ = app.usage.save();
+    app.close_outages();
+    if let Some(mut server) = app.iperf_server.take() { let _ = server.kill(); }
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste)?;
+    Ok(())
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
+    let density = Density::of(f.size());
+    let active_ips = app.get_active_ips();
+    if density == Density::StatusLine {
+        if app.raster_key.take().is_some() { app.raster_reset = true; }
+        render_status_line(f, f.size(), app, &active_ips);
+        render_overlays(f, app);
+        theme::apply(f.buffer_mut(), app.scheme);
+        return;
+    }
+    let compact = density == Density::Compact;
+    let page_chunks = Layout::default().direction(Direction::Vertical).constraints([Constraint::Length(1), Constraint::Min(0)]).split(f.size());
+    render_tabs(f, page_chunks[0], app);
+    let main_chunks = Layout::default().direction(Direction::Vertical).constraints(if app.linear {
+        [Constraint::Min(3), Constraint::Length(0), Constraint::Length(3)]
+    } else if compact {
+        [Constraint::Min(4), Constraint::Length(if f.size().height >= 18 { 7 } else { 0 }), Constraint::Length(2)]
+    } else {
+        [Constraint::Percentage(60), Constraint::Percentage(30), Constraint::Length(3)]
+    }).split(page_chunks[1]);
+
+    let top_chunks = Layout::default().direction(Direction::Horizontal).constraints(if compact {
+        [Constraint::Percentage(100), Constraint::Percentage(0)]
+    } else {
+        [Constraint::Percentage(40), Constraint::Percentage(60)]
+    }).split(main_chunks[0]);
+
+    let view = if matches!(app.selection_mode, SelectionMode::Credentials | SelectionMode::TextInput | SelectionMode::Palette) { app.previous_mode } else { app.selection_mode };
+    if view == SelectionMode::Topology {
+        render_topology(f, main_chunks[0], app);
+    } else if view == SelectionMode::Docker {
+        render_docker(f, main_chunks[0], app);
+    } else if view == SelectionMode::RouterAdverts {
+        render_router_adverts(f, main_chunks[0], app);
+    } else if view == SelectionMode::Schedule {
+        render_schedule(f, main_chunks[0], app);
+    } else if view == SelectionMode::Events {
+        render_events(f, main_chunks[0], app);
+    } else if view == SelectionMode::Journal {
+        render_journal(f, main_chunks[0], app);
+    } else if view == SelectionMode::LanDevices {
+        render_lan_devices(f, main_chunks[0], app);
+    } else if view == SelectionMode::SpeedHistory {
+        render_speed_history(f, main_chunks[0], app);
+    } else if view == SelectionMode::Outages {
+        render_outages(f, main_chunks[0], app);
+    } else if view == SelectionMode::Certificates {
+        render_certificates(f, main_chunks[0], app);
+    } else if view == SelectionMode::Rules {
+        render_rules(f, main_chunks[0], app);
+    } else if view == SelectionMode::Routes {
+        render_routes(f, main_chunks[0], app);
+    } else if view == SelectionMode::AppVpn {
+        render_app_vpn(f, main_chunks[0], app);
+    } else if view == SelectionMode::Peers {
+        render_peers(f, main_chunks[0], app);
+    } else if view == SelectionMode::EncryptedDns {
+        render_encrypted_dns(f, main_chunks[0], app);
+    } else if view == SelectionMode::TopTalkers {
+        render_talkers(f, main_chunks[0], app);
+    } else if view == SelectionMode::PingSweep {
+        render_ping_sweep(f, main_chunks[0], app);
+    } else if view == SelectionMode::FilePicker {
+        render_file_picker(f, main_chunks[0], app);
+    } else if view == SelectionMode::DataUsage {
+        render_data_usage(f, main_chunks[0], app);
+    } else if view == SelectionMode::AccessPoints {
+        render_access_points(f, main_chunks[0], app);
+    } else if view == SelectionMode::PropertyEditor {
+        render_property_editor(f, main_chunks[0], app);
+    } else if view == SelectionMode::SplitTunnel {
+        render_split_tunnel(f, main_chunks[0], app);
+    } else if view == SelectionMode::NewConnection {
+        let mut items: Vec<ListItem> = wizard::Kind::ALL.iter().map(|k| ListItem::new(format!(" + {}", k.label()))).collect();
+        items.push(ListItem::new(" ⚙ Other types (VPN plugins, bridges, bonds...) in nm-connection-editor").style(if app.has("nm-connection-editor") { Style::default() } else { Style::default().fg(Color::DarkGray) }));
+        let list_widget = List::new(items)
+            .block(Block::default().title(" [ NEW CONNECTION ] [ENTER] Fill in ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Cyan)))
+            .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+        render_list(f, main_chunks[0], list_widget, &mut app.list_state, &mut app.list_rows);
+    } else if view == SelectionMode::Tools {
+        let items: Vec<ListItem> = Tool::ALL.iter().map(|t| ListItem::new(format!(" ⚙ {}", t.label()))).collect();
+        let list_widget = List::new(items)
+            .block(Block::default().title(" [ TOOLS ] ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightBlue)))
+            .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+        render_list(f, main_chunks[0], list_widget, &mut app.list_state, &mut app.list_rows);
+    } else if view == SelectionMode::PortMappings {
+        render_port_mappings(f, main_chunks[0], app);
+    } else if view == SelectionMode::Namespaces {
+        let current = app.netns.clone();
+        let items: Vec<ListItem> = std::iter::once(None).chain(app.namespaces.iter().map(Some)).map(|ns| {
+            let active = ns == current.as_ref();
+            let label = ns.map(|n| n.as_str()).unwrap_or("(host)");
+            match app.netns_error.as_ref().filter(|_| active && ns.is_some()) {
+                Some(e) => ListItem::new(format!(" {} {}  cannot read its counters: {}", "●", label, e)).style(Style::default().fg(Color::LightRed)),
+                None => ListItem::new(format!(" {} {}", if active { "●" } else { "○" }, label)).style(if active { Style::default().fg(Color::Green) } else { Style::default() }),
+            }
+        }).collect();
+        let list_widget = List::new(items)
+            .block(Block::default().title(" [ NETWORK NAMESPACES ] ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Green)))
+            .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+        render_list(f, main_chunks[0], list_widget, &mut app.list_state, &mut app.list_rows);
+    } else if app.linear {
+        render_linear(f, main_chunks[0], app, &active_ips, view);
+    } else {
+        let (title, items) = match view {
+            SelectionMode::Overview => (" [ INTERFACES ] [ENTER] Graph ", active_ips.iter().map(|(n, ip)| {
+                let rate = app.rates.get(n).map(|(rx, tx)| format!("↓ {} ↑ {}", format::rate(*rx, app.rate_unit), format::rate(*tx, app.rate_unit))).unwrap_or_default();
+                ListItem::new(format!(" {} {:<10} {:<15} {}", if app.active_uplink.as_ref() == Some(n) { "⇅" } else { "•" }, n, ip, rate))
+            }).collect::<Vec<ListItem>>()),
+            SelectionMode::Vpn if app.monitor_only => (" [ DEVICES ] (monitor-only) ", app.devices.iter().map(|d| {
+                let up = d.operstate == "up" || (d.carrier && d.operstate == "unknown");
+                let speed = d.speed.map(|s| format!("{} Mb/s", s)).unwrap_or_default();
+                ListItem::new(format!(" {} {:<14} {:<8} {:<6} {:<11} mtu {:<6} {}", if up { "●" } else { "○" }, d.name, d.kind, d.operstate, speed, d.mtu, d.mac))
+                    .style(if up { Style::default().fg(Color::Green) } else { Style::default().fg(Color::DarkGray) })
+            }).collect::<Vec<ListItem>>()),
+            SelectionMode::WiFi if app.monitor_only => (" [ ROUTES ] (monitor-only) ", app.routes.iter().map(|r| {
+                ListItem::new(format!(" {}", r)).style(if r.starts_with("default") { Style::default().fg(Color::Yellow) } else { Style::default() })
+            }).collect::<Vec<ListItem>>()),
+            SelectionMode::WiFi => (match app.protection {
+                _ if app.show_blacklisted => " [ WIFI SCAN ] showing hidden ",
+                _ if app.config.autovpn.is_none() => " [ WIFI SCAN ] ",
+                Protection::Trusted => " [ WIFI SCAN ] trusted ",
+                Protection::Securing(_) => " [ WIFI SCAN ] ⏳ securing... ",
+                Protection::Protected => " [ WIFI SCAN ] 🛡 protected ",
+                Protection::Failed => " [ WIFI SCAN ] ⚠ UNPROTECTED ",
+            }, app.wifi_ssids.iter().map(|s| {
+                let active = s == &app.current_ssid;
+                let hidden = app.blacklist.contains(s);
+                ListItem::new(format!(" {} {}{}{}{}{}{}{}", if active { "📶" } else { "  " }, app.star(s), s, app.ap_badge(s), app.slot_badge(s), if hidden { " (hidden)" } else { "" }, app.activation_badge(s), app.usage_badge(s)))
+                    .style(if active { Style::default().fg(Color::Yellow) } else if hidden { Style::default().fg(Color::DarkGray) } else { Style::default() })
+            }).collect::<Vec<ListItem>>()),
+            _ => (" [ VPN LIST ] ", app.vpn_names.iter().map(|s| {
+                let active = app.active_vpns.contains(s);
+                ListItem::new(format!(" {} {}{}{}{}", if active { "●" } else { "○" }, app.star(s), s, app.slot_badge(s), app.activation_badge(s))).style(if active { Style::default().fg(Color::Cyan) } else { Style::default() })
+            }).collect::<Vec<ListItem>>()),
+        };
+
+        let list_widget = List::new(items)
+            .block(Block::default().title(title).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(match view { SelectionMode::WiFi => Color::Yellow, SelectionMode::Overview => Color::Green, _ => Color::Cyan })))
+            .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+        render_list(f, top_chunks[0], list_widget, &mut app.list_state, &mut app.list_rows);
+
+        if !compact {
+            let ifs: Vec<ListItem> = active_ips.iter().map(|(n, ip)| {
+                let dead = app.config.failover.as_ref().is_some_and(|f| app.uplink_failures.get(n).is_some_and(|c| *c >= f.failures));
+                let tunnel = n.starts_with("tun") || n.starts_with("wg");
+                let color = if dead { Color::Red } else if tunnel { Color::Cyan } else { Color::Green };
+                let marker = if app.active_uplink.as_ref() == Some(n) { "⇅" } else if tunnel { "◆" } else { "•" };
+                ListItem::new(format!(" {} {:<15}: {}", marker, n, ip)).style(Style::default().fg(color).add_modifier(if dead { Modifier::CROSSED_OUT } else { Modifier::empty() }))
+            }).collect();
+            let ifs_title = match &app.netns { Some(ns) => format!(" [ ACTIVE INTERFACES @ {} ] ", ns), None => " [ ACTIVE INTERFACES ] ".to_string() };
+            let transfer = app.transfer_rows(&app.details_iface);
+            let services_height = if app.config.targets.is_empty() { 0 } else { app.config.targets.len() as u16 + 2 };
+            let right_chunks = Layout::default().direction(Direction::Vertical).constraints([Constraint::Min(3), Constraint::Length(3), Constraint::Length(3), Constraint::Length(services_height), Constraint::Length(app.details.len() as u16 + app.details_action.is_some() as u16 + transfer.len() as u16 + 2)]).split(top_chunks[1]);
+            if view == SelectionMode::Overview {
+                let vpns: Vec<&str> = app.active_vpns.iter().filter(|v| app.vpn_names.contains(v)).map(|v| v.as_str()).collect();
+                let uplink = ListItem::new(format!(" ⇅  {:<10}: {}", "Uplink", app.active_uplink.clone().filter(|_| app.netns.is_none()).or_else(|| app.routes.iter().find(|r| r.starts_with("default")).cloned()).unwrap_or_else(|| "no default route".to_string()))).style(Style::default().fg(Color::Green));
+                let mut connections = match &app.netns {
+                    Some(ns) => vec![uplink, match &app.netns_error {
+                        Some(e) => ListItem::new(format!(" ⚠  {:<10}: cannot read {}: {}", "Namespace", ns, e)).style(Style::default().fg(Color::LightRed)),
+                        None => ListItem::new(format!(" ⓘ  {:<10}: Wi-Fi, VPN, public IP and latency: host only ({} shown)", "Namespace", ns)).style(Style::default().fg(Color::DarkGray)),
+                    }],
+                    None => vec![
+                        ListItem::new(format!(" 📶 {:<10}: {}", "WiFi", if app.current_ssid.is_empty() { "not connected" } else { &app.current_ssid })).style(Style::default().fg(if app.current_ssid.is_empty() { Color::DarkGray } else { Color::Yellow })),
+                        ListItem::new(format!(" ◆  {:<10}: {}", "VPN", if vpns.is_empty() { "none".to_string() } else { vpns.join(", ") })).style(Style::default().fg(if vpns.is_empty() { Color::DarkGray } else { Color::Cyan })),
+                        ListItem::new(format!(" 🌐 {:<10}: {}", "Public IP", app.public_ip.as_deref().unwrap_or(if app.config.public_ip.is_some() { "checking…" } else { "not checked ([public_ip] url)" }))).style(Style::default().fg(if app.public_ip.is_some() { Color::White } else { Color::DarkGray })),
+                        uplink,
+                    ],
+                };
+                if app.on_battery {
+                    connections.push(ListItem::new(format!(" 🔋 {:<10}: on battery, sampling every {} s{}", "Power", format::number(app.intervals().0.as_secs_f64(), 1), if app.probes_paused() { ", probes paused" } else { "" })).style(Style::default().fg(Color::Yellow)));
+                }
+                if let Some(pending) = &app.checkpoint {
+                    connections.push(ListItem::new(format!(" ⟲  {:<10}: {} change(s) {} in {} s", "Checkpoint", pending.changes.len(), if pending.confirm { "rolled back unless kept" } else { "kept if connectivity holds" }, pending.remaining())).style(Style::default().fg(Color::Yellow)));
+                }
+                connections.extend(app.anomalies.iter().flat_map(|d| d.active.iter()).map(|flag| {
+                    ListItem::new(format!(" ⚠  {:<10}: {} {} {} since {} (usually {}) ⇧M", "Anomaly", flag.iface, anomaly::DIRECTIONS[flag.direction], format::rate(flag.rate, app.rate_unit), format::time(flag.since), format::rate(flag.expected, app.rate_unit))).style(Style::default().fg(Color::LightRed))
+                }));
+                connections.extend(app.latency_rows().into_iter().filter(|_| app.netns.is_none()).map(|(label, series)| {
+                    let (marker, color) = if label == gateway::INTERNET { ("🌍", Color::LightBlue) } else { ("⌂ ", Color::Green) };
+                    let color = if series.samples.back().is_some_and(Option::is_none) { Color::Red } else { color };
+                    ListItem::new(format!(" {} {:<10}: {:<15} {:>9}  {}", marker, label, series.host, series.describe(), series.sparkline(30))).style(Style::default().fg(color))
+                }));
+                f.render_widget(List::new(connections).block(Block::default().title(match &app.netns { Some(ns) => format!(" [ CONNECTIONS @ {} ] ", ns), None => " [ CONNECTIONS ] ".to_string() }).borders(Borders::ALL)), right_chunks[0]);
+            } else {
+                f.render_widget(List::new(ifs).block(Block::default().title(ifs_title).borders(Borders::ALL)), right_chunks[0]);
+            }
+            let (rx, tx) = app.total_rates();
+            let boot = app.boot_bytes();
+            let summary = format!(" ↓ {}  ↑ {}  │  Session ↓ {}  ↑ {}  │  Boot ↓ {}  ↑ {}", format::rate(rx, app.rate_unit), format::rate(tx, app.rate_unit), format::bytes(app.session_bytes.0), format::bytes(app.session_bytes.1), format::bytes(boot.0), format::bytes(boot.1));
+            f.render_widget(Paragraph::new(summary).style(Style::default().fg(Color::LightGreen)).block(Block::default().title(" [ TOTAL ] ").borders(Borders::ALL)), right_chunks[1]);
+            let skewed = app.ntp.offset_ms.is_some_and(|o| o.abs() > 100.0);
+            let clock_color = match app.ntp.synced { Some(true) if !skewed => Color::Green, Some(false) => Color::LightRed, _ => Color::Yellow };
+            f.render_widget(Paragraph::new(format!(" {} {}", if app.ntp.synced == Some(true) { "●" } else { "○" }, app.ntp.describe())).style(Style::default().fg(clock_color)).block(Block::default().title(" [ CLOCK ] ").borders(Borders::ALL)), right_chunks[2]);
+            let details: Vec<ListItem> = app.details_action.iter().map(|a| ListItem::new(format!(" {:<12}: {}", "Last action", a)).style(Style::default().fg(Color::Yellow)))
+                .chain(app.details.iter().map(|(k, v)| ListItem::new(format!(" {:<12}: {}", k, v))))
+                .chain(transfer.iter().map(|(k, v)| ListItem::new(format!(" {:<12}: {}", k, v)).style(Style::default().fg(Color::LightGreen)))).collect();
+            if services_height > 0 {
+                let now = clock::epoch_now();
+                let services: Vec<ListItem> = app.config.targets.iter().map(|t| {
+                    let (marker, color, latency) = match app.sla_status.get(&t.name) {
+                        Some(Some(ms)) => ("●", Color::Green, format!("{} ms", format::number(*ms, 0))),
+                        Some(None) => ("●", Color::Red, "down".to_string()),
+                        None => ("○", Color::DarkGray, "…".to_string()),
+                    };
+                    let windows: Vec<String> = sla::WINDOWS.iter().map(|(label, span)| match app.sla.availability(&t.name, *span, now) {
+                        Some(pct) => format!("{} {}%", label, format::number(pct, 2)),
+                        None => format!("{} -", label),
+                    }).collect();
+                    ListItem::new(format!(" {} {:<14} {:>8}  {}", marker, t.name, latency, windows.join("  "))).style(Style::default().fg(color))
+                }).collect();
+                f.render_widget(List::new(services).block(Block::default().title(" [ SERVICES ] availability ").borders(Borders::ALL)), right_chunks[3]);
+            }
+            f.render_widget(List::new(details).block(Block::default().title(format!(" [ DETAILS: {} ] [U] Renew [⇧U] Release ", app.details_iface)).borders(Borders::ALL)), right_chunks[4]);
+        }
+    }
+
+    let graphs = app.graph_targets(&active_ips);
+    let overlay_open = app.popup.is_some() || app.confirm.is_some() || matches!(app.selection_mode, SelectionMode::Credentials | SelectionMode::TextInput | SelectionMode::Palette);
+    let raster_target = app.graphics.filter(|_| !overlay_open).zip(graphics::cell_size());
+    let mut raster_update = None;
+    let graph_visible = !app.linear && main_chunks[1].height > 2;
+    if !graphs.is_empty() && graph_visible {
+        let (name, data) = graphs[app.graph_index % graphs.len()];
+        let marked: Vec<_> = graphs.iter().filter(|(n, _)| app.compared.contains(n)).copied().collect();
+        let axis = GraphAxes { last_x: app.counter, times: &app.tick_times, cursor: (app.graph_cursor > 0).then_some(app.counter - app.graph_cursor as f64), unit: app.rate_unit };
+        let breakdown = app.config.protocols.as_ref().filter(|p| app.graph_mode == GraphMode::Single && main_chunks[1].width >= p.width.saturating_add(40) && app.protocols.as_ref().is_some_and(|c| c.iface == *name)).map(|p| p.width);
+        let graph_area = match breakdown {
+            Some(width) => {
+                let chunks = Layout::default().direction(Direction::Horizontal).constraints([Constraint::Min(40), Constraint::Length(width)]).split(main_chunks[1]);
+                render_protocols(f, chunks[1], app);
+                chunks[0]
+            }
+            None => main_chunks[1],
+        };
+        match app.graph_mode {
+            GraphMode::Single => {
+                let capacity = app.config.capacity(name);
+                let (warn, critical) = (app.config.warn_percent, app.config.critical_percent);
+                let color = |y: f64| match capacity {
+                    Some(c) if y * 100.0 / c >= critical => Color::Red,
+                    Some(c) if y * 100.0 / c >= warn => Color::Yellow,
+                    Some(_) => Color::Green,
+                    None => data.color,
+                };
+                let title = match capacity { Some(c) => format!("{} ({}%)", name, format::number(data.current_speed * 100.0 / c, 0)), None => name.to_string() };
+                if let Some((protocol, (cell_w, cell_h))) = raster_target {
+                    let block = Block::default().title(format!(" {} - {} ", title, format::rate(data.current_speed, app.rate_unit))).borders(Borders::ALL).border_type(BorderType::Rounded);
+                    let inner = block.inner(graph_area);
+                    f.render_widget(block, graph_area);
+                    let key = (app.counter as u64, inner, title);
+                    raster_update = Some(if app.raster_key.as_ref() == Some(&key) { (key, None) } else {
+                        let max_val = data.history.iter().map(|&(_, y)| y).fold(1.0, f64::max);
+                        let image = graphics::plot(inner.width as u32 * cell_w, inner.height as u32 * cell_h, &data.history, (app.counter - 300.0, app.counter), max_val, |y| graphics::rgb(color(y)));
+                        (key, Some((inner, graphics::encode(&image, protocol, inner.width, inner.height))))
+                    });
+                } else {
+                    render_braille_graph(f, graph_area, &title, data.current_speed, &data.history, color, &axis);
+                }
+            }
+            GraphMode::Overlay => render_overlay_graph(f, main_chunks[1], if marked.len() >= 2 { &marked } else { &graphs }, &axis),
+            GraphMode::Stacked => render_stacked_graph(f, main_chunks[1], &graphs.iter().filter(|(_, d)| !d.remote).copied().collect::<Vec<_>>(), &axis),
+        }
+    } else if graph_visible {
+        f.render_widget(Paragraph::new("Attente d'une IP active...").alignment(ratatui::layout::Alignment::Center).block(Block::default().borders(Borders::ALL)), main_chunks[1]);
+    }
+
+    match raster_update {
+        Some((key, image)) => {
+            if image.is_some() { app.raster = image; }
+            app.raster_key = Some(key);
+        }
+        None if app.raster_key.is_some() => { app.raster_key = None; app.raster_reset = true; }
+        None => {}
+    }
+
+    let help_chunks = Layout::default().direction(Direction::Horizontal).constraints([Constraint::Min(20), Constraint::Length(if app.events.last().is_some() && !app.linear && !compact { 50 } else { 0 })]).split(main_chunks[2]);
+    if let Some(event) = app.events.last().filter(|_| !app.linear && !compact) {
+        f.render_widget(Paragraph::new(format!(" {} [{}] {}", event.timestamp(), event.category, event.message)).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded)).style(Style::default().fg(Color::LightYellow)), help_chunks[1]);
+    }
+    let help: Vec<&str> = [("[TAB] Page", ""), ("[^P] Go to", ""), ("[G] Graph", ""), ("[⇧G] Compare", ""), ("[T] Topology", ""), ("[N] Netns", ""), ("[D] Docker", ""), ("[P] Ports", ""), ("[O] Tools", ""), ("[V] RA", ""), ("[S] Schedule", ""), ("[I] LAN", ""), ("[H] Speed", ""), ("[L] Events", ""), ("[⇧O] Outages", ""), ("[⇧K] Certs", "openssl"), ("[⇧A] App VPN", "nft"), ("[⇧R] Routes", "ip"), ("[⇧P] Rules", "ip"), ("[⇧J] Journal", "journalctl"), ("[A] New", "NetworkManager"), ("[ENTER] Connect", "NetworkManager"), ("[C] Last VPN", "NetworkManager"), ("[X] Disc", "NetworkManager"), ("[Q] Quit", "")]
+        .iter().filter(|(_, tool)| tool.is_empty() || app.has(tool)).map(|(label, _)| *label).collect();
+    if compact && !app.linear {
+        let (rx, tx) = app.total_rates();
+        let ifs: Vec<String> = active_ips.iter().map(|(n, ip)| format!("{}{} {}", if app.active_uplink.as_ref() == Some(n) { "⇅" } else { "" }, n, ip)).collect();
+        let lines = vec![
+            ratatui::text::Line::styled(format!(" ↓ {}  ↑ {}  │  {}", format::rate(rx, app.rate_unit), format::rate(tx, app.rate_unit), ifs.join(", ")), Style::default().fg(Color::LightGreen)),
+            ratatui::text::Line::styled(format!(" {}", fit_labels(&help, main_chunks[2].width.saturating_sub(2) as usize)), Style::default().fg(Color::Gray)),
+        ];
+        f.render_widget(Paragraph::new(lines), main_chunks[2]);
+        render_overlays(f, app);
+        theme::apply(f.buffer_mut(), app.scheme);
+        return;
+    }
+    let mut help_block = Block::default().borders(Borders::ALL).border_type(BorderType::Rounded);
+    if !app.missing.is_empty() { help_block = help_block.title(ratatui::text::Span::styled(format!(" missing: {} ", app.missing.join(", ")), Style::default().fg(Color::Red))); }
+    f.render_widget(Paragraph::new(format!(" {} ", fit_labels(&help, help_chunks[0].width.saturating_sub(4) as usize))).block(help_block).style(Style::default().fg(Color::Gray)), help_chunks[0]);
+
+    render_overlays(f, app);
+    theme::apply(f.buffer_mut(), app.scheme);
+}
+
+fn render_list(f: &mut Frame, area: Rect, list: List, state: &mut ListState, page_rows: &mut usize) {
+    let len = list.len();
+    f.render_stateful_widget(list, area, state);
+    let rows = area.height.saturating_sub(2) as usize;
+    *page_rows = rows.max(1);
+    if len <= rows || rows == 0 { return; }
+    let mut scrollbar = ScrollbarState::new(len - rows).viewport_content_length(rows).position(state.offset());
+    f.render_stateful_widget(Scrollbar::new(ScrollbarOrientation::VerticalRight).begin_symbol(None).end_symbol(None), area.inner(&Margin { vertical: 1, horizontal: 0 }), &mut scrollbar);
+    let label = match state.selected() { Some(i) => format!(" {} of {} ", i.min(len - 1) + 1, len), None => format!(" {} ", len) };
+    let width = label.chars().count() as u16;
+    if area.width > width + 4 { f.render_widget(Paragraph::new(label).style(Style::default().fg(Color::Gray)), Rect { x: area.right() - width - 2, y: area.bottom() - 1, width, height: 1 }); }
+}
+
+fn render_tabs(f: &mut Frame, area: Rect, app: &App) {
+    let selected = Page::ALL.iter().position(|p| *p == app.page()).unwrap_or(0);
+    f.render_widget(Tabs::new(Page::ALL.iter().enumerate().map(|(i, p)| format!("{} {}", i + 1, p.title())).collect::<Vec<_>>()).select(selected).divider("│")
+        .style(Style::default().fg(Color::Gray)).highlight_style(Style::default().fg(Color::LightBlue).add_modifier(Modifier::BOLD | Modifier::REVERSED)), area);
+}
+
+fn fit_labels(labels: &[&str], width: usize) -> String {
+    let Some((last, rest)) = labels.split_last() else { return String::new() };
+    let mut text = String::new();
+    for label in rest {
+        if text.chars().count() + label.chars().count() + last.chars().count() + 3 > width { break; }
+        text += label;
+        text += " | ";
+    }
+    text + last
+}
+
+fn render_status_line(f: &mut Frame, area: Rect, app: &App, active_ips: &[(String, String)]) {
+    let (rx, tx) = app.total_rates();
+    let uplink = app.active_uplink.as_ref().and_then(|u| active_ips.iter().find(|(n, _)| n == u)).or(active_ips.first()).map(|(n, ip)| format!("{} {}", n, ip)).unwrap_or_else(|| "no address".to_string());
+    let vpns: Vec<&str> = app.active_vpns.iter().filter(|v| app.vpn_names.contains(v)).map(|v| v.as_str()).collect();
+    let mut lines = vec![
+        ratatui::text::Line::styled(format!("↓ {} ↑ {}", format::rate(rx, app.rate_unit), format::rate(tx, app.rate_unit)), Style::default().fg(Color::LightGreen)),
+        ratatui::text::Line::raw(if app.current_ssid.is_empty() { uplink } else { format!("{} │ 📶 {}", uplink, app.current_ssid) }),
+        ratatui::text::Line::styled(if vpns.is_empty() { "VPN off".to_string() } else { format!("VPN {}", vpns.join(", ")) }, Style::default().fg(if vpns.is_empty() { Color::DarkGray } else { Color::Cyan })),
+    ];
+    if let Some(event) = app.events.last() { lines.push(ratatui::text::Line::styled(format!("{} {}", event.timestamp(), event.message), Style::default().fg(Color::LightYellow))); }
+    lines.push(ratatui::text::Line::styled(format!("{}×{}: enlarge to 80×24 for the full view, Q quits", area.width, area.height), Style::default().fg(Color::DarkGray)));
+    f.render_widget(Paragraph::new(lines), area);
+}
+
+fn render_overlays(f: &mut Frame, app: &App) {
+    if app.selection_mode == SelectionMode::Palette {
+        let area = centered_rect(60, 60, f.size());
+        f.render_widget(Clear, area);
+        let block = Block::default().title(" Go to: connections, interfaces, hosts, views, tools ").title_bottom(" [↑↓] Select [ENTER] Go [ESC] Close ").borders(Borders::ALL).border_style(Style::default().fg(Color::Magenta)).border_type(BorderType::Double);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let chunks = Layout::default().direction(Direction::Vertical).constraints([Constraint::Length(2), Constraint::Min(0)]).split(inner);
+        f.render_widget(Paragraph::new(app.text_input.line(false)).block(Block::default().borders(Borders::BOTTOM)), chunks[0]);
+        let matches = app.palette_matches();
+        let items: Vec<ListItem> = matches.iter().map(|(label, jump)| {
+            let color = match jump { Jump::Vpn(_) => Color::Cyan, Jump::WiFi(_) => Color::Yellow, Jump::Interface(_) | Jump::Host(_) => Color::Green, Jump::View(_) => Color::White, _ => Color::LightBlue };
+            ListItem::new(format!(" {}", label)).style(Style::default().fg(color))
+        }).collect();
+        let mut state = ListState::default().with_selected((!matches.is_empty()).then_some(app.palette_index.min(matches.len() - 1)));
+        if matches.is_empty() {
+            f.render_widget(Paragraph::new(" No match.").style(Style::default().fg(Color::DarkGray)), chunks[1]);
+        } else {
+            f.render_stateful_widget(List::new(items).highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD)).highlight_symbol(">> "), chunks[1], &mut state);
+        }
+    }
+
+    if app.selection_mode == SelectionMode::Credentials {
+        let area = centered_rect(50, 20, f.size());
+        let area = Rect { height: area.height.max(app.form.len() as u16 * 2 + 5), ..area };
+        f.render_widget(Clear, area);
+        let title = match app.wizard {
+            Some(kind) => format!(" New {} connection (TAB next field, ENTER reviews) ", kind.title()),
+            None => format!(" Credentials for {} (TAB next field, ENTER connects) ", app.connect_target.as_ref().map_or("", |t| t.name())),
+        };
+        let mut lines: Vec<ratatui::text::Line> = app.form.iter().enumerate().flat_map(|(i, field)| {
+            let focused = i == app.form_focus;
+            let mut spans = vec![ratatui::text::Span::styled(format!(" {:>15}: ", field.label), if focused { Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD) } else { Style::default() })];
+            if focused { spans.extend(field.input.line(field.masked).spans); } else { spans.push(ratatui::text::Span::raw(field.input.shown(field.masked))); }
+            [ratatui::text::Line::from(""), ratatui::text::Line::from(spans)]
+        }).collect();
+        let saved: Vec<&str> = app.form.iter().filter(|f| f.saved()).map(|f| f.label).collect();
+        if app.wizard.is_none() && !saved.is_empty() {
+            lines.extend([ratatui::text::Line::from(""), ratatui::text::Line::styled(format!(" Saved in the profile: {}. Secrets are not stored.", saved.join(", ").to_lowercase()), Style::default().fg(Color::DarkGray))]);
+        }
+        f.render_widget(Paragraph::new(lines).block(Block::default().title(title).borders(Borders::ALL).border_style(Style::default().fg(Color::Magenta)).border_type(BorderType::Double)), area);
+    }
+
+    if app.selection_mode == SelectionMode::TextInput {
+        let title = match app.input_action {
+            InputAction::UpnpAdd => " Add mapping: <TCP|UDP> <ext port> [ip:]<int port> [description] ",
+            InputAction::ReachabilityPort => " TCP port to test from the Internet ",
+            InputAction::EditProperty => " New value (empty clears the property) ",
+            InputAction::ExportProfile => " Export to directory (or .nmconnection file) ",
+            InputAction::CloneProfile => " Name of the duplicated profile ",
+            InputAction::ImportDirectory => " Import .nmconnection / .ovpn / .conf files from directory ",
+            InputAction::LabelDevice => " Label for this device (empty clears it) ",
+            InputAction::Lookup => " IP address to look up (reverse DNS, RDAP/whois) ",
+            InputAction::Iperf => " iperf3 server [-R for download] [seconds, default 10] ",
+            InputAction::CertEndpoint => " Host[:port] or https:// URL whose certificate to check ",
+            InputAction::AppVpn => " <tunnel> <pid to move | command to launch> ",
+            InputAction::AddRoute => " <destination> [via <gateway>] [dev <device>] [metric <n>] ",
+            InputAction::PinSlot => " Quick-connect slot for this connection (1-9, its current slot unpins it) ",
+            InputAction::SplitEntry => " Subnet to route through the tunnel (10.0.0.0/8 [gateway] [metric]) or DNS domain (corp.example) ",
+            InputAction::PingSweep => " Subnet to sweep (CIDR, up to 4096 addresses) ",
+        };
+        let area = centered_rect(60, 20, f.size());
+        f.render_widget(Clear, area);
+        f.render_widget(Paragraph::new(app.text_input.line(false)).block(Block::default().title(title).borders(Borders::ALL).border_style(Style::default().fg(Color::Magenta)).border_type(BorderType::Double)).alignment(ratatui::layout::Alignment::Center), area);
+    }
+
+    if let Some((title, lines)) = &app.popup {
+        let area = centered_rect(70, 50, f.size());
+        f.render_widget(Clear, area);
+        let text = lines.iter().map(|l| format!(" {}", l)).collect::<Vec<_>>().join("\n");
+        f.render_widget(Paragraph::new(text).wrap(ratatui::widgets::Wrap { trim: false }).block(Block::default().title(title.as_str()).title_bottom(" [ESC] Close ").borders(Borders::ALL).border_style(Style::default().fg(Color::LightBlue)).border_type(BorderType::Double)), area);
+    }
+
+    if let Some((title, lines, _)) = &app.confirm {
+        let area = centered_rect(70, 50, f.size());
+        f.render_widget(Clear, area);
+        let text: Vec<ratatui::text::Line> = lines.iter().map(|l| {
+            let color = if l.starts_with('+') { Color::Green } else if l.starts_with('-') { Color::Red } else { Color::White };
+            ratatui::text::Line::styled(format!(" {}", l), Style::default().fg(color))
+        }).collect();
+        f.render_widget(Paragraph::new(text).wrap(ratatui::widgets::Wrap { trim: false }).block(Block::default().title(title.as_str()).title_bottom(" [Y] Yes  [N] No  [ESC] Cancel ").borders(Borders::ALL).border_style(Style::default().fg(Color::Yellow)).border_type(BorderType::Double)), area);
+    }
+}
+
+fn render_linear(f: &mut Frame, area: Rect, app: &App, active_ips: &[(String, String)], view: SelectionMode) {
+    let mut lines: Vec<String> = Vec::new();
+    let selected = app.list_state.selected().unwrap_or(0);
+    let marker = |i: usize| if i == selected { ">" } else { " " };
+    if view == SelectionMode::Overview {
+        lines.push(format!("Overview, {} interfaces with an address. Enter graphs the selected one, Tab switches pages.", active_ips.len()));
+        let vpns: Vec<&str> = app.active_vpns.iter().filter(|v| app.vpn_names.contains(v)).map(|v| v.as_str()).collect();
+        lines.push(format!("WiFi: {}. VPN: {}.", if app.current_ssid.is_empty() { "not connected" } else { &app.current_ssid }, if vpns.is_empty() { "none".to_string() } else { vpns.join(", ") }));
+        let latency: Vec<String> = app.latency_rows().into_iter().map(|(label, series)| format!("{} {} {}", label.replace("gw ", "gateway of "), series.host, series.describe())).collect();
+        if !latency.is_empty() { lines.push(format!("Latency: {}.", latency.join("; "))); }
+        let total: f64 = app.protocol_classes.iter().map(|c| c.down + c.up).sum();
+        if let Some(collector) = app.protocols.as_ref().filter(|_| total > 0.0) {
+            lines.push(format!("Protocols on {}: {}.", collector.iface, app.protocol_classes.iter().take(5).map(|c| format!("{} {}%", c.service, format::number((c.down + c.up) * 100.0 / total, 0))).collect::<Vec<_>>().join(", ")));
+        }
+    } else if app.monitor_only && view == SelectionMode::WiFi {
+        lines.push(format!("Routing table, {} routes, read-only. Tab switches pages.", app.routes.len()));
+        lines.extend(app.routes.iter().enumerate().map(|(i, r)| format!("{} {}", marker(i), r)));
+    } else if app.monitor_only {
+        lines.push(format!("Network devices, {} found, read-only because NetworkManager is not available. Tab switches pages.", app.devices.len()));
+        lines.extend(app.devices.iter().enumerate().map(|(i, d)| format!("{} {}, {}, {}{}", marker(i), d.name, d.kind, d.operstate, d.speed.map(|s| format!(", {} Mb/s", s)).unwrap_or_default())));
+    } else if view == SelectionMode::WiFi {
+        let status = match app.protection {
+            _ if app.config.autovpn.is_none() => String::new(),
+            Protection::Trusted => ", trusted network".to_string(),
+            Protection::Securing(_) => ", securing with VPN".to_string(),
+            Protection::Protected => ", protected by VPN".to_string(),
+            Protection::Failed => ", UNPROTECTED".to_string(),
+        };
+        let hidden = app.scanned_ssids.len() - app.wifi_ssids.len();
+        lines.push(format!("WiFi networks, {} found{}{}. Tab switches pages.", app.wifi_ssids.len(), if hidden > 0 { format!(", {} hidden", hidden) } else { String::new() }, status));
+        lines.extend(app.wifi_ssids.iter().enumerate().map(|(i, s)| format!("{} {}{}{}{}{}{}{}", if i == selected { ">" } else { " " }, app.star(s), s, app.ap_badge(s), app.slot_badge(s), if *s == app.current_ssid { ", connected" } else if app.blacklist.contains(s) { ", hidden" } else { "" }, app.activation_badge(s), app.usage_badge(s))));
+    } else {
+        lines.push(format!("VPN list, {} profiles. Tab switches pages.", app.vpn_names.len()));
+        lines.extend(app.vpn_names.iter().enumerate().map(|(i, s)| format!("{} {}{}{}, {}{}", if i == selected { ">" } else { " " }, app.star(s), s, app.slot_badge(s), if app.active_vpns.contains(s) { "active" } else { "inactive" }, app.activation_badge(s))));
+    }
+    lines.push(String::new());
+    lines.push(match &app.netns { Some(ns) => format!("Active interfaces in namespace {}:", ns), None => "Active interfaces:".to_string() });
+    for (i, (name, ip)) in active_ips.iter().enumerate() {
+        let uplink = if app.active_uplink.as_ref() == Some(name) { ", uplink" } else { "" };
+        let rate = app.rates.get(name).map(|(rx, tx)| format!(", down {}, up {}", format::rate(*rx, app.rate_unit), format::rate(*tx, app.rate_unit))).unwrap_or_default();
+        lines.push(format!("{} {} {}{}{}", if view == SelectionMode::Overview { marker(i) } else { " " }, name, ip, uplink, rate));
+    }
+    let (rx, tx) = app.total_rates();
+    let boot = app.boot_bytes();
+    lines.push(format!("Total: down {}, up {}. Session: down {}, up {}. Since boot: down {}, up {}.", format::rate(rx, app.rate_unit), format::rate(tx, app.rate_unit), format::bytes(app.session_bytes.0), format::bytes(app.session_bytes.1), format::bytes(boot.0), format::bytes(boot.1)));
+    lines.push(format!("Clock: {}.", app.ntp.describe()));
+    if !app.config.targets.is_empty() {
+        let now = clock::epoch_now();
+        lines.push(String::new());
+        lines.push("Services:".to_string());
+        for t in &app.config.targets {
+            let state = match app.sla_status.get(&t.name) { Some(Some(ms)) => format!("up, {} ms", format::number(*ms, 0)), Some(None) => "down".to_string(), None => "not checked yet".to_string() };
+            let windows: Vec<String> = sla::WINDOWS.iter().filter_map(|(label, span)| app.sla.availability(&t.name, *span, now).map(|pct| format!("{} {}%", label, format::number(pct, 2)))).collect();
+            lines.push(format!("  {} {}{}{}", t.name, state, if windows.is_empty() { "" } else { ", availability " }, windows.join(", ")));
+        }
+    }
+    if !app.details.is_empty() {
+        lines.push(String::new());
+        lines.push(format!("Details for {}:", app.details_iface));
+        lines.extend(app.details_action.iter().map(|a| format!("  Last action: {}", a)));
+        lines.extend(app.details.iter().chain(app.transfer_rows(&app.details_iface).iter()).map(|(k, v)| format!("  {}: {}", k, v)));
+    }
+    if let Some(event) = app.events.last() {
+        lines.push(String::new());
+        lines.push(format!("Last event at {}: {}, {}", event.timestamp(), event.category, event.message));
+    }
+    f.render_widget(Paragraph::new(lines.join("\n")), area);
+}
+
+fn render_topology(f: &mut Frame, area: Rect, app: &mut App) {
+    let rate = |name: &str| app.rates.get(name).map(|(rx, tx)| format!("↓ {:>13}  ↑ {:>13}", format::rate(*rx, app.rate_unit), format::rate(*tx, app.rate_unit))).unwrap_or_default();
+    let mut items: Vec<ListItem> = Vec::new();
+    for master in &app.topology {
+        let (kind, color) = match master.kind { topology::MasterKind::Bridge => ("bridge", Color::Magenta), topology::MasterKind::Bond => ("bond", Color::Blue) };
+        let header = format!("[{}: {}]", kind, master.detail);
+        items.push(ListItem::new(format!(" ■ {:<17} {:<22} {}", master.name, header, rate(&master.name))).style(Style::default().fg(color).add_modifier(Modifier::BOLD)));
+        for (i, port) in master.ports.iter().enumerate() {
+            let branch = if i + 1 == master.ports.len() { "└─" } else { "├─" };
+            let color = if port.state.starts_with("forwarding") || port.state.starts_with("active") { Color::Green }
+                else if port.state.starts_with("blocking") || port.state.starts_with("backup") { Color::Yellow } else { Color::DarkGray };
+            items.push(ListItem::new(format!("   {} {:<14} {:<22} {}", branch, port.name, port.state, rate(&port.name))).style(Style::default().fg(color)));
+        }
+    }
+    if items.is_empty() { items.push(ListItem::new(" No bridge or bond interface found.").style(Style::default().fg(Color::DarkGray))); }
+    let list_widget = List::new(items).block(Block::default().title(" [ TOPOLOGY ] ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Magenta)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)));
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_docker(f: &mut Frame, area: Rect, app: &mut App) {
+    let items: Vec<ListItem> = match &app.containers {
+        Err(e) => vec![ListItem::new(format!(" Docker unavailable: {}", e)).style(Style::default().fg(Color::Red))],
+        Ok(containers) if containers.is_empty() => vec![ListItem::new(" No running container.").style(Style::default().fg(Color::DarkGray))],
+        Ok(containers) => containers.iter().map(|c| {
+            let (down, up) = c.veths.iter().filter_map(|v| app.rates.get(v)).fold((0.0, 0.0), |(d, u), (rx, tx)| (d + tx, u + rx));
+            let veths = if c.veths.is_empty() { "-".to_string() } else { c.veths.join(",") };
+            ListItem::new(format!(" 🐳 {:<20} {:<16} {:<14} ↓ {:>13}  ↑ {:>13}  {} ({})", c.name, veths, c.networks.join(","), format::rate(down, app.rate_unit), format::rate(up, app.rate_unit), c.image, c.state))
+                .style(Style::default().fg(if c.veths.is_empty() { Color::DarkGray } else { Color::Blue }))
+        }).collect(),
+    };
+    let list_widget = List::new(items).block(Block::default().title(" [ DOCKER CONTAINERS ] ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Blue)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)));
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_port_mappings(f: &mut Frame, area: Rect, app: &mut App) {
+    let (header, items): (String, Vec<ListItem>) = match &app.upnp {
+        None => (" Discovering the gateway (UPnP, then NAT-PMP/PCP)...".to_string(), Vec::new()),
+        Some(Err(e)) => (format!(" Port mapping unavailable: {}", e), Vec::new()),
+        Some(Ok(gw)) => (
+            format!(" {} | LAN {} | WAN {}", gw.device, gw.lan_ip, gw.external_ip),
+            gw.mappings.iter().map(|m| {
+                let local = m.internal_addr == gw.lan_ip;
+                ListItem::new(format!(" {:<4} {:>5} → {:>15}:{:<5}  {:<30} {}", m.protocol, m.external_port, m.internal_addr, m.internal_port, m.description, m.lease))
+                    .style(Style::default().fg(if local { Color::Green } else { Color::White }))
+            }).collect(),
+        ),
+    };
+    let chunks = Layout::default().direction(Direction::Vertical).constraints([Constraint::Length(3), Constraint::Min(0)]).split(area);
+    let busy = if app.upnp_task.is_some() { " (working...)" } else { "" };
+    f.render_widget(Paragraph::new(format!("{}\n {}{}", header, app.upnp_message, busy)).block(Block::default().borders(Borders::TOP | Borders::LEFT | Borders::RIGHT).title(" [ PORT MAPPINGS ] ").border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightRed))), chunks[0]);
+    let list_widget = List::new(items)
+        .block(Block::default().title(" [+] Add  [-] Remove  [R] Refresh ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightRed)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, chunks[1], list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_router_adverts(f: &mut Frame, area: Rect, app: &mut App) {
+    let mut items: Vec<ListItem> = Vec::new();
+    if let Some(e) = &app.ra_error {
+        items.push(ListItem::new(format!(" RA listener unavailable: {}", e)).style(Style::default().fg(Color::Red)));
+    }
+    for seen in &app.routers {
+        let ra = &seen.advert;
+        let flags = [(ra.managed, "M"), (ra.other, "O")].iter().filter(|(on, _)| *on).map(|(_, f)| *f).collect::<Vec<_>>().join("");
+        let color = if !seen.expected { Color::Red } else if ra.lifetime == 0 { Color::DarkGray } else { Color::Green };
+        items.push(ListItem::new(format!(" {} {} on {} [{}]  lifetime {}s  pref {}  flags {}  hop {}{}  seen {}s ago",
+            if seen.expected { "●" } else { "⚠" }, ra.router, ra.iface, if ra.mac.is_empty() { "?" } else { &ra.mac }, ra.lifetime, ra.preference,
+            if flags.is_empty() { "-" } else { &flags }, ra.hop_limit, ra.mtu.map(|m| format!("  mtu {}", m)).unwrap_or_default(), seen.last_seen.elapsed().as_secs()))
+            .style(Style::default().fg(color).add_modifier(Modifier::BOLD)));
+        for p in &ra.prefixes {
+            items.push(ListItem::new(format!("   ├─ prefix {}/{}  {}{}  valid {}s  preferred {}s", p.prefix, p.len, if p.on_link { "L" } else { "-" }, if p.autonomous { "A" } else { "-" }, p.valid, p.preferred)).style(Style::default().fg(color)));
+        }
+        if !ra.dns.is_empty() {
+            items.push(ListItem::new(format!("   └─ RDNSS {}", ra.dns.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", "))).style(Style::default().fg(color)));
+        }
+    }
+    if items.is_empty() { items.push(ListItem::new(" Listening for Router Advertisements...").style(Style::default().fg(Color::DarkGray))); }
+    let list_widget = List::new(items).block(Block::default().title(" [ ROUTER ADVERTISEMENTS ] ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightGreen)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)));
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_property_editor(f: &mut Frame, area: Rect, app: &mut App) {
+    let Some(editor) = &app.editor else { return };
+    let mut last_setting = "";
+    let items: Vec<ListItem> = editor.properties.iter().map(|p| {
+        let mut lines = Vec::new();
+        if p.setting() != last_setting {
+            last_setting = p.setting();
+            lines.push(ratatui::text::Line::styled(format!(" ── {} ──", last_setting), Style::default().fg(Color::LightBlue).add_modifier(Modifier::BOLD)));
+        }
+        let text = if p.changed() { format!("   {:<40} {}  (was: {})", p.key, p.value, p.original) } else { format!("   {:<40} {}", p.key, p.value) };
+        lines.push(ratatui::text::Line::styled(text, Style::default().fg(if p.changed() { Color::Yellow } else { Color::White })));
+        ListItem::new(lines)
+    }).collect();
+    let pending = editor.properties.iter().filter(|p| p.changed()).count();
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ EDIT: {} ] {} pending ", editor.connection, pending)).title_bottom(" [ENTER] Edit  [W] Review & apply  [ESC] Close ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightBlue)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_split_tunnel(f: &mut Frame, area: Rect, app: &mut App) {
+    let Some(split) = &app.split else { return };
+    let chunks = Layout::default().direction(Direction::Horizontal).constraints([Constraint::Percentage(55), Constraint::Percentage(45)]).split(area);
+    let (s, o) = (&split.settings, &split.original);
+    let color = |changed: bool| Style::default().fg(if changed { Color::Yellow } else { Color::White });
+    let items: Vec<ListItem> = split.rows().iter().map(|row| match row {
+        split::Row::Mode => ListItem::new(format!(" Mode                 {}", split::Settings::describe(s.never_default, "split tunnel (never-default)", "full tunnel (default route)"))).style(color(s.never_default != o.never_default)),
+        split::Row::Pushed => ListItem::new(format!(" Server-pushed routes {}", split::Settings::describe(s.ignore_pushed, "ignored", "accepted"))).style(color(s.ignore_pushed != o.ignore_pushed)),
+        split::Row::Route(i) => ListItem::new(format!(" Route                {}", s.routes[*i])).style(color(!o.routes.contains(&s.routes[*i]))),
+        split::Row::Domain(i) => ListItem::new(format!(" DNS domain           {}", s.domains[*i])).style(color(!o.domains.contains(&s.domains[*i]))),
+    }).collect();
+    let pending = split.diff().len() / 2;
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ SPLIT TUNNEL: {} ] {} pending ", split.connection, pending)).title_bottom(" [ENTER] Toggle  [+] Add  [-] Remove  [W] Review & apply  [ESC] Close ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightBlue)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    let preview = split.preview(&app.routes).into_iter().map(|l| format!(" {}", l)).collect::<Vec<_>>().join("\n");
+    f.render_widget(Paragraph::new(preview).wrap(ratatui::widgets::Wrap { trim: false }).block(Block::default().title(" [ PREVIEW ] ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightBlue))), chunks[1]);
+    render_list(f, chunks[0], list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_schedule(f: &mut Frame, area: Rect, app: &mut App) {
+    let now = clock::epoch_now();
+    let mut upcoming: Vec<(i64, &schedule::Schedule)> = app.config.schedules.iter().filter_map(|s| s.next_run(now).map(|at| (at, s))).collect();
+    upcoming.sort_by_key(|(at, _)| *at);
+    let mut items: Vec<ListItem> = upcoming.iter().map(|(at, s)| {
+        let t = clock::local(*at);
+        let wait = at - now;
+        ListItem::new(format!(" ⏰ {} {}  (in {}h{:02}m)  {:<16} {}", clock::WEEKDAYS[t.weekday as usize], format::hour_minute(t.hour, t.minute), wait / 3600, (wait % 3600) / 60, s.name, s.action.describe()))
+            .style(Style::default().fg(if wait < 3600 { Color::Yellow } else { Color::White }))
+    }).collect();
+    if items.is_empty() { items.push(ListItem::new(" No scheduled action. Add [schedule.<name>] sections to the configuration.").style(Style::default().fg(Color::DarkGray))); }
+    let list_widget = List::new(items).block(Block::default().title(" [ UPCOMING SCHEDULED ACTIONS ] ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightYellow)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)));
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_events(f: &mut Frame, area: Rect, app: &mut App) {
+    let mut items: Vec<ListItem> = app.events.iter().rev().map(|e| {
+        let color = match e.category {
+            "failover" | "watchdog" | "ra" | "rogue" | "bandwidth" | "outage" | "tls" | "clock" | "checkpoint" => Color::LightRed,
+            "vpn" | "autovpn" => Color::Cyan,
+            "wifi" | "roam" => Color::Yellow,
+            "user" | "preset" | "schedule" => Color::LightGreen,
+            _ => Color::White,
+        };
+        ListItem::new(format!(" {}  {:<10} {}", e.timestamp(), e.category, e.message)).style(Style::default().fg(color))
+    }).collect();
+    if items.is_empty() { items.push(ListItem::new(" Nothing recorded yet.").style(Style::default().fg(Color::DarkGray))); }
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ EVENTS ] ({}, newest first) ", app.events.len())).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightYellow)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+const SPEED_RANGES: [(&str, i64); 4] = [("1 day", 86_400), ("7 days", 604_800), ("30 days", 2_592_000), ("90 days", 7_776_000)];
+
+fn render_speed_history(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default().direction(Direction::Vertical).constraints([Constraint::Min(6), Constraint::Length(5)]).split(area);
+    let (range_label, span) = SPEED_RANGES[app.speed_range];
+    let now = clock::epoch_now();
+    let samples: Vec<&speedtest::Sample> = app.speedtests.iter().filter(|s| s.time >= now - span).collect();
+    let max_val = samples.iter().map(|s| s.download.max(s.upload)).fold(1.0, f64::max) * 1.1;
+    let (x0, x1) = ((now - span) as f64, now as f64);
+    let canvas = Canvas::default()
+        .block(Block::default().title(format!(" [ SPEED TEST HISTORY ] {} — ↓ download ↑ upload, Mb/s [+/-] Zoom ", range_label)).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightCyan)))
+        .marker(symbols::Marker::Braille).x_bounds([x0, x1]).y_bounds([0.0, max_val])
+        .paint(|ctx| {
+            for fraction in [0.25, 0.5, 0.75] {
+                ctx.draw(&Line { x1: x0, y1: max_val * fraction, x2: x1, y2: max_val * fraction, color: Color::Indexed(238) });
+                ctx.print(x0, max_val * fraction, ratatui::text::Span::styled(format::number(max_val * fraction, 0), Style::default().fg(Color::DarkGray)));
+            }
+            for pair in samples.windows(2) {
+                ctx.draw(&Line { x1: pair[0].time as f64, y1: pair[0].download, x2: pair[1].time as f64, y2: pair[1].download, color: Color::Green });
+                ctx.draw(&Line { x1: pair[0].time as f64, y1: pair[0].upload, x2: pair[1].time as f64, y2: pair[1].upload, color: Color::Cyan });
+            }
+            for s in &samples {
+                ctx.print(s.time as f64, s.download, ratatui::text::Span::styled("•", Style::default().fg(Color::LightGreen)));
+            }
+            ctx.print(x0, 0.0, ratatui::text::Span::styled(format::date(now - span), Style::default().fg(Color::DarkGray)));
+        });
+    f.render_widget(canvas, chunks[0]);
+    let mut lines = Vec::new();
+    match app.speedtests.last() {
+        Some(s) => lines.push(format!(" Last: {} {}  ↓ {} Mb/s  ↑ {} Mb/s  ping {} ms  {}", format::date(s.time), format::time(s.time), format::number(s.download, 1), format::number(s.upload, 1), format::number(s.ping, 0), s.server)),
+        None => lines.push(" No speed test yet. Run one from the Tools menu (O) or set [speedtest] interval in the configuration.".to_string()),
+    }
+    if !samples.is_empty() {
+        let buckets: Vec<String> = (0..4).map(|b| {
+            let hours = b * 6..b * 6 + 6;
+            let values: Vec<f64> = samples.iter().filter(|s| hours.contains(&clock::local(s.time).hour)).map(|s| s.download).collect();
+            let avg = if values.is_empty() { "-".to_string() } else { format::number(values.iter().sum::<f64>() / values.len() as f64, 1) };
+            format!("{:02}-{:02}h {}", hours.start, hours.end, avg)
+        }).collect();
+        let (min, max) = samples.iter().fold((f64::MAX, 0.0f64), |(lo, hi), s| (lo.min(s.download), hi.max(s.download)));
+        lines.push(format!(" {} tests over {}: download min {} / max {} Mb/s", samples.len(), range_label, format::number(min, 1), format::number(max, 1)));
+        lines.push(format!(" Average download by time of day: {}", buckets.join("  |  ")));
+    }
+    f.render_widget(Paragraph::new(lines.join("\n")).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded)), chunks[1]);
+}
+
+fn render_outages(f: &mut Frame, area: Rect, app: &mut App) {
+    let chunks = Layout::default().direction(Direction::Vertical).constraints([Constraint::Min(4), Constraint::Length(4)]).split(area);
+    let now = clock::epoch_now();
+    let mut items: Vec<ListItem> = app.outages.iter().rev().map(|o| {
+        ListItem::new(format!(" {} {}  →  {:<19}  {:>12}  {:<10} {}", format::date(o.start), format::time(o.start), o.end_label(), format::duration(o.duration(now)), o.iface, o.target))
+            .style(Style::default().fg(if o.is_open() { Color::LightRed } else if o.end.is_none() { Color::Yellow } else { Color::White }))
+    }).collect();
+    if items.is_empty() {
+        items.push(ListItem::new(if app.outage_checks.is_some() { " No outage recorded." } else { " Outage tracking is off. Enable it with [outages] enabled = yes in the configuration." }).style(Style::default().fg(Color::DarkGray)));
+    }
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ OUTAGES ] ({}, newest first) [E] Export report ", app.outages.len())).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightRed)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, chunks[0], list_widget, &mut app.list_state, &mut app.list_rows);
+    let summary: Vec<String> = outages::report(&app.outages, now).into_iter().skip(2).take(4).filter(|l| !l.is_empty()).collect();
+    f.render_widget(Paragraph::new(format!(" This month — {}", summary.join("  |  "))).wrap(ratatui::widgets::Wrap { trim: true }).block(Block::default().borders(Borders::ALL).border_type(BorderType::Rounded)), chunks[1]);
+}
+
+fn render_certificates(f: &mut Frame, area: Rect, app: &mut App) {
+    let now = clock::epoch_now();
+    let warn = app.config.certificates.as_ref().map_or(14, |c| c.warn_days);
+    let mut items: Vec<ListItem> = app.certs.iter().map(|check| match &check.result {
+        Ok(cert) => {
+            let days = (cert.not_after - now).div_euclid(86_400);
+            let color = if days < warn { Color::LightRed } else if days < warn * 2 { Color::Yellow } else { Color::Green };
+            ListItem::new(format!(" ● {:<28} {:>5} days  {}  {:<24} {}", check.endpoint, days, format::date(cert.not_after), cert.issuer, cert.sans.join(", "))).style(Style::default().fg(color))
+        }
+        Err(e) => ListItem::new(format!(" ✖ {:<28} {}", check.endpoint, e)).style(Style::default().fg(Color::LightRed)),
+    }).collect();
+    if items.is_empty() {
+        items.push(ListItem::new(if app.cert_checks.is_some() { " Checking certificates..." } else { " No endpoint configured. Add [certificates] endpoints = host:port, ... to the configuration, or use the Tools menu (O)." }).style(Style::default().fg(Color::DarkGray)));
+    }
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ TLS CERTIFICATES ] days until expiry, warning below {} ", warn)).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightYellow)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_rules(f: &mut Frame, area: Rect, app: &mut App) {
+    let chunks = Layout::default().direction(Direction::Horizontal).constraints([Constraint::Percentage(60), Constraint::Percentage(40)]).split(area);
+    let items: Vec<ListItem> = app.rules.iter().map(|r| {
+        let text = format!(" {:>5}  {}  {}{}", r.priority, if r.v6 { "v6" } else { "v4" }, r.text, r.origin.as_ref().map(|o| format!("  [{}]", o)).unwrap_or_default());
+        ListItem::new(text).style(Style::default().fg(if r.system() { Color::DarkGray } else if r.origin.is_some() { Color::LightMagenta } else { Color::White }))
+    }).collect();
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ POLICY ROUTING ] {} rules, {} from VPNs ", app.rules.len(), app.rules.iter().filter(|r| r.origin.is_some()).count())).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightGreen)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    let detail = match app.rules.get(app.list_state.selected().unwrap_or(0)) {
+        None => " No rule (ip rule show returned nothing).".to_string(),
+        Some(r) => {
+            let mut lines = vec![format!(" Priority {} ({})", r.priority, if r.v6 { "IPv6" } else { "IPv4" })];
+            lines.extend(r.mark.as_ref().map(|m| format!(" Matches packets marked {}", m)));
+            lines.extend(r.table.as_ref().map(|t| format!(" Looks up table {}", t)));
+            lines.extend(r.origin.as_ref().map(|o| format!(" Installed by: {}", o)));
+            lines.push(String::new());
+            match &r.table {
+                Some(t) if ["local", "main", "default"].contains(&t.as_str()) => lines.push(format!(" Table {} is a system table (R shows the main one).", t)),
+                Some(t) if r.routes.is_empty() => lines.push(format!(" Table {} is empty: matching packets fall through to the next rule.", t)),
+                Some(t) => { lines.push(format!(" Routes in table {}:", t)); lines.extend(r.routes.iter().map(|route| format!("   {}", route))); }
+                None => {}
+            }
+            lines.join("\n")
+        }
+    };
+    f.render_widget(Paragraph::new(detail).wrap(ratatui::widgets::Wrap { trim: false }).block(Block::default().title(" [ RULE ] ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightGreen))), chunks[1]);
+    render_list(f, chunks[0], list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_routes(f: &mut Frame, area: Rect, app: &mut App) {
+    let items: Vec<ListItem> = app.routes.iter().map(|r| {
+        if routes::is_created(r, &app.created_routes) { ListItem::new(format!(" ✚ {}  (added by DashNet)", r)).style(Style::default().fg(Color::Yellow)) }
+        else { ListItem::new(format!("   {}", r)).style(Style::default().fg(if r.starts_with("default") { Color::Green } else { Color::White })) }
+    }).collect();
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ ROUTES ] {} routes, {} added by DashNet ", app.routes.len(), app.created_routes.len())).title_bottom(" [+] Add  [-] Delete  [C] Clean up DashNet routes  [ESC] Close ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightGreen)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_app_vpn(f: &mut Frame, area: Rect, app: &mut App) {
+    let mut items: Vec<ListItem> = app.app_vpn_rows().into_iter().map(|(t, process)| {
+        let tunnel = format!(" {} {:<10} mark {:#06x} table {:<5}", if t.up { "●" } else { "✖" }, t.iface, t.mark, t.table);
+        match process {
+            Some(p) => ListItem::new(format!("{} → {:>7}  {}", tunnel, p.pid, p.name)).style(Style::default().fg(if t.up { Color::Green } else { Color::LightRed })),
+            None => ListItem::new(format!("{} → no process (moved out or exited)", tunnel)).style(Style::default().fg(Color::DarkGray)),
+        }
+    }).collect();
+    if items.is_empty() {
+        items.push(ListItem::new(" No application is routed through a tunnel. Press + to launch a command or move a running process by pid.").style(Style::default().fg(Color::DarkGray)));
+    }
+    let list_widget = List::new(items)
+        .block(Block::default().title(" [ PER-APP VPN ] [+] Launch/tag  [-] Untag ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightMagenta)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_peers(f: &mut Frame, area: Rect, app: &mut App) {
+    let now = clock::epoch_now();
+    let mut items: Vec<ListItem> = app.peers.iter().map(|p| {
+        let (age, color) = match p.handshake {
+            Some(t) if now - t < 180 => (format!("{} ago", format::duration(now - t)), Color::Green),
+            Some(t) if matches!(p.server, peers::Server::OpenVpn(_)) => (format!("since {}", format::time(t)), Color::Green),
+            Some(t) => (format!("{} ago", format::duration(now - t)), Color::Yellow),
+            None => ("never".to_string(), Color::DarkGray),
+        };
+        ListItem::new(format!(" {:<8} {:<14} {:<24} {:<22} {:<14} ↓ {:>9} ↑ {:>9}", p.server.label(), p.name, p.endpoint, p.address, age, format::bytes(p.rx), format::bytes(p.tx))).style(Style::default().fg(color))
+    }).collect();
+    if items.is_empty() {
+        items.push(ListItem::new(" No client is connected to a WireGuard or OpenVPN server on this machine.").style(Style::default().fg(Color::DarkGray)));
+        items.extend(app.peer_errors.iter().map(|e| ListItem::new(format!(" {}", e)).style(Style::default().fg(Color::DarkGray))));
+    }
+    let servers = app.peers.iter().map(|p| p.server.label()).fold(Vec::new(), |mut v, s| { if !v.contains(&s) { v.push(s); } v });
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ PEERS ] {} clients{} ", app.peers.len(), if servers.is_empty() { String::new() } else { format!(" on {}", servers.join(", ")) })).title_bottom(" [X] Disconnect (OpenVPN)  [-] Revoke (WireGuard)  [ESC] Close ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightMagenta)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_encrypted_dns(f: &mut Frame, area: Rect, app: &mut App) {
+    let items: Vec<ListItem> = match &app.dns_links {
+        Ok(links) if !links.is_empty() => links.iter().map(|link| {
+            let color = match link.mode.as_str() { "yes" => Color::Green, "opportunistic" => Color::Yellow, _ => Color::LightRed };
+            let profile = link.connection.as_ref().map(|(_, setting)| format!("  profile: {}", if setting.is_empty() { "-" } else { setting })).unwrap_or_default();
+            ListItem::new(format!(" {} {:<36} {:<14} {}{}", if link.mode == "no" { "🔓" } else { "🔒" }, link.label(), link.mode, if link.servers.is_empty() { "-" } else { &link.servers }, profile)).style(Style::default().fg(color))
+        }).collect(),
+        Ok(_) => vec![ListItem::new(" systemd-resolved reported no link.").style(Style::default().fg(Color::DarkGray))],
+        Err(e) => vec![ListItem::new(format!(" resolvectl failed: {} (is systemd-resolved running?)", e)).style(Style::default().fg(Color::DarkGray))],
+    };
+    let list_widget = List::new(items)
+        .block(Block::default().title(" [ ENCRYPTED DNS ] DNS-over-TLS through systemd-resolved ").title_bottom(" [ENTER] Switch no → opportunistic → yes  [T] Verify  DoH is not supported by resolved  [ESC] Close ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightBlue)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_talkers(f: &mut Frame, area: Rect, app: &mut App) {
+    let mut items: Vec<ListItem> = app.talkers.iter().map(|t| {
+        let color = if t.up >= 1.0 { Color::LightRed } else if t.up + t.down >= 0.1 { Color::Yellow } else { Color::White };
+        ListItem::new(format!(" {:>7} {:<16} {:>3} conn  ↑ {:>11} ↓ {:>11}  sent {:>9} recv {:>9}  {}", if t.pid == 0 { "-".to_string() } else { t.pid.to_string() }, t.process, t.connections,
+            format::rate(t.up, app.rate_unit), format::rate(t.down, app.rate_unit), format::bytes(t.sent), format::bytes(t.received), t.remote)).style(Style::default().fg(color))
+    }).collect();
+    if items.is_empty() {
+        items.push(ListItem::new(format!(" {}", app.talker_error.as_deref().unwrap_or("No established TCP connection outside this machine."))).style(Style::default().fg(Color::DarkGray)));
+    }
+    let flagged: Vec<String> = app.anomalies.iter().flat_map(|d| d.active.iter()).map(|flag| format!("{} {}", flag.iface, anomaly::DIRECTIONS[flag.direction])).collect();
+    let bottom = if flagged.is_empty() { " Sorted by upload. Processes of other users need root  [ESC] Close ".to_string() } else { format!(" ⚠ Unusual traffic: {}  [ESC] Close ", flagged.join(", ")) };
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ TOP TALKERS ] {} processes with TCP connections ", app.talkers.len())).title_bottom(bottom).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(if flagged.is_empty() { Color::LightGreen } else { Color::LightRed })))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_data_usage(f: &mut Frame, area: Rect, app: &mut App) {
+    let now = clock::epoch_now();
+    let pair = |(rx, tx): (u64, u64)| format!("{:>9} (↓ {} ↑ {})", format::bytes(rx + tx), format::bytes(rx), format::bytes(tx));
+    let mut items: Vec<ListItem> = app.usage.rows(now).iter().map(|r| {
+        let connected = r.ssid == app.current_ssid;
+        ListItem::new(format!(" {} {:<24} {:<36} last month {:>9}  since {} {:>9}", if connected { "📶" } else { "  " }, r.ssid, pair(r.month), format::bytes(r.previous.0 + r.previous.1), r.since, format::bytes(r.total.0 + r.total.1)))
+            .style(Style::default().fg(if connected { Color::Yellow } else if r.month == (0, 0) { Color::DarkGray } else { Color::White }))
+    }).collect();
+    if items.is_empty() {
+        items.push(ListItem::new(" No WiFi traffic recorded yet: usage is counted while DashNet runs, against the network connected at the time.").style(Style::default().fg(Color::DarkGray)));
+    }
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ DATA USAGE ] {} by WiFi network ", usage::month(now))).title_bottom(" Counted while DashNet (or dashnet --daemon) runs  [ESC] Close ").borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Yellow)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_file_picker(f: &mut Frame, area: Rect, app: &mut App) {
+    let step = app.cert_flow.as_ref().and_then(|(_, files)| profile::CERT_STEPS.get(files.len())).copied().unwrap_or("file");
+    let items: Vec<ListItem> = app.picker_entries.iter().map(|e| {
+        if e.dir { ListItem::new(format!(" 📁 {}/", e.name)).style(Style::default().fg(Color::LightBlue)) }
+        else { ListItem::new(format!(" 📄 {}", e.name)).style(if e.is_cert() { Style::default().fg(Color::Green) } else { Style::default().fg(Color::DarkGray) }) }
+    }).collect();
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ SELECT {} ] {} (ENTER choose, TAB keep current, ⌫ parent, ESC cancel) ", step.to_uppercase(), app.picker_dir.display())).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Cyan)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_access_points(f: &mut Frame, area: Rect, app: &mut App) {
+    let items: Vec<ListItem> = app.bssids().iter().map(|ap| {
+        let color = if ap.signal >= 60 { Color::Green } else if ap.signal >= 35 { Color::Yellow } else { Color::LightRed };
+        ListItem::new(format!(" 📶 {}  ch {:>3}  {:>3}%  {}{}", ap.bssid, ap.channel, ap.signal, if ap.security.is_empty() { "open" } else { &ap.security }, app.activation_badge(&ap.bssid))).style(Style::default().fg(color))
+    }).collect();
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ ACCESS POINTS ] {} (ENTER connects to this BSSID, ← back) ", app.expanded_ssid)).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::Yellow)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_ping_sweep(f: &mut Frame, area: Rect, app: &mut App) {
+    let neighbors = lan::neighbors();
+    let mut items: Vec<ListItem> = app.sweep_replies.iter().filter_map(|r| r.rtt.map(|rtt| (r.ip.to_string(), rtt))).map(|(ip, rtt)| {
+        let mac = neighbors.iter().find(|n| n.ip == ip).map(|n| n.mac.clone()).unwrap_or_default();
+        let label = app.lan_devices.iter().find(|d| !mac.is_empty() && d.mac == mac).map(|d| d.label.as_str()).unwrap_or("");
+        ListItem::new(format!(" ● {:<15} {:>8} ms  {:<17} {}", ip, format::number(rtt, 1), mac, label)).style(Style::default().fg(if rtt < 10.0 { Color::Green } else if rtt < 100.0 { Color::Yellow } else { Color::LightRed }))
+    }).collect();
+    if items.is_empty() { items.push(ListItem::new(if app.sweep_rx.is_some() { " Waiting for replies..." } else { " No address answered." }).style(Style::default().fg(Color::DarkGray))); }
+    let alive = app.sweep_replies.iter().filter(|r| r.rtt.is_some()).count();
+    let state = if app.sweep_rx.is_some() { format!("{}/{} probed", app.sweep_replies.len(), app.sweep_total) } else { "done [R] Again".to_string() };
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ PING SWEEP ] {} — {} up, {} ", app.sweep_target, alive, state)).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightGreen)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_lan_devices(f: &mut Frame, area: Rect, app: &mut App) {
+    let now = clock::epoch_now();
+    let fresh = app.config.lan.as_ref().map_or(600, |l| l.interval.max(30) as i64 * 2 + 10);
+    let mut items: Vec<ListItem> = app.lan_devices.iter().map(|d| {
+        let online = now - d.last_seen <= fresh;
+        let label = if d.label.is_empty() { "(unlabeled)" } else { d.label.as_str() };
+        let seen = if online { "online".to_string() } else { format!("seen {}", format::time(d.last_seen)) };
+        ListItem::new(format!(" {} {:<20} {:<17} {:<15} {:<12} {}", if online { "●" } else { "○" }, label, d.mac, d.ip, d.network, seen))
+            .style(Style::default().fg(if d.label.is_empty() { Color::Yellow } else if online { Color::Green } else { Color::DarkGray }))
+    }).collect();
+    if items.is_empty() {
+        items.push(ListItem::new(if app.lan_scan.is_some() { " Scanning the LAN..." } else { " LAN discovery is off. Enable it with [lan] enabled = yes in the configuration." }).style(Style::default().fg(Color::DarkGray)));
+    }
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ LAN DEVICES ] ({} known) [ENTER] Label ", app.lan_devices.len())).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightGreen)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn render_journal(f: &mut Frame, area: Rect, app: &mut App) {
+    let mut items: Vec<ListItem> = app.journal.iter().map(|line| {
+        let lower = line.to_lowercase();
+        let color = if lower.contains("error") || lower.contains("fail") { Color::LightRed } else if lower.contains("warn") { Color::Yellow } else { Color::White };
+        ListItem::new(format!(" {}", line)).style(Style::default().fg(color))
+    }).collect();
+    if items.is_empty() { items.push(ListItem::new(if app.journal_task.is_some() { " Reading the journal..." } else { " No matching log line (journal access may need the systemd-journal or adm group)." }).style(Style::default().fg(Color::DarkGray))); }
+    let filter = if app.journal_all || app.journal_filters.is_empty() { "all".to_string() } else { app.journal_filters.join(", ") };
+    let hint = if app.journal_filters.is_empty() { "" } else if app.journal_all { " [F] Filter" } else { " [F] All" };
+    let list_widget = List::new(items)
+        .block(Block::default().title(format!(" [ JOURNAL ] NetworkManager + wpa_supplicant, filter: {}{} ", filter, hint)).borders(Borders::ALL).border_type(BorderType::Thick).border_style(Style::default().fg(Color::LightMagenta)))
+        .highlight_style(Style::default().bg(Color::Indexed(237)).add_modifier(Modifier::BOLD))
+        .highlight_symbol(">> ");
+    render_list(f, area, list_widget, &mut app.list_state, &mut app.list_rows);
+}
+
+fn centered_rect(px: u16, py: u16, r: Rect) -> Rect {
+    let layout = Layout::default().direction(Direction::Vertical).constraints([Constraint::Percentage((100-py)/2), Constraint::Percentage(py), Constraint::Percentage((100-py)/2)]).split(r);
+    Layout::default().direction(Direction::Horizontal).constraints([Constraint::Percentage((100-px)/2), Constraint::Percentage(px), Constraint::Percentage((100-px)/2)]).split(layout[1])[1]
+}
+
+fn render_overlay_graph(f: &mut Frame, area: Rect, series: &[(&String, &InterfaceData)], axis: &GraphAxes) {
+    let max_val = series.iter().flat_map(|(_, d)| d.history.iter().map(|&(_, y)| y)).fold(1.0, f64::max).max(1.0);
+    let last_x = axis.last_x;
+    let canvas = Canvas::default().block(Block::default().title(" COMPARE [M] Mark [⇧G] Stacked ").borders(Borders::ALL).border_type(BorderType::Rounded))
+        .marker(symbols::Marker::Braille).x_bounds([last_x - 300.0, last_x]).y_bounds([y_floor(area, max_val), max_val])
+        .paint(|ctx| {
+            draw_axes(ctx, area, axis, max_val);
+            for (i, (name, data)) in series.iter().enumerate() {
+                let color = OVERLAY_COLORS[i % OVERLAY_COLORS.len()];
+                for w in data.history.windows(2) {
+                    ctx.draw(&Line { x1: w[0].0, y1: w[0].1, x2: w[1].0, y2: w[1].1, color });
+                }
+                ctx.print(last_x - 240.0, max_val * (0.95 - 0.1 * i as f64), ratatui::text::Span::styled(format!("■ {} {}", name, format::rate(data.current_speed, axis.unit)), Style::default().fg(color)));
+            }
+        });
+    f.render_widget(canvas, area);
+}
+
+fn render_stacked_graph(f: &mut Frame, area: Rect, series: &[(&String, &InterfaceData)], axis: &GraphAxes) {
+    let last_x = axis.last_x;
+    let mut totals: HashMap<i64, f64> = HashMap::new();
+    for (_, data) in series { for &(x, y) in &data.history { *totals.entry(x as i64).or_insert(0.0) += y; } }
+    let max_val = totals.values().copied().fold(1.0, f64::max);
+    let total: f64 = series.iter().map(|(_, d)| d.current_speed).sum();
+    let canvas = Canvas::default().block(Block::default().title(format!(" TOTAL - {} [⇧G] Single ", format::rate(total, axis.unit))).borders(Borders::ALL).border_type(BorderType::Rounded))
+        .marker(symbols::Marker::Braille).x_bounds([last_x - 300.0, last_x]).y_bounds([y_floor(area, max_val), max_val])
+        .paint(|ctx| {
+            draw_axes(ctx, area, axis, max_val);
+            let mut base: HashMap<i64, f64> = HashMap::new();
+            for (i, (name, data)) in series.iter().enumerate() {
+                let color = OVERLAY_COLORS[i % OVERLAY_COLORS.len()];
+                for &(x, y) in &data.history {
+                    let lower = base.entry(x as i64).or_insert(0.0);
+                    ctx.draw(&Line { x1: x, y1: *lower, x2: x, y2: *lower + y, color });
+                    *lower += y;
+                }
+                ctx.print(last_x - 240.0, max_val * (0.95 - 0.1 * i as f64), ratatui::text::Span::styled(format!("█ {} {}", name, format::rate(data.current_speed, axis.unit)), Style::default().fg(color)));
+            }
+        });
+    f.render_widget(canvas, area);
+}
+
+fn y_floor(area: Rect, max_val: f64) -> f64 {
+    -max_val / (area.height.saturating_sub(3).max(1) as f64)
+}
+
+fn draw_axes(ctx: &mut ratatui::widgets::canvas::Context, area: Rect, axis: &GraphAxes, max_val: f64) {
+    let last_x = axis.last_x;
+    let col = 300.0 / area.width.saturating_sub(2).max(1) as f64;
+    let row = max_val / area.height.saturating_sub(3).max(1) as f64;
+    for fraction in [0.25, 0.5, 0.75, 1.0] {
+        let y = max_val * fraction;
+        ctx.draw(&Line { x1: last_x - 300.0, y1: y, x2: last_x, y2: y, color: Color::Indexed(238) });
+        let label = format::number(axis.unit.scale(y), 1);
+        ctx.print(last_x - col * (label.len() as f64 + 1.0), y, ratatui::text::Span::styled(label, Style::default().fg(Color::DarkGray)));
+    }
+    for step in 0..5 {
+        let x = last_x - 60.0 * step as f64;
+        let Some(time) = axis.time_at(x) else { continue };
+        let label = format::time(time);
+        let offset = if step == 0 { label.len() as f64 + 1.0 } else { label.len() as f64 / 2.0 };
+        ctx.print(x - col * offset, -row, ratatui::text::Span::styled(label, Style::default().fg(Color::DarkGray)));
+    }
+    if let Some(x) = axis.cursor {
+        ctx.draw(&Line { x1: x, y1: 0.0, x2: x, y2: max_val, color: Color::White });
+    }
+}
+
+fn render_protocols(f: &mut Frame, area: Rect, app: &App) {
+    const COLORS: [Color; 5] = [Color::LightBlue, Color::Magenta, Color::Cyan, Color::Yellow, Color::Gray];
+    let block = Block::default().title(" [ PROTOCOLS ] ").borders(Borders::ALL).border_type(BorderType::Rounded);
+    let width = block.inner(area).width.saturating_sub(2) as usize;
+    let classes = &app.protocol_classes;
+    let total: f64 = classes.iter().map(|c| c.down + c.up).sum();
+    let mut lines: Vec<ratatui::text::Line> = Vec::new();
+    if let Some(e) = app.protocols.as_ref().and_then(|c| c.error()) {
+        lines.push(ratatui::text::Line::styled(format!(" {}", e), Style::default().fg(Color::Red)));
+    } else if total <= 0.0 {
+        lines.push(ratatui::text::Line::styled(" No traffic captured yet", Style::default().fg(Color::DarkGray)));
+    } else {
+        let shares: Vec<f64> = (0..protocols::TRANSPORTS.len()).map(|t| classes.iter().filter(|c| c.transport == t).map(|c| c.down + c.up).sum::<f64>() / total).collect();
+        let mut spans = vec![ratatui::text::Span::raw(" ")];
+        let mut drawn = 0;
+        for (t, share) in shares.iter().enumerate() {
+            let cells = if t == shares.len() - 1 { width - drawn } else { ((share * width as f64).round() as usize).min(width - drawn) };
+            drawn += cells;
+            spans.push(ratatui::text::Span::styled("█".repeat(cells), Style::default().fg(COLORS[t])));
+        }
+        lines.push(ratatui::text::Line::from(spans));
+        let mut legend = vec![ratatui::text::Span::raw(" ")];
+        for (t, share) in shares.iter().enumerate().filter(|(_, s)| **s > 0.0) {
+            legend.push(ratatui::text::Span::styled(format!("{} {}%  ", protocols::TRANSPORTS[t], format::number(share * 100.0, 0)), Style::default().fg(COLORS[t])));
+        }
+        lines.push(ratatui::text::Line::from(legend));
+        lines.push(ratatui::text::Line::raw(""));
+        let bar = width.saturating_sub(30).max(4);
+        for class in classes.iter().take(area.height.saturating_sub(5) as usize) {
+            let share = (class.down + class.up) / total;
+            lines.push(ratatui::text::Line::styled(format!(" {:<12} {:<bar$} {:>4}% {:>10}", class.service, "▇".repeat((share * bar as f64).ceil() as usize), format::number(share * 100.0, 0), format::rate(class.down + class.up, app.rate_unit), bar = bar), Style::default().fg(COLORS[class.transport])));
+        }
+    }
+    f.render_widget(Paragraph::new(lines).block(block.title_bottom(format!(" {} ", app.protocols.as_ref().map_or("", |c| c.iface.as_str())))), area);
 }
 
-fn render_braille_graph(f: &mut Frame, area: Rect, interface: &str, speed: f64, data: &[(f64, f64)], color: Color, last_x: f64) {
+fn render_braille_graph(f: &mut Frame, area: Rect, interface: &str, speed: f64, data: &[(f64, f64)], color: impl Fn(f64) -> Color, axis: &GraphAxes) {
     let max_val = data.iter().map(|&(_, y)| y).fold(1.0, f64::max).max(1.0);
-    let canvas = Canvas::default().block(Block::default().title(format!(" {} - {:.2} Mb/s ", interface, speed)).borders(Borders::ALL).border_type(BorderType::Rounded))
-        .marker(symbols::Marker::Braille).x_bounds([last_x - 300.0, last_x]).y_bounds([0.0, max_val])
+    let last_x = axis.last_x;
+    let readout = axis.cursor.and_then(|x| data.iter().find(|(dx, _)| *dx == x).map(|(_, y)| (x, *y)))
+        .map(|(x, y)| format!("[{} → {}] ", axis.time_at(x).map(format::time).unwrap_or_default(), format::rate(y, axis.unit))).unwrap_or_default();
+    let canvas = Canvas::default().block(Block::default().title(format!(" {} - {} {}", interface, format::rate(speed, axis.unit), readout)).title_bottom(" [ / ] Cursor ").borders(Borders::ALL).border_type(BorderType::Rounded))
+        .marker(symbols::Marker::Braille).x_bounds([last_x - 300.0, last_x]).y_bounds([y_floor(area, max_val), max_val])
         .paint(|ctx| {
-            ctx.print(last_x - 295.0, max_val * 0.7, format!("{:.1} Mb/s max", max_val));
+            draw_axes(ctx, area, axis, max_val);
             for i in 0..data.len().saturating_sub(1) {
-                ctx.draw(&Line { x1: data[i].0, y1: data[i].1, x2: data[i+1].0, y2: data[i+1].1, color });
+                ctx.draw(&Line { x1: data[i].0, y1: data[i].1, x2: data[i+1].0, y2: data[i+1].1, color: color(data[i].1.max(data[i+1].1)) });
             }
         });
     f.render_widget(canvas, area);
@@ -0,0 +1,60 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use crate::config::{self, Mqtt};
+
+struct Message {
+    topic: String,
+    payload: String,
+    retain: bool,
+}
+
+pub struct Publisher {
+    prefix: String,
+    tx: Sender<Message>,
+}
+
+fn args(config: &Mqtt) -> Vec<String> {
+    let mut args = vec!["-h".to_string(), config.host.clone(), "-p".to_string(), config.port.to_string(), "-i".to_string(), config.client_id.clone()];
+    match (&config.cafile, config.tls) {
+        (Some(cafile), _) => args.extend(["--cafile", cafile].map(String::from)),
+        (None, true) => args.extend(["--capath", "/etc/ssl/certs"].map(String::from)),
+        _ => {}
+    }
+    args
+}
+
+fn credentials(config: &Mqtt) -> io::Result<PathBuf> {
+    let dir = config::private_dir("mqtt")?;
+    let written = OpenOptions::new().write(true).create_new(true).mode(0o600).open(dir.join("mosquitto_pub")).and_then(|mut file| writeln!(file, "-u {}\n-P {}", config.user, config.password));
+    if let Err(e) = written { let _ = fs::remove_dir_all(&dir); return Err(e); }
+    Ok(dir)
+}
+
+impl Publisher {
+    pub fn start(config: &Mqtt) -> Publisher {
+        let (tx, rx) = mpsc::channel::<Message>();
+        let base = args(config);
+        let options = if config.user.is_empty() { None } else { credentials(config).ok() };
+        thread::spawn(move || {
+            for message in rx {
+                let mut cmd = Command::new("mosquitto_pub");
+                if let Some(dir) = &options { cmd.env("XDG_CONFIG_HOME", dir); }
+                cmd.args(&base).args(["-q", "0", "-t", &message.topic, "-m", &message.payload]);
+                if message.retain { cmd.arg("-r"); }
+                let _ = cmd.stdout(Stdio::null()).stderr(Stdio::null()).status();
+            }
+            if let Some(dir) = options { let _ = fs::remove_dir_all(dir); }
+        });
+        Publisher { prefix: config.prefix.trim_end_matches('/').to_string(), tx }
+    }
+
+    pub fn publish(&self, topic: &str, payload: String, retain: bool) {
+        let _ = self.tx.send(Message { topic: format!("{}/{}", self.prefix, topic), payload, retain });
+    }
+}
@@ -0,0 +1,158 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::gateway;
+use crate::upnp::{Gateway, Mapping, Protocol};
+
+const PORT: u16 = 5351;
+const LIFETIME: u32 = 7200;
+const NATPMP_RESULTS: [&str; 6] = ["success", "unsupported version", "refused by the gateway", "network failure", "out of resources", "unsupported opcode"];
+const PCP_RESULTS: [&str; 14] = ["success", "unsupported version", "not authorized", "malformed request", "unsupported opcode", "unsupported option", "malformed option", "network failure", "no resources", "unsupported protocol", "user quota exceeded", "cannot provide the external port", "address mismatch", "too many remote peers"];
+
+struct Created {
+    mapping: Mapping,
+    nonce: [u8; 12],
+    expires: Instant,
+    external: Option<Ipv4Addr>,
+}
+
+static CREATED: Mutex<Vec<Created>> = Mutex::new(Vec::new());
+
+fn router() -> Result<Ipv4Addr, String> {
+    gateway::defaults().into_iter().find_map(|(_, via)| via.parse().ok()).ok_or_else(|| "no IPv4 default gateway".to_string())
+}
+
+fn open(router: Ipv4Addr) -> Result<(UdpSocket, Ipv4Addr), String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
+    socket.connect((router, PORT)).map_err(|e| format!("{}: {}", router, e))?;
+    let local = match socket.local_addr() { Ok(SocketAddr::V4(addr)) => *addr.ip(), _ => Ipv4Addr::UNSPECIFIED };
+    Ok((socket, local))
+}
+
+fn exchange(socket: &UdpSocket, request: &[u8], valid: impl Fn(&[u8]) -> bool) -> Result<Vec<u8>, String> {
+    let mut buffer = [0u8; 1100];
+    for attempt in 0..4 {
+        socket.send(request).map_err(|e| e.to_string())?;
+        let _ = socket.set_read_timeout(Some(Duration::from_millis(250 << attempt)));
+        loop {
+            match socket.recv(&mut buffer) {
+                Ok(n) if valid(&buffer[..n]) => return Ok(buffer[..n].to_vec()),
+                Ok(_) => continue,
+                Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => break,
+                Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => return Err("port 5351 is closed on the gateway".to_string()),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+    Err("no answer on port 5351".to_string())
+}
+
+fn nonce() -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    if File::open("/dev/urandom").and_then(|mut f| f.read_exact(&mut nonce)).is_err() {
+        let seed = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos() ^ std::process::id() as u128;
+        nonce.copy_from_slice(&seed.to_be_bytes()[4..]);
+    }
+    nonce
+}
+
+fn result(table: &[&str], code: u16) -> Result<(), String> {
+    if code == 0 { Ok(()) } else { Err(table.get(code as usize).map_or_else(|| format!("error {}", code), |s| s.to_string())) }
+}
+
+fn protocol_number(protocol: &str) -> u8 {
+    if protocol == "UDP" { 17 } else { 6 }
+}
+
+fn pcp_request(opcode: u8, lifetime: u32, local: Ipv4Addr) -> Vec<u8> {
+    let mut m = vec![2, opcode, 0, 0];
+    m.extend(lifetime.to_be_bytes());
+    m.extend(local.to_ipv6_mapped().octets());
+    m
+}
+
+fn pcp_map(local: Ipv4Addr, mapping: &Mapping, lifetime: u32, nonce: [u8; 12]) -> Result<(u16, Ipv4Addr, u32), String> {
+    let (socket, _) = open(router()?)?;
+    let mut request = pcp_request(1, lifetime, local);
+    request.extend(nonce);
+    request.extend([protocol_number(&mapping.protocol), 0, 0, 0]);
+    request.extend(mapping.internal_port.to_be_bytes());
+    request.extend(mapping.external_port.to_be_bytes());
+    request.extend(Ipv4Addr::UNSPECIFIED.to_ipv6_mapped().octets());
+    let reply = exchange(&socket, &request, |r| r.len() >= 60 && r[1] == 0x81 && r[24..36] == nonce)?;
+    result(&PCP_RESULTS, reply[3] as u16)?;
+    let external = std::net::Ipv6Addr::from(<[u8; 16]>::try_from(&reply[44..60]).unwrap_or_default()).to_ipv4_mapped().unwrap_or(Ipv4Addr::UNSPECIFIED);
+    Ok((u16::from_be_bytes([reply[42], reply[43]]), external, u32::from_be_bytes([reply[4], reply[5], reply[6], reply[7]])))
+}
+
+fn natpmp_map(mapping: &Mapping, lifetime: u32) -> Result<(u16, u32), String> {
+    let (socket, _) = open(router()?)?;
+    let opcode = if mapping.protocol == "UDP" { 1 } else { 2 };
+    let mut request = vec![0, opcode, 0, 0];
+    request.extend(mapping.internal_port.to_be_bytes());
+    request.extend(if lifetime == 0 { 0 } else { mapping.external_port }.to_be_bytes());
+    request.extend(lifetime.to_be_bytes());
+    let reply = exchange(&socket, &request, |r| r.len() >= 16 && r[1] == 128 + opcode)?;
+    result(&NATPMP_RESULTS, u16::from_be_bytes([reply[2], reply[3]]))?;
+    Ok((u16::from_be_bytes([reply[10], reply[11]]), u32::from_be_bytes([reply[12], reply[13], reply[14], reply[15]])))
+}
+
+fn created() -> Vec<Mapping> {
+    let Ok(mut created) = CREATED.lock() else { return Vec::new() };
+    created.retain(|c| c.expires > Instant::now());
+    created.iter().map(|c| Mapping { lease: format!("{}s", c.expires.saturating_duration_since(Instant::now()).as_secs()), ..c.mapping.clone() }).collect()
+}
+
+pub fn discover() -> Result<Gateway, String> {
+    let router = router()?;
+    let (socket, local) = open(router)?;
+    let reply = exchange(&socket, &[0, 0], |r| r.len() >= 4 && r[1] == 128)?;
+    let code = u16::from_be_bytes([reply[2], reply[3]]);
+    let (protocol, external_ip) = if code == 1 || reply[0] != 0 {
+        exchange(&socket, &pcp_request(0, 0, local), |r| r.len() >= 24 && r[0] == 2 && r[1] == 0x80).map_err(|e| format!("PCP on {}: {}", router, e))?;
+        let known = CREATED.lock().ok().and_then(|c| c.iter().rev().find_map(|c| c.external));
+        (Protocol::Pcp, known.map_or_else(|| "shown once a mapping is made".to_string(), |ip| ip.to_string()))
+    } else {
+        result(&NATPMP_RESULTS, code).map_err(|e| format!("NAT-PMP on {}: {}", router, e))?;
+        let ip = reply.get(8..12).map(|b| Ipv4Addr::new(b[0], b[1], b[2], b[3]).to_string()).unwrap_or_default();
+        (Protocol::NatPmp, ip)
+    };
+    Ok(Gateway { device: format!("{} {}", protocol.label(), router), lan_ip: local.to_string(), external_ip, mappings: created(), protocol })
+}
+
+pub fn add(protocol: Protocol, lan_ip: &str, mut mapping: Mapping) -> Result<(), String> {
+    if mapping.internal_addr != lan_ip { return Err(format!("{} only maps ports to this machine ({})", protocol.label(), lan_ip)); }
+    let nonce = nonce();
+    let (port, external, lifetime) = match protocol {
+        Protocol::Pcp => {
+            let local: Ipv4Addr = lan_ip.parse().map_err(|_| format!("invalid LAN address '{}'", lan_ip))?;
+            let (port, external, lifetime) = pcp_map(local, &mapping, LIFETIME, nonce)?;
+            (port, Some(external), lifetime)
+        }
+        _ => { let (port, lifetime) = natpmp_map(&mapping, LIFETIME)?; (port, None, lifetime) }
+    };
+    mapping.external_port = port;
+    let expires = Instant::now() + Duration::from_secs(lifetime as u64);
+    if let Ok(mut created) = CREATED.lock() {
+        created.retain(|c| !(c.mapping.protocol == mapping.protocol && c.mapping.external_port == mapping.external_port));
+        created.push(Created { mapping, nonce, expires, external });
+    }
+    Ok(())
+}
+
+pub fn remove(protocol: Protocol, mapping: &Mapping) -> Result<(), String> {
+    let same = |c: &Created| c.mapping.protocol == mapping.protocol && c.mapping.external_port == mapping.external_port;
+    let nonce = CREATED.lock().ok().and_then(|c| c.iter().find(|c| same(c)).map(|c| c.nonce)).ok_or("only mappings made by DashNet in this session can be removed")?;
+    match protocol {
+        Protocol::Pcp => {
+            let local: Ipv4Addr = mapping.internal_addr.parse().map_err(|_| format!("invalid LAN address '{}'", mapping.internal_addr))?;
+            pcp_map(local, mapping, 0, nonce)?;
+        }
+        _ => { natpmp_map(mapping, 0)?; }
+    }
+    if let Ok(mut created) = CREATED.lock() { created.retain(|c| !same(c)); }
+    Ok(())
+}
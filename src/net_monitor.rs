@@ -1,26 +1,66 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
 use std::collections::HashMap;
 
+use crate::netns;
+
 pub struct NetStats {
     pub rx: u64,
     pub tx: u64,
 }
 
-pub fn get_net_data() -> HashMap<String, NetStats> {
+fn parse_net_dev<R: Read>(source: R) -> HashMap<String, NetStats> {
     let mut stats = HashMap::new();
-    if let Ok(file) = File::open("/proc/net/dev") {
-        let reader = BufReader::new(file);
-        for line in reader.lines().skip(2).flatten() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() > 10 {
-                let iface = parts[0].replace(':', "");
-                let rx = parts[1].parse::<u64>().unwrap_or(0);
-                let tx = parts[9].parse::<u64>().unwrap_or(0);
-                stats.insert(iface, NetStats { rx, tx });
-            }
+    let reader = BufReader::new(source);
+    for line in reader.lines().skip(2).flatten() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() > 10 {
+            let iface = parts[0].replace(':', "");
+            let rx = parts[1].parse::<u64>().unwrap_or(0);
+            let tx = parts[9].parse::<u64>().unwrap_or(0);
+            stats.insert(iface, NetStats { rx, tx });
         }
     }
     stats
 }
 
+pub fn get_net_data() -> HashMap<String, NetStats> {
+    File::open("/proc/net/dev").map(parse_net_dev).unwrap_or_default()
+}
+
+pub fn get_net_data_in(ns: &str) -> Result<HashMap<String, NetStats>, String> {
+    let out = netns::exec_in(ns, "cat", &["/proc/net/dev"]).stdin(std::process::Stdio::null()).output().map_err(|e| format!("ip: {}", e))?;
+    if !out.status.success() {
+        let err = String::from_utf8_lossy(&out.stderr).trim().to_string();
+        return Err(if err.is_empty() { format!("ip netns exec failed ({})", out.status) } else { err });
+    }
+    Ok(parse_net_dev(out.stdout.as_slice()))
+}
+
+pub fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname").map(|h| h.trim().to_string()).unwrap_or_else(|_| "localhost".to_string())
+}
+
+pub fn ipv4_addrs() -> Vec<(String, String)> {
+    ipv4_networks().into_iter().map(|(name, ip, _)| (name, ip.to_string())).collect()
+}
+
+pub fn ipv4_networks() -> Vec<(String, std::net::Ipv4Addr, u32)> {
+    let mut addrs = Vec::new();
+    let mut head: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut head) } != 0 { return addrs; }
+    let mut cursor = head;
+    while !cursor.is_null() {
+        let entry = unsafe { &*cursor };
+        if !entry.ifa_addr.is_null() && unsafe { (*entry.ifa_addr).sa_family } as i32 == libc::AF_INET {
+            let sin = unsafe { &*(entry.ifa_addr as *const libc::sockaddr_in) };
+            let name = unsafe { std::ffi::CStr::from_ptr(entry.ifa_name) }.to_string_lossy().to_string();
+            let ip = std::net::Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr));
+            let prefix = if entry.ifa_netmask.is_null() { 32 } else { u32::from_be(unsafe { &*(entry.ifa_netmask as *const libc::sockaddr_in) }.sin_addr.s_addr).count_ones() };
+            if name != "lo" { addrs.push((name, ip, prefix)); }
+        }
+        cursor = entry.ifa_next;
+    }
+    unsafe { libc::freeifaddrs(head) };
+    addrs
+}
@@ -0,0 +1,23 @@
+use std::fs;
+use std::process::Command;
+
+pub fn list_namespaces() -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir("/run/netns")
+        .map(|entries| entries.flatten().map(|e| e.file_name().to_string_lossy().to_string()).collect())
+        .unwrap_or_default();
+    if let Ok(out) = Command::new("ip").args(["netns", "list"]).output() {
+        let s = String::from_utf8_lossy(&out.stdout);
+        names.extend(s.lines().filter_map(|l| l.split_whitespace().next()).map(|n| n.to_string()));
+    }
+    names.sort(); names.dedup(); names
+}
+
+pub fn command(ns: Option<&str>, program: &str) -> Command {
+    match ns { Some(ns) => exec_in(ns, program, &[]), None => Command::new(program) }
+}
+
+pub fn exec_in(ns: &str, program: &str, args: &[&str]) -> Command {
+    let mut cmd = Command::new("ip");
+    cmd.args(["netns", "exec", ns, program]).args(args);
+    cmd
+}
@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+
+use crate::clock;
+use crate::schedule::Schedule;
+
+static PREFS: OnceLock<Prefs> = OnceLock::new();
+static SENT: Mutex<VecDeque<(i64, String)>> = Mutex::new(VecDeque::new());
+
+const VPN_EVENTS: [&str; 5] = ["vpn-up", "vpn-down", "protected", "protection-failed", "connect-failed"];
+const WIFI_EVENTS: [&str; 3] = ["untrusted-wifi", "roam", "rogue-ap"];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Level {
+    All,
+    Critical,
+    Off,
+}
+
+impl Level {
+    pub fn parse(value: &str) -> Option<Level> {
+        match value {
+            "all" | "yes" | "true" | "on" | "1" => Some(Level::All),
+            "critical" => Some(Level::Critical),
+            "off" | "no" | "false" | "0" => Some(Level::Off),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Prefs {
+    pub vpn: Level,
+    pub wifi: Level,
+    pub alerts: Level,
+    pub urgency: Vec<(String, String)>,
+    pub dedup: i64,
+    pub per_minute: usize,
+    pub quiet: Option<((u32, u32), (u32, u32))>,
+}
+
+impl Default for Prefs {
+    fn default() -> Self {
+        Prefs { vpn: Level::All, wifi: Level::All, alerts: Level::All, urgency: Vec::new(), dedup: 60, per_minute: 0, quiet: None }
+    }
+}
+
+impl Prefs {
+    pub fn parse_quiet(spec: &str) -> Option<((u32, u32), (u32, u32))> {
+        let (start, end) = spec.split_once('-')?;
+        Some((Schedule::parse_time(start)?, Schedule::parse_time(end)?))
+    }
+
+    fn level(&self, event: &str) -> Level {
+        if VPN_EVENTS.contains(&event) { self.vpn } else if WIFI_EVENTS.contains(&event) { self.wifi } else { self.alerts }
+    }
+
+    fn quiet_now(&self) -> bool {
+        let Some((start, end)) = self.quiet else { return false };
+        let t = clock::local(clock::epoch_now());
+        let now = (t.hour, t.minute);
+        if start <= end { now >= start && now < end } else { now >= start || now < end }
+    }
+}
+
+pub fn init(prefs: Prefs) {
+    let _ = PREFS.set(prefs);
+}
+
+pub fn urgency(event: &str, critical: bool) -> &'static str {
+    let configured = PREFS.get().and_then(|p| p.urgency.iter().find(|(e, _)| e == event)).map(|(_, u)| u.as_str());
+    match configured {
+        Some("low") => "low",
+        Some("normal") => "normal",
+        Some("critical") => "critical",
+        _ if critical => "critical",
+        _ => "normal",
+    }
+}
+
+fn allowed(event: &str, summary: &str, body: &str, critical: bool) -> bool {
+    let default = Prefs::default();
+    let prefs = PREFS.get().unwrap_or(&default);
+    match prefs.level(event) {
+        Level::Off => return false,
+        Level::Critical if !critical => return false,
+        _ => {}
+    }
+    if !critical && prefs.quiet_now() { return false; }
+    let now = clock::epoch_now();
+    let key = format!("{}\n{}\n{}", event, summary, body);
+    let Ok(mut sent) = SENT.lock() else { return true };
+    while sent.front().is_some_and(|(t, _)| now - t >= prefs.dedup.max(60)) { sent.pop_front(); }
+    if sent.iter().any(|(t, k)| *k == key && now - t < prefs.dedup) { return false; }
+    if prefs.per_minute > 0 && sent.iter().filter(|(t, _)| now - t < 60).count() >= prefs.per_minute { return false; }
+    sent.push_back((now, key));
+    true
+}
+
+pub fn send(event: &str, summary: &str, body: &str, critical: bool) {
+    let urgency = urgency(event, critical);
+    if !allowed(event, summary, body, urgency == "critical") { return; }
+    let icon = if urgency == "critical" { "network-error" } else { "network-transmit-receive" };
+    let _ = Command::new("notify-send").args(["-u", urgency, "-i", icon, summary, body]).spawn();
+}
@@ -0,0 +1,70 @@
+use std::process::Command;
+
+#[derive(Default)]
+pub struct Status {
+    pub synced: Option<bool>,
+    pub source: &'static str,
+    pub server: Option<String>,
+    pub offset_ms: Option<f64>,
+    pub jitter_ms: Option<f64>,
+}
+
+impl Status {
+    pub fn describe(&self) -> String {
+        if self.synced.is_none() && self.server.is_none() { return "sync status unavailable (needs chronyc or timedatectl)".to_string(); }
+        let ms = |v: f64| format!("{}{} ms", if v >= 0.0 { "+" } else { "" }, crate::format::number(v, 2));
+        let mut parts = vec![match self.synced { Some(true) => "NTP synced".to_string(), Some(false) => "NOT synchronized".to_string(), None => "sync unknown".to_string() }];
+        parts.extend(self.server.as_ref().map(|s| format!("server {}", s)));
+        parts.extend(self.offset_ms.map(|v| format!("offset {}", ms(v))));
+        parts.extend(self.jitter_ms.map(|v| format!("jitter {} ms", crate::format::number(v, 2))));
+        format!("{} ({})", parts.join("  "), self.source)
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let out = Command::new(program).args(args).output().ok()?;
+    out.status.success().then(|| String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+fn value<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    text.lines().find_map(|l| l.split_once(':').filter(|(k, _)| k.trim() == key).map(|(_, v)| v.trim()))
+}
+
+fn millis(text: &str) -> Option<f64> {
+    let number: String = text.chars().take_while(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.')).collect();
+    let n: f64 = number.parse().ok()?;
+    Some(match text[number.len()..].trim() {
+        "us" | "µs" => n / 1000.0,
+        "ms" => n,
+        "min" => n * 60_000.0,
+        _ => n * 1000.0,
+    })
+}
+
+fn chrony() -> Option<Status> {
+    let text = run("chronyc", &["tracking"])?;
+    let seconds = |key: &str| value(&text, key).and_then(|v| v.split_whitespace().next()).and_then(|v| v.parse::<f64>().ok()).map(|s| s * 1000.0);
+    Some(Status {
+        synced: value(&text, "Leap status").map(|v| v != "Not synchronised"),
+        source: "chrony",
+        server: value(&text, "Reference ID").and_then(|v| v.split_once('(')).map(|(_, name)| name.trim_end_matches(')').to_string()),
+        offset_ms: seconds("Last offset"),
+        jitter_ms: seconds("RMS offset"),
+    })
+}
+
+fn timesyncd() -> Status {
+    let synced = run("timedatectl", &["show", "-p", "NTPSynchronized", "--value"]).map(|v| v.trim() == "yes");
+    let text = run("timedatectl", &["timesync-status"]).unwrap_or_default();
+    Status {
+        synced,
+        source: "timedatectl",
+        server: value(&text, "Server").map(str::to_string),
+        offset_ms: value(&text, "Offset").and_then(millis),
+        jitter_ms: value(&text, "Jitter").and_then(millis),
+    }
+}
+
+pub fn query() -> Status {
+    chrony().unwrap_or_else(timesyncd)
+}
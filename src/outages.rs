@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use crate::health::{self, Probe};
+use crate::{clock, config, format, net_monitor, profile, watchdog};
+
+pub struct Outage {
+    pub start: i64,
+    pub end: Option<i64>,
+    pub iface: String,
+    pub target: String,
+    pub stopped: Option<i64>,
+}
+
+impl Outage {
+    pub fn is_open(&self) -> bool {
+        self.end.is_none() && self.stopped.is_none()
+    }
+
+    pub fn duration(&self, now: i64) -> i64 {
+        self.end.or(self.stopped).unwrap_or(now) - self.start
+    }
+
+    pub fn end_label(&self) -> String {
+        match (self.end, self.stopped) {
+            (Some(e), _) => format!("{} {}", format::date(e), format::time(e)),
+            (None, Some(_)) => "ended: unknown (DashNet stopped)".to_string(),
+            (None, None) => "ongoing".to_string(),
+        }
+    }
+
+    fn within(&self, from: i64, now: i64) -> i64 {
+        (self.end.or(self.stopped).unwrap_or(now).min(now) - self.start.max(from)).max(0)
+    }
+}
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("outages")
+}
+
+pub fn load() -> Vec<Outage> {
+    fs::read_to_string(store_path()).unwrap_or_default().lines().filter_map(|line| {
+        let f: Vec<&str> = line.split('\t').collect();
+        if f.len() < 4 { return None; }
+        Some(Outage { start: f[0].parse().ok()?, end: f[1].parse().ok(), iface: f[2].to_string(), target: f[3].to_string(), stopped: f.get(4).and_then(|s| s.parse().ok()) })
+    }).collect()
+}
+
+pub fn save(outages: &[Outage]) -> Result<(), String> {
+    let path = store_path();
+    if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    let text: String = outages.iter().map(|o| format!("{}\t{}\t{}\t{}\t{}\n", o.start, o.end.map_or("-".to_string(), |e| e.to_string()), o.iface, o.target, o.stopped.map_or("-".to_string(), |s| s.to_string()))).collect();
+    fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn spawn_checker(target: String, interval: u64) -> Receiver<Probe> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || loop {
+        let iface = watchdog::default_iface().unwrap_or_else(|| "-".to_string());
+        if tx.send(Probe { iface, rtt: health::probe(None, &target) }).is_err() { return; }
+        thread::sleep(Duration::from_secs(interval.max(1)));
+    });
+    rx
+}
+
+pub fn downtime(outages: &[Outage], from: i64, now: i64) -> i64 {
+    outages.iter().map(|o| o.within(from, now)).sum()
+}
+
+pub fn report(outages: &[Outage], now: i64) -> Vec<String> {
+    let from = clock::month_start(now);
+    let month: Vec<&Outage> = outages.iter().filter(|o| o.end.or(o.stopped).unwrap_or(now) > from).collect();
+    let down = downtime(outages, from, now);
+    let elapsed = (now - from).max(1);
+    let mut lines = vec![
+        format!("Connectivity outages on {} from {} to {} {}", net_monitor::hostname(), format::date(from), format::date(now), format::time(now)),
+        String::new(),
+        format!("Outages: {}", month.len()),
+        format!("Total downtime: {}", format::duration(down)),
+        format!("Availability: {} %", format::number(100.0 - down as f64 * 100.0 / elapsed as f64, 3)),
+        month.iter().map(|o| o.duration(now)).max().map_or(String::new(), |d| format!("Longest outage: {}", format::duration(d))),
+        String::new(),
+    ];
+    lines.extend(month.iter().map(|o| format!("{} {}  →  {}  {:>12}  {} (probe {})", format::date(o.start), format::time(o.start),
+        o.end_label(), format::duration(o.within(from, now)), o.iface, o.target)));
+    lines
+}
+
+pub fn export(outages: &[Outage], now: i64) -> Result<PathBuf, String> {
+    let t = clock::local(now);
+    let path = profile::expand_home(&format!("~/dashnet-outages-{}-{:02}.txt", t.year, t.month));
+    fs::write(&path, report(outages, now).join("\n") + "\n").map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
@@ -0,0 +1,50 @@
+pub fn score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (next..text.len()).find(|&i| text[i] == q)?;
+        if found == next && found > 0 { score += 3; }
+        if found == 0 || !text[found - 1].is_alphanumeric() { score += 2; }
+        score -= (found - next).min(5) as i32;
+        next = found + 1;
+    }
+    Some(score)
+}
+
+pub fn rank<T>(query: &str, entries: Vec<(String, T)>) -> Vec<(String, T)> {
+    let mut scored: Vec<(i32, String, T)> = entries.into_iter().filter_map(|(label, item)| Some((score(query, &label)?, label, item))).collect();
+    scored.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, label, item)| (label, item)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_beats_subsequence() {
+        assert!(score("wi", "WiFi").unwrap() > score("wi", "sweep interval").unwrap());
+        let ranked = rank("wi", vec![("sweep interval".to_string(), 1), ("WiFi".to_string(), 2)]);
+        assert_eq!(ranked.iter().map(|(_, id)| *id).collect::<Vec<_>>(), vec![2, 1]);
+    }
+
+    #[test]
+    fn word_start_beats_middle_of_word() {
+        assert!(score("eth", "br eth0").unwrap() > score("eth", "breth0").unwrap());
+    }
+
+    #[test]
+    fn ties_keep_entry_order() {
+        assert_eq!(score("vpn", "vpn office"), score("vpn", "vpn home"));
+        let ranked = rank("vpn", vec![("vpn office".to_string(), 1), ("vpn home".to_string(), 2), ("vpn lab".to_string(), 3)]);
+        assert_eq!(ranked.iter().map(|(_, id)| *id).collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn drops_entries_missing_a_query_letter() {
+        assert_eq!(score("wg0", "wlan0"), None);
+        assert_eq!(score("W LAN", "wlan0"), score("wlan", "wlan0"));
+        assert_eq!(rank("wg", vec![("wlan0".to_string(), 1), ("wg0".to_string(), 2)]).len(), 1);
+    }
+}
@@ -0,0 +1,134 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::os::unix::net::UnixStream;
+use std::process::Command;
+use std::time::Duration;
+
+#[derive(Clone, PartialEq)]
+pub enum Server {
+    WireGuard(String),
+    OpenVpn(String),
+}
+
+#[derive(Clone)]
+pub struct Peer {
+    pub server: Server,
+    pub id: String,
+    pub name: String,
+    pub endpoint: String,
+    pub address: String,
+    pub handshake: Option<i64>,
+    pub rx: u64,
+    pub tx: u64,
+}
+
+impl Server {
+    pub fn label(&self) -> String {
+        match self {
+            Server::WireGuard(iface) => iface.clone(),
+            Server::OpenVpn(_) => "openvpn".to_string(),
+        }
+    }
+}
+
+fn wireguard(servers: &[String]) -> Result<Vec<Peer>, String> {
+    let out = Command::new("wg").args(["show", "all", "dump"]).output().map_err(|e| format!("wg: {}", e))?;
+    if !out.status.success() { return Err(format!("wg: {}", String::from_utf8_lossy(&out.stderr).trim())); }
+    let text = String::from_utf8_lossy(&out.stdout);
+    let rows: Vec<Vec<&str>> = text.lines().map(|l| l.split('\t').collect()).collect();
+    let is_server = |iface: &str| {
+        if !servers.is_empty() { return servers.iter().any(|s| s == iface); }
+        let peers: Vec<&Vec<&str>> = rows.iter().filter(|f| f.len() == 9 && f[0] == iface).collect();
+        peers.iter().all(|f| !f[4].split(',').any(|a| matches!(a.trim(), "0.0.0.0/0" | "::/0"))) && peers.iter().any(|f| f[3] == "(none)")
+    };
+    let servers: Vec<&str> = rows.iter().filter(|f| f.len() == 5 && f[3] != "0" && is_server(f[0])).map(|f| f[0]).collect();
+    Ok(rows.iter().filter(|f| f.len() == 9 && servers.contains(&f[0])).map(|f| Peer {
+        server: Server::WireGuard(f[0].to_string()),
+        id: f[1].to_string(),
+        name: f[1].chars().take(8).collect::<String>() + "…",
+        endpoint: if f[3] == "(none)" { "-".to_string() } else { f[3].to_string() },
+        address: f[4].replace(',', ", "),
+        handshake: f[5].parse().ok().filter(|t| *t > 0),
+        rx: f[6].parse().unwrap_or(0),
+        tx: f[7].parse().unwrap_or(0),
+    }).collect())
+}
+
+fn management(address: &str, command: &str) -> Result<Vec<String>, String> {
+    let timeout = Duration::from_secs(2);
+    let (mut writer, reader): (Box<dyn Write>, Box<dyn std::io::Read>) = if address.starts_with('/') {
+        let stream = UnixStream::connect(address).map_err(|e| format!("{}: {}", address, e))?;
+        let _ = stream.set_read_timeout(Some(timeout));
+        (Box::new(stream.try_clone().map_err(|e| e.to_string())?), Box::new(stream))
+    } else {
+        let addr = address.to_socket_addrs().map_err(|e| format!("{}: {}", address, e))?.next().ok_or_else(|| format!("{}: no address", address))?;
+        let stream = TcpStream::connect_timeout(&addr, timeout).map_err(|e| format!("{}: {}", address, e))?;
+        let _ = stream.set_read_timeout(Some(timeout));
+        (Box::new(stream.try_clone().map_err(|e| e.to_string())?), Box::new(stream))
+    };
+    writer.write_all(format!("{}\nquit\n", command).as_bytes()).map_err(|e| e.to_string())?;
+    let mut lines = Vec::new();
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else { break };
+        let line = line.trim_end().to_string();
+        if line.starts_with(">INFO") || line.starts_with("ENTER PASSWORD") { continue; }
+        let done = line == "END" || line.starts_with("SUCCESS:") || line.starts_with("ERROR:");
+        lines.push(line);
+        if done { break; }
+    }
+    match lines.last() {
+        Some(l) if l.starts_with("ERROR:") => Err(l.trim_start_matches("ERROR:").trim().to_string()),
+        _ => Ok(lines),
+    }
+}
+
+fn openvpn(address: &str) -> Result<Vec<Peer>, String> {
+    Ok(management(address, "status 3")?.iter().filter_map(|line| {
+        let f: Vec<&str> = line.split('\t').collect();
+        if f.first() != Some(&"CLIENT_LIST") || f.len() < 11 { return None; }
+        Some(Peer {
+            server: Server::OpenVpn(address.to_string()),
+            id: f[10].to_string(),
+            name: f[1].to_string(),
+            endpoint: f[2].to_string(),
+            address: [f[3], f[4]].iter().filter(|a| !a.is_empty()).copied().collect::<Vec<_>>().join(", "),
+            handshake: f[8].parse().ok(),
+            rx: f[5].parse().unwrap_or(0),
+            tx: f[6].parse().unwrap_or(0),
+        })
+    }).collect())
+}
+
+pub fn list(wireguard_servers: &[String], openvpn_management: Option<&str>) -> (Vec<Peer>, Vec<String>) {
+    let mut peers = Vec::new();
+    let mut errors = Vec::new();
+    match wireguard(wireguard_servers) {
+        Ok(found) => peers.extend(found),
+        Err(e) => errors.push(e),
+    }
+    if let Some(address) = openvpn_management {
+        match openvpn(address) {
+            Ok(found) => peers.extend(found),
+            Err(e) => errors.push(format!("OpenVPN management {}", e)),
+        }
+    }
+    (peers, errors)
+}
+
+pub fn disconnect(peer: &Peer) -> Result<String, String> {
+    match &peer.server {
+        Server::OpenVpn(address) => management(address, &format!("client-kill {}", peer.id)).map(|_| format!("{} disconnected", peer.name)),
+        Server::WireGuard(_) => Err("WireGuard has no sessions to close: revoke the peer with - instead".to_string()),
+    }
+}
+
+pub fn revoke(peer: &Peer) -> Result<String, String> {
+    match &peer.server {
+        Server::WireGuard(iface) => {
+            let out = Command::new("wg").args(["set", iface, "peer", &peer.id, "remove"]).output().map_err(|e| format!("wg: {}", e))?;
+            if !out.status.success() { return Err(String::from_utf8_lossy(&out.stderr).trim().to_string()); }
+            Ok(format!("peer {} removed from {} (until the interface is reloaded from its configuration file)", peer.name, iface))
+        }
+        Server::OpenVpn(_) => Err("OpenVPN certificates are revoked with the CA (easy-rsa revoke + CRL); X disconnects the client".to_string()),
+    }
+}
@@ -0,0 +1,27 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CERT_EXTENSIONS: [&str; 8] = ["pem", "crt", "cer", "der", "key", "p12", "pfx", "ovpn"];
+
+pub struct Entry {
+    pub name: String,
+    pub path: PathBuf,
+    pub dir: bool,
+}
+
+impl Entry {
+    pub fn is_cert(&self) -> bool {
+        self.path.extension().and_then(|e| e.to_str()).is_some_and(|e| CERT_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+    }
+}
+
+pub fn list(dir: &Path) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = fs::read_dir(dir).map(|rd| rd.filter_map(Result::ok).filter_map(|e| {
+        let name = e.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') { return None; }
+        Some(Entry { dir: e.path().is_dir(), path: e.path(), name })
+    }).collect()).unwrap_or_default();
+    entries.sort_by(|a, b| b.dir.cmp(&a.dir).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+    if let Some(parent) = dir.parent() { entries.insert(0, Entry { name: "..".to_string(), path: parent.to_path_buf(), dir: true }); }
+    entries
+}
@@ -0,0 +1,27 @@
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+pub fn on_battery() -> Option<bool> {
+    let mut battery = false;
+    for entry in fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let read = |name: &str| fs::read_to_string(entry.path().join(name)).map(|s| s.trim().to_string()).unwrap_or_default();
+        match read("type").as_str() {
+            "Mains" | "USB" if read("online") == "1" => return Some(false),
+            "Battery" if read("scope") != "Device" && read("present") != "0" => battery = true,
+            _ => {}
+        }
+    }
+    battery.then_some(true)
+}
+
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::Relaxed);
+}
+
+pub fn wait_while_paused() {
+    while PAUSED.load(Ordering::Relaxed) { thread::sleep(Duration::from_secs(5)); }
+}
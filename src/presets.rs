@@ -0,0 +1,66 @@
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+#[derive(Clone)]
+pub struct Preset {
+    pub name: String,
+    pub wifi: Option<String>,
+    pub vpns: Vec<String>,
+    pub proxy: Option<String>,
+}
+
+fn nmcli(args: &[&str]) -> Result<(), String> {
+    let out = Command::new("nmcli").args(args).output().map_err(|e| format!("nmcli: {}", e))?;
+    if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+fn step(progress: &Sender<String>, label: String, result: Result<(), String>) {
+    let _ = progress.send(match result {
+        Ok(()) => format!("✓ {}", label),
+        Err(e) => format!("✗ {}: {}", label, e),
+    });
+}
+
+fn active_vpns() -> Vec<String> {
+    Command::new("nmcli").args(["-t", "-f", "NAME,TYPE", "con", "show", "--active"]).output().map(|out| {
+        String::from_utf8_lossy(&out.stdout).lines()
+            .filter(|l| l.ends_with(":vpn") || l.ends_with(":wireguard"))
+            .filter_map(|l| l.rsplit_once(':').map(|(n, _)| n.replace("\\:", ":")))
+            .collect()
+    }).unwrap_or_default()
+}
+
+fn set_proxy(mode: &str) -> Result<(), String> {
+    let mode = match mode { "on" | "manual" => "manual", "auto" => "auto", _ => "none" };
+    let out = Command::new("gsettings").args(["set", "org.gnome.system.proxy", "mode", mode]).output().map_err(|e| format!("gsettings: {}", e))?;
+    if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+pub fn activate(preset: Preset) -> Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        if let Some(ssid) = &preset.wifi {
+            let _ = tx.send(format!("→ Joining WiFi '{}'...", ssid));
+            let result = nmcli(&["--wait", "30", "con", "up", "id", ssid]).or_else(|_| nmcli(&["--wait", "30", "dev", "wifi", "connect", ssid]));
+            step(&tx, format!("WiFi '{}'", ssid), result);
+        }
+        let wanted: Vec<&String> = preset.vpns.iter().filter(|v| v.as_str() != "off").collect();
+        if !preset.vpns.is_empty() {
+            for vpn in active_vpns().iter().filter(|v| !wanted.contains(v)) {
+                let _ = tx.send(format!("→ Stopping VPN '{}'...", vpn));
+                step(&tx, format!("VPN '{}' down", vpn), nmcli(&["con", "down", "id", vpn]));
+            }
+        }
+        let active = active_vpns();
+        for vpn in wanted.iter().filter(|v| !active.contains(v)) {
+            let _ = tx.send(format!("→ Starting VPN '{}'...", vpn));
+            step(&tx, format!("VPN '{}' up", vpn), nmcli(&["--wait", "60", "con", "up", "id", vpn]));
+        }
+        if let Some(mode) = &preset.proxy {
+            step(&tx, format!("Proxy {}", mode), set_proxy(mode));
+        }
+        let _ = tx.send(format!("Preset '{}' done.", preset.name));
+    });
+    rx
+}
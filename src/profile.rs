@@ -0,0 +1,235 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const READ_ONLY: [&str; 4] = ["connection.uuid", "connection.timestamp", "connection.read-only", "connection.type"];
+
+pub struct Property {
+    pub key: String,
+    pub original: String,
+    pub value: String,
+}
+
+impl Property {
+    pub fn setting(&self) -> &str {
+        if self.key.starts_with("vpn.data") { "vpn.data" } else { self.key.split('.').next().unwrap_or("") }
+    }
+
+    pub fn changed(&self) -> bool {
+        self.value != self.original
+    }
+}
+
+pub struct ProfileEditor {
+    pub connection: String,
+    pub properties: Vec<Property>,
+}
+
+fn unescape(value: &str) -> String {
+    value.replace("\\:", ":").replace("\\\\", "\\")
+}
+
+fn is_bool(value: &str) -> bool {
+    matches!(value, "yes" | "no" | "true" | "false")
+}
+
+pub fn check_cidrs(value: &str, v6: bool) -> Result<(), String> {
+    for item in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let (ip, prefix) = item.split_once('/').unwrap_or((item, if v6 { "128" } else { "32" }));
+        let max = if v6 { 128 } else { 32 };
+        let ip_ok = if v6 { ip.parse::<Ipv6Addr>().is_ok() } else { ip.parse::<Ipv4Addr>().is_ok() };
+        if !ip_ok || prefix.parse::<u8>().map(|p| p > max).unwrap_or(true) { return Err(format!("'{}' is not a valid address/prefix", item)); }
+    }
+    Ok(())
+}
+
+fn check_ips(value: &str, v6: bool) -> Result<(), String> {
+    for item in value.split([',', ' ']).map(str::trim).filter(|s| !s.is_empty()) {
+        let ok = if v6 { item.parse::<Ipv6Addr>().is_ok() } else { item.parse::<Ipv4Addr>().is_ok() };
+        if !ok { return Err(format!("'{}' is not a valid IP{} address", item, if v6 { "v6" } else { "v4" })); }
+    }
+    Ok(())
+}
+
+pub fn validate(property: &Property, value: &str) -> Result<(), String> {
+    let key = property.key.as_str();
+    if READ_ONLY.contains(&key) { return Err(format!("{} is read-only", key)); }
+    if value.is_empty() { return Ok(()); }
+    let v6 = key.starts_with("ipv6.");
+    match key {
+        "ipv4.method" if !["auto", "manual", "link-local", "shared", "disabled"].contains(&value) => Err("expected auto|manual|link-local|shared|disabled".to_string()),
+        "ipv6.method" if !["auto", "dhcp", "manual", "link-local", "shared", "ignore", "disabled"].contains(&value) => Err("expected auto|dhcp|manual|link-local|shared|ignore|disabled".to_string()),
+        "ipv4.addresses" | "ipv6.addresses" => check_cidrs(value, v6),
+        "ipv4.gateway" | "ipv6.gateway" | "ipv4.dns" | "ipv6.dns" => check_ips(value, v6),
+        _ if is_bool(&property.original) && !is_bool(value) => Err("expected yes or no".to_string()),
+        _ if property.original.parse::<i64>().is_ok() && value.parse::<i64>().is_err() => Err("expected an integer".to_string()),
+        _ => Ok(()),
+    }
+}
+
+impl ProfileEditor {
+    pub fn load(connection: &str) -> Result<ProfileEditor, String> {
+        let out = Command::new("nmcli").args(["-t", "con", "show", "id", connection]).output().map_err(|e| format!("nmcli: {}", e))?;
+        if !out.status.success() { return Err(String::from_utf8_lossy(&out.stderr).trim().to_string()); }
+        let s = String::from_utf8_lossy(&out.stdout);
+        let mut properties: Vec<Property> = s.lines()
+            .filter_map(|l| l.split_once(':'))
+            .filter(|(k, _)| k.starts_with(|c: char| c.is_ascii_lowercase()) && !READ_ONLY.contains(k))
+            .map(|(k, v)| { let v = unescape(v); Property { key: k.to_string(), original: v.clone(), value: v } })
+            .collect();
+        properties.sort_by(|a, b| a.setting().cmp(b.setting()).then(a.key.cmp(&b.key)));
+        Ok(ProfileEditor { connection: connection.to_string(), properties })
+    }
+
+    pub fn diff(&self) -> Vec<String> {
+        self.properties.iter().filter(|p| p.changed())
+            .flat_map(|p| [format!("- {}: {}", p.key, p.original), format!("+ {}: {}", p.key, p.value)])
+            .collect()
+    }
+
+    pub fn apply(&self) -> Result<usize, String> {
+        let changes: Vec<&Property> = self.properties.iter().filter(|p| p.changed()).collect();
+        if changes.is_empty() { return Ok(0); }
+        let mut cmd = Command::new("nmcli");
+        cmd.args(["con", "modify", "id", &self.connection]);
+        for p in &changes { cmd.arg(&p.key).arg(&p.value); }
+        let out = cmd.output().map_err(|e| format!("nmcli: {}", e))?;
+        if out.status.success() { Ok(changes.len()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+    }
+    pub fn is_active(&self) -> bool {
+        vpn_property(&self.connection, "GENERAL.STATE") == "activated"
+    }
+}
+
+pub fn reactivate(connection: &str) -> Result<(), String> {
+    let out = Command::new("nmcli").args(["--wait", "30", "con", "up", "id", connection]).output().map_err(|e| format!("nmcli: {}", e))?;
+    if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+pub fn clone_profile(connection: &str, new_name: &str) -> Result<(), String> {
+    let out = Command::new("nmcli").args(["con", "clone", "id", connection, new_name]).output().map_err(|e| format!("nmcli: {}", e))?;
+    if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+pub const CERT_STEPS: [&str; 3] = ["CA certificate", "client certificate", "private key"];
+
+fn vpn_property(connection: &str, field: &str) -> String {
+    Command::new("nmcli").args(["-g", field, "con", "show", "id", connection]).output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string()).unwrap_or_default()
+}
+
+pub fn cert_keys(connection: &str) -> Result<[&'static str; 3], String> {
+    match vpn_property(connection, "vpn.service-type").rsplit('.').next().unwrap_or("") {
+        "openvpn" => Ok(["ca", "cert", "key"]),
+        "strongswan" => Ok(["certificate", "usercert", "userkey"]),
+        "" => Err(format!("'{}' is not a VPN profile", connection)),
+        other => Err(format!("certificate files are only supported for OpenVPN and strongSwan profiles, not {}", other)),
+    }
+}
+
+pub fn set_cert_files(connection: &str, files: &[Option<PathBuf>]) -> Result<String, String> {
+    let keys = cert_keys(connection)?;
+    let mut data: Vec<String> = keys.iter().zip(files).filter_map(|(key, file)| file.as_ref().map(|f| format!("{} = {}", key, f.display()))).collect();
+    if data.is_empty() { return Err("no file selected".to_string()); }
+    let current = vpn_property(connection, "vpn.data");
+    if keys[0] == "ca" {
+        data.push(format!("connection-type = {}", if current.contains("connection-type = password") { "password-tls" } else { "tls" }));
+    } else {
+        data.push("method = key".to_string());
+    }
+    let out = Command::new("nmcli").args(["con", "modify", "id", connection, "+vpn.data", &data.join(", ")]).output().map_err(|e| format!("nmcli: {}", e))?;
+    if out.status.success() { Ok(format!("{}: {}", connection, data.join(", "))) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+pub fn wifi_password(connection: &str) -> Result<String, String> {
+    let out = Command::new("nmcli").args(["-s", "-g", "802-11-wireless-security.key-mgmt,802-11-wireless-security.psk,802-11-wireless-security.wep-key0,802-1x.password", "con", "show", "id", connection])
+        .output().map_err(|e| format!("nmcli: {}", e))?;
+    if !out.status.success() { return Err(String::from_utf8_lossy(&out.stderr).trim().to_string()); }
+    let s = String::from_utf8_lossy(&out.stdout);
+    let fields: Vec<String> = s.lines().map(unescape).collect();
+    match fields.first().map(String::as_str) {
+        None | Some("") => return Err(format!("'{}' is an open network, it has no password", connection)),
+        Some("owe") => return Err(format!("'{}' uses Enhanced Open (OWE), it has no password", connection)),
+        _ => {}
+    }
+    fields.into_iter().skip(1).find(|f| !f.is_empty())
+        .ok_or_else(|| "NetworkManager returned no secret: it is kept by a desktop secret agent, or reading it needs more privileges (try as root)".to_string())
+}
+
+const SECRET_KEYS: [&str; 6] = ["psk", "password", "private-key", "preshared-key", "wep-key0", "pin"];
+
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => home::home_dir().map(|h| h.join(rest)).unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+fn strip_secrets(keyfile: &str) -> String {
+    let mut out = Vec::new();
+    let mut in_secrets = false;
+    for line in keyfile.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_secrets = trimmed.ends_with("-secrets]");
+            if in_secrets { continue; }
+        }
+        if in_secrets { continue; }
+        let key = trimmed.split('=').next().unwrap_or("").trim();
+        if SECRET_KEYS.contains(&key) || key.ends_with("-password") || key.ends_with("-psk") { continue; }
+        out.push(line);
+    }
+    out.join("\n") + "\n"
+}
+
+pub fn export_keyfile(connection: &str, path: &Path, with_secrets: bool) -> Result<PathBuf, String> {
+    let out = Command::new("nmcli").args(["-t", "-f", "NAME,FILENAME", "con", "show"]).output().map_err(|e| format!("nmcli: {}", e))?;
+    let s = String::from_utf8_lossy(&out.stdout);
+    let source = s.lines().filter_map(|l| l.rsplit_once(':')).find(|(name, _)| unescape(name) == connection).map(|(_, file)| unescape(file))
+        .ok_or_else(|| format!("no stored keyfile found for '{}'", connection))?;
+    let content = fs::read_to_string(&source).map_err(|e| format!("cannot read {}: {} (root access is needed for system connections)", source, e))?;
+    let content = if with_secrets { content } else { strip_secrets(&content) };
+    let target = if path.extension().is_some_and(|e| e == "nmconnection") { path.to_path_buf() } else { path.join(format!("{}.nmconnection", connection.replace('/', "_"))) };
+    if let Some(parent) = target.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    let mut file = OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(&target).map_err(|e| format!("cannot write {}: {}", target.display(), e))?;
+    file.set_permissions(fs::Permissions::from_mode(0o600)).map_err(|e| format!("cannot restrict {} to mode 0600: {}", target.display(), e))?;
+    file.write_all(content.as_bytes()).map_err(|e| format!("cannot write {}: {}", target.display(), e))?;
+    Ok(target)
+}
+
+fn import_file(file: &Path) -> Result<String, String> {
+    let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let path = file.to_string_lossy().to_string();
+    let out = match ext {
+        "ovpn" => Command::new("nmcli").args(["con", "import", "type", "openvpn", "file", &path]).output(),
+        "conf" => Command::new("nmcli").args(["con", "import", "type", "wireguard", "file", &path]).output(),
+        "nmconnection" => {
+            let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let target = Path::new("/etc/NetworkManager/system-connections").join(name);
+            fs::copy(file, &target).map_err(|e| format!("cannot copy to {}: {}", target.display(), e))?;
+            let _ = fs::set_permissions(&target, fs::Permissions::from_mode(0o600));
+            Command::new("nmcli").args(["con", "load", &target.to_string_lossy()]).output()
+        }
+        _ => return Err("unsupported file type".to_string()),
+    }.map_err(|e| format!("nmcli: {}", e))?;
+    if out.status.success() { Ok(String::from_utf8_lossy(&out.stdout).trim().to_string()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+pub fn import_directory(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else { return vec![format!("Cannot read directory {}", dir.display())] };
+    let mut files: Vec<PathBuf> = entries.flatten().map(|e| e.path())
+        .filter(|p| p.is_file() && matches!(p.extension().and_then(|e| e.to_str()), Some("nmconnection" | "ovpn" | "conf")))
+        .collect();
+    files.sort();
+    if files.is_empty() { return vec![format!("No .nmconnection, .ovpn or .conf file in {}", dir.display())]; }
+    files.iter().map(|f| {
+        let name = f.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        match import_file(f) {
+            Ok(msg) => format!("[OK]   {} {}", name, msg),
+            Err(e) => format!("[FAIL] {}: {}", name, e),
+        }
+    }).collect()
+}
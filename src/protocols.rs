@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+pub const TRANSPORTS: [&str; 5] = ["TCP", "UDP", "QUIC", "ICMP", "Other"];
+
+const TCP_SERVICES: [(u16, &str); 17] = [(443, "HTTPS"), (80, "HTTP"), (8080, "HTTP"), (22, "SSH"), (53, "DNS"), (853, "DNS-over-TLS"), (445, "SMB"), (139, "SMB"), (25, "Mail"), (465, "Mail"), (587, "Mail"), (993, "Mail"), (143, "Mail"), (3389, "RDP"), (5900, "VNC"), (1194, "OpenVPN"), (9418, "Git")];
+const UDP_SERVICES: [(u16, &str); 13] = [(53, "DNS"), (123, "NTP"), (67, "DHCP"), (68, "DHCP"), (546, "DHCP"), (547, "DHCP"), (5353, "mDNS"), (1900, "SSDP"), (51820, "WireGuard"), (1194, "OpenVPN"), (500, "IPsec"), (4500, "IPsec"), (3478, "STUN")];
+
+#[derive(Clone)]
+pub struct Class {
+    pub transport: usize,
+    pub service: &'static str,
+    pub down: f64,
+    pub up: f64,
+}
+
+type Counts = HashMap<(usize, &'static str), (u64, u64)>;
+
+pub struct Collector {
+    pub iface: String,
+    counts: Arc<Mutex<Counts>>,
+    error: Arc<Mutex<Option<String>>>,
+    stop: Arc<AtomicBool>,
+    since: Instant,
+}
+
+fn port(packet: &[u8], offset: usize) -> u16 {
+    packet.get(offset..offset + 2).map_or(0, |b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn transport(ethertype: u16, packet: &[u8]) -> Option<(u8, Option<usize>)> {
+    match ethertype {
+        0x0800 if packet.len() >= 20 => {
+            let fragment = u16::from_be_bytes([packet[6], packet[7]]) & 0x1fff;
+            Some((packet[9], (fragment == 0).then_some(((packet[0] & 0x0f) as usize) * 4)))
+        }
+        0x86dd if packet.len() >= 40 => {
+            let (mut next, mut at) = (packet[6], 40);
+            loop {
+                let (Some(&following), Some(&len)) = (packet.get(at), packet.get(at + 1)) else { return Some((next, None)) };
+                match next {
+                    0 | 43 | 60 => at += (len as usize + 1) * 8,
+                    51 => at += (len as usize + 2) * 4,
+                    44 if port(packet, at + 2) >> 3 != 0 => return Some((following, None)),
+                    44 => at += 8,
+                    _ => return Some((next, Some(at))),
+                }
+                next = following;
+            }
+        }
+        _ => None,
+    }
+}
+
+fn classify(ethertype: u16, packet: &[u8]) -> (usize, &'static str) {
+    let (protocol, l4) = match transport(ethertype, packet) {
+        Some(found) => found,
+        None if ethertype == 0x0806 => return (4, "ARP"),
+        None => return (4, "Other"),
+    };
+    let (source, destination) = l4.map_or((0, 0), |l4| (port(packet, l4), port(packet, l4 + 2)));
+    let service = |table: &[(u16, &'static str)]| [source.min(destination), source.max(destination)].into_iter().find_map(|p| table.iter().find(|(n, _)| *n == p).map(|(_, s)| *s));
+    match protocol {
+        6 => (0, service(&TCP_SERVICES).unwrap_or("TCP other")),
+        17 if source == 443 || destination == 443 => (2, "QUIC"),
+        17 => (1, service(&UDP_SERVICES).unwrap_or("UDP other")),
+        1 | 58 => (3, "ICMP"),
+        50 => (4, "IPsec"),
+        47 => (4, "GRE"),
+        _ => (4, "Other"),
+    }
+}
+
+fn open(iface: &str) -> Result<i32, String> {
+    let name = CString::new(iface).map_err(|e| e.to_string())?;
+    let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+    if index == 0 { return Err(format!("no interface named {}", iface)); }
+    let all = (libc::ETH_P_ALL as u16).to_be();
+    let fd = unsafe { libc::socket(libc::AF_PACKET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, all as i32) };
+    if fd < 0 {
+        let e = std::io::Error::last_os_error();
+        return Err(if e.raw_os_error() == Some(libc::EPERM) { "capturing needs root or CAP_NET_RAW".to_string() } else { format!("packet socket: {}", e) });
+    }
+    let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+    addr.sll_family = libc::AF_PACKET as u16;
+    addr.sll_protocol = all;
+    addr.sll_ifindex = index as i32;
+    let timeout = libc::timeval { tv_sec: 1, tv_usec: 0 };
+    let bound = unsafe {
+        libc::setsockopt(fd, libc::SOL_SOCKET, libc::SO_RCVTIMEO, &timeout as *const _ as *const libc::c_void, std::mem::size_of::<libc::timeval>() as u32);
+        libc::bind(fd, &addr as *const _ as *const libc::sockaddr, std::mem::size_of::<libc::sockaddr_ll>() as u32)
+    };
+    if bound < 0 {
+        let e = std::io::Error::last_os_error();
+        unsafe { libc::close(fd) };
+        return Err(format!("binding to {}: {}", iface, e));
+    }
+    Ok(fd)
+}
+
+fn capture(fd: i32, counts: &Mutex<Counts>, stop: &AtomicBool) {
+    let mut buffer = [0u8; 96];
+    while !stop.load(Ordering::Relaxed) {
+        let mut addr: libc::sockaddr_ll = unsafe { std::mem::zeroed() };
+        let mut len = std::mem::size_of::<libc::sockaddr_ll>() as u32;
+        let size = unsafe { libc::recvfrom(fd, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len(), libc::MSG_TRUNC, &mut addr as *mut _ as *mut libc::sockaddr, &mut len) };
+        if size <= 0 { continue; }
+        let key = classify(u16::from_be(addr.sll_protocol), &buffer[..(size as usize).min(buffer.len())]);
+        let Ok(mut counts) = counts.lock() else { return };
+        let entry = counts.entry(key).or_insert((0, 0));
+        if addr.sll_pkttype == libc::PACKET_OUTGOING { entry.1 += size as u64; } else { entry.0 += size as u64; }
+    }
+}
+
+impl Collector {
+    pub fn start(iface: &str) -> Collector {
+        let collector = Collector { iface: iface.to_string(), counts: Arc::default(), error: Arc::default(), stop: Arc::default(), since: Instant::now() };
+        let (counts, error, stop, iface) = (collector.counts.clone(), collector.error.clone(), collector.stop.clone(), iface.to_string());
+        thread::spawn(move || match open(&iface) {
+            Ok(fd) => {
+                capture(fd, &counts, &stop);
+                unsafe { libc::close(fd) };
+            }
+            Err(e) => { if let Ok(mut error) = error.lock() { *error = Some(e); } }
+        });
+        collector
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().ok().and_then(|e| e.clone())
+    }
+
+    pub fn sample(&mut self) -> Vec<Class> {
+        let secs = self.since.elapsed().as_secs_f64().max(0.001);
+        self.since = Instant::now();
+        let counts = self.counts.lock().map(|mut c| std::mem::take(&mut *c)).unwrap_or_default();
+        let rate = |bytes: u64| bytes as f64 * 8.0 / (1024.0 * 1024.0) / secs;
+        let mut classes: Vec<Class> = counts.into_iter().map(|((transport, service), (rx, tx))| Class { transport, service, down: rate(rx), up: rate(tx) }).collect();
+        classes.sort_by(|a, b| (b.down + b.up).total_cmp(&(a.down + a.up)));
+        classes
+    }
+}
+
+impl Drop for Collector {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
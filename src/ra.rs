@@ -0,0 +1,205 @@
+use std::ffi::CStr;
+use std::io;
+use std::net::Ipv6Addr;
+use std::os::fd::{FromRawFd, OwnedFd, AsRawFd};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+const ND_ROUTER_ADVERT: u8 = 134;
+const OPT_SOURCE_LL: u8 = 1;
+const OPT_PREFIX_INFO: u8 = 3;
+const OPT_MTU: u8 = 5;
+const OPT_RDNSS: u8 = 25;
+
+pub struct Prefix {
+    pub prefix: Ipv6Addr,
+    pub len: u8,
+    pub on_link: bool,
+    pub autonomous: bool,
+    pub valid: u32,
+    pub preferred: u32,
+}
+
+pub struct Advert {
+    pub router: Ipv6Addr,
+    pub iface: String,
+    pub mac: String,
+    pub hop_limit: u8,
+    pub managed: bool,
+    pub other: bool,
+    pub preference: &'static str,
+    pub lifetime: u16,
+    pub mtu: Option<u32>,
+    pub prefixes: Vec<Prefix>,
+    pub dns: Vec<Ipv6Addr>,
+}
+
+fn be32(b: &[u8]) -> u32 {
+    u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn addr(b: &[u8]) -> Ipv6Addr {
+    let mut octets = [0u8; 16];
+    octets.copy_from_slice(&b[..16]);
+    Ipv6Addr::from(octets)
+}
+
+fn iface_name(index: u32) -> String {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    let ptr = unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) };
+    if ptr.is_null() { format!("if{}", index) } else { unsafe { CStr::from_ptr(ptr) }.to_string_lossy().to_string() }
+}
+
+fn parse(packet: &[u8], router: Ipv6Addr, hops: u8, iface: String) -> Option<Advert> {
+    if hops != 255 || router.segments()[0] & 0xffc0 != 0xfe80 { return None; }
+    if packet.len() < 16 || packet[0] != ND_ROUTER_ADVERT || packet[1] != 0 { return None; }
+    let flags = packet[5];
+    let mut advert = Advert {
+        router,
+        iface,
+        mac: String::new(),
+        hop_limit: packet[4],
+        managed: flags & 0x80 != 0,
+        other: flags & 0x40 != 0,
+        preference: match (flags >> 3) & 0x3 { 1 => "high", 3 => "low", 0 => "medium", _ => "reserved" },
+        lifetime: u16::from_be_bytes([packet[6], packet[7]]),
+        mtu: None,
+        prefixes: Vec::new(),
+        dns: Vec::new(),
+    };
+    let mut opts = &packet[16..];
+    while opts.len() >= 8 {
+        let len = opts[1] as usize * 8;
+        if len == 0 || len > opts.len() { break; }
+        let opt = &opts[..len];
+        match opt[0] {
+            OPT_SOURCE_LL => advert.mac = opt[2..8].iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":"),
+            OPT_MTU => advert.mtu = Some(be32(&opt[4..8])),
+            OPT_PREFIX_INFO if len >= 32 => advert.prefixes.push(Prefix {
+                len: opt[2],
+                on_link: opt[3] & 0x80 != 0,
+                autonomous: opt[3] & 0x40 != 0,
+                valid: be32(&opt[4..8]),
+                preferred: be32(&opt[8..12]),
+                prefix: addr(&opt[16..32]),
+            }),
+            OPT_RDNSS => advert.dns.extend(opt[8..].chunks_exact(16).map(addr)),
+            _ => {}
+        }
+        opts = &opts[len..];
+    }
+    Some(advert)
+}
+
+fn hop_limit(msg: &libc::msghdr) -> Option<u8> {
+    let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(msg) };
+    while !cmsg.is_null() {
+        let header = unsafe { &*cmsg };
+        if header.cmsg_level == libc::IPPROTO_IPV6 && header.cmsg_type == libc::IPV6_HOPLIMIT {
+            let hops = unsafe { std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const libc::c_int) };
+            return u8::try_from(hops).ok();
+        }
+        cmsg = unsafe { libc::CMSG_NXTHDR(msg, cmsg) };
+    }
+    None
+}
+
+fn listen(tx: &mpsc::Sender<Result<Advert, String>>) -> io::Result<()> {
+    let raw = unsafe { libc::socket(libc::AF_INET6, libc::SOCK_RAW, libc::IPPROTO_ICMPV6) };
+    if raw < 0 { return Err(io::Error::last_os_error()); }
+    let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+    let on: libc::c_int = 1;
+    let set = unsafe {
+        libc::setsockopt(fd.as_raw_fd(), libc::IPPROTO_IPV6, libc::IPV6_RECVHOPLIMIT, &on as *const _ as *const libc::c_void, std::mem::size_of::<libc::c_int>() as libc::socklen_t)
+    };
+    if set < 0 { return Err(io::Error::last_os_error()); }
+    let mut buf = [0u8; 1500];
+    let mut control = [0u64; 8];
+    loop {
+        let mut from: libc::sockaddr_in6 = unsafe { std::mem::zeroed() };
+        let mut iov = libc::iovec { iov_base: buf.as_mut_ptr() as *mut libc::c_void, iov_len: buf.len() };
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        msg.msg_name = &mut from as *mut _ as *mut libc::c_void;
+        msg.msg_namelen = std::mem::size_of::<libc::sockaddr_in6>() as libc::socklen_t;
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = std::mem::size_of_val(&control) as _;
+        let n = unsafe { libc::recvmsg(fd.as_raw_fd(), &mut msg, 0) };
+        if n < 0 { return Err(io::Error::last_os_error()); }
+        let Some(hops) = hop_limit(&msg) else { continue };
+        let router = Ipv6Addr::from(from.sin6_addr.s6_addr);
+        if let Some(advert) = parse(&buf[..n as usize], router, hops, iface_name(from.sin6_scope_id)) {
+            if tx.send(Ok(advert)).is_err() { return Ok(()); }
+        }
+    }
+}
+
+pub fn spawn_listener() -> Receiver<Result<Advert, String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        if let Err(e) = listen(&tx) {
+            let hint = if e.kind() == io::ErrorKind::PermissionDenied { " (needs CAP_NET_RAW)" } else { "" };
+            let _ = tx.send(Err(format!("{}{}", e, hint)));
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAPTURED: &str = "8600 1f3a 4048 0708 0000 0000 0000 0000 \
+        0101 3c52 821a 2b3c \
+        0501 0000 0000 05dc \
+        0304 40c0 0027 8d00 0009 3a80 0000 0000 2001 0db8 0001 0002 0000 0000 0000 0000 \
+        1905 0000 0000 0e10 2001 0db8 0000 0000 0000 0000 0000 0053 2001 0db8 0000 0000 0000 0000 0000 0054";
+
+    fn bytes(hex: &str) -> Vec<u8> {
+        let digits: Vec<u8> = hex.bytes().filter(u8::is_ascii_hexdigit).collect();
+        digits.chunks(2).map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap()).collect()
+    }
+
+    fn router() -> Ipv6Addr {
+        "fe80::3e52:82ff:fe1a:2b3c".parse().unwrap()
+    }
+
+    #[test]
+    fn parses_a_captured_advert() {
+        let advert = parse(&bytes(CAPTURED), router(), 255, "eth0".into()).unwrap();
+        assert_eq!((advert.hop_limit, advert.managed, advert.other, advert.preference, advert.lifetime), (64, false, true, "high", 1800));
+        assert_eq!(advert.mac, "3c:52:82:1a:2b:3c");
+        assert_eq!(advert.mtu, Some(1500));
+        assert_eq!(advert.prefixes.len(), 1);
+        let prefix = &advert.prefixes[0];
+        assert_eq!((prefix.prefix, prefix.len, prefix.on_link, prefix.autonomous), ("2001:db8:1:2::".parse().unwrap(), 64, true, true));
+        assert_eq!((prefix.valid, prefix.preferred), (2_592_000, 604_800));
+        assert_eq!(advert.dns, ["2001:db8::53".parse::<Ipv6Addr>().unwrap(), "2001:db8::54".parse().unwrap()]);
+    }
+
+    #[test]
+    fn stops_at_truncated_options() {
+        let packet = bytes(CAPTURED);
+        let advert = parse(&packet[..packet.len() - 8], router(), 255, "eth0".into()).unwrap();
+        assert_eq!(advert.prefixes.len(), 1);
+        assert!(advert.dns.is_empty());
+        let mut zero = packet.clone();
+        zero[25] = 0;
+        let advert = parse(&zero, router(), 255, "eth0".into()).unwrap();
+        assert_eq!((advert.mac.as_str(), advert.mtu, advert.prefixes.len()), ("3c:52:82:1a:2b:3c", None, 0));
+        assert!(parse(&packet[..15], router(), 255, "eth0".into()).is_none());
+    }
+
+    #[test]
+    fn ignores_adverts_that_are_not_from_an_on_link_router() {
+        let packet = bytes(CAPTURED);
+        assert!(parse(&packet, router(), 254, "eth0".into()).is_none());
+        assert!(parse(&packet, "2001:db8::1".parse().unwrap(), 255, "eth0".into()).is_none());
+        assert!(parse(&packet, "febf::1".parse().unwrap(), 255, "eth0".into()).is_some());
+        assert!(parse(&packet, "fec0::1".parse().unwrap(), 255, "eth0".into()).is_none());
+        let mut solicit = packet.clone();
+        solicit[0] = 133;
+        assert!(parse(&solicit, router(), 255, "eth0".into()).is_none());
+    }
+}
@@ -0,0 +1,49 @@
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::json::Json;
+
+fn temporary_listener(port: u16, lifetime: Duration) -> String {
+    match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(listener) => {
+            let _ = listener.set_nonblocking(true);
+            thread::spawn(move || {
+                let deadline = Instant::now() + lifetime;
+                while Instant::now() < deadline {
+                    if listener.accept().is_err() { thread::sleep(Duration::from_millis(100)); }
+                }
+            });
+            "temporary DashNet listener started for the test".to_string()
+        }
+        Err(e) if e.kind() == ErrorKind::AddrInUse => "a local service is already listening".to_string(),
+        Err(e) => format!("nothing listening locally ({})", e),
+    }
+}
+
+pub fn check(port: u16, service: &str) -> Vec<String> {
+    let mut lines = vec![format!("Port: {}/tcp", port), format!("Local side: {}", temporary_listener(port, Duration::from_secs(20)))];
+    let url = service.replace("{port}", &port.to_string());
+    lines.push(format!("Check service: {}", url));
+    let out = match Command::new("curl").args(["-s", "-m", "15", "-H", "Accept: application/json", &url]).output() {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).to_string(),
+        Ok(out) => { lines.push(format!("Result: check failed (curl exit {})", out.status.code().unwrap_or(-1))); return lines; }
+        Err(e) => { lines.push(format!("Result: check failed ({})", e)); return lines; }
+    };
+    let (public_ip, reachable) = match Json::parse(&out) {
+        Some(json) => (
+            json.get("ip").and_then(Json::as_str).map(str::to_string),
+            json.get("reachable").or_else(|| json.get("open")).and_then(Json::as_bool),
+        ),
+        None => (None, Some(out.to_lowercase().contains("open") && !out.to_lowercase().contains("not open"))),
+    };
+    if let Some(ip) = public_ip { lines.push(format!("Public IP: {}", ip)); }
+    lines.push(match reachable {
+        Some(true) => "Result: OPEN - reachable from the Internet".to_string(),
+        Some(false) => "Result: CLOSED/FILTERED - not reachable from outside (check NAT forwarding and firewall)".to_string(),
+        None => format!("Result: unrecognized answer: {}", out.trim()),
+    });
+    lines
+}
@@ -0,0 +1,18 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config;
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("last_vpn")
+}
+
+pub fn last_vpn() -> Option<String> {
+    fs::read_to_string(store_path()).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+pub fn remember_vpn(name: &str) -> Result<(), String> {
+    let path = store_path();
+    if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    fs::write(&path, format!("{}\n", name)).map_err(|e| format!("{}: {}", path.display(), e))
+}
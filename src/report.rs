@@ -0,0 +1,30 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::{clock, gateway, profile};
+
+fn ping_stats(host: &str) -> String {
+    let Ok(out) = Command::new("ping").args(["-n", "-q", "-c", "5", "-i", "0.2", "-W", "2", host]).output() else { return "ping unavailable".to_string() };
+    let s = String::from_utf8_lossy(&out.stdout);
+    let loss = s.split(", ").find(|p| p.ends_with("packet loss")).and_then(|p| p.split_whitespace().next()).unwrap_or("100%");
+    match s.lines().find(|l| l.contains("min/avg/max")).and_then(|l| l.split(" = ").nth(1)).map(|v| v.split('/').take(3).collect::<Vec<_>>()) {
+        Some(v) if v.len() == 3 => format!("min {} / avg {} / max {} ms, {} loss", v[0], v[1], v[2], loss),
+        _ => format!("no reply ({} loss)", loss),
+    }
+}
+
+pub fn complete(mut lines: Vec<String>, target: &str) -> String {
+    lines.push(String::new());
+    lines.push("## Latency (5 pings)".to_string());
+    let hosts: Vec<(String, String)> = gateway::defaults().into_iter().next().map(|(_, g)| ("gateway".to_string(), g)).into_iter().chain([("internet".to_string(), target.to_string())]).collect();
+    lines.extend(hosts.iter().map(|(label, host)| format!("- {} {}: {}", label, host, ping_stats(host))));
+    lines.join("\n") + "\n"
+}
+
+pub fn save(text: &str) -> Result<PathBuf, String> {
+    let t = clock::local(clock::epoch_now());
+    let path = profile::expand_home(&format!("~/dashnet-report-{}{:02}{:02}-{:02}{:02}{:02}.md", t.year, t.month, t.day, t.hour, t.minute, t.second));
+    fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
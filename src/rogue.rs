@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::config;
+
+const SIGNAL_JUMP: u8 = 30;
+
+pub struct Ap {
+    pub ssid: String,
+    pub bssid: String,
+    pub security: String,
+    pub signal: u8,
+    pub channel: u32,
+}
+
+pub struct Seen {
+    pub ssid: String,
+    pub bssid: String,
+    pub security: String,
+    pub max_signal: u8,
+    pub channel: u32,
+}
+
+pub fn split_escaped(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            ':' => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+pub fn scan() -> Vec<Ap> {
+    let Ok(out) = Command::new("nmcli").args(["-t", "-f", "SSID,BSSID,SECURITY,SIGNAL,CHAN", "dev", "wifi", "list"]).output() else { return Vec::new() };
+    String::from_utf8_lossy(&out.stdout).lines().filter_map(|line| {
+        let f = split_escaped(line);
+        if f.len() < 5 || f[0].is_empty() { return None; }
+        Some(Ap { ssid: f[0].clone(), bssid: f[1].to_lowercase(), security: f[2].clone(), signal: f[3].parse().unwrap_or(0), channel: f[4].parse().unwrap_or(0) })
+    }).collect()
+}
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("bssids")
+}
+
+pub fn load() -> Vec<Seen> {
+    fs::read_to_string(store_path()).unwrap_or_default().lines().filter_map(|line| {
+        let f: Vec<&str> = line.split('\t').collect();
+        if f.len() < 5 { return None; }
+        Some(Seen { ssid: f[0].to_string(), bssid: f[1].to_string(), security: f[2].to_string(), max_signal: f[3].parse().ok()?, channel: f[4].parse().ok()? })
+    }).collect()
+}
+
+pub fn save(history: &[Seen]) -> Result<(), String> {
+    let path = store_path();
+    if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    let text: String = history.iter().map(|s| format!("{}\t{}\t{}\t{}\t{}\n", s.ssid.replace('\t', " "), s.bssid, s.security, s.max_signal, s.channel)).collect();
+    fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn locally_administered(bssid: &str) -> bool {
+    u8::from_str_radix(bssid.get(..2).unwrap_or("00"), 16).is_ok_and(|b| b & 0x02 != 0)
+}
+
+pub fn check(history: &mut Vec<Seen>, aps: &[Ap], saved: &[String]) -> (Vec<(String, String)>, bool) {
+    let mut warnings = Vec::new();
+    let mut changed = false;
+    for ap in aps.iter().filter(|ap| saved.contains(&ap.ssid)) {
+        if let Some(seen) = history.iter_mut().find(|s| s.bssid == ap.bssid && s.ssid == ap.ssid) {
+            if ap.signal > seen.max_signal { seen.max_signal = ap.signal; changed = true; }
+            if ap.security != seen.security { warnings.push((ap.bssid.clone(), format!("{} ({}) now advertises '{}' instead of '{}'", ap.ssid, ap.bssid, ap.security, seen.security))); }
+            continue;
+        }
+        let known: Vec<&Seen> = history.iter().filter(|s| s.ssid == ap.ssid).collect();
+        if !known.is_empty() {
+            let strongest = known.iter().map(|s| s.max_signal).max().unwrap_or(0);
+            let mut reasons = Vec::new();
+            if known.iter().all(|s| s.security != ap.security) { reasons.push(format!("security '{}' (known: '{}')", ap.security, known[0].security)); }
+            if ap.signal >= strongest.saturating_add(SIGNAL_JUMP) { reasons.push(format!("signal {}% far above the usual {}%", ap.signal, strongest)); }
+            if locally_administered(&ap.bssid) && !known.iter().any(|s| locally_administered(&s.bssid)) { reasons.push("randomized (locally administered) BSSID".to_string()); }
+            if !reasons.is_empty() {
+                warnings.push((ap.bssid.clone(), format!("new access point {} for {} on channel {}: {}", ap.bssid, ap.ssid, ap.channel, reasons.join(", "))));
+                continue;
+            }
+        }
+        history.push(Seen { ssid: ap.ssid.clone(), bssid: ap.bssid.clone(), security: ap.security.clone(), max_signal: ap.signal, channel: ap.channel });
+        changed = true;
+    }
+    (warnings, changed)
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{config, netns};
+
+fn hex_addr(field: &str) -> Option<Ipv4Addr> {
+    u32::from_str_radix(field, 16).ok().map(|v| Ipv4Addr::from(v.to_le_bytes()))
+}
+
+fn from_proc() -> Vec<String> {
+    let text = fs::read_to_string("/proc/net/route").unwrap_or_default();
+    text.lines().skip(1).filter_map(|l| {
+        let f: Vec<&str> = l.split_whitespace().collect();
+        if f.len() < 8 { return None; }
+        let (dest, gateway, mask) = (hex_addr(f[1])?, hex_addr(f[2])?, hex_addr(f[7])?);
+        let prefix = u32::from(mask).count_ones();
+        let target = if prefix == 0 { "default".to_string() } else { format!("{}/{}", dest, prefix) };
+        let via = if gateway.is_unspecified() { String::new() } else { format!(" via {}", gateway) };
+        Some(format!("{}{} dev {} metric {}", target, via, f[0], f[6]))
+    }).collect()
+}
+
+pub fn list(ns: Option<&str>) -> Vec<String> {
+    let ip = |family: &str| netns::command(ns, "ip").args([family, "route", "show"]).output().ok().filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().map(|l| l.trim().to_string()).collect::<Vec<_>>());
+    match (ip("-4"), ip("-6")) {
+        (Some(v4), v6) => v4.into_iter().chain(v6.unwrap_or_default()).collect(),
+        (None, _) if ns.is_none() => from_proc(),
+        (None, _) => Vec::new(),
+    }
+}
+
+const FLAGS: [&str; 6] = ["linkdown", "dead", "onlink", "pervasive", "offload", "trap"];
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("routes")
+}
+
+pub fn created() -> Vec<String> {
+    fs::read_to_string(store_path()).unwrap_or_default().lines().filter(|l| !l.trim().is_empty()).map(str::to_string).collect()
+}
+
+fn save_created(specs: &[String]) -> Result<(), String> {
+    let path = store_path();
+    if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    let text: String = specs.iter().map(|s| format!("{}\n", s)).collect();
+    fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn is_created(line: &str, created: &[String]) -> bool {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    created.iter().any(|spec| {
+        let spec: Vec<&str> = spec.split_whitespace().collect();
+        spec.first() == words.first() && spec[1..].chunks(2).all(|kv| words.windows(2).any(|w| w == kv))
+    })
+}
+
+pub fn parse(text: &str) -> Result<String, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let Some((dest, rest)) = words.split_first() else { return Err("no destination given".to_string()) };
+    let dest = match *dest {
+        "default" | "0.0.0.0/0" | "::/0" => "default".to_string(),
+        d => {
+            let (ip, prefix) = d.split_once('/').unwrap_or((d, if d.contains(':') { "128" } else { "32" }));
+            let v6 = ip.contains(':');
+            let ok = if v6 { ip.parse::<Ipv6Addr>().is_ok() } else { ip.parse::<Ipv4Addr>().is_ok() };
+            if !ok || prefix.parse::<u8>().map_or(true, |p| p > if v6 { 128 } else { 32 }) { return Err(format!("'{}' is not a valid destination", d)); }
+            if prefix == if v6 { "128" } else { "32" } { ip.to_string() } else { format!("{}/{}", ip, prefix) }
+        }
+    };
+    let mut spec = vec![dest];
+    let mut rest = rest.iter();
+    while let Some(word) = rest.next() {
+        let key = match *word { "via" | "gw" | "gateway" => "via", "dev" | "device" => "dev", "metric" => "metric", w if w.parse::<IpAddr>().is_ok() => { spec.extend(["via".to_string(), w.to_string()]); continue; } _ => return Err(format!("unexpected '{}' (expected via, dev or metric)", word)) };
+        let value = rest.next().ok_or_else(|| format!("{} needs a value", key))?;
+        match key {
+            "via" if value.parse::<IpAddr>().is_err() => return Err(format!("'{}' is not a valid gateway", value)),
+            "metric" if value.parse::<u32>().is_err() => return Err(format!("'{}' is not a valid metric", value)),
+            "dev" if !Path::new("/sys/class/net").join(value).exists() => return Err(format!("no interface named {}", value)),
+            _ => {}
+        }
+        spec.extend([key.to_string(), value.to_string()]);
+    }
+    if !spec.iter().any(|w| w == "via" || w == "dev") { return Err("give a gateway (via) or a device (dev)".to_string()); }
+    Ok(spec.join(" "))
+}
+
+fn ip_route(action: &str, spec: &str) -> Result<(), String> {
+    let mut words: Vec<&str> = spec.split_whitespace().filter(|w| !FLAGS.contains(w)).collect();
+    if let Some(i) = words.iter().position(|w| *w == "expires") { words.drain(i..(i + 2).min(words.len())); }
+    let family = if words.iter().take(3).any(|w| w.contains(':')) { "-6" } else { "-4" };
+    let out = Command::new("ip").args([family, "route", action]).args(&words).output().map_err(|e| format!("ip: {}", e))?;
+    if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+pub fn add(spec: &str) -> Result<(), String> {
+    ip_route("add", spec)?;
+    let mut specs = created();
+    specs.push(spec.to_string());
+    save_created(&specs)
+}
+
+pub fn restore(line: &str) -> Result<(), String> {
+    ip_route("add", line)
+}
+
+pub fn delete(line: &str) -> Result<(), String> {
+    ip_route("del", line)?;
+    let mut specs = created();
+    let before = specs.len();
+    specs.retain(|spec| !is_created(line, std::slice::from_ref(spec)));
+    if specs.len() != before { save_created(&specs)?; }
+    Ok(())
+}
+
+pub fn clean_up() -> Vec<String> {
+    let specs = created();
+    if specs.is_empty() { return vec!["No route added by DashNet.".to_string()]; }
+    let mut kept = Vec::new();
+    let mut lines: Vec<String> = specs.iter().map(|spec| match ip_route("del", spec) {
+        Ok(()) => format!("removed {}", spec),
+        Err(e) if e.contains("No such process") => format!("{} was already gone", spec),
+        Err(e) => { kept.push(spec.clone()); format!("{}: {}", spec, e) }
+    }).collect();
+    if let Err(e) = save_created(&kept) { lines.push(e); }
+    lines
+}
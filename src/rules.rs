@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use crate::{appvpn, netns};
+
+const SYSTEM_TABLES: [&str; 3] = ["local", "main", "default"];
+const TUNNELS: [&str; 5] = ["wg", "tun", "tap", "ppp", "ipsec"];
+
+pub struct Rule {
+    pub v6: bool,
+    pub priority: u32,
+    pub text: String,
+    pub mark: Option<String>,
+    pub table: Option<String>,
+    pub origin: Option<String>,
+    pub routes: Vec<String>,
+}
+
+impl Rule {
+    pub fn system(&self) -> bool {
+        self.table.as_deref().is_some_and(|t| SYSTEM_TABLES.contains(&t) && self.text == format!("from all lookup {}", t))
+    }
+}
+
+fn ip(ns: Option<&str>, args: &[&str]) -> String {
+    netns::command(ns, "ip").args(args).output().ok().filter(|o| o.status.success()).map(|o| String::from_utf8_lossy(&o.stdout).to_string()).unwrap_or_default()
+}
+
+fn after<'a>(words: &[&'a str], key: &str) -> Option<&'a str> {
+    words.iter().position(|w| *w == key).and_then(|i| words.get(i + 1)).copied()
+}
+
+fn origin(mark: Option<&str>, table: Option<&str>, words: &[&str], routes: &[String], dashnet: &[u32]) -> Option<String> {
+    let mark_value = mark.and_then(|m| u32::from_str_radix(m.split('/').next()?.trim_start_matches("0x"), 16).ok());
+    if mark_value.is_some_and(|m| dashnet.contains(&m)) { return Some("DashNet per-app VPN".to_string()); }
+    if mark == Some("0xca6c") || table == Some("51820") { return Some("WireGuard (wg-quick)".to_string()); }
+    if words.contains(&"suppress_prefixlength") { return Some("VPN full tunnel, main table minus its default route".to_string()); }
+    let tunnel = routes.iter().filter_map(|r| after(&r.split_whitespace().collect::<Vec<_>>(), "dev").map(str::to_string)).find(|dev| TUNNELS.iter().any(|p| dev.starts_with(p)));
+    if let Some(dev) = tunnel { return Some(format!("VPN: routes through {}", dev)); }
+    if mark.is_some() { return Some("firewall mark".to_string()); }
+    None
+}
+
+pub fn list(ns: Option<&str>) -> Vec<Rule> {
+    let dashnet = appvpn::marks();
+    let mut tables: HashMap<(bool, String), Vec<String>> = HashMap::new();
+    let mut rules = Vec::new();
+    for v6 in [false, true] {
+        let family = if v6 { "-6" } else { "-4" };
+        for line in ip(ns, &[family, "rule", "show"]).lines() {
+            let Some((priority, rest)) = line.split_once(':') else { continue };
+            let Ok(priority) = priority.trim().parse() else { continue };
+            let words: Vec<&str> = rest.split_whitespace().collect();
+            let table = after(&words, "lookup").or_else(|| after(&words, "table")).map(str::to_string);
+            let mark = after(&words, "fwmark").map(str::to_string);
+            let routes = match &table {
+                Some(t) if !SYSTEM_TABLES.contains(&t.as_str()) => tables.entry((v6, t.clone()))
+                    .or_insert_with(|| ip(ns, &[family, "route", "show", "table", t]).lines().map(|l| l.trim().to_string()).collect()).clone(),
+                _ => Vec::new(),
+            };
+            rules.push(Rule { v6, priority, origin: origin(mark.as_deref(), table.as_deref(), &words, &routes, &dashnet), text: words.join(" "), mark, table, routes });
+        }
+    }
+    rules.sort_by_key(|r| (r.priority, r.v6));
+    rules
+}
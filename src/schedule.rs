@@ -0,0 +1,129 @@
+use std::process::Command;
+use std::thread;
+
+use crate::clock::{self, WEEKDAYS};
+
+#[derive(Clone)]
+pub enum Action {
+    ConUp(String),
+    ConDown(String),
+    WifiOn,
+    WifiOff,
+    Preset(String),
+}
+
+pub struct Schedule {
+    pub name: String,
+    pub hour: u32,
+    pub minute: u32,
+    pub days: [bool; 7],
+    pub action: Action,
+}
+
+impl Action {
+    pub fn parse(spec: &str) -> Option<Action> {
+        let (verb, arg) = spec.split_once(' ').map(|(v, a)| (v, a.trim().to_string())).unwrap_or((spec, String::new()));
+        match verb {
+            "vpn-up" | "con-up" if !arg.is_empty() => Some(Action::ConUp(arg)),
+            "vpn-down" | "con-down" if !arg.is_empty() => Some(Action::ConDown(arg)),
+            "wifi-on" => Some(Action::WifiOn),
+            "wifi-off" => Some(Action::WifiOff),
+            "preset" if !arg.is_empty() => Some(Action::Preset(arg)),
+            _ => None,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Action::ConUp(name) => format!("bring up '{}'", name),
+            Action::ConDown(name) => format!("bring down '{}'", name),
+            Action::WifiOn => "turn WiFi on".to_string(),
+            Action::WifiOff => "turn WiFi off".to_string(),
+            Action::Preset(name) => format!("activate preset '{}'", name),
+        }
+    }
+
+    pub fn spawn(&self) {
+        let args: Vec<String> = match self {
+            Action::ConUp(name) => vec!["con".into(), "up".into(), "id".into(), name.clone()],
+            Action::ConDown(name) => vec!["con".into(), "down".into(), "id".into(), name.clone()],
+            Action::WifiOn => vec!["radio".into(), "wifi".into(), "on".into()],
+            Action::WifiOff => vec!["radio".into(), "wifi".into(), "off".into()],
+            Action::Preset(_) => return,
+        };
+        thread::spawn(move || { let _ = Command::new("nmcli").args(&args).output(); });
+    }
+}
+
+pub fn parse_days(spec: &str) -> Option<[bool; 7]> {
+    let index = |d: &str| WEEKDAYS.iter().position(|w| w.eq_ignore_ascii_case(d));
+    let mut days = [false; 7];
+    match spec.trim().to_lowercase().as_str() {
+        "" | "daily" | "all" => return Some([true; 7]),
+        "weekdays" => return Some([false, true, true, true, true, true, false]),
+        "weekends" => return Some([true, false, false, false, false, false, true]),
+        _ => {}
+    }
+    for part in spec.split(',').map(str::trim) {
+        if let Some((from, to)) = part.split_once('-') {
+            let (mut d, end) = (index(from)?, index(to)?);
+            loop { days[d] = true; if d == end { break; } d = (d + 1) % 7; }
+        } else {
+            days[index(part)?] = true;
+        }
+    }
+    Some(days)
+}
+
+impl Schedule {
+    pub fn parse_time(spec: &str) -> Option<(u32, u32)> {
+        let (h, m) = spec.trim().split_once(':')?;
+        let (h, m) = (h.parse().ok()?, m.parse().ok()?);
+        if h < 24 && m < 60 { Some((h, m)) } else { None }
+    }
+
+    pub fn due(&self, last: i64, now: i64) -> bool {
+        self.next_run(last).is_some_and(|at| at <= now)
+    }
+
+    pub fn next_run(&self, now: i64) -> Option<i64> {
+        (0..8).map(|d| clock::day_at(now, d, self.hour, self.minute))
+            .find(|&at| at > now && self.days[clock::local(at).weekday as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily(hour: u32, minute: u32) -> Schedule {
+        Schedule { name: "test".into(), hour, minute, days: [true; 7], action: Action::WifiOn }
+    }
+
+    #[test]
+    fn next_run_lands_on_the_scheduled_local_time() {
+        let now = 1_790_000_000;
+        for (hour, minute) in [(0, 0), (8, 30), (23, 59)] {
+            let at = daily(hour, minute).next_run(now).unwrap();
+            let t = clock::local(at);
+            assert_eq!((t.hour, t.minute, t.second), (hour, minute, 0));
+            assert!(at > now && at <= now + 86400 + 3600);
+        }
+        let mut weekly = daily(8, 30);
+        weekly.days = [false; 7];
+        assert!(weekly.next_run(now).is_none());
+        weekly.days[3] = true;
+        assert_eq!(clock::local(weekly.next_run(now).unwrap()).weekday, 3);
+    }
+
+    #[test]
+    fn fires_when_a_run_falls_between_two_ticks() {
+        let schedule = daily(8, 30);
+        let at = schedule.next_run(1_790_000_000).unwrap();
+        assert!(schedule.due(at - 1, at));
+        assert!(schedule.due(at - 30, at + 90));
+        assert!(schedule.due(at - 3600, at + 7200));
+        assert!(!schedule.due(at, at + 90));
+        assert!(!schedule.due(at - 120, at - 1));
+    }
+}
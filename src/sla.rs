@@ -0,0 +1,101 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{clock, config, health};
+
+pub const WINDOWS: [(&str, i64); 3] = [("24h", 86_400), ("7d", 604_800), ("30d", 2_592_000)];
+
+#[derive(Clone)]
+pub enum Check {
+    Ping(String),
+    Http(String),
+}
+
+#[derive(Clone)]
+pub struct Target {
+    pub name: String,
+    pub check: Check,
+    pub interval: u64,
+}
+
+pub struct Outcome {
+    pub name: String,
+    pub up: bool,
+    pub latency: Option<f64>,
+}
+
+struct Bucket {
+    target: String,
+    hour: i64,
+    up: u32,
+    total: u32,
+}
+
+pub struct History {
+    buckets: Vec<Bucket>,
+}
+
+fn http(url: &str) -> Option<f64> {
+    let start = Instant::now();
+    let out = Command::new("curl").args(["-s", "-o", "/dev/null", "-m", "10", "-w", "%{http_code}", url]).output().ok()?;
+    let code: u16 = String::from_utf8_lossy(&out.stdout).trim().parse().ok()?;
+    (200..400).contains(&code).then(|| start.elapsed().as_secs_f64() * 1000.0)
+}
+
+pub fn spawn_checks(targets: &[Target]) -> Receiver<Outcome> {
+    let (tx, rx) = mpsc::channel();
+    for target in targets.iter().cloned() {
+        let tx = tx.clone();
+        thread::spawn(move || loop {
+            let latency = match &target.check {
+                Check::Ping(host) => health::probe(None, host),
+                Check::Http(url) => http(url),
+            };
+            if tx.send(Outcome { name: target.name.clone(), up: latency.is_some(), latency }).is_err() { return; }
+            thread::sleep(Duration::from_secs(target.interval.max(5)));
+        });
+    }
+    rx
+}
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("sla")
+}
+
+impl History {
+    pub fn load() -> History {
+        let buckets = fs::read_to_string(store_path()).unwrap_or_default().lines().filter_map(|line| {
+            let f: Vec<&str> = line.split('\t').collect();
+            if f.len() < 4 { return None; }
+            Some(Bucket { target: f[0].to_string(), hour: f[1].parse().ok()?, up: f[2].parse().ok()?, total: f[3].parse().ok()? })
+        }).collect();
+        History { buckets }
+    }
+
+    pub fn save(&mut self) -> Result<(), String> {
+        let oldest = clock::epoch_now() - WINDOWS[WINDOWS.len() - 1].1;
+        self.buckets.retain(|b| b.hour + 3600 > oldest);
+        let path = store_path();
+        if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+        let text: String = self.buckets.iter().map(|b| format!("{}\t{}\t{}\t{}\n", b.target, b.hour, b.up, b.total)).collect();
+        fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn record(&mut self, target: &str, up: bool, now: i64) {
+        let hour = now - now.rem_euclid(3600);
+        match self.buckets.iter_mut().find(|b| b.target == target && b.hour == hour) {
+            Some(bucket) => { bucket.total += 1; bucket.up += up as u32; }
+            None => self.buckets.push(Bucket { target: target.to_string(), hour, up: up as u32, total: 1 }),
+        }
+    }
+
+    pub fn availability(&self, target: &str, window: i64, now: i64) -> Option<f64> {
+        let (up, total) = self.buckets.iter().filter(|b| b.target == target && b.hour + 3600 > now - window)
+            .fold((0u64, 0u64), |(u, t), b| (u + b.up as u64, t + b.total as u64));
+        (total > 0).then(|| up as f64 * 100.0 / total as f64)
+    }
+}
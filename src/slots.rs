@@ -0,0 +1,34 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config;
+
+pub const COUNT: usize = 9;
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("slots")
+}
+
+fn defaults(configured: &[(usize, String)]) -> Vec<Option<String>> {
+    let mut slots = vec![None; COUNT];
+    for (n, name) in configured.iter().filter(|(n, _)| (1..=COUNT).contains(n)) { slots[n - 1] = Some(name.clone()); }
+    slots
+}
+
+pub fn load(configured: &[(usize, String)]) -> Vec<Option<String>> {
+    let mut slots = defaults(configured);
+    for line in fs::read_to_string(store_path()).unwrap_or_default().lines() {
+        let Some((n, name)) = line.split_once('\t') else { continue };
+        let Some(n) = n.parse::<usize>().ok().filter(|n| (1..=COUNT).contains(n)) else { continue };
+        slots[n - 1] = (!name.is_empty()).then(|| name.to_string());
+    }
+    slots
+}
+
+pub fn save(slots: &[Option<String>], configured: &[(usize, String)]) -> Result<(), String> {
+    let path = store_path();
+    if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    let text: String = slots.iter().zip(defaults(configured)).enumerate().filter(|(_, (slot, default))| *slot != default)
+        .map(|(i, (slot, _))| format!("{}\t{}\n", i + 1, slot.as_deref().unwrap_or(""))).collect();
+    fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
@@ -0,0 +1,255 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{clock, format, profile};
+
+const WIDTH: u32 = 960;
+const HEIGHT: u32 = 420;
+const LEFT: u32 = 80;
+const RIGHT: u32 = 20;
+const TOP: u32 = 40;
+const BOTTOM: u32 = 36;
+const BACKGROUND: [u8; 3] = [16, 16, 16];
+const GRID: [u8; 3] = [58, 58, 58];
+const TEXT: [u8; 3] = [200, 200, 200];
+const SCALE: u32 = 2;
+
+const GLYPHS: [(char, [u8; 5]); 45] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]), ('1', [0b010, 0b110, 0b010, 0b010, 0b111]), ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]), ('4', [0b101, 0b101, 0b111, 0b001, 0b001]), ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]), ('7', [0b111, 0b001, 0b001, 0b010, 0b010]), ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]), ('A', [0b010, 0b101, 0b111, 0b101, 0b101]), ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]), ('D', [0b110, 0b101, 0b101, 0b101, 0b110]), ('E', [0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]), ('G', [0b011, 0b100, 0b101, 0b101, 0b011]), ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', [0b111, 0b010, 0b010, 0b010, 0b111]), ('J', [0b001, 0b001, 0b001, 0b101, 0b010]), ('K', [0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', [0b100, 0b100, 0b100, 0b100, 0b111]), ('M', [0b101, 0b111, 0b111, 0b101, 0b101]), ('N', [0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', [0b010, 0b101, 0b101, 0b101, 0b010]), ('P', [0b110, 0b101, 0b110, 0b100, 0b100]), ('Q', [0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', [0b110, 0b101, 0b110, 0b101, 0b101]), ('S', [0b011, 0b100, 0b010, 0b001, 0b110]), ('T', [0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', [0b101, 0b101, 0b101, 0b101, 0b111]), ('V', [0b101, 0b101, 0b101, 0b101, 0b010]), ('W', [0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', [0b101, 0b101, 0b010, 0b101, 0b101]), ('Y', [0b101, 0b101, 0b010, 0b010, 0b010]), ('Z', [0b111, 0b001, 0b010, 0b100, 0b111]),
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]), ('.', [0b000, 0b000, 0b000, 0b000, 0b010]), (',', [0b000, 0b000, 0b000, 0b010, 0b100]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100]), ('-', [0b000, 0b000, 0b111, 0b000, 0b000]), ('(', [0b010, 0b100, 0b100, 0b100, 0b010]),
+    (')', [0b010, 0b001, 0b001, 0b001, 0b010]), ('%', [0b101, 0b001, 0b010, 0b100, 0b101]), ('_', [0b000, 0b000, 0b000, 0b000, 0b111]),
+];
+
+pub struct Series {
+    pub name: String,
+    pub color: [u8; 3],
+    pub points: Vec<(i64, f64)>,
+}
+
+pub struct Chart {
+    pub title: String,
+    pub unit: &'static str,
+    pub series: Vec<Series>,
+}
+
+impl Chart {
+    fn x_range(&self) -> (i64, i64) {
+        let times = self.series.iter().flat_map(|s| s.points.iter().map(|(t, _)| *t));
+        let (min, max) = times.fold((i64::MAX, i64::MIN), |(lo, hi), t| (lo.min(t), hi.max(t)));
+        if min > max { (0, 1) } else { (min, max.max(min + 1)) }
+    }
+
+    fn max(&self) -> f64 {
+        self.series.iter().flat_map(|s| s.points.iter().map(|(_, v)| *v)).fold(1.0, f64::max) * 1.1
+    }
+
+    fn to_px(&self, (t, v): (i64, f64)) -> (f64, f64) {
+        let ((t0, t1), max) = (self.x_range(), self.max());
+        let (w, h) = ((WIDTH - LEFT - RIGHT) as f64, (HEIGHT - TOP - BOTTOM) as f64);
+        (LEFT as f64 + (t - t0) as f64 / (t1 - t0) as f64 * w, TOP as f64 + h * (1.0 - v / max))
+    }
+
+    fn y_labels(&self) -> Vec<(f64, String)> {
+        let max = self.max();
+        (0..=4).map(|i| { let v = max * i as f64 / 4.0; (v, format::number(v, if max < 10.0 { 2 } else { 1 })) }).collect()
+    }
+
+    fn x_labels(&self) -> Vec<(i64, String)> {
+        let (t0, t1) = self.x_range();
+        (0..=4).map(|i| { let t = t0 + (t1 - t0) * i / 4; (t, format::time(t)) }).collect()
+    }
+}
+
+struct Canvas {
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Canvas {
+    fn set(&mut self, x: i64, y: i64, color: [u8; 3]) {
+        if (0..WIDTH as i64).contains(&x) && (0..HEIGHT as i64).contains(&y) { self.pixels[(y as u32 * WIDTH + x as u32) as usize] = color; }
+    }
+
+    fn line(&mut self, (x0, y0): (f64, f64), (x1, y1): (f64, f64), color: [u8; 3], thick: bool) {
+        let steps = (x1 - x0).abs().max((y1 - y0).abs()).max(1.0) as u32;
+        for i in 0..=steps {
+            let t = i as f64 / steps as f64;
+            let (x, y) = ((x0 + (x1 - x0) * t).round() as i64, (y0 + (y1 - y0) * t).round() as i64);
+            self.set(x, y, color);
+            if thick { self.set(x, y + 1, color); self.set(x + 1, y, color); }
+        }
+    }
+
+    fn text(&mut self, x: u32, y: u32, text: &str, color: [u8; 3]) {
+        for (i, c) in text.chars().enumerate() {
+            let Some((_, rows)) = GLYPHS.iter().find(|(g, _)| *g == c.to_ascii_uppercase()) else { continue };
+            let left = x + i as u32 * 4 * SCALE;
+            for (row, bits) in rows.iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) == 0 { continue; }
+                    for d in 0..SCALE * SCALE { self.set((left + col * SCALE + d % SCALE) as i64, (y + row as u32 * SCALE + d / SCALE) as i64, color); }
+                }
+            }
+        }
+    }
+}
+
+fn text_width(text: &str) -> u32 {
+    text.chars().count() as u32 * 4 * SCALE
+}
+
+fn draw(chart: &Chart) -> Canvas {
+    let mut canvas = Canvas { pixels: vec![BACKGROUND; (WIDTH * HEIGHT) as usize] };
+    for (v, label) in chart.y_labels() {
+        let (_, y) = chart.to_px((0, v));
+        for x in (LEFT..WIDTH - RIGHT).step_by(3) { canvas.set(x as i64, y as i64, GRID); }
+        canvas.text(LEFT.saturating_sub(text_width(&label) + 8), (y as u32).saturating_sub(5), &label, TEXT);
+    }
+    for (t, label) in chart.x_labels() {
+        let (x, _) = chart.to_px((t, 0.0));
+        canvas.line((x, TOP as f64), (x, (HEIGHT - BOTTOM) as f64), GRID, false);
+        let left = (x as u32).saturating_sub(text_width(&label) / 2).clamp(LEFT / 2, (WIDTH - RIGHT).saturating_sub(text_width(&label)).max(LEFT / 2));
+        canvas.text(left, HEIGHT - BOTTOM + 10, &label, TEXT);
+    }
+    let title = format!("{} ({})", chart.title, chart.unit);
+    canvas.text(LEFT, 12, &title, TEXT);
+    let mut right = WIDTH - RIGHT;
+    for series in chart.series.iter().rev() {
+        right = right.saturating_sub(text_width(&series.name) + 16);
+        if right < LEFT + text_width(&title) + 16 { break; }
+        canvas.text(right, 12, &series.name, series.color);
+    }
+    for series in &chart.series {
+        for pair in series.points.windows(2) { canvas.line(chart.to_px(pair[0]), chart.to_px(pair[1]), series.color, true); }
+    }
+    canvas
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &b| (0..8).fold(crc ^ b as u32, |c, _| if c & 1 != 0 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 }))
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &x| { let a = (a + x as u32) % 65521; (a, (b + a) % 65521) });
+    (b << 16) | a
+}
+
+struct Bits {
+    out: Vec<u8>,
+    acc: u32,
+    len: u32,
+}
+
+impl Bits {
+    fn push(&mut self, value: u32, count: u32) {
+        self.acc |= value << self.len;
+        self.len += count;
+        while self.len >= 8 { self.out.push(self.acc as u8); self.acc >>= 8; self.len -= 8; }
+    }
+
+    fn code(&mut self, code: u32, count: u32) {
+        self.push((0..count).fold(0, |r, i| (r << 1) | ((code >> i) & 1)), count);
+    }
+
+    fn symbol(&mut self, sym: u32) {
+        match sym {
+            0..=143 => self.code(0x30 + sym, 8),
+            144..=255 => self.code(0x190 + sym - 144, 9),
+            256..=279 => self.code(sym - 256, 7),
+            _ => self.code(0xc0 + sym - 280, 8),
+        }
+    }
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    const BASES: [u32; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+    const EXTRA: [u32; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+    let mut bits = Bits { out: vec![0x78, 0x01], acc: 0, len: 0 };
+    bits.push(0b011, 3);
+    let mut i = 0;
+    while i < data.len() {
+        let run = if i > 0 { data[i..].iter().take(258).take_while(|&&b| b == data[i - 1]).count() } else { 0 };
+        if run >= 3 {
+            let code = BASES.iter().rposition(|&b| b <= run as u32).unwrap_or(0);
+            bits.symbol(257 + code as u32);
+            bits.push(run as u32 - BASES[code], EXTRA[code]);
+            bits.code(0, 5);
+            i += run;
+        } else {
+            bits.symbol(data[i] as u32);
+            i += 1;
+        }
+    }
+    bits.symbol(256);
+    if bits.len > 0 { bits.push(0, 8 - bits.len); }
+    bits.out.extend(adler32(data).to_be_bytes());
+    bits.out
+}
+
+fn png(canvas: &Canvas) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(((WIDTH * 3 + 1) * HEIGHT) as usize);
+    for row in canvas.pixels.chunks(WIDTH as usize) {
+        raw.push(1);
+        let bytes: Vec<u8> = row.iter().flatten().copied().collect();
+        raw.extend(bytes.iter().enumerate().map(|(i, &b)| if i < 3 { b } else { b.wrapping_sub(bytes[i - 3]) }));
+    }
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut header = WIDTH.to_be_bytes().to_vec();
+    header.extend(HEIGHT.to_be_bytes());
+    header.extend([8, 2, 0, 0, 0]);
+    for (kind, body) in [(b"IHDR", header), (b"IDAT", deflate(&raw)), (b"IEND", Vec::new())] {
+        out.extend((body.len() as u32).to_be_bytes());
+        let chunk: Vec<u8> = kind.iter().chain(&body).copied().collect();
+        out.extend(&chunk);
+        out.extend(crc32(&chunk).to_be_bytes());
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn svg(chart: &Chart) -> String {
+    let hex = |c: [u8; 3]| format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2]);
+    let mut out = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"monospace\" font-size=\"12\">\n<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n", hex(BACKGROUND), w = WIDTH, h = HEIGHT);
+    for (v, label) in chart.y_labels() {
+        let (_, y) = chart.to_px((0, v));
+        let _ = writeln!(out, "<line x1=\"{}\" y1=\"{y:.1}\" x2=\"{}\" y2=\"{y:.1}\" stroke=\"{}\" stroke-dasharray=\"2,2\"/><text x=\"{}\" y=\"{:.1}\" fill=\"{}\" text-anchor=\"end\">{}</text>", LEFT, WIDTH - RIGHT, hex(GRID), LEFT - 8, y + 4.0, hex(TEXT), escape(&label));
+    }
+    for (t, label) in chart.x_labels() {
+        let (x, _) = chart.to_px((t, 0.0));
+        let _ = writeln!(out, "<line x1=\"{x:.1}\" y1=\"{}\" x2=\"{x:.1}\" y2=\"{}\" stroke=\"{}\"/><text x=\"{x:.1}\" y=\"{}\" fill=\"{}\" text-anchor=\"middle\">{}</text>", TOP, HEIGHT - BOTTOM, hex(GRID), HEIGHT - BOTTOM + 20, hex(TEXT), escape(&label));
+    }
+    let _ = writeln!(out, "<text x=\"{}\" y=\"24\" fill=\"{}\" font-size=\"14\">{} ({})</text>", LEFT, hex(TEXT), escape(&chart.title), chart.unit);
+    let legend: Vec<String> = chart.series.iter().map(|s| format!("<tspan fill=\"{}\">{}</tspan>", hex(s.color), escape(&s.name))).collect();
+    let _ = writeln!(out, "<text x=\"{}\" y=\"24\" text-anchor=\"end\" xml:space=\"preserve\">{}</text>", WIDTH - RIGHT, legend.join("  "));
+    for series in &chart.series {
+        let points: Vec<String> = series.points.iter().map(|p| { let (x, y) = chart.to_px(*p); format!("{:.1},{:.1}", x, y) }).collect();
+        let _ = writeln!(out, "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>", points.join(" "), hex(series.color));
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+pub fn export(chart: &Chart) -> Result<(PathBuf, PathBuf), String> {
+    if chart.series.iter().all(|s| s.points.len() < 2) { return Err("not enough samples yet".to_string()); }
+    let t = clock::local(clock::epoch_now());
+    let base = format!("~/dashnet-graph-{}{:02}{:02}-{:02}{:02}{:02}", t.year, t.month, t.day, t.hour, t.minute, t.second);
+    let (png_path, svg_path) = (profile::expand_home(&format!("{}.png", base)), profile::expand_home(&format!("{}.svg", base)));
+    fs::write(&png_path, png(&draw(chart))).map_err(|e| format!("{}: {}", png_path.display(), e))?;
+    fs::write(&svg_path, svg(chart)).map_err(|e| format!("{}: {}", svg_path.display(), e))?;
+    Ok((png_path, svg_path))
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{self, SnmpTarget};
+
+const IF_NAME: &str = ".1.3.6.1.2.1.31.1.1.1.1";
+const IF_HC_IN_OCTETS: &str = ".1.3.6.1.2.1.31.1.1.1.6";
+const IF_HC_OUT_OCTETS: &str = ".1.3.6.1.2.1.31.1.1.1.10";
+
+pub struct Sample {
+    pub source: String,
+    pub iface: String,
+    pub rx: f64,
+    pub tx: f64,
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace(['\n', '\r'], " "))
+}
+
+fn auth_args(target: &SnmpTarget) -> Vec<String> {
+    let mut args = vec!["-v".to_string(), target.version.clone()];
+    if target.version == "3" {
+        let level = if !target.priv_password.is_empty() { "authPriv" } else if !target.auth_password.is_empty() { "authNoPriv" } else { "noAuthNoPriv" };
+        args.extend(["-l", level].map(String::from));
+        if !target.auth_password.is_empty() { args.extend(["-a".to_string(), target.auth_protocol.clone()]); }
+        if !target.priv_password.is_empty() { args.extend(["-x".to_string(), target.priv_protocol.clone()]); }
+    }
+    args.extend(["-t", "1", "-r", "0", "-Oqv"].map(String::from));
+    args
+}
+
+fn secrets(target: &SnmpTarget) -> io::Result<PathBuf> {
+    let mut text = String::new();
+    if target.version == "3" {
+        text += &format!("defSecurityName {}\n", quote(&target.user));
+        if !target.auth_password.is_empty() { text += &format!("defAuthPassphrase {}\n", quote(&target.auth_password)); }
+        if !target.priv_password.is_empty() { text += &format!("defPrivPassphrase {}\n", quote(&target.priv_password)); }
+    } else {
+        text += &format!("defCommunity {}\n", quote(&target.community));
+    }
+    let dir = config::private_dir("snmp")?;
+    let written = OpenOptions::new().write(true).create_new(true).mode(0o600).open(dir.join("snmp.conf")).and_then(|mut file| file.write_all(text.as_bytes()));
+    if let Err(e) = written { let _ = fs::remove_dir_all(&dir); return Err(e); }
+    Ok(dir)
+}
+
+fn poll(target: &SnmpTarget, auth: &[String], conf: &Path, index: u32) -> Option<(String, u64, u64)> {
+    let oids = [IF_NAME, IF_HC_IN_OCTETS, IF_HC_OUT_OCTETS].map(|oid| format!("{}.{}", oid, index));
+    let out = Command::new("snmpget").env("SNMPCONFPATH", conf).args(auth).arg(&target.host).args(&oids).output().ok()?;
+    if !out.status.success() { return None; }
+    let s = String::from_utf8_lossy(&out.stdout);
+    let values: Vec<&str> = s.lines().map(|l| l.trim().trim_matches('"')).collect();
+    if values.len() < 3 { return None; }
+    let name = if values[0].is_empty() { format!("if{}", index) } else { values[0].to_string() };
+    Some((name, values[1].parse().ok()?, values[2].parse().ok()?))
+}
+
+pub fn spawn_collectors(targets: &[SnmpTarget]) -> Receiver<Sample> {
+    let (tx, rx) = mpsc::channel();
+    for target in targets.iter().cloned() {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            let auth = auth_args(&target);
+            let Ok(conf) = secrets(&target) else { return };
+            let mut last: HashMap<u32, (u64, u64, Instant)> = HashMap::new();
+            loop {
+                for &index in &target.interfaces {
+                    let Some((iface, rx_bytes, tx_bytes)) = poll(&target, &auth, &conf, index) else { continue };
+                    let now = Instant::now();
+                    if let Some((old_rx, old_tx, when)) = last.insert(index, (rx_bytes, tx_bytes, now)) {
+                        let secs = now.duration_since(when).as_secs_f64().max(0.001);
+                        let to_mbits = |bytes: u64| (bytes as f64 * 8.0) / (1024.0 * 1024.0) / secs;
+                        let sample = Sample { source: target.name.clone(), iface, rx: to_mbits(rx_bytes.saturating_sub(old_rx)), tx: to_mbits(tx_bytes.saturating_sub(old_tx)) };
+                        if tx.send(sample).is_err() { let _ = fs::remove_dir_all(&conf); return; }
+                    }
+                }
+                thread::sleep(Duration::from_secs(target.interval.max(1)));
+            }
+        });
+    }
+    rx
+}
@@ -0,0 +1,80 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::json::Json;
+use crate::{clock, config, deps};
+
+pub struct Sample {
+    pub time: i64,
+    pub download: f64,
+    pub upload: f64,
+    pub ping: f64,
+    pub server: String,
+}
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("speedtests")
+}
+
+fn field(json: &Json, path: &[&str]) -> Option<f64> {
+    path.iter().try_fold(json, |j, key| j.get(key))?.as_f64()
+}
+
+fn text(json: &Json, path: &[&str]) -> String {
+    path.iter().try_fold(json, |j, key| j.get(key)).and_then(Json::as_str).unwrap_or("").to_string()
+}
+
+pub fn measure() -> Result<Sample, String> {
+    let (program, args, ookla): (&str, &[&str], bool) = if deps::in_path("speedtest") {
+        ("speedtest", &["-f", "json", "--accept-license", "--accept-gdpr"], true)
+    } else if deps::in_path("speedtest-cli") {
+        ("speedtest-cli", &["--json", "--secure"], false)
+    } else {
+        return Err("neither speedtest (Ookla) nor speedtest-cli is installed".to_string());
+    };
+    let out = Command::new(program).args(args).output().map_err(|e| format!("{}: {}", program, e))?;
+    if !out.status.success() { return Err(String::from_utf8_lossy(&out.stderr).trim().to_string()); }
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let json = stdout.lines().rev().find_map(Json::parse).ok_or_else(|| format!("{}: unexpected output", program))?;
+    let to_mbits = |bits: f64| bits / 1_000_000.0;
+    Ok(if ookla {
+        Sample {
+            time: clock::epoch_now(),
+            download: to_mbits(field(&json, &["download", "bandwidth"]).unwrap_or(0.0) * 8.0),
+            upload: to_mbits(field(&json, &["upload", "bandwidth"]).unwrap_or(0.0) * 8.0),
+            ping: field(&json, &["ping", "latency"]).unwrap_or(0.0),
+            server: format!("{} ({})", text(&json, &["server", "name"]), text(&json, &["server", "location"])),
+        }
+    } else {
+        Sample {
+            time: clock::epoch_now(),
+            download: to_mbits(field(&json, &["download"]).unwrap_or(0.0)),
+            upload: to_mbits(field(&json, &["upload"]).unwrap_or(0.0)),
+            ping: field(&json, &["ping"]).unwrap_or(0.0),
+            server: format!("{} ({})", text(&json, &["server", "sponsor"]), text(&json, &["server", "name"])),
+        }
+    })
+}
+
+pub fn run() -> Result<Sample, String> {
+    let sample = measure()?;
+    append(&sample)?;
+    Ok(sample)
+}
+
+fn append(sample: &Sample) -> Result<(), String> {
+    let path = store_path();
+    if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+    let mut file = OpenOptions::new().create(true).append(true).open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    writeln!(file, "{}\t{:.2}\t{:.2}\t{:.1}\t{}", sample.time, sample.download, sample.upload, sample.ping, sample.server.replace('\t', " ")).map_err(|e| e.to_string())
+}
+
+pub fn load() -> Vec<Sample> {
+    fs::read_to_string(store_path()).unwrap_or_default().lines().filter_map(|line| {
+        let f: Vec<&str> = line.split('\t').collect();
+        if f.len() < 5 { return None; }
+        Some(Sample { time: f[0].parse().ok()?, download: f[1].parse().ok()?, upload: f[2].parse().ok()?, ping: f[3].parse().ok()?, server: f[4].to_string() })
+    }).collect()
+}
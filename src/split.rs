@@ -0,0 +1,179 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::process::Command;
+
+use crate::profile;
+
+const FIELDS: &str = "ipv4.never-default,ipv6.never-default,ipv4.ignore-auto-routes,ipv6.ignore-auto-routes,ipv4.routes,ipv6.routes,ipv4.dns-search";
+
+#[derive(Clone)]
+pub struct Settings {
+    pub never_default: [bool; 2],
+    pub ignore_pushed: [bool; 2],
+    pub routes: Vec<String>,
+    pub domains: Vec<String>,
+}
+
+pub enum Row {
+    Mode,
+    Pushed,
+    Route(usize),
+    Domain(usize),
+}
+
+pub struct SplitTunnel {
+    pub connection: String,
+    pub original: Settings,
+    pub settings: Settings,
+}
+
+fn list(value: &str) -> Vec<String> {
+    value.split(',').map(|s| s.trim().replace("\\:", ":")).filter(|s| !s.is_empty()).collect()
+}
+
+fn cidr(text: &str) -> Option<(u32, u32)> {
+    let (ip, prefix) = text.split_once('/').unwrap_or((text, "32"));
+    let prefix: u32 = prefix.parse().ok().filter(|p| *p <= 32)?;
+    let mask = if prefix == 0 { 0 } else { u32::MAX << (32 - prefix) };
+    Some((u32::from(ip.parse::<Ipv4Addr>().ok()?) & mask, prefix))
+}
+
+fn overlaps(a: (u32, u32), b: (u32, u32)) -> bool {
+    let prefix = a.1.min(b.1);
+    let mask = if prefix == 0 { 0 } else { u32::MAX << (32 - prefix) };
+    a.0 & mask == b.0 & mask
+}
+
+impl Settings {
+    pub fn split(&self) -> bool {
+        self.never_default[0]
+    }
+
+    pub fn toggle(flags: &mut [bool; 2]) {
+        let on = !flags.iter().all(|f| *f);
+        *flags = [on; 2];
+    }
+
+    pub fn describe(flags: [bool; 2], yes: &str, no: &str) -> String {
+        match flags {
+            [v4, v6] if v4 == v6 => (if v4 { yes } else { no }).to_string(),
+            [v4, v6] => {
+                let short = |on: bool| (if on { yes } else { no }).split(" (").next().unwrap_or_default();
+                format!("IPv4 {}, IPv6 {}", short(v4), short(v6))
+            }
+        }
+    }
+
+    fn is_v6(route: &str) -> bool {
+        route.split_whitespace().next().is_some_and(|d| d.contains(':'))
+    }
+
+    fn args(&self) -> Vec<String> {
+        let yes_no = |b: bool| if b { "yes" } else { "no" }.to_string();
+        let routes = |v6: bool| self.routes.iter().filter(|r| Self::is_v6(r) == v6).cloned().collect::<Vec<_>>().join(", ");
+        vec![
+            "ipv4.never-default".to_string(), yes_no(self.never_default[0]),
+            "ipv6.never-default".to_string(), yes_no(self.never_default[1]),
+            "ipv4.ignore-auto-routes".to_string(), yes_no(self.ignore_pushed[0]),
+            "ipv6.ignore-auto-routes".to_string(), yes_no(self.ignore_pushed[1]),
+            "ipv4.routes".to_string(), routes(false),
+            "ipv6.routes".to_string(), routes(true),
+            "ipv4.dns-search".to_string(), self.domains.join(","),
+        ]
+    }
+}
+
+impl SplitTunnel {
+    pub fn load(connection: &str) -> Result<SplitTunnel, String> {
+        let out = Command::new("nmcli").args(["-g", FIELDS, "con", "show", "id", connection]).output().map_err(|e| format!("nmcli: {}", e))?;
+        if !out.status.success() { return Err(String::from_utf8_lossy(&out.stderr).trim().to_string()); }
+        let text = String::from_utf8_lossy(&out.stdout);
+        let f: Vec<&str> = text.lines().collect();
+        let field = |i: usize| f.get(i).copied().unwrap_or("");
+        let settings = Settings {
+            never_default: [field(0) == "yes", field(1) == "yes"],
+            ignore_pushed: [field(2) == "yes", field(3) == "yes"],
+            routes: list(field(4)).into_iter().chain(list(field(5))).collect(),
+            domains: list(field(6)),
+        };
+        Ok(SplitTunnel { connection: connection.to_string(), original: settings.clone(), settings })
+    }
+
+    pub fn rows(&self) -> Vec<Row> {
+        [Row::Mode, Row::Pushed].into_iter()
+            .chain((0..self.settings.routes.len()).map(Row::Route))
+            .chain((0..self.settings.domains.len()).map(Row::Domain))
+            .collect()
+    }
+
+    pub fn add(&mut self, entry: &str) -> Result<(), String> {
+        let entry = entry.trim();
+        let destination = entry.split_whitespace().next().unwrap_or("");
+        if destination.contains('/') || destination.parse::<IpAddr>().is_ok() {
+            profile::check_cidrs(destination, destination.contains(':'))?;
+            if self.settings.routes.iter().any(|r| r.split_whitespace().next() == Some(destination)) { return Err(format!("{} is already routed through the tunnel", destination)); }
+            self.settings.routes.push(entry.to_string());
+            return Ok(());
+        }
+        let name = destination.trim_start_matches('~').trim_start_matches("*.").trim_end_matches('.');
+        if entry.contains(char::is_whitespace) || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.')) { return Err(format!("'{}' is neither an address/prefix nor a domain", entry)); }
+        let domain = if name.is_empty() { "~.".to_string() } else { format!("~{}", name) };
+        if !self.settings.domains.contains(&domain) { self.settings.domains.push(domain); }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, row: &Row) {
+        match row {
+            Row::Route(i) if *i < self.settings.routes.len() => { self.settings.routes.remove(*i); }
+            Row::Domain(i) if *i < self.settings.domains.len() => { self.settings.domains.remove(*i); }
+            _ => {}
+        }
+    }
+
+    pub fn diff(&self) -> Vec<String> {
+        let (old, new) = (self.original.args(), self.settings.args());
+        old.chunks(2).zip(new.chunks(2)).filter(|(a, b)| a[1] != b[1])
+            .flat_map(|(a, b)| [format!("- {}: {}", a[0], a[1]), format!("+ {}: {}", b[0], b[1])])
+            .collect()
+    }
+
+    pub fn apply(&self) -> Result<(), String> {
+        let out = Command::new("nmcli").args(["con", "modify", "id", &self.connection]).args(self.settings.args()).output().map_err(|e| format!("nmcli: {}", e))?;
+        if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+    }
+
+    pub fn preview(&self, local: &[String]) -> Vec<String> {
+        let s = &self.settings;
+        let uplink = local.iter().find(|r| r.starts_with("default")).and_then(|r| r.split_whitespace().skip_while(|w| *w != "dev").nth(1)).unwrap_or("your normal connection").to_string();
+        let mut lines = Vec::new();
+        if s.split() {
+            lines.push(format!("Split tunnel: Internet traffic keeps using {}.", uplink));
+            lines.push("Through the tunnel:".to_string());
+            lines.push("  • the VPN's own subnet".to_string());
+            lines.push(format!("  • routes pushed by the server: {}", Settings::describe(s.ignore_pushed, "ignored", "accepted")));
+            lines.extend(s.routes.iter().map(|r| format!("  • {}", r)));
+            if s.routes.is_empty() && s.ignore_pushed == [true; 2] { lines.push("Nothing but the VPN subnet is reachable through the tunnel.".to_string()); }
+        } else {
+            lines.push(format!("Full tunnel: all traffic leaves through the VPN, {} only carries the encrypted tunnel.", uplink));
+            if !s.routes.is_empty() { lines.push(format!("{} extra route{} already covered by the default route.", s.routes.len(), if s.routes.len() == 1 { " is" } else { "s are" })); }
+        }
+        if s.never_default[0] != s.never_default[1] {
+            lines.push(format!("⚠ IPv6 Internet traffic {} (ipv6.never-default = {}).", if s.never_default[1] { "stays outside the tunnel" } else { "goes through the tunnel" }, if s.never_default[1] { "yes" } else { "no" }));
+        }
+        lines.push(String::new());
+        lines.push(if s.domains.iter().any(|d| d == "~.") {
+            "DNS: every query goes to the VPN's DNS servers.".to_string()
+        } else if s.domains.is_empty() {
+            if s.split() { "DNS: names resolve through your normal DNS, the VPN's servers are only used if they have priority.".to_string() } else { "DNS: the VPN's DNS servers take over while it is up.".to_string() }
+        } else {
+            format!("DNS: {} resolve through the VPN's DNS servers, the rest as before.", s.domains.iter().map(|d| format!("*.{}", d.trim_start_matches('~'))).collect::<Vec<_>>().join(", "))
+        });
+        let warnings: Vec<String> = s.routes.iter().filter_map(|r| cidr(r.split_whitespace().next()?).map(|c| (r, c))).flat_map(|(r, c)| {
+            local.iter().filter(|l| !l.starts_with("default")).filter_map(move |l| {
+                let dest = cidr(l.split_whitespace().next()?)?;
+                overlaps(c, dest).then(|| format!("⚠ {} overlaps the local route {}: the more specific one wins.", r.split_whitespace().next().unwrap_or(r), l))
+            })
+        }).collect();
+        if !warnings.is_empty() { lines.push(String::new()); lines.extend(warnings); }
+        lines
+    }
+}
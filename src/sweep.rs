@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+use std::net::Ipv4Addr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+use crate::{health, net_monitor};
+
+const MAX_HOSTS: u32 = 4096;
+const WORKERS: u32 = 64;
+
+pub struct Reply {
+    pub ip: Ipv4Addr,
+    pub rtt: Option<f64>,
+}
+
+pub fn local_subnet(docker: &HashSet<String>) -> Option<String> {
+    net_monitor::ipv4_networks().into_iter()
+        .find(|(iface, _, prefix)| *prefix < 31 && !docker.contains(iface) && !iface.starts_with("veth"))
+        .map(|(_, ip, prefix)| format!("{}/{}", Ipv4Addr::from(u32::from(ip) & u32::MAX.checked_shl(32 - prefix).unwrap_or(0)), prefix))
+}
+
+fn parse_cidr(text: &str) -> Result<(u32, u32), String> {
+    let (addr, prefix) = text.split_once('/').unwrap_or((text, "32"));
+    let ip: Ipv4Addr = addr.trim().parse().map_err(|_| format!("invalid address '{}'", addr))?;
+    let prefix: u32 = prefix.trim().parse().ok().filter(|p| *p <= 32).ok_or_else(|| format!("invalid prefix '{}'", prefix))?;
+    let hosts = 1u64 << (32 - prefix);
+    if hosts > MAX_HOSTS as u64 { return Err(format!("/{} is {} addresses, the sweep is limited to {}", prefix, hosts, MAX_HOSTS)); }
+    let network = if prefix == 0 { 0 } else { u32::from(ip) & (u32::MAX << (32 - prefix)) };
+    Ok(if prefix >= 31 { (network, hosts as u32) } else { (network + 1, hosts as u32 - 2) })
+}
+
+pub fn start(cidr: &str) -> Result<(usize, Receiver<Reply>), String> {
+    let (first, count) = parse_cidr(cidr)?;
+    let (tx, rx) = mpsc::channel();
+    let next = Arc::new(AtomicU32::new(0));
+    for _ in 0..WORKERS.min(count) {
+        let (tx, next) = (tx.clone(), next.clone());
+        thread::spawn(move || loop {
+            let offset = next.fetch_add(1, Ordering::Relaxed);
+            if offset >= count { return; }
+            let ip = Ipv4Addr::from(first + offset);
+            if tx.send(Reply { ip, rtt: health::probe(None, &ip.to_string()) }).is_err() { return; }
+        });
+    }
+    Ok((count as usize, rx))
+}
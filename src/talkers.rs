@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+
+use crate::netns;
+
+pub struct Talker {
+    pub process: String,
+    pub pid: u32,
+    pub connections: usize,
+    pub remote: String,
+    pub sent: u64,
+    pub received: u64,
+    pub up: f64,
+    pub down: f64,
+}
+
+pub fn host(address: &str) -> &str {
+    let host = if address.matches(':').count() > 1 && !address.starts_with('[') { address } else { address.rsplit_once(':').map_or(address, |(h, _)| h) };
+    host.trim_matches(['[', ']']).split('%').next().unwrap_or_default()
+}
+
+fn loopback(address: &str) -> bool {
+    let host = host(address);
+    host.starts_with("127.") || host == "::1" || host.starts_with("::ffff:127.")
+}
+
+fn counter(info: &str, key: &str) -> u64 {
+    info.split_whitespace().find_map(|w| w.strip_prefix(key).and_then(|v| v.strip_prefix(':'))).and_then(|v| v.parse().ok()).unwrap_or(0)
+}
+
+pub fn sample(ns: Option<&str>, previous: &mut HashMap<String, (u64, u64)>, secs: f64) -> Result<Vec<Talker>, String> {
+    let out = netns::command(ns, "ss").args(["-tinpH", "state", "established"]).stdin(Stdio::null()).output().map_err(|e| format!("ss: {}", e))?;
+    if !out.status.success() { return Err(String::from_utf8_lossy(&out.stderr).trim().to_string()); }
+    let text = String::from_utf8_lossy(&out.stdout);
+    let mut current: HashMap<String, (u64, u64)> = HashMap::new();
+    let mut talkers: Vec<Talker> = Vec::new();
+    let mut busiest: Vec<f64> = Vec::new();
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.next() {
+        let info = if lines.peek().is_some_and(|l| l.starts_with(['\t', ' '])) { lines.next().unwrap_or("") } else { "" };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.len() < 4 || loopback(words[3]) { continue; }
+        let users = words.iter().find(|w| w.starts_with("users:")).copied().unwrap_or("");
+        let process = users.split('"').nth(1).unwrap_or("?").to_string();
+        let pid = users.split("pid=").nth(1).and_then(|v| v.split([',', ')']).next()).and_then(|v| v.parse().ok()).unwrap_or(0);
+        let totals = (counter(info, "bytes_acked"), counter(info, "bytes_received"));
+        let key = format!("{} {}", words[2], words[3]);
+        let before = previous.get(&key).copied().unwrap_or(totals);
+        let rate = |now: u64, then: u64| now.saturating_sub(then) as f64 * 8.0 / (1024.0 * 1024.0) / secs.max(0.001);
+        let (up, down) = (rate(totals.0, before.0), rate(totals.1, before.1));
+        current.insert(key, totals);
+        let index = talkers.iter().position(|t| t.pid == pid && t.process == process).unwrap_or_else(|| {
+            talkers.push(Talker { process, pid, connections: 0, remote: String::new(), sent: 0, received: 0, up: 0.0, down: 0.0 });
+            busiest.push(-1.0);
+            talkers.len() - 1
+        });
+        let talker = &mut talkers[index];
+        if up + down > busiest[index] { talker.remote = words[3].to_string(); busiest[index] = up + down; }
+        talker.connections += 1;
+        talker.sent += totals.0;
+        talker.received += totals.1;
+        talker.up += up;
+        talker.down += down;
+    }
+    *previous = current;
+    talkers.sort_by(|a, b| b.up.total_cmp(&a.up).then(b.sent.cmp(&a.sent)));
+    Ok(talkers)
+}
@@ -0,0 +1,18 @@
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+pub struct Task<T> {
+    rx: Receiver<T>,
+}
+
+impl<T: Send + 'static> Task<T> {
+    pub fn spawn(job: impl FnOnce() -> T + Send + 'static) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || { let _ = tx.send(job()); });
+        Task { rx }
+    }
+
+    pub fn poll(&self) -> Option<T> {
+        self.rx.try_recv().ok()
+    }
+}
@@ -0,0 +1,60 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+
+const SELECTION: Color = Color::Indexed(237);
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Scheme {
+    Default,
+    HighContrast,
+    Colorblind,
+    NoColor,
+}
+
+impl Scheme {
+    pub fn parse(value: &str) -> Option<Scheme> {
+        match value {
+            "default" => Some(Scheme::Default),
+            "high-contrast" => Some(Scheme::HighContrast),
+            "colorblind" => Some(Scheme::Colorblind),
+            "none" | "no-color" => Some(Scheme::NoColor),
+            _ => None,
+        }
+    }
+
+    fn map(self, color: Color) -> Color {
+        match (self, color) {
+            (Scheme::HighContrast, Color::DarkGray | Color::Gray) => Color::White,
+            (Scheme::HighContrast, Color::Indexed(238)) => Color::Gray,
+            (Scheme::HighContrast, Color::Green) => Color::LightGreen,
+            (Scheme::HighContrast, Color::Cyan) => Color::LightCyan,
+            (Scheme::HighContrast, Color::Blue) => Color::LightBlue,
+            (Scheme::HighContrast, Color::Red) => Color::LightRed,
+            (Scheme::HighContrast, Color::Yellow) => Color::LightYellow,
+            (Scheme::HighContrast, Color::Magenta) => Color::LightMagenta,
+            (Scheme::Colorblind, Color::Green) => Color::Indexed(33),
+            (Scheme::Colorblind, Color::LightGreen) => Color::Indexed(75),
+            (Scheme::Colorblind, Color::Cyan) => Color::Indexed(117),
+            (Scheme::Colorblind, Color::Red) => Color::Indexed(166),
+            (Scheme::Colorblind, Color::LightRed) => Color::Indexed(208),
+            (Scheme::Colorblind, Color::Yellow | Color::LightYellow) => Color::Indexed(227),
+            (Scheme::Colorblind, Color::Magenta) => Color::Indexed(175),
+            (Scheme::NoColor, _) => Color::Reset,
+            _ => color,
+        }
+    }
+}
+
+pub fn apply(buf: &mut Buffer, scheme: Scheme) {
+    if scheme == Scheme::Default { return; }
+    for cell in buf.content.iter_mut() {
+        let selected = cell.bg == SELECTION;
+        match scheme {
+            Scheme::NoColor if selected => cell.modifier |= Modifier::REVERSED,
+            Scheme::HighContrast if selected => { cell.bg = Color::White; cell.fg = Color::Black; continue; }
+            _ => {}
+        }
+        cell.fg = scheme.map(cell.fg);
+        cell.bg = if selected && scheme != Scheme::NoColor { SELECTION } else { scheme.map(cell.bg) };
+    }
+}
@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::Path;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum MasterKind {
+    Bridge,
+    Bond,
+}
+
+pub struct Port {
+    pub name: String,
+    pub state: String,
+}
+
+pub struct Master {
+    pub name: String,
+    pub kind: MasterKind,
+    pub detail: String,
+    pub ports: Vec<Port>,
+}
+
+fn read_sys(path: &Path) -> String {
+    fs::read_to_string(path).map(|s| s.trim().to_string()).unwrap_or_default()
+}
+
+fn stp_port_state(code: &str) -> String {
+    match code {
+        "0" => "disabled",
+        "1" => "listening",
+        "2" => "learning",
+        "3" => "forwarding",
+        "4" => "blocking",
+        _ => "unknown",
+    }.to_string()
+}
+
+fn bridge_ports(dev: &Path) -> Vec<Port> {
+    let mut ports: Vec<Port> = fs::read_dir(dev.join("brif")).map(|entries| entries.flatten().map(|e| {
+        let name = e.file_name().to_string_lossy().to_string();
+        Port { state: stp_port_state(&read_sys(&e.path().join("state"))), name }
+    }).collect()).unwrap_or_default();
+    ports.sort_by(|a, b| a.name.cmp(&b.name));
+    ports
+}
+
+fn bond_ports(dev: &Path, root: &Path) -> Vec<Port> {
+    read_sys(&dev.join("bonding/slaves")).split_whitespace().map(|name| {
+        let slave = root.join(name).join("bonding_slave");
+        let state = read_sys(&slave.join("state"));
+        let mii = read_sys(&slave.join("mii_status"));
+        Port { name: name.to_string(), state: format!("{} / mii {}", state, if mii.is_empty() { "?" } else { &mii }) }
+    }).collect()
+}
+
+pub fn get_topology() -> Vec<Master> {
+    let root = Path::new("/sys/class/net");
+    let mut masters = Vec::new();
+    if let Ok(entries) = fs::read_dir(root) {
+        for entry in entries.flatten() {
+            let dev = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if dev.join("bridge").is_dir() {
+                let stp = if read_sys(&dev.join("bridge/stp_state")) == "0" { "STP off" } else { "STP on" };
+                masters.push(Master { name, kind: MasterKind::Bridge, detail: stp.to_string(), ports: bridge_ports(&dev) });
+            } else if dev.join("bonding").is_dir() {
+                let mode = read_sys(&dev.join("bonding/mode"));
+                let active = read_sys(&dev.join("bonding/active_slave"));
+                let detail = if active.is_empty() { mode } else { format!("{}, active {}", mode, active) };
+                masters.push(Master { name, kind: MasterKind::Bond, detail, ports: bond_ports(&dev, root) });
+            }
+        }
+    }
+    masters.sort_by(|a, b| a.name.cmp(&b.name));
+    masters
+}
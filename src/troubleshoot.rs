@@ -0,0 +1,106 @@
+use std::process::{Command, Stdio};
+
+use crate::dhcp;
+
+const PORTAL_CHECK: &str = "http://connectivitycheck.gstatic.com/generate_204";
+
+#[derive(Clone)]
+pub enum Fix {
+    ConnectDevice(String),
+    RenewDhcp(String),
+    ReleaseRenew(String),
+    FlushDns,
+    OpenPortal(String),
+}
+
+impl Fix {
+    pub fn describe(&self) -> String {
+        match self {
+            Fix::ConnectDevice(iface) => format!("connect {} with NetworkManager", iface),
+            Fix::RenewDhcp(iface) => format!("renew the DHCP lease on {}", iface),
+            Fix::ReleaseRenew(iface) => format!("release and renew {}", iface),
+            Fix::FlushDns => "flush the DNS cache".to_string(),
+            Fix::OpenPortal(url) => format!("open the captive portal {} in the browser", url),
+        }
+    }
+
+    pub fn apply(&self) -> Result<String, String> {
+        match self {
+            Fix::ConnectDevice(iface) => run("nmcli", &["device", "connect", iface]).map(|_| format!("{} connected", iface)).ok_or_else(|| format!("nmcli could not connect {}", iface)),
+            Fix::RenewDhcp(iface) => dhcp::renew(iface, false),
+            Fix::ReleaseRenew(iface) => dhcp::renew(iface, true),
+            Fix::FlushDns => run("resolvectl", &["flush-caches"]).map(|_| "DNS cache flushed".to_string()).ok_or_else(|| "resolvectl flush-caches failed".to_string()),
+            Fix::OpenPortal(url) => Command::new("xdg-open").arg(url).stdout(Stdio::null()).stderr(Stdio::null()).spawn()
+                .map(|_| format!("{} opened", url)).map_err(|e| format!("xdg-open: {}", e)),
+        }
+    }
+}
+
+pub struct Report {
+    pub lines: Vec<String>,
+    pub fix: Option<Fix>,
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    Command::new(program).args(args).output().ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+}
+
+fn link() -> Result<String, (String, Option<Fix>)> {
+    let devices = run("nmcli", &["-t", "-f", "DEVICE,TYPE,STATE", "device"]).unwrap_or_default();
+    let physical: Vec<Vec<&str>> = devices.lines().map(|l| l.split(':').collect::<Vec<_>>()).filter(|p| p.len() == 3 && matches!(p[1], "ethernet" | "wifi")).collect();
+    if let Some(p) = physical.iter().find(|p| p[2] == "connected") { return Ok(p[0].to_string()); }
+    let candidate = physical.iter().find(|p| p[2] == "disconnected").map(|p| Fix::ConnectDevice(p[0].to_string()));
+    Err(("no wired or wireless device is connected".to_string(), candidate))
+}
+
+pub fn run_wizard() -> Report {
+    let mut lines = Vec::new();
+    let mut fix = None;
+    let steps = ["Link", "IP address", "Gateway", "DNS", "External reachability", "Captive portal"];
+    let mut iface = String::new();
+    for (i, step) in steps.iter().enumerate() {
+        let result: Result<String, (String, Option<Fix>)> = match i {
+            0 => link().inspect(|dev| iface = dev.clone()),
+            1 => run("ip", &["-4", "-o", "addr", "show", "dev", &iface, "scope", "global"])
+                .and_then(|s| s.split_whitespace().nth(3).map(str::to_string))
+                .ok_or_else(|| (format!("{} has no IPv4 address", iface), Some(Fix::RenewDhcp(iface.clone())))),
+            2 => {
+                let gateway = run("ip", &["route", "show", "default", "dev", &iface]).and_then(|s| s.split_whitespace().skip_while(|w| *w != "via").nth(1).map(str::to_string)).unwrap_or_default();
+                if gateway.is_empty() { Err((format!("no default route via {}", iface), Some(Fix::RenewDhcp(iface.clone())))) }
+                else if run("ping", &["-n", "-c", "2", "-W", "2", &gateway]).is_some() { Ok(format!("{} answers", gateway)) }
+                else { Err((format!("gateway {} does not answer", gateway), Some(Fix::ReleaseRenew(iface.clone())))) }
+            }
+            3 => run("getent", &["hosts", "example.com"]).and_then(|s| s.split_whitespace().next().map(|ip| format!("example.com → {}", ip)))
+                .ok_or_else(|| ("name resolution failed".to_string(), Some(Fix::FlushDns))),
+            4 => run("ping", &["-n", "-c", "2", "-W", "2", "1.1.1.1"]).map(|_| "1.1.1.1 answers".to_string())
+                .ok_or_else(|| ("no answer beyond the gateway: the problem is upstream (router or ISP)".to_string(), None)),
+            _ => {
+                let out = run("curl", &["-s", "-o", "/dev/null", "-m", "5", "-w", "%{http_code} %{redirect_url}", PORTAL_CHECK]).unwrap_or_default();
+                let mut parts = out.split_whitespace();
+                match (parts.next(), parts.next()) {
+                    (Some("204"), _) => Ok("no portal detected".to_string()),
+                    (Some(code), redirect) => Err((format!("HTTP {} instead of 204: a captive portal intercepts traffic", code), Some(Fix::OpenPortal(redirect.unwrap_or(PORTAL_CHECK).to_string())))),
+                    (None, _) => Err(("HTTP check failed".to_string(), None)),
+                }
+            }
+        };
+        match result {
+            Ok(detail) => lines.push(format!("[PASS] {}: {}", step, detail)),
+            Err((detail, suggestion)) => {
+                lines.push(format!("[FAIL] {}: {}", step, detail));
+                lines.extend(steps[i + 1..].iter().map(|s| format!("[SKIP] {}", s)));
+                fix = suggestion;
+                break;
+            }
+        }
+    }
+    lines.push(String::new());
+    lines.push(match (&fix, lines.iter().any(|l| l.starts_with("[FAIL]"))) {
+        (_, false) => "Summary: the connection looks healthy".to_string(),
+        (Some(f), true) => format!("Suggested fix: {}", f.describe()),
+        (None, true) => "No in-app fix available for this failure".to_string(),
+    });
+    Report { lines, fix }
+}
@@ -0,0 +1,100 @@
+use std::process::Command;
+
+use crate::natpmp;
+
+#[derive(Clone)]
+pub struct Mapping {
+    pub protocol: String,
+    pub external_port: u16,
+    pub internal_addr: String,
+    pub internal_port: u16,
+    pub description: String,
+    pub lease: String,
+}
+
+#[derive(Clone, Copy)]
+pub enum Protocol {
+    Upnp,
+    NatPmp,
+    Pcp,
+}
+
+pub struct Gateway {
+    pub device: String,
+    pub lan_ip: String,
+    pub external_ip: String,
+    pub mappings: Vec<Mapping>,
+    pub protocol: Protocol,
+}
+
+impl Protocol {
+    pub fn label(self) -> &'static str {
+        match self { Protocol::Upnp => "UPnP IGD", Protocol::NatPmp => "NAT-PMP", Protocol::Pcp => "PCP" }
+    }
+}
+
+fn upnpc(args: &[&str]) -> Result<String, String> {
+    let out = Command::new("upnpc").args(args).output().map_err(|e| format!("upnpc: {}", e))?;
+    let stdout = String::from_utf8_lossy(&out.stdout).to_string();
+    if stdout.contains("No IGD UPnP Device found") || stdout.contains("No valid UPNP Internet Gateway Device found") {
+        return Err("no UPnP Internet Gateway Device found on the LAN".to_string());
+    }
+    Ok(stdout)
+}
+
+fn parse_mapping(line: &str) -> Option<Mapping> {
+    let mut parts = line.split_whitespace();
+    parts.next()?.parse::<u32>().ok()?;
+    let protocol = parts.next()?.to_string();
+    let (ext, int) = parts.next()?.split_once("->")?;
+    let (addr, port) = int.rsplit_once(':')?;
+    let rest: Vec<&str> = parts.collect();
+    let rest = rest.join(" ");
+    let quoted: Vec<&str> = rest.split('\'').collect();
+    Some(Mapping {
+        protocol,
+        external_port: ext.parse().ok()?,
+        internal_addr: addr.to_string(),
+        internal_port: port.parse().ok()?,
+        description: quoted.get(1).unwrap_or(&"").to_string(),
+        lease: quoted.last().map(|l| l.trim()).filter(|l| !l.is_empty() && *l != "0").map(|l| format!("{}s", l)).unwrap_or_else(|| "permanent".to_string()),
+    })
+}
+
+pub fn discover() -> Result<Gateway, String> {
+    let out = match upnpc(&["-l"]) {
+        Ok(out) => out,
+        Err(upnp) => return natpmp::discover().map_err(|e| format!("{}; {}", upnp, e)),
+    };
+    let field = |prefix: &str| out.lines().find_map(|l| l.trim().strip_prefix(prefix)).map(|v| v.trim_start_matches([' ', ':', '=']).trim().to_string()).unwrap_or_default();
+    Ok(Gateway {
+        device: format!("{} {}", Protocol::Upnp.label(), field("Found valid IGD")),
+        lan_ip: field("Local LAN ip address"),
+        external_ip: field("ExternalIPAddress"),
+        mappings: out.lines().filter_map(parse_mapping).collect(),
+        protocol: Protocol::Upnp,
+    })
+}
+
+pub fn add_mapping(spec: &str, lan_ip: &str, via: Protocol) -> Result<(), String> {
+    let parts: Vec<&str> = spec.split_whitespace().collect();
+    if parts.len() < 3 { return Err("expected: <TCP|UDP> <external port> [ip:]<internal port> [description]".to_string()); }
+    let protocol = parts[0].to_uppercase();
+    if protocol != "TCP" && protocol != "UDP" { return Err(format!("invalid protocol '{}'", parts[0])); }
+    let external: u16 = parts[1].parse().map_err(|_| format!("invalid port '{}'", parts[1]))?;
+    let (addr, internal) = parts[2].rsplit_once(':').unwrap_or((lan_ip, parts[2]));
+    let internal: u16 = internal.parse().map_err(|_| format!("invalid port '{}'", internal))?;
+    let description = if parts.len() > 3 { parts[3..].join(" ") } else { "DashNet".to_string() };
+    if !matches!(via, Protocol::Upnp) {
+        let mapping = Mapping { protocol, external_port: external, internal_addr: addr.to_string(), internal_port: internal, description, lease: String::new() };
+        return natpmp::add(via, lan_ip, mapping);
+    }
+    let out = upnpc(&["-e", &description, "-a", addr, &internal.to_string(), &external.to_string(), &protocol])?;
+    if out.contains("failed") { Err(out.lines().find(|l| l.contains("failed")).unwrap_or("AddPortMapping failed").trim().to_string()) } else { Ok(()) }
+}
+
+pub fn remove_mapping(mapping: &Mapping, via: Protocol) -> Result<(), String> {
+    if !matches!(via, Protocol::Upnp) { return natpmp::remove(via, mapping); }
+    let out = upnpc(&["-d", &mapping.external_port.to_string(), &mapping.protocol])?;
+    if out.contains("failed") { Err(out.lines().find(|l| l.contains("failed")).unwrap_or("DeletePortMapping failed").trim().to_string()) } else { Ok(()) }
+}
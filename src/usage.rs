@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{clock, config};
+
+pub struct Entry {
+    pub month: String,
+    pub ssid: String,
+    pub rx: u64,
+    pub tx: u64,
+}
+
+pub struct Row {
+    pub ssid: String,
+    pub month: (u64, u64),
+    pub previous: (u64, u64),
+    pub total: (u64, u64),
+    pub since: String,
+}
+
+#[derive(Default)]
+pub struct Ledger {
+    pub entries: Vec<Entry>,
+    dirty: bool,
+}
+
+fn store_path() -> PathBuf {
+    config::data_dir().join("usage")
+}
+
+pub fn month(epoch: i64) -> String {
+    let t = clock::local(epoch);
+    format!("{}-{:02}", t.year, t.month)
+}
+
+fn previous_month(epoch: i64) -> String {
+    let t = clock::local(epoch);
+    if t.month == 1 { format!("{}-12", t.year - 1) } else { format!("{}-{:02}", t.year, t.month - 1) }
+}
+
+pub fn is_wireless(iface: &str) -> bool {
+    Path::new("/sys/class/net").join(iface).join("wireless").exists()
+}
+
+impl Ledger {
+    pub fn load() -> Ledger {
+        let entries = fs::read_to_string(store_path()).unwrap_or_default().lines().filter_map(|line| {
+            let f: Vec<&str> = line.split('\t').collect();
+            if f.len() < 4 { return None; }
+            Some(Entry { month: f[0].to_string(), ssid: f[1].to_string(), rx: f[2].parse().ok()?, tx: f[3].parse().ok()? })
+        }).collect();
+        Ledger { entries, dirty: false }
+    }
+
+    pub fn save(&mut self) -> Result<(), String> {
+        if !self.dirty { return Ok(()); }
+        let path = store_path();
+        if let Some(parent) = path.parent() { fs::create_dir_all(parent).map_err(|e| e.to_string())?; }
+        let text: String = self.entries.iter().map(|e| format!("{}\t{}\t{}\t{}\n", e.month, e.ssid, e.rx, e.tx)).collect();
+        fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    pub fn add(&mut self, ssid: &str, now: i64, rx: u64, tx: u64) {
+        if rx == 0 && tx == 0 { return; }
+        let (month, ssid) = (month(now), ssid.replace(['\t', '\n'], " "));
+        match self.entries.iter_mut().find(|e| e.month == month && e.ssid == ssid) {
+            Some(entry) => { entry.rx += rx; entry.tx += tx; }
+            None => self.entries.push(Entry { month, ssid, rx, tx }),
+        }
+        self.dirty = true;
+    }
+
+    pub fn this_month(&self, ssid: &str, now: i64) -> Option<u64> {
+        let month = month(now);
+        self.entries.iter().find(|e| e.month == month && e.ssid == ssid).map(|e| e.rx + e.tx)
+    }
+
+    pub fn rows(&self, now: i64) -> Vec<Row> {
+        let (month, previous) = (month(now), previous_month(now));
+        let mut rows: Vec<Row> = Vec::new();
+        for e in &self.entries {
+            let index = rows.iter().position(|r| r.ssid == e.ssid).unwrap_or_else(|| {
+                rows.push(Row { ssid: e.ssid.clone(), month: (0, 0), previous: (0, 0), total: (0, 0), since: e.month.clone() });
+                rows.len() - 1
+            });
+            let row = &mut rows[index];
+            if e.month == month { row.month = (row.month.0 + e.rx, row.month.1 + e.tx); }
+            if e.month == previous { row.previous = (row.previous.0 + e.rx, row.previous.1 + e.tx); }
+            row.total = (row.total.0 + e.rx, row.total.1 + e.tx);
+            if e.month < row.since { row.since = e.month.clone(); }
+        }
+        rows.sort_by(|a, b| (b.month.0 + b.month.1).cmp(&(a.month.0 + a.month.1)).then((b.total.0 + b.total.1).cmp(&(a.total.0 + a.total.1))));
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEC: i64 = 1_765_800_000;
+    const JAN: i64 = 1_768_478_400;
+    const FEB: i64 = 1_771_156_800;
+
+    #[test]
+    fn accumulates_per_ssid() {
+        let mut ledger = Ledger::default();
+        ledger.add("Home", JAN, 100, 10);
+        ledger.add("Cafe", JAN, 5, 1);
+        ledger.add("Home", JAN + 60, 200, 20);
+        ledger.add("Home", JAN + 120, 0, 0);
+        assert_eq!(ledger.entries.len(), 2);
+        assert_eq!(ledger.this_month("Home", JAN), Some(330));
+        assert_eq!(ledger.this_month("Cafe", JAN), Some(6));
+        assert_eq!(ledger.this_month("Office", JAN), None);
+        let rows = ledger.rows(JAN);
+        assert_eq!(rows.iter().map(|r| (r.ssid.as_str(), r.month)).collect::<Vec<_>>(), vec![("Home", (300, 30)), ("Cafe", (5, 1))]);
+    }
+
+    #[test]
+    fn rolls_over_at_the_month_boundary() {
+        let mut ledger = Ledger::default();
+        ledger.add("Home", DEC, 1, 1);
+        ledger.add("Home", JAN, 100, 10);
+        ledger.add("Home", FEB, 7, 3);
+        assert_eq!(ledger.entries.len(), 3);
+        assert_eq!(ledger.this_month("Home", FEB), Some(10));
+        let feb = &ledger.rows(FEB)[0];
+        assert_eq!((feb.month, feb.previous, feb.total, feb.since.as_str()), ((7, 3), (100, 10), (108, 14), "2025-12"));
+        let jan = &ledger.rows(JAN)[0];
+        assert_eq!((jan.month, jan.previous), ((100, 10), (1, 1)));
+    }
+}
@@ -0,0 +1,59 @@
+use std::process::{Command, Stdio};
+
+use crate::dhcp;
+
+#[derive(Clone, Copy)]
+pub enum Step {
+    RenewDhcp,
+    RestartConnection,
+    RestartDevice,
+    Notify,
+}
+
+impl Step {
+    pub const SEQUENCE: [Step; 4] = [Step::RenewDhcp, Step::RestartConnection, Step::RestartDevice, Step::Notify];
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Step::RenewDhcp => "renew DHCP lease",
+            Step::RestartConnection => "restart connection",
+            Step::RestartDevice => "restart device",
+            Step::Notify => "give up and notify",
+        }
+    }
+
+    pub fn run(&self, iface: &str) -> Result<String, String> {
+        match self {
+            Step::RenewDhcp => dhcp::renew(iface, false),
+            Step::RestartConnection => {
+                let connection = connection_on(iface).ok_or_else(|| format!("no connection active on {}", iface))?;
+                run("nmcli", &["con", "down", "id", &connection])?;
+                run("nmcli", &["con", "up", "id", &connection])?;
+                Ok(format!("restarted '{}'", connection))
+            }
+            Step::RestartDevice => {
+                run("nmcli", &["device", "disconnect", iface])?;
+                run("nmcli", &["device", "connect", iface])?;
+                Ok(format!("restarted {}", iface))
+            }
+            Step::Notify => Ok(String::new()),
+        }
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let out = Command::new(program).args(args).stdin(Stdio::null()).output().map_err(|e| format!("{}: {}", program, e))?;
+    if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+fn connection_on(iface: &str) -> Option<String> {
+    let out = Command::new("nmcli").args(["-t", "-f", "GENERAL.CONNECTION", "dev", "show", iface]).output().ok()?;
+    let s = String::from_utf8_lossy(&out.stdout);
+    s.lines().next().and_then(|l| l.split_once(':')).map(|(_, c)| c.trim().to_string()).filter(|c| !c.is_empty() && c != "--")
+}
+
+pub fn default_iface() -> Option<String> {
+    let out = Command::new("ip").args(["route", "show", "default"]).output().ok()?;
+    let s = String::from_utf8_lossy(&out.stdout);
+    s.split_whitespace().skip_while(|w| *w != "dev").nth(1).map(str::to_string)
+}
@@ -0,0 +1,104 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::thread;
+
+use crate::json::Json;
+use crate::{clock, format, net_monitor};
+
+static WEBHOOKS: OnceLock<Vec<Webhook>> = OnceLock::new();
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    Slack,
+    Discord,
+    Ntfy,
+    Json,
+}
+
+impl Kind {
+    pub fn parse(value: &str, url: &str) -> Kind {
+        match value {
+            "slack" => Kind::Slack,
+            "discord" => Kind::Discord,
+            "ntfy" => Kind::Ntfy,
+            "json" => Kind::Json,
+            _ if url.contains("hooks.slack.com") => Kind::Slack,
+            _ if url.contains("discord.com/api/webhooks") || url.contains("discordapp.com/api/webhooks") => Kind::Discord,
+            _ if url.contains("ntfy") => Kind::Ntfy,
+            _ => Kind::Json,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Webhook {
+    pub name: String,
+    pub url: String,
+    pub kind: Kind,
+    pub events: Vec<String>,
+    pub critical_only: bool,
+    pub template: String,
+    pub templates: Vec<(String, String)>,
+}
+
+pub fn init(webhooks: Vec<Webhook>) {
+    let _ = WEBHOOKS.set(webhooks);
+}
+
+fn render(template: &str, event: &str, summary: &str, body: &str) -> String {
+    template.replace("{event}", event).replace("{summary}", summary).replace("{body}", body)
+        .replace("{host}", &net_monitor::hostname()).replace("{time}", &format::time(clock::epoch_now()))
+}
+
+fn config_line(key: &str, value: &str) -> String {
+    format!("{} = \"{}\"\n", key, value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\r', "\\r"))
+}
+
+fn post(hook: &Webhook, event: &str, summary: &str, message: &str, critical: bool) -> Result<(), String> {
+    let mut cmd = Command::new("curl");
+    cmd.args(["-sS", "-f", "-m", "10", "-X", "POST"]);
+    let data = match hook.kind {
+        Kind::Slack => Json::Obj(vec![("text".to_string(), Json::Str(message.to_string()))]).to_string(),
+        Kind::Discord => Json::Obj(vec![("content".to_string(), Json::Str(message.to_string()))]).to_string(),
+        Kind::Ntfy => {
+            cmd.args(["-H", &format!("Title: {}", summary), "-H", if critical { "Priority: urgent" } else { "Priority: default" }, "-H", &format!("Tags: {}", event)]);
+            message.to_string()
+        }
+        Kind::Json => Json::Obj(vec![
+            ("event".to_string(), Json::Str(event.to_string())),
+            ("summary".to_string(), Json::Str(summary.to_string())),
+            ("body".to_string(), Json::Str(message.to_string())),
+            ("critical".to_string(), Json::Bool(critical)),
+            ("host".to_string(), Json::Str(net_monitor::hostname())),
+            ("time".to_string(), Json::Num(clock::epoch_now() as f64)),
+        ]).to_string(),
+    };
+    if hook.kind != Kind::Ntfy { cmd.args(["-H", "Content-Type: application/json"]); }
+    let mut child = cmd.args(["--data-binary", &data, "--config", "-"]).stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::piped()).spawn().map_err(|e| format!("curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() { stdin.write_all(config_line("url", &hook.url).as_bytes()).map_err(|e| e.to_string())?; }
+    let out = child.wait_with_output().map_err(|e| format!("curl: {}", e))?;
+    if out.status.success() { Ok(()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().to_string()) }
+}
+
+pub fn test() -> Vec<String> {
+    let webhooks = WEBHOOKS.get().map(Vec::as_slice).unwrap_or_default();
+    if webhooks.is_empty() { return vec!["No [webhook.<name>] section in the configuration.".to_string()]; }
+    webhooks.iter().map(|hook| {
+        let message = render(&hook.template, "test", "Test DashNet", "Message de test.");
+        match post(hook, "test", "Test DashNet", &message, false) {
+            Ok(()) => format!("[OK]   {}", hook.name),
+            Err(e) => format!("[FAIL] {}: {}", hook.name, e),
+        }
+    }).collect()
+}
+
+pub fn dispatch(event: &'static str, summary: &str, body: &str, critical: bool) {
+    let Some(webhooks) = WEBHOOKS.get() else { return };
+    for hook in webhooks.iter().filter(|h| (h.events.is_empty() || h.events.iter().any(|e| e == event)) && (critical || !h.critical_only)) {
+        let template = hook.templates.iter().find(|(e, _)| e == event).map(|(_, t)| t.as_str()).unwrap_or(&hook.template);
+        let message = render(template, event, summary, body);
+        let (hook, summary) = (hook.clone(), summary.to_string());
+        thread::spawn(move || { let _ = post(&hook, event, &summary, &message, critical); });
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::{base64, sha1};
+
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+type Clients = Arc<Mutex<Vec<SyncSender<Arc<Vec<u8>>>>>>;
+
+pub struct Server {
+    clients: Clients,
+}
+
+fn accept(key: &str) -> String {
+    base64::encode(&sha1::digest(format!("{}{}", key, GUID).as_bytes()))
+}
+
+fn local_origin(origin: &str) -> bool {
+    if matches!(origin, "" | "null") || origin.starts_with("file://") { return true; }
+    let Some((scheme, rest)) = origin.split_once("://") else { return false };
+    let host = rest.split('/').next().unwrap_or("");
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or(""),
+        None => host.split(':').next().unwrap_or(""),
+    };
+    matches!(scheme, "http" | "https") && matches!(host.to_ascii_lowercase().as_str(), "localhost" | "127.0.0.1" | "::1")
+}
+
+fn allowed(origin: Option<&str>, origins: &[String]) -> bool {
+    let Some(origin) = origin else { return true };
+    local_origin(origin) || origins.iter().any(|o| o.trim_end_matches('/').eq_ignore_ascii_case(origin.trim_end_matches('/')))
+}
+
+fn handshake(stream: &TcpStream, origins: &[String]) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let (mut key, mut origin) = (None, None);
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 { return Err(io::ErrorKind::UnexpectedEof.into()); }
+        let line = line.trim_end();
+        if line.is_empty() { break; }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("sec-websocket-key") { key = Some(value.trim().to_string()); }
+            if name.eq_ignore_ascii_case("origin") { origin = Some(value.trim().to_string()); }
+        }
+    }
+    if !allowed(origin.as_deref(), origins) {
+        let _ = (&*stream).write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n");
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("origin {} not allowed", origin.unwrap_or_default())));
+    }
+    let Some(key) = key else {
+        let _ = (&*stream).write_all(b"HTTP/1.1 426 Upgrade Required\r\nContent-Length: 0\r\n\r\n");
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a WebSocket request"));
+    };
+    let response = format!("HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n", accept(&key));
+    (&*stream).write_all(response.as_bytes())
+}
+
+fn frame(text: &str) -> Vec<u8> {
+    let payload = text.as_bytes();
+    let mut out = vec![0x81];
+    match payload.len() {
+        n if n < 126 => out.push(n as u8),
+        n if n <= u16::MAX as usize => { out.push(126); out.extend_from_slice(&(n as u16).to_be_bytes()); }
+        n => { out.push(127); out.extend_from_slice(&(n as u64).to_be_bytes()); }
+    }
+    out.extend_from_slice(payload);
+    out
+}
+
+impl Server {
+    pub fn start(addr: &str, origins: Vec<String>) -> io::Result<Server> {
+        let listener = TcpListener::bind(addr)?;
+        let clients: Clients = Arc::default();
+        let accepted = Arc::clone(&clients);
+        let origins = Arc::new(origins);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (accepted, origins) = (Arc::clone(&accepted), Arc::clone(&origins));
+                thread::spawn(move || {
+                    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+                    if handshake(&stream, &origins).is_err() { return; }
+                    let _ = stream.set_write_timeout(Some(Duration::from_secs(10)));
+                    let (tx, rx) = mpsc::sync_channel::<Arc<Vec<u8>>>(8);
+                    if let Ok(mut clients) = accepted.lock() { clients.push(tx); }
+                    for data in rx {
+                        if (&stream).write_all(&data).is_err() { break; }
+                    }
+                });
+            }
+        });
+        Ok(Server { clients })
+    }
+
+    pub fn broadcast(&self, text: &str) {
+        let data = Arc::new(frame(text));
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.retain(|client| !matches!(client.try_send(data.clone()), Err(TrySendError::Disconnected(_))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_matches_rfc_6455_example() {
+        assert_eq!(accept("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    fn answer(request: &str, origins: &[String]) -> (bool, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client.write_all(request.as_bytes()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let accepted = handshake(&server, origins).is_ok();
+        drop(server);
+        let mut status = String::new();
+        BufReader::new(client).read_line(&mut status).unwrap();
+        (accepted, status.trim_end().to_string())
+    }
+
+    #[test]
+    fn refuses_foreign_origins() {
+        let request = |origin: &str| format!("GET / HTTP/1.1\r\nHost: 127.0.0.1:9750\r\nUpgrade: websocket\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n{}\r\n", origin);
+        assert_eq!(answer(&request("Origin: https://evil.example\r\n"), &[]), (false, "HTTP/1.1 403 Forbidden".to_string()));
+        assert_eq!(answer(&request("Origin: http://localhost.evil.example\r\n"), &[]).1, "HTTP/1.1 403 Forbidden");
+        assert_eq!(answer(&request(""), &[]), (true, "HTTP/1.1 101 Switching Protocols".to_string()));
+        assert_eq!(answer(&request("Origin: https://grafana.lan\r\n"), &["https://grafana.lan/".to_string()]).1, "HTTP/1.1 101 Switching Protocols");
+    }
+
+    #[test]
+    fn local_origins_are_allowed() {
+        for origin in ["null", "file://", "http://localhost:3000", "http://127.0.0.1", "https://[::1]:8443/"] {
+            assert!(allowed(Some(origin), &[]), "{}", origin);
+        }
+        assert!(!allowed(Some("http://192.168.1.20"), &[]));
+    }
+
+    #[test]
+    fn frame_lengths() {
+        assert_eq!(frame("hi"), vec![0x81, 2, b'h', b'i']);
+        assert_eq!(&frame(&"x".repeat(300))[..4], &[0x81, 126, 1, 44]);
+        assert_eq!(&frame(&"x".repeat(70_000))[..10], &[0x81, 127, 0, 0, 0, 0, 0, 1, 0x11, 0x70]);
+    }
+}
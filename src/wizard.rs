@@ -0,0 +1,247 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::connect::Field;
+use crate::{config, profile};
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Kind {
+    WiFi,
+    Ethernet,
+    WireGuard,
+}
+
+pub struct Created {
+    pub name: String,
+    pub message: String,
+    pub activate: bool,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 3] = [Kind::WiFi, Kind::Ethernet, Kind::WireGuard];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Kind::WiFi => "Wi-Fi network (WPA/WPA2/WPA3 personal, or open)",
+            Kind::Ethernet => "Ethernet with a static IP address",
+            Kind::WireGuard => "WireGuard tunnel from keys and an endpoint",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Kind::WiFi => "Wi-Fi",
+            Kind::Ethernet => "Ethernet",
+            Kind::WireGuard => "WireGuard",
+        }
+    }
+
+    pub fn fields(&self) -> Vec<Field> {
+        match self {
+            Kind::WiFi => vec![
+                Field::entry("SSID", "ssid", false, ""),
+                Field::entry("Password", "psk", true, ""),
+                Field::entry("Hidden (yes/no)", "hidden", false, "no"),
+                Field::entry("Profile name", "name", false, ""),
+            ],
+            Kind::Ethernet => vec![
+                Field::entry("Interface", "ifname", false, &ethernet_iface().unwrap_or_default()),
+                Field::entry("Addresses/prefix", "address", false, ""),
+                Field::entry("Gateway", "gateway", false, ""),
+                Field::entry("DNS servers", "dns", false, ""),
+                Field::entry("Profile name", "name", false, ""),
+            ],
+            Kind::WireGuard => vec![
+                Field::entry("Private key", "private-key", true, ""),
+                Field::entry("Address/prefix", "address", false, ""),
+                Field::entry("Peer public key", "public-key", false, ""),
+                Field::entry("Preshared key", "preshared-key", true, ""),
+                Field::entry("Endpoint", "endpoint", false, ""),
+                Field::entry("Allowed IPs", "allowed-ips", false, "0.0.0.0/0, ::/0"),
+                Field::entry("DNS servers", "dns", false, ""),
+                Field::entry("Keepalive (s)", "keepalive", false, "25"),
+                Field::entry("Interface name", "name", false, "wg0"),
+            ],
+        }
+    }
+}
+
+fn ethernet_iface() -> Option<String> {
+    let mut names: Vec<String> = fs::read_dir("/sys/class/net").ok()?.flatten()
+        .filter(|e| e.path().join("device").exists() && !e.path().join("wireless").exists())
+        .map(|e| e.file_name().to_string_lossy().to_string()).collect();
+    names.sort();
+    names.into_iter().next()
+}
+
+fn get<'a>(values: &'a [(&'static str, String)], key: &str) -> &'a str {
+    values.iter().find(|(k, _)| *k == key).map(|(_, v)| v.trim()).unwrap_or("")
+}
+
+fn is_key(value: &str) -> bool {
+    value.len() == 44 && value.ends_with('=') && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '='))
+}
+
+fn check_cidrs(value: &str) -> Result<(), String> {
+    value.split(',').map(str::trim).filter(|s| !s.is_empty()).try_for_each(|item| profile::check_cidrs(item, item.contains(':')))
+}
+
+fn by_family(value: &str) -> (Vec<&str>, Vec<&str>) {
+    let (v6, v4) = value.split([',', ' ']).map(str::trim).filter(|s| !s.is_empty()).partition(|item| item.contains(':'));
+    (v4, v6)
+}
+
+fn check_ips(value: &str) -> Result<(), String> {
+    value.split([',', ' ']).map(str::trim).filter(|s| !s.is_empty())
+        .try_for_each(|item| item.parse::<std::net::IpAddr>().map(|_| ()).map_err(|_| format!("'{}' is not a valid IP address", item)))
+}
+
+pub fn values(fields: &[Field]) -> Vec<(&'static str, String)> {
+    fields.iter().map(|f| (f.key(), f.input.value().trim().to_string())).collect()
+}
+
+pub fn name(kind: Kind, values: &[(&'static str, String)]) -> String {
+    match (get(values, "name"), kind) {
+        ("", Kind::WiFi) => get(values, "ssid").to_string(),
+        ("", Kind::Ethernet) => format!("{} static", get(values, "ifname")),
+        (name, _) => name.to_string(),
+    }
+}
+
+pub fn validate(kind: Kind, values: &[(&'static str, String)]) -> Result<(), String> {
+    let required = |key: &str, label: &str| if get(values, key).is_empty() { Err(format!("{} is required", label)) } else { Ok(()) };
+    match kind {
+        Kind::WiFi => {
+            required("ssid", "the SSID")?;
+            let psk = get(values, "psk");
+            let hex = psk.len() == 64 && psk.chars().all(|c| c.is_ascii_hexdigit());
+            if !(psk.is_empty() || (8..=63).contains(&psk.len()) || hex) { return Err("a WPA password has 8 to 63 characters (or 64 hex digits)".to_string()); }
+            if !matches!(get(values, "hidden"), "" | "yes" | "no") { return Err("hidden is yes or no".to_string()); }
+        }
+        Kind::Ethernet => {
+            required("ifname", "the interface")?;
+            required("address", "the address")?;
+            let (v4, v6) = by_family(get(values, "address"));
+            if v4.iter().chain(&v6).any(|a| !a.contains('/')) { return Err("give each address with its prefix, like 192.168.1.10/24 or 2001:db8::10/64".to_string()); }
+            check_cidrs(&v4.iter().chain(&v6).copied().collect::<Vec<_>>().join(","))?;
+            check_ips(get(values, "gateway"))?;
+            let (gateway4, gateway6) = by_family(get(values, "gateway"));
+            if gateway4.len() > 1 || gateway6.len() > 1 { return Err("give at most one gateway per address family".to_string()); }
+            if !gateway4.is_empty() && v4.is_empty() { return Err("an IPv4 gateway needs an IPv4 address".to_string()); }
+            if !gateway6.is_empty() && v6.is_empty() { return Err("an IPv6 gateway needs an IPv6 address".to_string()); }
+            check_ips(get(values, "dns"))?;
+        }
+        Kind::WireGuard => {
+            if !is_key(get(values, "private-key")) { return Err("the private key is 44 base64 characters (wg genkey)".to_string()); }
+            required("address", "the tunnel address")?;
+            check_cidrs(get(values, "address"))?;
+            if !is_key(get(values, "public-key")) { return Err("the peer public key is 44 base64 characters".to_string()); }
+            if !get(values, "preshared-key").is_empty() && !is_key(get(values, "preshared-key")) { return Err("the preshared key is 44 base64 characters (wg genpsk)".to_string()); }
+            if !get(values, "endpoint").rsplit_once(':').is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok()) { return Err("the endpoint is host:port".to_string()); }
+            required("allowed-ips", "the allowed IPs")?;
+            check_cidrs(get(values, "allowed-ips"))?;
+            check_ips(get(values, "dns"))?;
+            if get(values, "keepalive").parse::<u16>().is_err() && !get(values, "keepalive").is_empty() { return Err("the keepalive is a number of seconds".to_string()); }
+            let name = get(values, "name");
+            if name.is_empty() || name.len() > 15 || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) { return Err("the interface name has 1 to 15 letters, digits, '-', '_' or '.'".to_string()); }
+        }
+    }
+    Ok(())
+}
+
+pub fn summary(kind: Kind, values: &[(&'static str, String)]) -> Vec<String> {
+    let mut lines = vec![format!("New {} profile '{}':", kind.title(), name(kind, values))];
+    let shown = |key: &str, value: &str| if matches!(key, "psk" | "private-key" | "preshared-key") && !value.is_empty() { "••••••••".to_string() } else if value.is_empty() { "-".to_string() } else { value.to_string() };
+    lines.extend(kind.fields().iter().filter(|f| f.key() != "name").map(|f| format!("  {:<16} {}", f.label, shown(f.key(), get(values, f.key())))));
+    lines.push(String::new());
+    lines.push(match kind {
+        Kind::WiFi if get(values, "psk").is_empty() => "Created as an open network.".to_string(),
+        Kind::WiFi => "The password is saved in the profile via nmcli's editor on stdin, never on a command line.".to_string(),
+        Kind::Ethernet => format!("Created with nmcli con add ({}).", match by_family(get(values, "address")) {
+            (v4, v6) if v6.is_empty() => format!("IPv4 manual with {} address(es), IPv6 automatic", v4.len()),
+            (v4, v6) if v4.is_empty() => format!("IPv6 manual with {} address(es), IPv4 disabled", v6.len()),
+            (v4, v6) => format!("IPv4 and IPv6 manual with {} and {} address(es)", v4.len(), v6.len()),
+        }),
+        Kind::WireGuard => "Imported with nmcli from a temporary wg-quick file, which is deleted right after.".to_string(),
+    });
+    lines
+}
+
+fn nmcli(args: &[String]) -> Result<String, String> {
+    let out = Command::new("nmcli").args(args).stdin(Stdio::null()).output().map_err(|e| format!("nmcli: {}", e))?;
+    if out.status.success() { Ok(String::from_utf8_lossy(&out.stdout).trim().to_string()) } else { Err(String::from_utf8_lossy(&out.stderr).trim().trim_start_matches("Error: ").to_string()) }
+}
+
+fn store_psk(name: &str, psk: &str) -> Result<(), String> {
+    let mut child = Command::new("nmcli").args(["con", "edit", "id", name]).stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().map_err(|e| format!("nmcli: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() { write!(stdin, "set 802-11-wireless-security.psk {}\nsave persistent\nquit\n", psk).map_err(|e| e.to_string())?; }
+    let out = child.wait_with_output().map_err(|e| e.to_string())?;
+    let text = format!("{}{}", String::from_utf8_lossy(&out.stdout), String::from_utf8_lossy(&out.stderr));
+    if let Some(error) = text.lines().map(str::trim).find(|l| l.starts_with("Error")) { return Err(error.trim_start_matches("Error: ").to_string()); }
+    if out.status.success() { Ok(()) } else { Err(text.trim().to_string()) }
+}
+
+fn wireguard_file(values: &[(&'static str, String)]) -> Result<PathBuf, String> {
+    let base = config::private_dir("wizard").map_err(|e| format!("private directory: {}", e))?;
+    let path = base.join(format!("{}.conf", get(values, "name")));
+    let mut text = format!("[Interface]\nPrivateKey = {}\nAddress = {}\n", get(values, "private-key"), get(values, "address"));
+    if !get(values, "dns").is_empty() { text += &format!("DNS = {}\n", get(values, "dns")); }
+    text += &format!("\n[Peer]\nPublicKey = {}\nEndpoint = {}\nAllowedIPs = {}\n", get(values, "public-key"), get(values, "endpoint"), get(values, "allowed-ips"));
+    if !get(values, "preshared-key").is_empty() { text += &format!("PresharedKey = {}\n", get(values, "preshared-key")); }
+    if !matches!(get(values, "keepalive"), "" | "0") { text += &format!("PersistentKeepalive = {}\n", get(values, "keepalive")); }
+    let written = OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path).and_then(|mut file| file.write_all(text.as_bytes()));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_dir(&base);
+        return Err(format!("{}: {}", path.display(), e));
+    }
+    Ok(path)
+}
+
+pub fn create(kind: Kind, values: &[(&'static str, String)]) -> Result<Created, String> {
+    validate(kind, values)?;
+    let name = name(kind, values);
+    let arg = |s: &str| s.to_string();
+    match kind {
+        Kind::WiFi => {
+            let mut args: Vec<String> = ["con", "add", "type", "wifi", "con-name", &name, "ifname", "*", "ssid", get(values, "ssid")].map(arg).to_vec();
+            if get(values, "hidden") == "yes" { args.extend(["802-11-wireless.hidden", "yes"].map(arg)); }
+            let psk = get(values, "psk");
+            if !psk.is_empty() { args.extend(["802-11-wireless-security.key-mgmt", "wpa-psk"].map(arg)); }
+            nmcli(&args)?;
+            if !psk.is_empty() {
+                if let Err(e) = store_psk(&name, psk) {
+                    let _ = nmcli(&["con", "delete", "id", &name].map(arg));
+                    return Err(format!("cannot store the password: {}", e));
+                }
+            }
+            Ok(Created { message: format!("Profile '{}' created{}, connecting.", name, if psk.is_empty() { "" } else { " with its password" }), name, activate: true })
+        }
+        Kind::Ethernet => {
+            let mut args: Vec<String> = ["con", "add", "type", "ethernet", "con-name", &name, "ifname", get(values, "ifname")].map(arg).to_vec();
+            let (addresses, gateways, dns) = (by_family(get(values, "address")), by_family(get(values, "gateway")), by_family(get(values, "dns")));
+            for (family, addresses, gateways, dns) in [("ipv4", addresses.0, gateways.0, dns.0), ("ipv6", addresses.1, gateways.1, dns.1)] {
+                match addresses.is_empty() {
+                    true if family == "ipv4" => args.extend(["ipv4.method", "disabled"].map(arg)),
+                    true => continue,
+                    false => args.extend([format!("{}.method", family), "manual".to_string(), format!("{}.addresses", family), addresses.join(", ")]),
+                }
+                if let Some(gateway) = gateways.first() { args.extend([format!("{}.gateway", family), gateway.to_string()]); }
+                if !dns.is_empty() { args.extend([format!("{}.dns", family), dns.join(" ")]); }
+            }
+            nmcli(&args)?;
+            Ok(Created { message: format!("Profile '{}' created on {} with {}.", name, get(values, "ifname"), get(values, "address")), name, activate: false })
+        }
+        Kind::WireGuard => {
+            let path = wireguard_file(values)?;
+            let result = nmcli(&["con", "import", "type", "wireguard", "file", &path.to_string_lossy()].map(arg));
+            let _ = fs::remove_file(&path);
+            if let Some(dir) = path.parent() { let _ = fs::remove_dir(dir); }
+            result?;
+            Ok(Created { message: format!("WireGuard profile '{}' imported.", name), name, activate: false })
+        }
+    }
+}
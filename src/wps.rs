@@ -0,0 +1,77 @@
+use std::fs;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const WALK_TIME: Duration = Duration::from_secs(120);
+
+pub enum Update {
+    Stage(String),
+    Done(String),
+    Failed(String),
+}
+
+pub fn wifi_iface() -> Option<String> {
+    let mut names: Vec<String> = fs::read_dir("/sys/class/net").ok()?.flatten()
+        .filter(|e| e.path().join("wireless").exists()).map(|e| e.file_name().to_string_lossy().to_string()).collect();
+    names.sort();
+    names.into_iter().next()
+}
+
+fn wpa_cli(iface: &str, args: &[&str]) -> Result<String, String> {
+    let out = Command::new("wpa_cli").args(["-i", iface]).args(args).stdin(Stdio::null()).output().map_err(|e| format!("wpa_cli: {}", e))?;
+    let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    if out.status.success() && !text.starts_with("FAIL") { Ok(text) } else { Err(if text.is_empty() { String::from_utf8_lossy(&out.stderr).trim().to_string() } else { text }) }
+}
+
+fn field(status: &str, key: &str) -> String {
+    status.lines().find_map(|l| l.strip_prefix(key).and_then(|v| v.strip_prefix('='))).unwrap_or("").to_string()
+}
+
+fn describe(state: &str) -> &'static str {
+    match state {
+        "SCANNING" => "looking for a router in push-button mode",
+        "AUTHENTICATING" | "ASSOCIATING" => "associating with the router",
+        "ASSOCIATED" => "associated, exchanging WPS messages",
+        "4WAY_HANDSHAKE" | "GROUP_HANDSHAKE" => "credentials received, negotiating keys",
+        "COMPLETED" => "connected",
+        "DISCONNECTED" | "INACTIVE" => "waiting for the router",
+        _ => "working",
+    }
+}
+
+pub fn start(iface: String, bssid: Option<String>) -> Receiver<Update> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let before = wpa_cli(&iface, &["status"]).unwrap_or_default();
+        let before = (field(&before, "bssid"), field(&before, "ssid"));
+        let args: Vec<&str> = ["wps_pbc"].into_iter().chain(bssid.as_deref()).collect();
+        if let Err(e) = wpa_cli(&iface, &args) {
+            let _ = tx.send(Update::Failed(format!("wpa_supplicant refused to start WPS on {}: {}", iface, e)));
+            return;
+        }
+        let _ = tx.send(Update::Stage(format!("WPS started on {}: press the WPS button on the router now (within 2 minutes)", iface)));
+        let start = Instant::now();
+        let mut last = String::new();
+        let mut exchanged = false;
+        while start.elapsed() < WALK_TIME {
+            thread::sleep(Duration::from_secs(1));
+            let status = wpa_cli(&iface, &["status"]).unwrap_or_default();
+            let state = field(&status, "wpa_state");
+            exchanged |= field(&status, "key_mgmt") == "WPS";
+            let moved = (field(&status, "bssid"), field(&status, "ssid")) != before;
+            if state == "COMPLETED" && field(&status, "key_mgmt") != "WPS" && (exchanged || moved) {
+                let _ = tx.send(Update::Done(field(&status, "ssid")));
+                return;
+            }
+            if state != last {
+                if tx.send(Update::Stage(format!("{}s: {}", start.elapsed().as_secs(), describe(&state)))).is_err() { let _ = wpa_cli(&iface, &["wps_cancel"]); return; }
+                last = state;
+            }
+        }
+        let _ = wpa_cli(&iface, &["wps_cancel"]);
+        let _ = tx.send(Update::Failed("no router answered in push-button mode within 2 minutes".to_string()));
+    });
+    rx
+}